@@ -0,0 +1,12 @@
+// Compiles the C++ side of the cxx bridge in src/lib.rs. The generated header is
+// written to target/cxxbridge/privacy_http_sdk/src/lib.rs.h.
+fn main() {
+    // No C++ toolchain for wasm builds, which only use the wasm-bindgen API
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32") {
+        return;
+    }
+    cxx_build::bridge("src/lib.rs")
+        .std("c++17")
+        .compile("privacy_http_sdk_cxx");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+}
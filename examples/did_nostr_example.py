@@ -8,7 +8,13 @@ This example demonstrates how to:
 4. Canonicalize a request for signing
 """
 
-from http_client_module import did_nostr
+from privacy_http_sdk.identity import (
+    DidNostr,
+    NostrPublicKey,
+    NostrSignature,
+    NostrVerifier,
+    RequestCanonicalizer,
+)
 
 def main():
     print("=== DID-NOSTR Example ===")
@@ -17,8 +23,8 @@ def main():
     print("\n1. Creating DID from NOSTR public key:")
     try:
         pubkey_hex = "a" * 64
-        pubkey = did_nostr.PyNostrPublicKey(pubkey_hex)
-        did = did_nostr.PyDidNostr.from_pubkey(pubkey)
+        pubkey = NostrPublicKey(pubkey_hex)
+        did = DidNostr.from_pubkey(pubkey)
         print(f"   Created DID: {did}")
     except Exception as e:
         print(f"   Error: {e}")
@@ -27,7 +33,7 @@ def main():
     print("\n2. Parsing DID from string:")
     try:
         did_str = f"did:nostr:{'b' * 64}"
-        did = did_nostr.PyDidNostr.from_str(did_str)
+        did = DidNostr.from_str(did_str)
         print(f"   Parsed DID: {did}")
         print(f"   Public Key: {did.pubkey().as_hex()}")
     except Exception as e:
@@ -44,7 +50,7 @@ def main():
     ]
     body = '{"message": "Hello, NOSTR!"}'
 
-    canonical = did_nostr.PyRequestCanonicalizer.canonicalize(
+    canonical = RequestCanonicalizer.canonicalize(
         method, path, headers, body
     )
     print("   Canonical form:")
@@ -54,9 +60,9 @@ def main():
     # Example 4: Verify a signature (stub)
     print("\n4. Verifying NOSTR signature (stub):")
     try:
-        pubkey = did_nostr.PyNostrPublicKey("c" * 64)
-        signature = did_nostr.PyNostrSignature("d" * 128)
-        result = did_nostr.PyNostrVerifier.verify(
+        pubkey = NostrPublicKey("c" * 64)
+        signature = NostrSignature("d" * 128)
+        result = NostrVerifier.verify(
             pubkey, "test message", signature
         )
         print(f"   Verification result: {'Valid' if result.valid else 'Invalid'}")
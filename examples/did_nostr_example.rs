@@ -163,7 +163,7 @@ fn main() {
         Ok(pubkey) => {
             let did = DidNostr::from_pubkey(pubkey);
             println!("   Created DID: {}", did);
-            println!("   Full DID: {}", did.to_string());
+            println!("   Full DID: {}", did);
         }
         Err(e) => println!("   Error: {}", e),
     }
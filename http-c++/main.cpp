@@ -143,9 +143,19 @@ void start_api_server() {
     svr.listen("127.0.0.1", 8080);
 }
 
-// Extend the privacy_http_sdk client interface
+// Helpers for the privacy_http_sdk client (requests go through the Rust privacy pipeline)
 namespace privacy_http_sdk {
-    // The generate_image logic is now centralized in Rust
+    rust::Vec<Header> to_headers(const std::unordered_map<std::string, std::string>& map) {
+        rust::Vec<Header> headers;
+        for (const auto& [key, value] : map) {
+            headers.push_back(Header{rust::String(key), rust::String(value)});
+        }
+        return headers;
+    }
+
+    std::string body_text(const Response& response) {
+        return std::string(response.body.begin(), response.body.end());
+    }
 }
 
 // A2A Server
@@ -335,8 +345,11 @@ int main() {
     // Wait briefly to ensure server starts
     std::this_thread::sleep_for(std::chrono::seconds(2));
 
-    // Initialize the privacy_http_sdk client
-    auto client = privacy_http_sdk::new_http_client();
+    // Initialize the privacy_http_sdk client (ClientConfig JSON; errors throw rust::Error)
+    auto client = privacy_http_sdk::new_http_client(R"({
+        "privacy": {"tls_enforce": true, "user_agent": "Mozilla/5.0"},
+        "retries": {"max_retries": 2}
+    })");
 
     std::cout << "PrivacyHttpSdk Version: " << PRIVACY_HTTP_SDK_VERSION << std::endl;
 
@@ -355,8 +368,9 @@ int main() {
             }}
         };
         std::string mcp_body = mcp_message.dump();
-        auto mcp_response = client->post(mcp_url, headers, mcp_body);
-        std::cout << "MCP Response: " << mcp_response << std::endl;
+        auto mcp_response = client->post(mcp_url, privacy_http_sdk::to_headers(headers), mcp_body);
+        std::cout << "MCP Response (" << mcp_response.status << "): "
+                  << privacy_http_sdk::body_text(mcp_response) << std::endl;
     } catch (const std::exception& e) {
         std::cerr << "MCP Error: " << e.what() << std::endl;
     }
@@ -381,22 +395,25 @@ int main() {
     };
     for (const auto& url : urls) {
         try {
-            auto response = client->get(url, headers);
-            std::cout << "GET Response from " << url << ": " << response << std::endl;
+            auto response = client->get(url, privacy_http_sdk::to_headers(headers));
+            std::cout << "GET Response from " << url << " (" << response.status << "): "
+                      << privacy_http_sdk::body_text(response) << std::endl;
         } catch (const std::exception& e) {
             std::cerr << "GET Error from " << url << ": " << e.what() << std::endl;
         }
     }
     try {
         std::string body = R"({"prompt": "Hello, world!", "max_tokens": 5})";
-        auto response = client->post("https://api.openai.com/v1/completions", headers, body);
-        std::cout << "POST Response: " << response << std::endl;
+        auto response = client->post("https://api.openai.com/v1/completions", privacy_http_sdk::to_headers(headers), body);
+        std::cout << "POST Response (" << response.status << "): "
+                  << privacy_http_sdk::body_text(response) << std::endl;
     } catch (const std::exception& e) {
         std::cerr << "POST Error: " << e.what() << std::endl;
     }
 
     // Perform Stable Diffusion image generation (unchanged)
     try {
+        client->set_image_provider(R"({"provider": "automatic1111"})");
         client->generate_image("A serene landscape", 512, 512, 50, "output.png");
         std::cout << "Image saved to output.png" << std::endl;
     } catch (const std::exception& e) {
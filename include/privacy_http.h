@@ -0,0 +1,148 @@
+#ifndef PRIVACY_HTTP_H
+#define PRIVACY_HTTP_H
+
+/* Generated with cbindgen from src/c_api.rs and src/did_nostr_ffi.rs; do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Success
+#define PHTTP_OK 0
+
+// A required pointer argument was NULL
+#define PHTTP_ERR_NULL 1
+
+// A string argument was not valid UTF-8
+#define PHTTP_ERR_UTF8 2
+
+// The client config was rejected
+#define PHTTP_ERR_CONFIG 3
+
+// The request was blocked by a privacy rule or failed to send
+#define PHTTP_ERR_REQUEST 4
+
+// An argument had an invalid value (e.g. a malformed DID)
+#define PHTTP_ERR_INVALID 5
+
+// The library panicked; the handle involved should not be used again
+#define PHTTP_ERR_PANIC 6
+
+// Opaque client handle
+typedef struct phttp_client phttp_client;
+
+// Header name/value pair
+typedef struct phttp_header {
+  const char *name;
+  const char *value;
+} phttp_header;
+
+// Request to send; all memory stays owned by the caller
+typedef struct phttp_request {
+  // Method (NULL: GET)
+  const char *method;
+  const char *url;
+  const struct phttp_header *headers;
+  size_t headers_len;
+  const uint8_t *body;
+  size_t body_len;
+} phttp_request;
+
+// Response owned by the library; release with `phttp_response_free`
+typedef struct phttp_response {
+  uint16_t status;
+  struct phttp_header *headers;
+  size_t headers_len;
+  uint8_t *body;
+  size_t body_len;
+} phttp_response;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last failed call on this thread (NULL if none). The string is owned
+// by the library and valid until the next failing call on the same thread.
+const char *phttp_last_error(void);
+
+// Create a client from a `ClientConfig` JSON string (NULL: defaults)
+//
+// # Safety
+// `config_json` must be NULL or a valid NUL-terminated string; `out` must be writable
+int32_t phttp_client_new(const char *config_json, struct phttp_client **out);
+
+// Release a client (NULL is ignored)
+//
+// # Safety
+// `client` must come from `phttp_client_new`, must not be in use and is invalid afterwards
+void phttp_client_free(struct phttp_client *client);
+
+// Send a request through the client's privacy pipeline. Blocks until the response
+// arrives. A handle must not be used from several threads at once. Fails with
+// `PHTTP_ERR_INVALID` if a response header contains a NUL byte.
+//
+// # Safety
+// `client` must be a live handle, `request` must point to a valid `phttp_request`
+// and `out` must be writable
+int32_t phttp_client_send(const struct phttp_client *client,
+                          const struct phttp_request *request,
+                          struct phttp_response **out);
+
+// Release a response (NULL is ignored)
+//
+// # Safety
+// `response` must come from `phttp_client_send` and is invalid afterwards
+void phttp_response_free(struct phttp_response *response);
+
+// Release a string returned by the library (NULL is ignored)
+//
+// # Safety
+// `ptr` must come from a `phttp_*` or `did_nostr_*` out-parameter and is invalid afterwards
+void phttp_string_free(char *ptr);
+
+// Create a DID from NOSTR public key (hex)
+// # Safety
+// Caller must ensure pubkey_hex is a valid null-terminated string and out is writable
+int32_t did_nostr_create(const char *pubkey_hex, char **out);
+
+// Parse DID from string (normalized form)
+// # Safety
+// Caller must ensure did_str is a valid null-terminated string and out is writable
+int32_t did_nostr_parse(const char *did_str, char **out);
+
+// Extract public key (hex) from DID
+// # Safety
+// Caller must ensure did_str is a valid null-terminated string and out is writable
+int32_t did_nostr_get_pubkey(const char *did_str, char **out);
+
+// Sign an unsigned event (`{"kind": 1, "content": "...", "tags": [...], "created_at": ...}`,
+// `created_at` defaults to now) with a hex secret key; writes the NIP-01 event JSON
+// # Safety
+// Caller must ensure secret_hex and unsigned_json are valid null-terminated strings and out is writable
+int32_t did_nostr_event_sign(const char *secret_hex, const char *unsigned_json, char **out);
+
+// Strictly parse and verify an event (id and signature); writes the author's DID
+// # Safety
+// Caller must ensure event_json is a valid null-terminated string and out is writable
+int32_t did_nostr_event_verify(const char *event_json, char **out);
+
+// Compute the id of an event from its fields (hex)
+// # Safety
+// Caller must ensure event_json is a valid null-terminated string and out is writable
+int32_t did_nostr_event_id(const char *event_json, char **out);
+
+// Value of the first tag named `name`; PHTTP_ERR_INVALID when there is none or it
+// contains a NUL byte
+// # Safety
+// Caller must ensure event_json and name are valid null-terminated strings and out is writable
+int32_t did_nostr_event_tag_value(const char *event_json, const char *name, char **out);
+
+// Free a string allocated by the Rust library
+// # Safety
+// Caller must ensure ptr is a valid pointer returned by did_nostr_* functions
+void did_nostr_free(char *ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PRIVACY_HTTP_H */
@@ -0,0 +1,13 @@
+"""Privacy-preserving HTTP client with DID-NOSTR identity
+
+Submodules:
+
+- ``privacy_http_sdk.http``: ``HttpClientPy``, interceptor views and the MCP client
+- ``privacy_http_sdk.identity``: DID-NOSTR keys, DIDs, signing and verification
+- ``privacy_http_sdk.codecs``: payload codecs
+- ``privacy_http_sdk.policy``: privacy policy and PII scrubbing
+"""
+
+from ._native import HttpClientPy, codecs, http, identity, policy
+
+__all__ = ["HttpClientPy", "codecs", "http", "identity", "policy"]
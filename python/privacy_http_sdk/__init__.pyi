@@ -0,0 +1,7 @@
+from . import codecs as codecs
+from . import http as http
+from . import identity as identity
+from . import policy as policy
+from .http import HttpClientPy as HttpClientPy
+
+__all__ = ["HttpClientPy", "codecs", "http", "identity", "policy"]
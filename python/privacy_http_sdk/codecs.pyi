@@ -0,0 +1,10 @@
+from typing import Optional
+
+class PayloadCodec:
+    def __init__(self, method: str, key: Optional[str] = None) -> None: ...
+    @staticmethod
+    def generate_key() -> str: ...
+    @property
+    def method(self) -> str: ...
+    def encode(self, data: bytes) -> bytes: ...
+    def decode(self, data: bytes) -> bytes: ...
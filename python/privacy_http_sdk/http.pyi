@@ -0,0 +1,112 @@
+from typing import Callable, Mapping, Optional, Sequence, Tuple, Union
+
+Headers = Union[Mapping[str, str], Sequence[Tuple[str, str]]]
+
+class RequestView:
+    method: str
+    url: str
+    headers: list[tuple[str, str]]
+    body: bytes
+    def header(self, name: str) -> Optional[str]: ...
+    def set_header(self, name: str, value: str) -> None: ...
+    def remove_header(self, name: str) -> None: ...
+    def block(self, reason: str) -> None: ...
+    def respond(self, status: int, body: bytes = ..., headers: Sequence[Tuple[str, str]] = ...) -> None: ...
+
+class ResponseView:
+    @property
+    def method(self) -> str: ...
+    @property
+    def url(self) -> str: ...
+    @property
+    def request_headers(self) -> list[tuple[str, str]]: ...
+    status: int
+    headers: list[tuple[str, str]]
+    body: bytes
+    def header(self, name: str) -> Optional[str]: ...
+    def set_header(self, name: str, value: str) -> None: ...
+    def remove_header(self, name: str) -> None: ...
+    def text(self) -> str: ...
+    def block(self, reason: str) -> None: ...
+
+class HttpClientPy:
+    api_key: str
+    openai_url: str
+    def __init__(self, api_key: str, openai_url: str) -> None: ...
+    def get(self, url: str, headers: Optional[Headers] = None) -> str: ...
+    def post(self, url: str, headers: Optional[Headers] = None, body: str = "") -> str: ...
+    def register_interceptor(
+        self,
+        on_request: Optional[Callable[[RequestView], object]] = None,
+        on_response: Optional[Callable[[ResponseView], object]] = None,
+        stage: str = "outer",
+        name: str = "python",
+    ) -> None: ...
+    def clear_interceptors(self) -> None: ...
+    def interceptor_names(self) -> list[str]: ...
+    def set_image_provider(self, config_json: str) -> None: ...
+    def set_privacy_config(self, config_json: str) -> None: ...
+    def set_cookie_site(self, site: Optional[str] = None) -> None: ...
+    def clear_cookies(self, site: Optional[str] = None) -> None: ...
+    def cookie_partitions(self) -> str: ...
+    def rate_limit_stats(self) -> str: ...
+    def set_a2a_server(self, url: str) -> None: ...
+    def set_mcp_server(self, url: str) -> None: ...
+    def a2a_agent_card(self) -> str: ...
+    def a2a_send_message(self, text: str) -> str: ...
+    def a2a_get_task(self, task_id: str) -> str: ...
+    def a2a_cancel_task(self, task_id: str) -> str: ...
+    def set_proxy_identity(self, identity: str) -> None: ...
+    def configure_ohttp(self, config_json: str) -> None: ...
+    def disable_ohttp(self) -> None: ...
+    def configure_retries(self, config_json: str) -> None: ...
+    def disable_retries(self) -> None: ...
+    def configure_cache(self, config_json: str) -> None: ...
+    def disable_cache(self) -> None: ...
+    def clear_cache(self) -> None: ...
+    def circuit_states(self) -> str: ...
+    def configure_storage(self, config_json: str) -> None: ...
+    def generate_image(
+        self,
+        prompt: str,
+        width: int,
+        height: int,
+        steps: int,
+        output_path: Optional[str] = None,
+        negative_prompt: Optional[str] = None,
+        seed: Optional[int] = None,
+        cfg_scale: Optional[float] = None,
+        sampler: Optional[str] = None,
+        count: int = 1,
+        format: str = "png",
+        output_uri: Optional[str] = None,
+        encryption_key: Optional[str] = None,
+    ) -> list[str]: ...
+    def prompt(self, prompt: str) -> str: ...
+    def enable_pii_scrubbing(self, config_json: Optional[str] = None) -> None: ...
+    def disable_pii_scrubbing(self) -> None: ...
+    def scrub_chat(self, messages_json: str) -> str: ...
+    def rehydrate(self, response: str) -> str: ...
+
+class McpClient:
+    @staticmethod
+    def http(
+        client: HttpClientPy,
+        url: Optional[str] = None,
+        headers: Optional[Sequence[Tuple[str, str]]] = None,
+        signer_secret: Optional[str] = None,
+    ) -> McpClient: ...
+    @staticmethod
+    def stdio(
+        command: str,
+        args: Optional[Sequence[str]] = None,
+        env: Optional[Sequence[Tuple[str, str]]] = None,
+    ) -> McpClient: ...
+    def initialize(self) -> str: ...
+    def list_tools(self) -> str: ...
+    def call_tool(self, name: str, arguments_json: Optional[str] = None) -> str: ...
+    def list_resources(self) -> str: ...
+    def read_resource(self, uri: str) -> str: ...
+    def list_prompts(self) -> str: ...
+    def get_prompt(self, name: str, arguments_json: Optional[str] = None) -> str: ...
+    def close(self) -> None: ...
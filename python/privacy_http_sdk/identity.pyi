@@ -0,0 +1,97 @@
+from typing import Optional, Sequence, Tuple
+
+class NostrPublicKey:
+    def __init__(self, hex: str) -> None: ...
+    def as_hex(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class DidNostr:
+    def __init__(self, did_str: str) -> None: ...
+    @staticmethod
+    def from_pubkey(pubkey: NostrPublicKey) -> DidNostr: ...
+    @staticmethod
+    def from_str(did_str: str) -> DidNostr: ...
+    def pubkey(self) -> NostrPublicKey: ...
+    def to_string(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class NostrSignature:
+    def __init__(self, hex: str) -> None: ...
+    def as_hex(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class VerificationResult:
+    @property
+    def valid(self) -> bool: ...
+    @property
+    def did(self) -> Optional[DidNostr]: ...
+    @property
+    def error(self) -> Optional[str]: ...
+    def __bool__(self) -> bool: ...
+
+class NostrVerifier:
+    @staticmethod
+    def verify(pubkey: NostrPublicKey, message: str, signature: NostrSignature) -> VerificationResult: ...
+    @staticmethod
+    def verify_request(
+        method: str, path: str, headers: Sequence[Tuple[str, str]], body: str
+    ) -> VerificationResult: ...
+
+class NostrSigner:
+    def __init__(self, secret_hex: str) -> None: ...
+    @staticmethod
+    def generate() -> NostrSigner: ...
+    def did(self) -> DidNostr: ...
+    def pubkey(self) -> NostrPublicKey: ...
+    def secret_hex(self) -> str: ...
+    def sign(self, message: str) -> NostrSignature: ...
+    def sign_request(self, method: str, path: str, body: str) -> list[tuple[str, str]]: ...
+
+class RequestCanonicalizer:
+    @staticmethod
+    def canonicalize(method: str, path: str, headers: Sequence[Tuple[str, str]], body: str) -> str: ...
+
+class DidResolver:
+    def __init__(self, key_format: str = "Multikey") -> None: ...
+    def add_services(self, did: DidNostr, relays: Sequence[str] = ..., nip05: Optional[str] = None) -> None: ...
+    def document(self, did: DidNostr) -> str: ...
+    def resolve(self, did: str) -> str: ...
+
+class NostrEvent:
+    def __init__(self, json: str) -> None: ...
+    @staticmethod
+    def from_json(json: str) -> NostrEvent: ...
+    @staticmethod
+    def sign(
+        signer: NostrSigner,
+        kind: int,
+        content: str,
+        tags: Sequence[Sequence[str]] = ...,
+        created_at: Optional[int] = None,
+    ) -> NostrEvent: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def pubkey(self) -> str: ...
+    @property
+    def created_at(self) -> int: ...
+    @property
+    def kind(self) -> int: ...
+    @property
+    def tags(self) -> list[list[str]]: ...
+    @property
+    def content(self) -> str: ...
+    @property
+    def sig(self) -> str: ...
+    def to_json(self) -> str: ...
+    def compute_id(self) -> str: ...
+    def verify(self) -> None: ...
+    def did(self) -> DidNostr: ...
+    def tag(self, name: str) -> Optional[list[str]]: ...
+    def tag_value(self, name: str) -> Optional[str]: ...
+    def tag_values(self, name: str) -> list[str]: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
@@ -0,0 +1,26 @@
+from typing import Optional, Sequence, Tuple
+
+class PolicyReport:
+    @property
+    def removed(self) -> list[tuple[str, str]]: ...
+    @property
+    def rewritten(self) -> list[tuple[str, str]]: ...
+
+class PrivacyPolicy:
+    def __init__(self, config_json: Optional[str] = None) -> None: ...
+    @staticmethod
+    def from_file(path: str) -> PrivacyPolicy: ...
+    def config_json(self) -> str: ...
+    def allows_header(self, name: str) -> bool: ...
+    def blocks_url(self, url: str) -> bool: ...
+    def apply(self, method: str, url: str, headers: Sequence[Tuple[str, str]] = ...) -> list[tuple[str, str]]: ...
+    def explain(self, method: str, url: str, headers: Sequence[Tuple[str, str]] = ...) -> PolicyReport: ...
+
+class PiiScrubber:
+    def __init__(self, config_json: Optional[str] = None) -> None: ...
+    def scrub(self, text: str) -> str: ...
+    def scrub_messages(self, messages_json: str) -> str: ...
+    def rehydrate(self, text: str) -> str: ...
+    def detect(self, text: str) -> list[tuple[str, int, int]]: ...
+    def clear(self) -> None: ...
+    def __len__(self) -> int: ...
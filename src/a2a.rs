@@ -0,0 +1,739 @@
+//! A2A Client Module
+//!
+//! This module implements an Agent-to-Agent (A2A) protocol client: Agent Card
+//! discovery and validation, JSON-RPC task and message calls, task polling and
+//! streaming. Like the image providers, every call takes a [`Transport`], normally the
+//! privacy `HttpClient`.
+//!
+//! # Features
+//! - Agent Card discovery (`/.well-known/agent-card.json`, falling back to `agent.json`)
+//! - Optional DID-NOSTR authenticated Agent Cards (`did` + `signature` fields)
+//! - `message/send`, `message/stream` and legacy `tasks/send`
+//! - `tasks/get` polling, `tasks/resubscribe` streaming, `tasks/cancel`
+//! - Optional DID-NOSTR signing of every request (`X-DID` / `X-Signature`)
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use crate::did_nostr::{DidNostr, NostrSignature, NostrSigner, NostrVerifier, VerificationResult};
+use crate::interceptor::SigningInterceptor;
+use crate::sse;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+/// Agent Card locations, tried in order
+pub const AGENT_CARD_PATHS: &[&str] = &["/.well-known/agent-card.json", "/.well-known/agent.json"];
+
+/// Agent capabilities
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AgentCapabilities {
+    pub streaming: bool,
+    pub push_notifications: bool,
+    pub state_transition_history: bool,
+}
+
+/// Skill advertised by an agent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AgentSkill {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+/// Agent Card
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AgentCard {
+    pub name: String,
+    pub description: String,
+    /// JSON-RPC endpoint of the agent
+    pub url: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    pub capabilities: AgentCapabilities,
+    pub skills: Vec<AgentSkill>,
+    pub default_input_modes: Vec<String>,
+    pub default_output_modes: Vec<String>,
+    /// `did:nostr` identity of the agent (authenticated cards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+    /// Hex Schnorr signature over the canonical card (authenticated cards only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl AgentCard {
+    /// Parse and validate a card
+    pub fn from_value(value: Value) -> Result<Self, String> {
+        let card: AgentCard = serde_json::from_value(value).map_err(|e| format!("Invalid agent card: {}", e))?;
+        card.validate()?;
+        Ok(card)
+    }
+
+    /// Check required fields
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Agent card is missing a name".to_string());
+        }
+        if self.version.trim().is_empty() {
+            return Err("Agent card is missing a version".to_string());
+        }
+        let url = reqwest::Url::parse(&self.url).map_err(|e| format!("Invalid agent card url {}: {}", self.url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Unsupported agent card url scheme: {}", url.scheme()));
+        }
+        Ok(())
+    }
+}
+
+/// How Agent Card signatures are checked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CardVerification {
+    /// Verify signed cards, accept unsigned ones
+    #[default]
+    IfSigned,
+    /// Reject unsigned cards
+    Required,
+    /// Require a valid signature from this identity
+    Expected(DidNostr),
+}
+
+/// Canonical JSON of a card for signing: the `signature` field is removed and
+/// object keys are sorted recursively
+pub fn canonical_card(card: &Value) -> String {
+    let mut card = card.clone();
+    if let Some(object) = card.as_object_mut() {
+        object.remove("signature");
+    }
+    canonical_json(&card)
+}
+
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|k| format!("{}:{}", Value::String(k.clone()), canonical_json(&object[k])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(",")),
+        other => other.to_string(),
+    }
+}
+
+/// Add `did` and `signature` fields to a card
+pub fn sign_agent_card(signer: &NostrSigner, card: &mut Value) -> Result<(), String> {
+    let object = card.as_object_mut().ok_or("Agent card must be a JSON object")?;
+    object.insert("did".to_string(), Value::String(signer.did().to_string()));
+    let signature = signer.sign(&canonical_card(card));
+    card["signature"] = Value::String(signature.as_hex().to_string());
+    Ok(())
+}
+
+/// Verify the `did` and `signature` fields of a card
+pub fn verify_agent_card(card: &Value) -> VerificationResult {
+    let (Some(did), Some(signature)) = (card["did"].as_str(), card["signature"].as_str()) else {
+        return VerificationResult::failure("Agent card is not signed".to_string());
+    };
+    let did = match DidNostr::from_str(did) {
+        Ok(did) => did,
+        Err(e) => return VerificationResult::failure(e),
+    };
+    let signature = match NostrSignature::from_hex(signature) {
+        Ok(signature) => signature,
+        Err(e) => return VerificationResult::failure(e),
+    };
+    NostrVerifier::verify(did.pubkey(), &canonical_card(card), &signature)
+}
+
+/// Task lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskState {
+    Submitted,
+    Working,
+    InputRequired,
+    AuthRequired,
+    Completed,
+    Canceled,
+    Failed,
+    Rejected,
+    #[serde(other)]
+    Unknown,
+}
+
+impl TaskState {
+    /// Whether the task can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TaskState::Completed | TaskState::Canceled | TaskState::Failed | TaskState::Rejected)
+    }
+}
+
+/// Task status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatus {
+    pub state: TaskState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// A2A task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub artifacts: Vec<Value>,
+    #[serde(default)]
+    pub history: Vec<Value>,
+}
+
+impl Task {
+    /// Concatenated text parts of all artifacts
+    pub fn artifact_text(&self) -> String {
+        self.artifacts
+            .iter()
+            .flat_map(|artifact| artifact["parts"].as_array().cloned().unwrap_or_default())
+            .filter_map(|part| part["text"].as_str().map(str::to_string))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A2A message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub role: String,
+    pub parts: Vec<Value>,
+    #[serde(default)]
+    pub message_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+}
+
+impl Message {
+    /// User message with a single text part
+    pub fn user_text(text: &str) -> Self {
+        Self {
+            role: "user".to_string(),
+            parts: vec![json!({ "kind": "text", "text": text })],
+            message_id: new_message_id(),
+            context_id: None,
+            task_id: None,
+        }
+    }
+}
+
+fn new_message_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("msg-{:x}-{}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Result of `message/send`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendResult {
+    Task(Task),
+    Message(Message),
+}
+
+/// Event received from `message/stream` or `tasks/resubscribe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Task(Task),
+    Message(Message),
+    /// `status-update` event
+    Status { task_id: String, status: TaskStatus, is_final: bool },
+    /// `artifact-update` event
+    Artifact { task_id: String, artifact: Value },
+}
+
+fn parse_send_result(result: Value) -> Result<SendResult, String> {
+    if result["kind"] == "message" {
+        serde_json::from_value(result).map(SendResult::Message)
+    } else {
+        serde_json::from_value(result).map(SendResult::Task)
+    }
+    .map_err(|e| format!("Invalid A2A result: {}", e))
+}
+
+fn parse_stream_event(result: Value) -> Result<StreamEvent, String> {
+    let task_id = result["taskId"].as_str().unwrap_or_default().to_string();
+    match result["kind"].as_str() {
+        Some("status-update") => Ok(StreamEvent::Status {
+            task_id,
+            is_final: result["final"].as_bool().unwrap_or(false),
+            status: serde_json::from_value(result["status"].clone())
+                .map_err(|e| format!("Invalid A2A status update: {}", e))?,
+        }),
+        Some("artifact-update") => Ok(StreamEvent::Artifact { task_id, artifact: result["artifact"].clone() }),
+        _ => Ok(match parse_send_result(result)? {
+            SendResult::Task(task) => StreamEvent::Task(task),
+            SendResult::Message(message) => StreamEvent::Message(message),
+        }),
+    }
+}
+
+/// A2A client
+#[derive(Debug)]
+pub struct A2aClient {
+    url: String,
+    headers: Vec<(String, String)>,
+    signer: Option<SigningInterceptor>,
+    card: Option<AgentCard>,
+    next_id: AtomicU64,
+}
+
+impl A2aClient {
+    /// Client for a known JSON-RPC endpoint
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: Vec::new(),
+            signer: None,
+            card: None,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Discover an agent from its base URL; the card's `url` becomes the endpoint
+    pub fn discover(transport: &dyn Transport, base_url: &str, verification: &CardVerification) -> Result<Self, String> {
+        let card = fetch_agent_card(transport, base_url, verification)?;
+        let mut client = Self::new(&card.url);
+        client.card = Some(card);
+        Ok(client)
+    }
+
+    /// Extra headers sent with every request (e.g. `Authorization`)
+    pub fn with_headers(mut self, headers: &[(String, String)]) -> Self {
+        self.headers.extend_from_slice(headers);
+        self
+    }
+
+    /// Sign every request with a DID-NOSTR key. The signature is added as the
+    /// transport's innermost step, so it covers the request as sent.
+    pub fn with_signer(mut self, signer: NostrSigner) -> Self {
+        self.signer = Some(SigningInterceptor::new(signer));
+        self
+    }
+
+    /// JSON-RPC endpoint
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Agent Card (when created with [`A2aClient::discover`])
+    pub fn card(&self) -> Option<&AgentCard> {
+        self.card.as_ref()
+    }
+
+    /// Send a message (`message/send`)
+    pub fn send_message(&self, transport: &dyn Transport, message: &Message) -> Result<SendResult, String> {
+        parse_send_result(self.call(transport, "message/send", json!({ "message": message }))?)
+    }
+
+    /// Send a task with the pre-0.2 `tasks/send` method (used by our Node server)
+    pub fn send_task(&self, transport: &dyn Transport, task_id: &str, message: &Message) -> Result<Task, String> {
+        let result = self.call(transport, "tasks/send", json!({ "id": task_id, "message": message }))?;
+        serde_json::from_value(result).map_err(|e| format!("Invalid A2A task: {}", e))
+    }
+
+    /// Send a message and collect the streamed events (`message/stream`)
+    pub fn stream_message(&self, transport: &dyn Transport, message: &Message) -> Result<Vec<StreamEvent>, String> {
+        let mut events = Vec::new();
+        self.stream_message_with(transport, message, &mut |event| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// Send a message, handing each streamed event to `on_event` as it arrives
+    pub fn stream_message_with(
+        &self,
+        transport: &dyn Transport,
+        message: &Message,
+        on_event: &mut dyn FnMut(StreamEvent) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.stream(transport, "message/stream", json!({ "message": message }), on_event)
+    }
+
+    /// Get the current state of a task (`tasks/get`)
+    pub fn get_task(&self, transport: &dyn Transport, task_id: &str) -> Result<Task, String> {
+        let result = self.call(transport, "tasks/get", json!({ "id": task_id }))?;
+        serde_json::from_value(result).map_err(|e| format!("Invalid A2A task: {}", e))
+    }
+
+    /// Cancel a task (`tasks/cancel`)
+    pub fn cancel_task(&self, transport: &dyn Transport, task_id: &str) -> Result<Task, String> {
+        let result = self.call(transport, "tasks/cancel", json!({ "id": task_id }))?;
+        serde_json::from_value(result).map_err(|e| format!("Invalid A2A task: {}", e))
+    }
+
+    /// Collect the remaining events of a running task (`tasks/resubscribe`)
+    pub fn resubscribe(&self, transport: &dyn Transport, task_id: &str) -> Result<Vec<StreamEvent>, String> {
+        let mut events = Vec::new();
+        self.resubscribe_with(transport, task_id, &mut |event| {
+            events.push(event);
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// Hand the remaining events of a running task to `on_event` as they arrive
+    pub fn resubscribe_with(
+        &self,
+        transport: &dyn Transport,
+        task_id: &str,
+        on_event: &mut dyn FnMut(StreamEvent) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.stream(transport, "tasks/resubscribe", json!({ "id": task_id }), on_event)
+    }
+
+    /// Poll `tasks/get` until the task reaches a terminal state
+    pub fn wait_for_task(
+        &self,
+        transport: &dyn Transport,
+        task_id: &str,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Task, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let task = self.get_task(transport, task_id)?;
+            if task.status.state.is_terminal() || task.status.state == TaskState::InputRequired {
+                return Ok(task);
+            }
+            if Instant::now() + interval > deadline {
+                return Err(format!("Timed out waiting for task {}", task_id));
+            }
+            std::thread::sleep(interval);
+        }
+    }
+
+    /// Send a JSON-RPC request and return its result
+    pub fn call(&self, transport: &dyn Transport, method: &str, params: Value) -> Result<Value, String> {
+        let request = self.build_request(method, params);
+        let response = match &self.signer {
+            Some(signer) => transport.send_with(request, signer)?,
+            None => transport.send(request)?,
+        };
+        let reply = response.error_for_status()?.json()?;
+        rpc_result(reply)
+    }
+
+    /// Parse SSE events from the body as it arrives
+    fn stream(
+        &self,
+        transport: &dyn Transport,
+        method: &str,
+        params: Value,
+        on_event: &mut dyn FnMut(StreamEvent) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let request = self.build_request(method, params).with_header("Accept", "text/event-stream");
+        let mut parser = sse::SseParser::new();
+        let mut streamed = false;
+        let mut dispatch = |events: Vec<sse::SseEvent>| -> Result<(), String> {
+            for event in events {
+                let reply: Value = serde_json::from_str(&event.data).map_err(|e| format!("Invalid A2A event: {}", e))?;
+                on_event(parse_stream_event(rpc_result(reply)?)?)?;
+            }
+            Ok(())
+        };
+        let mut on_chunk = |head: &HttpResponse, chunk: &[u8]| {
+            streamed = head.is_success() && is_event_stream(head);
+            if streamed { dispatch(parser.feed(chunk)) } else { Ok(()) }
+        };
+        let response = match &self.signer {
+            Some(signer) => transport.send_streaming_with(request, signer, &mut on_chunk)?,
+            None => transport.send_streaming(request, &mut on_chunk)?,
+        };
+        if streamed {
+            return dispatch(parser.finish().into_iter().collect());
+        }
+        // Servers without streaming support answer with a single JSON-RPC response
+        let response = response.error_for_status()?;
+        on_event(parse_stream_event(rpc_result(response.json()?)?)?)
+    }
+
+    fn build_request(&self, method: &str, params: Value) -> HttpRequest {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string();
+        HttpRequest::post(&self.url, body)
+            .with_header("Content-Type", "application/json")
+            .with_headers(&self.headers)
+    }
+}
+
+fn is_event_stream(response: &HttpResponse) -> bool {
+    response
+        .header("Content-Type")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .starts_with("text/event-stream")
+}
+
+fn rpc_result(mut reply: Value) -> Result<Value, String> {
+    if let Some(error) = reply.get("error") {
+        return Err(format!(
+            "A2A error {}: {}",
+            error["code"],
+            error["message"].as_str().unwrap_or("Unknown error")
+        ));
+    }
+    reply
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| "A2A response has no result".to_string())
+}
+
+/// Fetch, validate and (optionally) verify an Agent Card
+pub fn fetch_agent_card(transport: &dyn Transport, base_url: &str, verification: &CardVerification) -> Result<AgentCard, String> {
+    let base = base_url.trim_end_matches('/');
+    let mut last_error = String::new();
+    for path in AGENT_CARD_PATHS {
+        let response = transport.send(HttpRequest::get(&format!("{}{}", base, path)).with_header("Accept", "application/json"))?;
+        if response.status == 404 {
+            last_error = format!("No agent card at {}{}", base, path);
+            continue;
+        }
+        let value = response.error_for_status()?.json()?;
+        check_card_signature(&value, verification)?;
+        return AgentCard::from_value(value);
+    }
+    Err(last_error)
+}
+
+fn check_card_signature(card: &Value, verification: &CardVerification) -> Result<(), String> {
+    let signed = card.get("signature").is_some();
+    if !signed && *verification == CardVerification::IfSigned {
+        return Ok(());
+    }
+    let result = verify_agent_card(card);
+    if !result.valid {
+        return Err(result.error.unwrap_or_else(|| "Invalid agent card signature".to_string()));
+    }
+    if let (CardVerification::Expected(expected), Some(did)) = (verification, &result.did)
+        && expected != did
+    {
+        return Err(format!("Agent card signed by {}, expected {}", did, expected));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::ChunkSink;
+    use std::cell::RefCell;
+
+    struct MockTransport {
+        requests: RefCell<Vec<HttpRequest>>,
+        responses: RefCell<Vec<HttpResponse>>,
+    }
+
+    impl MockTransport {
+        fn new(responses: Vec<(u16, &str, String)>) -> Self {
+            let responses = responses
+                .into_iter()
+                .rev()
+                .map(|(status, content_type, body)| HttpResponse {
+                    status,
+                    headers: vec![("Content-Type".to_string(), content_type.to_string())],
+                    body: body.into_bytes(),
+                })
+                .collect();
+            Self { requests: RefCell::new(Vec::new()), responses: RefCell::new(responses) }
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+            self.requests.borrow_mut().push(request);
+            self.responses.borrow_mut().pop().ok_or_else(|| "no response".to_string())
+        }
+    }
+
+    fn card_json() -> Value {
+        json!({
+            "name": "PrivacyServerJS",
+            "description": "Privacy-focused HTTP server with A2A support",
+            "url": "http://localhost:3000",
+            "version": "1.0.0",
+            "capabilities": { "streaming": false, "pushNotifications": false, "stateTransitionHistory": false }
+        })
+    }
+
+    #[test]
+    fn test_discover_falls_back_to_agent_json() {
+        let transport = MockTransport::new(vec![
+            (404, "text/plain", "not found".to_string()),
+            (200, "application/json", card_json().to_string()),
+        ]);
+        let client = A2aClient::discover(&transport, "http://localhost:3000/", &CardVerification::IfSigned).unwrap();
+        assert_eq!(client.card().unwrap().name, "PrivacyServerJS");
+        assert_eq!(client.url(), "http://localhost:3000");
+        let requests = transport.requests.borrow();
+        assert!(requests[0].url.ends_with("/.well-known/agent-card.json"));
+        assert!(requests[1].url.ends_with("/.well-known/agent.json"));
+    }
+
+    #[test]
+    fn test_card_validation() {
+        let mut card = card_json();
+        card["url"] = json!("ftp://example.com");
+        assert!(AgentCard::from_value(card).is_err());
+        assert!(AgentCard::from_value(json!({ "url": "https://a.example" })).is_err());
+    }
+
+    #[test]
+    fn test_signed_card_verification() {
+        let signer = NostrSigner::generate();
+        let mut card = card_json();
+        sign_agent_card(&signer, &mut card).unwrap();
+        assert!(verify_agent_card(&card).valid);
+        assert!(check_card_signature(&card, &CardVerification::Expected(signer.did())).is_ok());
+        assert!(check_card_signature(&card, &CardVerification::Expected(NostrSigner::generate().did())).is_err());
+
+        card["description"] = json!("tampered");
+        assert!(check_card_signature(&card, &CardVerification::IfSigned).is_err());
+        assert!(check_card_signature(&card_json(), &CardVerification::Required).is_err());
+    }
+
+    #[test]
+    fn test_send_task_legacy_server() {
+        let reply = json!({
+            "jsonrpc": "2.0", "id": 1,
+            "result": {
+                "id": "task-1",
+                "status": { "state": "completed", "timestamp": "2025-01-01T00:00:00Z" },
+                "artifacts": [{ "parts": [{ "type": "text", "text": "Processed: hi" }], "index": 0 }]
+            }
+        });
+        let transport = MockTransport::new(vec![(200, "application/json", reply.to_string())]);
+        let signer = NostrSigner::generate();
+        let client = A2aClient::new("http://localhost:3000/?agent=echo").with_signer(signer);
+        let task = client.send_task(&transport, "task-1", &Message::user_text("hi")).unwrap();
+        assert_eq!(task.status.state, TaskState::Completed);
+        assert_eq!(task.artifact_text(), "Processed: hi");
+
+        let sent = &transport.requests.borrow()[0];
+        let body: Value = serde_json::from_slice(&sent.body).unwrap();
+        assert_eq!(body["method"], "tasks/send");
+        assert_eq!(body["params"]["message"]["parts"][0]["text"], "hi");
+        let result = NostrVerifier::verify_request("POST", "/?agent=echo", &sent.headers, &sent.body);
+        assert!(result.valid);
+    }
+
+    #[test]
+    fn test_stream_and_cancel() {
+        let stream = [
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "kind": "task", "id": "t1", "status": { "state": "submitted" } } }),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "kind": "artifact-update", "taskId": "t1", "artifact": { "parts": [{ "kind": "text", "text": "partial" }] } } }),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "kind": "status-update", "taskId": "t1", "status": { "state": "completed" }, "final": true } }),
+        ]
+        .iter()
+        .map(|event| format!("data: {}\n\n", event))
+        .collect::<String>();
+        let canceled = json!({ "jsonrpc": "2.0", "id": 2, "result": { "id": "t1", "status": { "state": "canceled" } } });
+        let transport = MockTransport::new(vec![
+            (200, "text/event-stream", stream),
+            (200, "application/json", canceled.to_string()),
+        ]);
+        let client = A2aClient::new("https://agent.example/a2a");
+        let events = client.stream_message(&transport, &Message::user_text("go")).unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[1], StreamEvent::Artifact { task_id, .. } if task_id == "t1"));
+        assert!(matches!(&events[2], StreamEvent::Status { is_final: true, status, .. } if status.state == TaskState::Completed));
+
+        let task = client.cancel_task(&transport, "t1").unwrap();
+        assert!(task.status.state.is_terminal());
+    }
+
+    /// Delivers the body in small chunks and counts how many were handed out
+    struct ChunkedTransport {
+        body: String,
+        delivered: RefCell<usize>,
+    }
+
+    impl Transport for ChunkedTransport {
+        fn send(&self, _request: HttpRequest) -> Result<HttpResponse, String> {
+            unreachable!("streaming calls use send_streaming")
+        }
+
+        fn send_streaming(&self, _request: HttpRequest, on_chunk: &mut ChunkSink<'_>) -> Result<HttpResponse, String> {
+            let head = HttpResponse {
+                status: 200,
+                headers: vec![("Content-Type".to_string(), "text/event-stream".to_string())],
+                body: Vec::new(),
+            };
+            for chunk in self.body.as_bytes().chunks(7) {
+                *self.delivered.borrow_mut() += 1;
+                on_chunk(&head, chunk)?;
+            }
+            Ok(HttpResponse { body: self.body.clone().into_bytes(), ..head })
+        }
+    }
+
+    #[test]
+    fn test_stream_events_arrive_incrementally() {
+        let event = json!({ "jsonrpc": "2.0", "id": 1, "result": { "kind": "status-update", "taskId": "t1", "status": { "state": "working" } } });
+        let transport = ChunkedTransport { body: format!("data: {}\n\n", event).repeat(3), delivered: RefCell::new(0) };
+        let total = transport.body.len().div_ceil(7);
+        let client = A2aClient::new("https://agent.example/a2a");
+
+        let mut seen = 0;
+        let err = client
+            .stream_message_with(&transport, &Message::user_text("go"), &mut |event| {
+                assert!(matches!(event, StreamEvent::Status { .. }));
+                seen += 1;
+                Err("stop".to_string())
+            })
+            .unwrap_err();
+        assert_eq!((err.as_str(), seen), ("stop", 1));
+        // The first event was handled before the rest of the body was read
+        assert!(*transport.delivered.borrow() < total);
+
+        *transport.delivered.borrow_mut() = 0;
+        assert_eq!(client.resubscribe(&transport, "t1").unwrap().len(), 3);
+        assert_eq!(*transport.delivered.borrow(), total);
+    }
+
+    #[test]
+    fn test_wait_for_task_and_errors() {
+        let working = json!({ "jsonrpc": "2.0", "id": 1, "result": { "id": "t1", "status": { "state": "working" } } });
+        let done = json!({ "jsonrpc": "2.0", "id": 2, "result": { "id": "t1", "status": { "state": "completed" } } });
+        let error = json!({ "jsonrpc": "2.0", "id": 3, "error": { "code": -32001, "message": "Task not found" } });
+        let transport = MockTransport::new(vec![
+            (200, "application/json", working.to_string()),
+            (200, "application/json", done.to_string()),
+            (200, "application/json", error.to_string()),
+        ]);
+        let client = A2aClient::new("https://agent.example/a2a");
+        let task = client
+            .wait_for_task(&transport, "t1", Duration::from_millis(1), Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(task.status.state, TaskState::Completed);
+        let err = client.get_task(&transport, "missing").unwrap_err();
+        assert!(err.contains("Task not found"));
+    }
+}
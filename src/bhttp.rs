@@ -0,0 +1,283 @@
+//! Binary HTTP Module
+//!
+//! Binary HTTP message encoding (RFC 9292) used as the inner message format of
+//! Oblivious HTTP. Messages are encoded in known-length form; both known-length and
+//! indeterminate-length messages are decoded.
+
+use crate::transport::{HttpRequest, HttpResponse};
+
+const KNOWN_LENGTH_REQUEST: u64 = 0;
+const KNOWN_LENGTH_RESPONSE: u64 = 1;
+const INDETERMINATE_REQUEST: u64 = 2;
+const INDETERMINATE_RESPONSE: u64 = 3;
+
+/// Append a QUIC variable-length integer (RFC 9000, Section 16)
+pub fn write_varint(out: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0x3f => out.push(value as u8),
+        0x40..=0x3fff => out.extend_from_slice(&(value as u16 | 0x4000).to_be_bytes()),
+        0x4000..=0x3fff_ffff => out.extend_from_slice(&(value as u32 | 0x8000_0000).to_be_bytes()),
+        _ => out.extend_from_slice(&(value | 0xc000_0000_0000_0000).to_be_bytes()),
+    }
+}
+
+fn write_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    write_varint(out, data.len() as u64);
+    out.extend_from_slice(data);
+}
+
+fn write_fields(out: &mut Vec<u8>, fields: &[(String, String)]) {
+    let mut section = Vec::new();
+    for (name, value) in fields {
+        write_bytes(&mut section, name.to_ascii_lowercase().as_bytes());
+        write_bytes(&mut section, value.as_bytes());
+    }
+    write_bytes(out, &section);
+}
+
+/// Cursor over an encoded message
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        let end = end.ok_or("Truncated binary HTTP message")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let first = self.take(1)?[0];
+        let len = 1usize << (first >> 6);
+        let mut value = u64::from(first & 0x3f);
+        for byte in self.take(len - 1)? {
+            value = (value << 8) | u64::from(*byte);
+        }
+        Ok(value)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.varint()?;
+        self.take(usize::try_from(len).map_err(|_| "Length too large".to_string())?)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| "Invalid UTF-8 in binary HTTP message".to_string())
+    }
+
+    fn fields(&mut self, indeterminate: bool) -> Result<Vec<(String, String)>, String> {
+        // Messages may be truncated after any complete section
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut fields = Vec::new();
+        if indeterminate {
+            loop {
+                let name = self.string()?;
+                if name.is_empty() {
+                    break;
+                }
+                fields.push((name, self.string()?));
+            }
+        } else {
+            let mut section = Reader { data: self.bytes()?, pos: 0 };
+            while !section.is_empty() {
+                fields.push((section.string()?, section.string()?));
+            }
+        }
+        Ok(fields)
+    }
+
+    fn content(&mut self, indeterminate: bool) -> Result<Vec<u8>, String> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !indeterminate {
+            return Ok(self.bytes()?.to_vec());
+        }
+        let mut content = Vec::new();
+        loop {
+            let chunk = self.bytes()?;
+            if chunk.is_empty() {
+                return Ok(content);
+            }
+            content.extend_from_slice(chunk);
+        }
+    }
+}
+
+/// Encode a request in known-length form
+pub fn encode_request(request: &HttpRequest) -> Result<Vec<u8>, String> {
+    let url = reqwest::Url::parse(&request.url).map_err(|e| format!("Invalid URL {}: {}", request.url, e))?;
+    let authority = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => String::new(),
+    };
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut out = Vec::new();
+    write_varint(&mut out, KNOWN_LENGTH_REQUEST);
+    write_bytes(&mut out, request.method.as_bytes());
+    write_bytes(&mut out, url.scheme().as_bytes());
+    write_bytes(&mut out, authority.as_bytes());
+    write_bytes(&mut out, path.as_bytes());
+    write_fields(&mut out, &request.headers);
+    write_bytes(&mut out, &request.body);
+    write_varint(&mut out, 0);
+    Ok(out)
+}
+
+/// Decode a request
+pub fn decode_request(data: &[u8]) -> Result<HttpRequest, String> {
+    let mut reader = Reader { data, pos: 0 };
+    let indeterminate = match reader.varint()? {
+        KNOWN_LENGTH_REQUEST => false,
+        INDETERMINATE_REQUEST => true,
+        other => return Err(format!("Not a binary HTTP request (framing indicator {})", other)),
+    };
+    let method = reader.string()?;
+    let scheme = reader.string()?;
+    let authority = reader.string()?;
+    let path = reader.string()?;
+    let headers = reader.fields(indeterminate)?;
+    let body = reader.content(indeterminate)?;
+
+    let authority = if authority.is_empty() {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("host"))
+            .map(|(_, v)| v.clone())
+            .ok_or("Binary HTTP request has no authority")?
+    } else {
+        authority
+    };
+    let mut request = HttpRequest::new(&method, &format!("{}://{}{}", scheme, authority, path));
+    request.headers = headers;
+    request.body = body;
+    Ok(request)
+}
+
+/// Encode a response in known-length form
+pub fn encode_response(response: &HttpResponse) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, KNOWN_LENGTH_RESPONSE);
+    write_varint(&mut out, u64::from(response.status));
+    write_fields(&mut out, &response.headers);
+    write_bytes(&mut out, &response.body);
+    write_varint(&mut out, 0);
+    out
+}
+
+/// Decode a response (informational responses are skipped)
+pub fn decode_response(data: &[u8]) -> Result<HttpResponse, String> {
+    let mut reader = Reader { data, pos: 0 };
+    let indeterminate = match reader.varint()? {
+        KNOWN_LENGTH_RESPONSE => false,
+        INDETERMINATE_RESPONSE => true,
+        other => return Err(format!("Not a binary HTTP response (framing indicator {})", other)),
+    };
+    let status = loop {
+        let status = reader.varint()?;
+        if !(100..200).contains(&status) {
+            break status;
+        }
+        reader.fields(indeterminate)?;
+    };
+    let status = u16::try_from(status)
+        .ok()
+        .filter(|s| (200..600).contains(s))
+        .ok_or_else(|| format!("Invalid status code {}", status))?;
+    let headers = reader.fields(indeterminate)?;
+    let body = reader.content(indeterminate)?;
+    Ok(HttpResponse { status, headers, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        for (value, encoded) in [
+            (37u64, vec![0x25]),
+            (15293, vec![0x7b, 0xbd]),
+            (494878333, vec![0x9d, 0x7f, 0x3e, 0x7d]),
+            (151288809941952652, vec![0xc2, 0x19, 0x7c, 0x5e, 0xff, 0x14, 0xe8, 0x8c]),
+        ] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            assert_eq!(out, encoded);
+            assert_eq!(Reader { data: &encoded, pos: 0 }.varint().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_request_roundtrip() {
+        let request = HttpRequest::post("https://api.example.com:8443/v1/chat?x=1", "{}")
+            .with_header("content-type", "application/json");
+        let decoded = decode_request(&encode_request(&request).unwrap()).unwrap();
+        assert_eq!(decoded, request);
+    }
+
+    #[test]
+    fn test_rfc9292_indeterminate_request() {
+        // RFC 9292, Section 5.1 (GET https://www.example.com/hello.txt, truncated)
+        let mut data = vec![0x02, 0x03];
+        data.extend_from_slice(b"GET");
+        data.push(0x05);
+        data.extend_from_slice(b"https");
+        data.push(0x00);
+        data.push(0x0a);
+        data.extend_from_slice(b"/hello.txt");
+        data.push(0x0a);
+        data.extend_from_slice(b"user-agent");
+        data.push(0x34);
+        data.extend_from_slice(b"curl/7.16.3 libcurl/7.16.3 OpenSSL/0.9.7l zlib/1.2.3");
+        data.push(0x04);
+        data.extend_from_slice(b"host");
+        data.push(0x0f);
+        data.extend_from_slice(b"www.example.com");
+        data.push(0x0f);
+        data.extend_from_slice(b"accept-language");
+        data.push(0x06);
+        data.extend_from_slice(b"en, mi");
+        data.extend_from_slice(&[0x00, 0x00, 0x00]);
+
+        let request = decode_request(&data).unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://www.example.com/hello.txt");
+        assert_eq!(request.header("accept-language"), Some("en, mi"));
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn test_response_roundtrip_and_informational() {
+        let response = HttpResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "text/plain".to_string())],
+            body: b"hello".to_vec(),
+        };
+        let encoded = encode_response(&response);
+        assert_eq!(decode_response(&encoded).unwrap(), response);
+
+        // 103 Early Hints followed by the final 200
+        let mut with_hints = vec![0x01];
+        write_varint(&mut with_hints, 103);
+        write_fields(&mut with_hints, &[("link".to_string(), "</a.css>".to_string())]);
+        with_hints.extend_from_slice(&encoded[1..]);
+        assert_eq!(decode_response(&with_hints).unwrap(), response);
+        assert!(decode_response(&[0x00]).is_err());
+    }
+}
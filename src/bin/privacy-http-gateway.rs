@@ -0,0 +1,77 @@
+//! privacy-http-gateway
+//!
+//! Reverse proxy in front of the `http-js`, `http-py` and `http-c++` servers: terminates
+//! TLS, verifies DID-NOSTR signatures, strips client-identifying headers and forwards
+//! the verified caller as `X-Verified-DID`.
+//!
+//! ```text
+//! privacy-http-gateway [--config FILE] [--listen ADDR] [--upstream URL]
+//! ```
+
+use privacy_http_sdk::gateway::{Gateway, GatewayConfig};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const USAGE: &str = "Usage: privacy-http-gateway [options]
+
+Options:
+  --config FILE   Gateway config (JSON); defaults apply when omitted
+  --listen ADDR   Listen address (default 127.0.0.1:8443)
+  --upstream URL  Upstream base URL (overrides the config)
+  -h, --help      Show this help";
+
+struct Options {
+    config: Option<String>,
+    listen: String,
+    upstream: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options { config: None, listen: "127.0.0.1:8443".to_string(), upstream: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        match arg.as_str() {
+            "--config" => options.config = Some(value("--config")?),
+            "--listen" => options.listen = value("--listen")?,
+            "--upstream" => options.upstream = Some(value("--upstream")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+    Ok(options)
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    let mut config = match &options.config {
+        Some(path) => GatewayConfig::from_file(path)?,
+        None => GatewayConfig::default(),
+    };
+    if let Some(upstream) = options.upstream {
+        config.upstream = upstream;
+    }
+    let scheme = if config.tls.is_some() { "https" } else { "http" };
+    let upstream = config.upstream.clone();
+    let gateway = Gateway::new(config)?;
+
+    let listener = TcpListener::bind(&options.listen)
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", options.listen, e))?;
+    eprintln!("privacy-http-gateway listening on {}://{} -> {}", scheme, options.listen, upstream);
+    Arc::new(gateway).serve(listener).await
+}
+
+#[tokio::main]
+async fn main() {
+    let result = match parse_args() {
+        Ok(options) => run(options).await,
+        Err(e) => Err(format!("{}\n\n{}", e, USAGE)),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,98 @@
+//! privacy-http-proxy
+//!
+//! Local HTTP/CONNECT forward proxy applying the privacy policy (spec.md JSON config)
+//! to every application pointed at it.
+//!
+//! ```text
+//! privacy-http-proxy [--config FILE] [--listen ADDR] [--mitm] [--ca-dir DIR] [--print-ca]
+//! ```
+
+use privacy_http_sdk::forward_proxy::ForwardProxy;
+use privacy_http_sdk::mitm::{CA_CERT_FILE, CertificateAuthority};
+use privacy_http_sdk::privacy::{PrivacyConfig, PrivacyPolicy};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const USAGE: &str = "Usage: privacy-http-proxy [options]
+
+Options:
+  --config FILE   Privacy config (spec.md JSON); defaults apply when omitted
+  --listen ADDR   Listen address (default 127.0.0.1:8899)
+  --mitm          Intercept HTTPS with a local CA so the policy applies inside TLS
+  --ca-dir DIR    CA directory (default ./privacy-proxy-ca)
+  --print-ca      Print the CA certificate (PEM) and exit
+  -h, --help      Show this help";
+
+const CA_NAME: &str = "Privacy HTTP Proxy CA";
+
+struct Options {
+    config: Option<String>,
+    listen: String,
+    mitm: bool,
+    ca_dir: String,
+    print_ca: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        config: None,
+        listen: "127.0.0.1:8899".to_string(),
+        mitm: false,
+        ca_dir: "privacy-proxy-ca".to_string(),
+        print_ca: false,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        match arg.as_str() {
+            "--config" => options.config = Some(value("--config")?),
+            "--listen" => options.listen = value("--listen")?,
+            "--ca-dir" => options.ca_dir = value("--ca-dir")?,
+            "--mitm" => options.mitm = true,
+            "--print-ca" => options.print_ca = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+    Ok(options)
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    if options.print_ca {
+        let ca = CertificateAuthority::load_or_create(&options.ca_dir, CA_NAME)?;
+        print!("{}", ca.cert_pem());
+        return Ok(());
+    }
+
+    let config = match &options.config {
+        Some(path) => PrivacyConfig::from_file(path)?,
+        None => PrivacyConfig::default(),
+    };
+    let mut proxy = ForwardProxy::new(PrivacyPolicy::new(config))?;
+    if options.mitm {
+        let ca = CertificateAuthority::load_or_create(&options.ca_dir, CA_NAME)?;
+        eprintln!("Intercepting HTTPS; trust {}/{} in your clients", options.ca_dir, CA_CERT_FILE);
+        proxy = proxy.with_mitm(Arc::new(ca));
+    }
+
+    let listener = TcpListener::bind(&options.listen)
+        .await
+        .map_err(|e| format!("Failed to listen on {}: {}", options.listen, e))?;
+    eprintln!("privacy-http-proxy listening on {}", options.listen);
+    Arc::new(proxy).serve(listener).await
+}
+
+#[tokio::main]
+async fn main() {
+    let result = match parse_args() {
+        Ok(options) => run(options).await,
+        Err(e) => Err(format!("{}\n\n{}", e, USAGE)),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,187 @@
+//! privacy-http
+//!
+//! curl-like client sending requests through `HttpClient`, to see exactly what the SDK
+//! sends under a privacy config.
+//!
+//! ```text
+//! privacy-http [options] URL
+//! ```
+
+use privacy_http_sdk::HttpClient;
+use privacy_http_sdk::did_nostr::NostrSigner;
+use privacy_http_sdk::interceptor::{InterceptorStage, SigningInterceptor};
+use privacy_http_sdk::privacy::{PrivacyConfig, PrivacyPolicy};
+use privacy_http_sdk::transport::{HttpRequest, Transport};
+use std::io::Write;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: privacy-http [options] URL
+
+Options:
+  -X, --request METHOD     Request method (default GET, or POST/PUT with a body)
+  -H, --header 'K: V'      Add a request header (repeatable)
+  -d, --data DATA          Request body; @FILE reads it from a file
+  -T, --upload-file FILE   Upload a file as the body (default method PUT)
+      --config FILE        Privacy config (spec.md JSON)
+      --nostr-key-file PATH
+                           Sign the request with the hex Nostr secret key
+                           in PATH (or set PRIVACY_HTTP_NOSTR_KEY)
+      --explain            Print which headers the policy stripped or rewrote
+      --dry-run            Print the final request without sending it
+  -i, --include            Print response status and headers
+      --pretty             Pretty-print JSON responses
+  -h, --help               Show this help";
+
+#[derive(Default)]
+struct Options {
+    url: Option<String>,
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+    upload: bool,
+    config: Option<String>,
+    nostr_key: Option<String>,
+    explain: bool,
+    dry_run: bool,
+    include: bool,
+    pretty: bool,
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options { nostr_key: std::env::var("PRIVACY_HTTP_NOSTR_KEY").ok(), ..Options::default() };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        match arg.as_str() {
+            "-X" | "--request" => options.method = Some(value("--request")?.to_uppercase()),
+            "-H" | "--header" => {
+                let header = value("--header")?;
+                let (name, value) = header.split_once(':').ok_or_else(|| format!("Invalid header: {}", header))?;
+                options.headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            "-d" | "--data" => {
+                let data = value("--data")?;
+                options.body = Some(match data.strip_prefix('@') {
+                    Some(path) => read_file(path)?,
+                    None => data.into_bytes(),
+                });
+            }
+            "-T" | "--upload-file" => {
+                options.body = Some(read_file(&value("--upload-file")?)?);
+                options.upload = true;
+            }
+            "--config" => options.config = Some(value("--config")?),
+            "--nostr-key-file" => {
+                let path = value("--nostr-key-file")?;
+                let key = String::from_utf8(read_file(&path)?).map_err(|_| format!("{} is not a hex key", path))?;
+                options.nostr_key = Some(key);
+            }
+            "--explain" => options.explain = true,
+            "--dry-run" => options.dry_run = true,
+            "-i" | "--include" => options.include = true,
+            "--pretty" => options.pretty = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other if other.starts_with('-') => return Err(format!("Unknown argument: {}", other)),
+            url if options.url.is_none() => options.url = Some(url.to_string()),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+    Ok(options)
+}
+
+fn print_headers(headers: &[(String, String)]) {
+    for (name, value) in headers {
+        println!("{}: {}", name, value);
+    }
+}
+
+fn print_body(body: &[u8], pretty: bool) {
+    let pretty_json = pretty
+        .then(|| serde_json::from_slice::<serde_json::Value>(body).ok())
+        .flatten()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok());
+    match pretty_json {
+        Some(json) => println!("{}", json),
+        None => {
+            // Raw bytes, so binary bodies can be redirected to a file
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(body).and_then(|_| stdout.flush());
+        }
+    }
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let url = options.url.ok_or("Missing URL")?;
+    let method = options.method.unwrap_or_else(|| match (&options.body, options.upload) {
+        (Some(_), true) => "PUT".to_string(),
+        (Some(_), false) => "POST".to_string(),
+        (None, _) => "GET".to_string(),
+    });
+    let body = options.body.unwrap_or_default();
+
+    let mut client = HttpClient::new(String::new());
+    let policy = match &options.config {
+        Some(path) => PrivacyPolicy::new(PrivacyConfig::from_file(path)?),
+        None => PrivacyPolicy::default(),
+    };
+    client.set_privacy_policy(policy.clone())?;
+
+    if let Some(key) = &options.nostr_key {
+        let signer = NostrSigner::from_secret_hex(key.trim())?;
+        if options.explain {
+            eprintln!("* signing as {}", signer.did());
+        }
+        // Inner stage: the signature covers the request as finally sent
+        client.add_interceptor(InterceptorStage::Inner, Arc::new(SigningInterceptor::new(signer)));
+    }
+
+    let request = HttpRequest::new(&method, &url).with_headers(&options.headers).with_body(body);
+
+    if options.explain {
+        let report = policy.explain(&mut request.clone());
+        for (name, rule) in &report.removed {
+            eprintln!("* removed {} ({})", name, rule);
+        }
+        for (name, value) in &report.rewritten {
+            eprintln!("* rewrote {}: {}", name, value);
+        }
+        if report.removed.is_empty() && report.rewritten.is_empty() {
+            eprintln!("* no headers changed by the privacy policy");
+        }
+    }
+    if options.dry_run {
+        // Only prepared when it is not sent, so stateful interceptors run once
+        let (prepared, _) = client.prepare(request)?;
+        println!("{} {}", prepared.method, prepared.url);
+        print_headers(&prepared.headers);
+        if !prepared.body.is_empty() {
+            println!();
+            print_body(&prepared.body, options.pretty);
+        }
+        return Ok(());
+    }
+
+    let response = client.send(request)?;
+    if options.include {
+        println!("HTTP {}", response.status);
+        print_headers(&response.headers);
+        println!();
+    }
+    print_body(&response.body, options.pretty);
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(e) = result {
+        eprintln!("privacy-http: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,12 @@
+//! uniffi-bindgen
+//!
+//! UniFFI binding generator matching the crate's uniffi version (see `uniffi_api`).
+//!
+//! ```text
+//! cargo run --features uniffi-cli --bin uniffi-bindgen -- generate \
+//!     --library target/release/libprivacy_http_sdk.so --language swift --out-dir bindings/swift
+//! ```
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,339 @@
+//! C ABI for `HttpClient`
+//!
+//! Clients are opaque `phttp_client` handles built from a `ClientConfig` JSON string.
+//! Every fallible function returns a `PHTTP_*` status code (`PHTTP_OK` is 0) and writes
+//! its result through an out-pointer; on failure the message is kept per thread and
+//! read with `phttp_last_error()`. Everything the library hands out is released with
+//! its matching `*_free` function. A string result containing a NUL byte cannot be
+//! handed out intact, so the call fails with `PHTTP_ERR_INVALID` instead.
+//!
+//! The header is `include/privacy_http.h`
+//! (`cbindgen --config cbindgen.toml --output include/privacy_http.h`).
+
+#![allow(non_camel_case_types)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use crate::HttpClient;
+use crate::config::ClientConfig;
+use crate::transport::{HttpRequest, Transport};
+
+/// Success
+pub const PHTTP_OK: i32 = 0;
+/// A required pointer argument was NULL
+pub const PHTTP_ERR_NULL: i32 = 1;
+/// A string argument was not valid UTF-8
+pub const PHTTP_ERR_UTF8: i32 = 2;
+/// The client config was rejected
+pub const PHTTP_ERR_CONFIG: i32 = 3;
+/// The request was blocked by a privacy rule or failed to send
+pub const PHTTP_ERR_REQUEST: i32 = 4;
+/// An argument had an invalid value (e.g. a malformed DID)
+pub const PHTTP_ERR_INVALID: i32 = 5;
+/// The library panicked; the handle involved should not be used again
+pub const PHTTP_ERR_PANIC: i32 = 6;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Failed call: status code and message
+pub(crate) type FfiError = (i32, String);
+
+/// Run an FFI body, turning errors and panics into a status code and last error
+pub(crate) fn ffi_call(body: impl FnOnce() -> Result<(), FfiError>) -> i32 {
+    let (code, message) = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return PHTTP_OK,
+        Ok(Err(error)) => error,
+        Err(_) => (PHTTP_ERR_PANIC, "Internal error (panic)".to_string()),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(c_string(&message)));
+    code
+}
+
+/// C string with interior NULs dropped (for error messages)
+pub(crate) fn c_string(value: &str) -> CString {
+    CString::new(value.replace('\0', "")).unwrap_or_default()
+}
+
+/// C string for a result, or `PHTTP_ERR_INVALID` if a NUL byte would cut it short
+pub(crate) fn out_string(value: &str, name: &str) -> Result<CString, FfiError> {
+    CString::new(value).map_err(|_| (PHTTP_ERR_INVALID, format!("{} contains a NUL byte", name)))
+}
+
+/// Borrow a C string argument
+///
+/// # Safety
+/// `ptr` must be NULL or a valid NUL-terminated string outliving the returned borrow
+pub(crate) unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err((PHTTP_ERR_NULL, format!("{} is NULL", name)));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| (PHTTP_ERR_UTF8, format!("{} is not valid UTF-8", name)))
+}
+
+/// Check an out-pointer argument
+pub(crate) fn check_out<T>(out: *mut T) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err((PHTTP_ERR_NULL, "out is NULL".to_string()));
+    }
+    Ok(())
+}
+
+/// Borrow a (pointer, length) array argument; NULL is allowed when empty
+///
+/// # Safety
+/// `ptr` must point to `len` initialized values outliving the returned borrow
+unsafe fn read_slice<'a, T>(ptr: *const T, len: usize, name: &str) -> Result<&'a [T], FfiError> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err((PHTTP_ERR_NULL, format!("{} is NULL", name))),
+        (false, _) => Ok(unsafe { std::slice::from_raw_parts(ptr, len) }),
+    }
+}
+
+/// Opaque client handle
+pub struct phttp_client {
+    inner: HttpClient,
+}
+
+/// Header name/value pair
+#[repr(C)]
+pub struct phttp_header {
+    pub name: *const c_char,
+    pub value: *const c_char,
+}
+
+/// Request to send; all memory stays owned by the caller
+#[repr(C)]
+pub struct phttp_request {
+    /// Method (NULL: GET)
+    pub method: *const c_char,
+    pub url: *const c_char,
+    pub headers: *const phttp_header,
+    pub headers_len: usize,
+    pub body: *const u8,
+    pub body_len: usize,
+}
+
+/// Response owned by the library; release with `phttp_response_free`
+#[repr(C)]
+pub struct phttp_response {
+    pub status: u16,
+    pub headers: *mut phttp_header,
+    pub headers_len: usize,
+    pub body: *mut u8,
+    pub body_len: usize,
+}
+
+/// Message of the last failed call on this thread (NULL if none). The string is owned
+/// by the library and valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn phttp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Create a client from a `ClientConfig` JSON string (NULL: defaults)
+///
+/// # Safety
+/// `config_json` must be NULL or a valid NUL-terminated string; `out` must be writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn phttp_client_new(config_json: *const c_char, out: *mut *mut phttp_client) -> i32 {
+    ffi_call(|| {
+        check_out(out)?;
+        let config = match config_json.is_null() {
+            true => ClientConfig::default(),
+            false => {
+                let json = unsafe { read_str(config_json, "config_json") }?;
+                ClientConfig::from_json(json).map_err(|e| (PHTTP_ERR_CONFIG, e))?
+            }
+        };
+        let inner = HttpClient::from_config(config).map_err(|e| (PHTTP_ERR_CONFIG, e))?;
+        unsafe { *out = Box::into_raw(Box::new(phttp_client { inner })) };
+        Ok(())
+    })
+}
+
+/// Release a client (NULL is ignored)
+///
+/// # Safety
+/// `client` must come from `phttp_client_new`, must not be in use and is invalid afterwards
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn phttp_client_free(client: *mut phttp_client) {
+    if !client.is_null() {
+        drop(unsafe { Box::from_raw(client) });
+    }
+}
+
+/// Send a request through the client's privacy pipeline. Blocks until the response
+/// arrives. A handle must not be used from several threads at once. Fails with
+/// `PHTTP_ERR_INVALID` if a response header contains a NUL byte.
+///
+/// # Safety
+/// `client` must be a live handle, `request` must point to a valid `phttp_request`
+/// and `out` must be writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn phttp_client_send(
+    client: *const phttp_client,
+    request: *const phttp_request,
+    out: *mut *mut phttp_response,
+) -> i32 {
+    ffi_call(|| {
+        check_out(out)?;
+        let client = unsafe { client.as_ref() }.ok_or((PHTTP_ERR_NULL, "client is NULL".to_string()))?;
+        let request = unsafe { request.as_ref() }.ok_or((PHTTP_ERR_NULL, "request is NULL".to_string()))?;
+        let method = match request.method.is_null() {
+            true => "GET",
+            false => unsafe { read_str(request.method, "method") }?,
+        };
+        let url = unsafe { read_str(request.url, "url") }?;
+        let headers = unsafe { read_slice(request.headers, request.headers_len, "headers") }?
+            .iter()
+            .map(|header| Ok((unsafe { read_str(header.name, "header name") }?.to_string(), unsafe { read_str(header.value, "header value") }?.to_string())))
+            .collect::<Result<Vec<_>, FfiError>>()?;
+        let body = unsafe { read_slice(request.body, request.body_len, "body") }?.to_vec();
+
+        let request = HttpRequest::new(method, url).with_headers(&headers).with_body(body);
+        let response = client.inner.send(request).map_err(|e| (PHTTP_ERR_REQUEST, e))?;
+
+        let headers = response
+            .headers
+            .iter()
+            .map(|(name, value)| Ok((out_string(name, "Response header name")?, out_string(value, "Response header value")?)))
+            .collect::<Result<Vec<_>, FfiError>>()?;
+        let headers: Box<[phttp_header]> = headers
+            .into_iter()
+            .map(|(name, value)| phttp_header { name: name.into_raw(), value: value.into_raw() })
+            .collect();
+        let body = response.body.into_boxed_slice();
+        let response = phttp_response {
+            status: response.status,
+            headers_len: headers.len(),
+            headers: Box::into_raw(headers) as *mut phttp_header,
+            body_len: body.len(),
+            body: Box::into_raw(body) as *mut u8,
+        };
+        unsafe { *out = Box::into_raw(Box::new(response)) };
+        Ok(())
+    })
+}
+
+/// Release a response (NULL is ignored)
+///
+/// # Safety
+/// `response` must come from `phttp_client_send` and is invalid afterwards
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn phttp_response_free(response: *mut phttp_response) {
+    if response.is_null() {
+        return;
+    }
+    let response = unsafe { Box::from_raw(response) };
+    let headers = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(response.headers, response.headers_len)) };
+    for header in headers.iter() {
+        drop(unsafe { CString::from_raw(header.name as *mut c_char) });
+        drop(unsafe { CString::from_raw(header.value as *mut c_char) });
+    }
+    drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(response.body, response.body_len)) });
+}
+
+/// Release a string returned by the library (NULL is ignored)
+///
+/// # Safety
+/// `ptr` must come from a `phttp_*` or `did_nostr_*` out-parameter and is invalid afterwards
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn phttp_string_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(unsafe { CString::from_raw(ptr) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(phttp_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_errors_are_reported_by_code() {
+        let mut client = ptr::null_mut();
+        let config = c_string(r#"{"retries": {"max_retries": "many"}}"#);
+        assert_eq!(unsafe { phttp_client_new(config.as_ptr(), &mut client) }, PHTTP_ERR_CONFIG);
+        assert!(client.is_null());
+        assert!(last_error().contains("Invalid retry config"));
+
+        assert_eq!(unsafe { phttp_client_new(ptr::null(), ptr::null_mut()) }, PHTTP_ERR_NULL);
+        assert_eq!(last_error(), "out is NULL");
+
+        // Default policy: TLS is enforced for non-loopback hosts
+        assert_eq!(unsafe { phttp_client_new(ptr::null(), &mut client) }, PHTTP_OK);
+        let url = c_string("http://example.com/");
+        let request = phttp_request {
+            method: ptr::null(),
+            url: url.as_ptr(),
+            headers: ptr::null(),
+            headers_len: 0,
+            body: ptr::null(),
+            body_len: 0,
+        };
+        let mut response = ptr::null_mut();
+        assert_eq!(unsafe { phttp_client_send(client, &request, &mut response) }, PHTTP_ERR_REQUEST);
+        assert!(response.is_null());
+        unsafe { phttp_client_free(client) };
+    }
+
+    #[test]
+    fn test_send_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !String::from_utf8_lossy(&request).contains("ping") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 201 Created\r\nX-Reply: yes\r\nContent-Length: 4\r\nConnection: close\r\n\r\npong").unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        let mut client = ptr::null_mut();
+        assert_eq!(unsafe { phttp_client_new(c_string("{}").as_ptr(), &mut client) }, PHTTP_OK);
+        let (method, url) = (c_string("POST"), c_string(&format!("http://127.0.0.1:{}/echo", port)));
+        let (name, value) = (c_string("X-Trace"), c_string("abc"));
+        let headers = [phttp_header { name: name.as_ptr(), value: value.as_ptr() }];
+        let request = phttp_request {
+            method: method.as_ptr(),
+            url: url.as_ptr(),
+            headers: headers.as_ptr(),
+            headers_len: headers.len(),
+            body: b"ping".as_ptr(),
+            body_len: 4,
+        };
+        let mut response = ptr::null_mut();
+        assert_eq!(unsafe { phttp_client_send(client, &request, &mut response) }, PHTTP_OK, "{}", last_error());
+
+        let sent = server.join().unwrap();
+        assert!(sent.starts_with("POST /echo"));
+        assert!(sent.to_ascii_lowercase().contains("x-trace: abc"));
+
+        let received = unsafe { &*response };
+        assert_eq!(received.status, 201);
+        assert_eq!(unsafe { std::slice::from_raw_parts(received.body, received.body_len) }, b"pong");
+        let headers = unsafe { std::slice::from_raw_parts(received.headers, received.headers_len) };
+        assert!(headers.iter().any(|h| unsafe { CStr::from_ptr(h.name) }.to_str() == Ok("x-reply")));
+        unsafe {
+            phttp_response_free(response);
+            phttp_client_free(client);
+        }
+    }
+}
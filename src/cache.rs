@@ -0,0 +1,539 @@
+//! HTTP Cache Module
+//!
+//! Private HTTP cache (RFC 9111) for `HttpClient`, configured with `configure_cache`.
+//! Only GET responses with explicit freshness (or validators) are stored.
+//!
+//! # Features
+//! - `Cache-Control` (`max-age`, `no-store`, `no-cache`), `Expires`, `Age`
+//! - `Vary` matching on request headers
+//! - Revalidation with `ETag` / `Last-Modified` (304 responses refresh the entry)
+//! - Memory or disk storage; disk entries are AES-GCM encrypted and named by an
+//!   HMAC of the URL, so file names do not reveal which URLs were visited. Each entry
+//!   holds its URL, so an entry copied to another URL's file name is not served
+//! - Requests carrying `Authorization` or `Cookie` (and responses setting cookies)
+//!   are not cached unless `cache_authenticated` is set
+//! - Validators are only sent to first-party sites: ETags can act as supercookies
+
+use crate::codec::AesGcmCodec;
+use crate::transport::{HttpRequest, HttpResponse};
+use base64::Engine;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// HKDF labels for the subkeys derived from `disk_key`
+const CIPHER_KEY_INFO: &[u8] = b"privacy_http_sdk cache cipher key";
+const NAME_KEY_INFO: &[u8] = b"privacy_http_sdk cache file-name key";
+
+/// Statuses cacheable by default (RFC 9110, Section 15.1)
+const HEURISTICALLY_CACHEABLE: &[u16] = &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// Cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Maximum number of stored URLs
+    pub max_entries: usize,
+    /// Directory for encrypted entries; `None` keeps the cache in memory
+    pub disk_path: Option<String>,
+    /// Base64 32-byte AES-GCM key for `disk_path`
+    #[serde(skip_serializing)]
+    pub disk_key: Option<String>,
+    /// Cache responses to requests with `Authorization`/`Cookie` and responses with `Set-Cookie`
+    pub cache_authenticated: bool,
+    /// Host patterns (exact host, `*.suffix`, or `*`) that receive stored validators
+    /// besides the top-level site
+    pub revalidate_hosts: Vec<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 512,
+            disk_path: None,
+            disk_key: None,
+            cache_authenticated: false,
+            revalidate_hosts: Vec::new(),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: CacheConfig = serde_json::from_str(json).map_err(|e| format!("Invalid cache config: {}", e))?;
+        if config.disk_path.is_some() {
+            AesGcmCodec::from_base64(config.disk_key.as_deref().ok_or("disk_path requires disk_key")?)?;
+        }
+        Ok(config)
+    }
+}
+
+/// Stored response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Request header values selected by `Vary`
+    vary: Vec<(String, Option<String>)>,
+    /// Unix time the response was received
+    stored_at: u64,
+    /// Age of the response when received
+    initial_age: u64,
+    /// Freshness lifetime in seconds (0: always revalidate)
+    lifetime: u64,
+}
+
+impl CachedResponse {
+    fn is_fresh(&self, now: u64) -> bool {
+        self.initial_age + now.saturating_sub(self.stored_at) < self.lifetime
+    }
+
+    fn response(&self) -> HttpResponse {
+        HttpResponse { status: self.status, headers: self.headers.clone(), body: self.body.clone() }
+    }
+
+    fn matches(&self, request: &HttpRequest) -> bool {
+        self.vary.iter().all(|(name, value)| request.header(name) == value.as_deref())
+    }
+}
+
+/// Result of a cache lookup
+#[derive(Debug)]
+pub enum CacheLookup {
+    /// Stored response that may be used without contacting the server
+    Fresh(HttpResponse),
+    /// Stored response needing revalidation; the request now carries validators
+    Stale,
+    Miss,
+}
+
+enum Store {
+    Memory { entries: HashMap<String, Vec<CachedResponse>>, order: VecDeque<String> },
+    Disk { dir: std::path::PathBuf, codec: Box<AesGcmCodec>, name_key: [u8; 32] },
+}
+
+/// 32-byte HKDF-SHA256 subkey of `disk_key`
+fn derive_subkey(master: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut subkey = [0u8; 32];
+    Hkdf::<Sha256>::new(None, master)
+        .expand(info, &mut subkey)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    subkey
+}
+
+/// Encrypted contents of a disk entry. The key is checked on load, since the file
+/// name alone does not bind the ciphertext to its URL.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    variants: Vec<CachedResponse>,
+}
+
+/// Hex HMAC-SHA256 of a cache key, used as its file name
+fn file_name(name_key: &[u8], key: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(name_key).expect("HMAC accepts any key length");
+    mac.update(key.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Private HTTP cache
+pub struct HttpCache {
+    config: CacheConfig,
+    store: Mutex<Store>,
+}
+
+impl std::fmt::Debug for HttpCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpCache").field("config", &self.config).finish()
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// `Cache-Control` directives (lowercase names, unquoted values)
+fn directives(value: Option<&str>) -> HashMap<String, Option<String>> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| match d.split_once('=') {
+            Some((name, value)) => (name.trim().to_ascii_lowercase(), Some(value.trim().trim_matches('"').to_string())),
+            None => (d.trim().to_ascii_lowercase(), None),
+        })
+        .collect()
+}
+
+fn cache_key(url: &str) -> String {
+    // Fragments never reach the server
+    url.split('#').next().unwrap_or(url).to_string()
+}
+
+impl HttpCache {
+    pub fn new(config: CacheConfig) -> Result<Self, String> {
+        let store = match &config.disk_path {
+            Some(dir) => {
+                std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
+                let key = config.disk_key.as_deref().ok_or("disk_path requires disk_key")?;
+                let master = base64::engine::general_purpose::STANDARD
+                    .decode(key.trim())
+                    .map_err(|e| format!("Invalid base64 key: {}", e))?;
+                if master.len() != 32 {
+                    return Err(format!("Invalid disk key length: expected 32, got {}", master.len()));
+                }
+                let codec = AesGcmCodec::new(&derive_subkey(&master, CIPHER_KEY_INFO))?;
+                let name_key = derive_subkey(&master, NAME_KEY_INFO);
+                Store::Disk { dir: dir.into(), codec: Box::new(codec), name_key }
+            }
+            None => Store::Memory { entries: HashMap::new(), order: VecDeque::new() },
+        };
+        Ok(Self { config, store: Mutex::new(store) })
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Whether a request may be answered from, or stored in, the cache
+    pub fn is_cacheable_request(&self, request: &HttpRequest) -> bool {
+        if request.method != "GET" || directives(request.header("cache-control")).contains_key("no-store") {
+            return false;
+        }
+        let authenticated = request.header("authorization").is_some() || request.header("cookie").is_some();
+        self.config.cache_authenticated || !authenticated
+    }
+
+    /// Whether stored validators may be sent to the request's host
+    pub fn may_revalidate(&self, request: &HttpRequest, top_level_site: Option<&str>) -> bool {
+        let Some(host) = reqwest::Url::parse(&request.url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)) else {
+            return false;
+        };
+        let first_party = top_level_site.is_some_and(|site| crate::cookies::site_of(site) == crate::cookies::site_of(&host));
+        first_party || self.config.revalidate_hosts.iter().any(|p| crate::proxy::host_matches(p, &host))
+    }
+
+    fn load(&self, key: &str) -> Vec<CachedResponse> {
+        match &*self.store.lock().unwrap() {
+            Store::Memory { entries, .. } => entries.get(key).cloned().unwrap_or_default(),
+            Store::Disk { dir, codec, name_key } => std::fs::read(dir.join(file_name(name_key, key)))
+                .ok()
+                .and_then(|data| codec.decrypt(&data).ok())
+                .and_then(|json| serde_json::from_slice::<DiskEntry>(&json).ok())
+                .filter(|entry| entry.key == key)
+                .map(|entry| entry.variants)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn save(&self, key: &str, variants: Vec<CachedResponse>) {
+        match &mut *self.store.lock().unwrap() {
+            Store::Memory { entries, order } => {
+                order.retain(|k| k != key);
+                if variants.is_empty() {
+                    entries.remove(key);
+                    return;
+                }
+                entries.insert(key.to_string(), variants);
+                order.push_back(key.to_string());
+                while order.len() > self.config.max_entries {
+                    if let Some(oldest) = order.pop_front() {
+                        entries.remove(&oldest);
+                    }
+                }
+            }
+            Store::Disk { dir, codec, name_key } => {
+                let path = dir.join(file_name(name_key, key));
+                if variants.is_empty() {
+                    let _ = std::fs::remove_file(path);
+                    return;
+                }
+                let entry = DiskEntry { key: key.to_string(), variants };
+                let Ok(json) = serde_json::to_vec(&entry) else { return };
+                if let Ok(data) = codec.encrypt(&json) {
+                    let _ = std::fs::write(path, data);
+                }
+                evict_oldest_files(dir, self.config.max_entries);
+            }
+        }
+    }
+
+    /// Look up a request; stale entries add `If-None-Match` / `If-Modified-Since` to it
+    /// when the host may receive validators
+    pub fn lookup(&self, request: &mut HttpRequest, top_level_site: Option<&str>) -> CacheLookup {
+        if !self.is_cacheable_request(request) {
+            return CacheLookup::Miss;
+        }
+        let Some(stored) = self.load(&cache_key(&request.url)).into_iter().find(|c| c.matches(request)) else {
+            return CacheLookup::Miss;
+        };
+        let request_directives = directives(request.header("cache-control"));
+        let forced = request_directives.contains_key("no-cache")
+            || request_directives.get("max-age").is_some_and(|v| v.as_deref() == Some("0"));
+        if stored.is_fresh(now_secs()) && !forced {
+            return CacheLookup::Fresh(stored.response());
+        }
+
+        let response = stored.response();
+        let etag = response.header("etag").map(str::to_string);
+        let last_modified = response.header("last-modified").map(str::to_string);
+        if (etag.is_none() && last_modified.is_none()) || !self.may_revalidate(request, top_level_site) {
+            return CacheLookup::Miss;
+        }
+        if let Some(etag) = etag {
+            request.headers.push(("If-None-Match".to_string(), etag));
+        }
+        if let Some(last_modified) = last_modified {
+            request.headers.push(("If-Modified-Since".to_string(), last_modified));
+        }
+        CacheLookup::Stale
+    }
+
+    /// Handle the network response to a (possibly revalidating) request. A 304 is
+    /// turned into the refreshed stored response.
+    pub fn complete(&self, request: &HttpRequest, response: HttpResponse) -> HttpResponse {
+        let key = cache_key(&request.url);
+        if !matches!(request.method.as_str(), "GET" | "HEAD" | "OPTIONS" | "TRACE") && response.status < 400 {
+            // Unsafe methods invalidate the target (RFC 9111, Section 4.4)
+            self.save(&key, Vec::new());
+            return response;
+        }
+        if !self.is_cacheable_request(request) {
+            return response;
+        }
+
+        let mut variants = self.load(&key);
+        if response.status == 304
+            && let Some(stored) = variants.iter_mut().find(|c| c.matches(request))
+        {
+            for (name, value) in &response.headers {
+                if name.eq_ignore_ascii_case("content-length") {
+                    continue;
+                }
+                stored.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+                stored.headers.push((name.clone(), value.clone()));
+            }
+            let refreshed = stored.response();
+            if let Some(entry) = self.entry_for(request, &refreshed) {
+                *stored = CachedResponse { body: std::mem::take(&mut stored.body), ..entry };
+            }
+            self.save(&key, variants);
+            return refreshed;
+        }
+
+        if let Some(entry) = self.entry_for(request, &response) {
+            variants.retain(|c| !c.matches(request));
+            variants.push(entry);
+            self.save(&key, variants);
+        }
+        response
+    }
+
+    /// Cache entry for a response, or `None` when it must not be stored
+    fn entry_for(&self, request: &HttpRequest, response: &HttpResponse) -> Option<CachedResponse> {
+        let cache_control = directives(response.header("cache-control"));
+        if cache_control.contains_key("no-store") || !HEURISTICALLY_CACHEABLE.contains(&response.status) {
+            return None;
+        }
+        if response.header("set-cookie").is_some() && !self.config.cache_authenticated {
+            return None;
+        }
+        let vary_names: Vec<String> = response
+            .header("vary")
+            .unwrap_or_default()
+            .split(',')
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if vary_names.iter().any(|v| v == "*") {
+            return None;
+        }
+
+        let now = now_secs();
+        let date = response.header("date").and_then(|d| httpdate::parse_http_date(d).ok());
+        let lifetime = if cache_control.contains_key("no-cache") {
+            0
+        } else if let Some(max_age) = cache_control.get("max-age") {
+            max_age.as_deref().and_then(|v| v.parse().ok()).unwrap_or(0)
+        } else if let Some(expires) = response.header("expires") {
+            let base = date.unwrap_or_else(SystemTime::now);
+            httpdate::parse_http_date(expires).ok().and_then(|e| e.duration_since(base).ok()).map_or(0, |d| d.as_secs())
+        } else {
+            0
+        };
+        let has_validator = response.header("etag").is_some() || response.header("last-modified").is_some();
+        if lifetime == 0 && !has_validator {
+            return None;
+        }
+        Some(CachedResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+            vary: vary_names.into_iter().map(|name| {
+                let value = request.header(&name).map(str::to_string);
+                (name, value)
+            }).collect(),
+            stored_at: now,
+            initial_age: response.header("age").and_then(|a| a.trim().parse().ok()).unwrap_or(0),
+            lifetime,
+        })
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) -> Result<(), String> {
+        match &mut *self.store.lock().unwrap() {
+            Store::Memory { entries, order } => {
+                entries.clear();
+                order.clear();
+                Ok(())
+            }
+            Store::Disk { dir, .. } => {
+                let files = std::fs::read_dir(&*dir).map_err(|e| format!("Failed to read cache directory: {}", e))?;
+                for file in files.flatten().filter(is_cache_file) {
+                    std::fs::remove_file(file.path()).map_err(|e| format!("Failed to remove cache entry: {}", e))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Whether a directory entry is a cache file (named by [`file_name`]). Only these are
+/// ever removed, so a `disk_path` shared with other files is safe.
+fn is_cache_file(entry: &std::fs::DirEntry) -> bool {
+    let name = entry.file_name();
+    let name = name.to_string_lossy();
+    name.len() == 64
+        && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        && entry.file_type().is_ok_and(|t| t.is_file())
+}
+
+fn evict_oldest_files(dir: &std::path::Path, max_entries: usize) {
+    let Ok(files) = std::fs::read_dir(dir) else { return };
+    let mut files: Vec<_> = files
+        .flatten()
+        .filter(is_cache_file)
+        .filter_map(|f| Some((f.metadata().ok()?.modified().ok()?, f.path())))
+        .collect();
+    if files.len() <= max_entries {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - max_entries] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    const URL: &str = "https://agent.example.com/.well-known/agent-card.json";
+
+    #[test]
+    fn test_fresh_hits_and_privacy_rules() {
+        let cache = HttpCache::new(CacheConfig::default()).unwrap();
+        let mut request = HttpRequest::get(URL);
+        assert!(matches!(cache.lookup(&mut request, None), CacheLookup::Miss));
+        cache.complete(&request, response(200, &[("Cache-Control", "max-age=60")], "card"));
+        match cache.lookup(&mut HttpRequest::get(URL), None) {
+            CacheLookup::Fresh(hit) => assert_eq!(hit.text(), "card"),
+            other => panic!("expected a hit, got {:?}", other),
+        }
+        let mut no_cache = HttpRequest::get(URL).with_header("Cache-Control", "no-cache");
+        assert!(matches!(cache.lookup(&mut no_cache, None), CacheLookup::Miss));
+
+        // Authenticated requests, cookies and no-store responses are not cached
+        let authed = HttpRequest::get("https://api.example.com/models").with_header("Authorization", "Bearer k");
+        cache.complete(&authed, response(200, &[("Cache-Control", "max-age=60")], "models"));
+        assert!(matches!(cache.lookup(&mut HttpRequest::get("https://api.example.com/models"), None), CacheLookup::Miss));
+        let set_cookie = HttpRequest::get("https://api.example.com/a");
+        cache.complete(&set_cookie, response(200, &[("Cache-Control", "max-age=60"), ("Set-Cookie", "id=1")], "a"));
+        assert!(matches!(cache.lookup(&mut HttpRequest::get("https://api.example.com/a"), None), CacheLookup::Miss));
+
+        // Unsafe methods invalidate
+        cache.complete(&HttpRequest::post(URL, "{}"), response(200, &[], ""));
+        assert!(matches!(cache.lookup(&mut HttpRequest::get(URL), None), CacheLookup::Miss));
+    }
+
+    #[test]
+    fn test_vary() {
+        let cache = HttpCache::new(CacheConfig::default()).unwrap();
+        let english = HttpRequest::get(URL).with_header("Accept-Language", "en");
+        cache.complete(&english, response(200, &[("Cache-Control", "max-age=60"), ("Vary", "Accept-Language")], "en"));
+        assert!(matches!(cache.lookup(&mut english.clone(), None), CacheLookup::Fresh(_)));
+        let mut german = HttpRequest::get(URL).with_header("Accept-Language", "de");
+        assert!(matches!(cache.lookup(&mut german, None), CacheLookup::Miss));
+    }
+
+    #[test]
+    fn test_etags_only_revalidate_with_first_parties() {
+        let cache = HttpCache::new(CacheConfig::default()).unwrap();
+        let request = HttpRequest::get(URL);
+        cache.complete(&request, response(200, &[("ETag", "\"v1\""), ("Cache-Control", "no-cache")], "card"));
+
+        let mut third_party = HttpRequest::get(URL);
+        assert!(matches!(cache.lookup(&mut third_party, None), CacheLookup::Miss));
+        assert_eq!(third_party.header("if-none-match"), None);
+
+        let mut first_party = HttpRequest::get(URL);
+        assert!(matches!(cache.lookup(&mut first_party, Some("example.com")), CacheLookup::Stale));
+        assert_eq!(first_party.header("if-none-match"), Some("\"v1\""));
+        let refreshed = cache.complete(&first_party, response(304, &[("ETag", "\"v1\""), ("Cache-Control", "max-age=60")], ""));
+        assert_eq!((refreshed.status, refreshed.text()), (200, "card".to_string()));
+        assert!(matches!(cache.lookup(&mut HttpRequest::get(URL), None), CacheLookup::Fresh(_)));
+    }
+
+    #[test]
+    fn test_encrypted_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("http-cache-{}", std::process::id()));
+        let config = CacheConfig {
+            disk_path: Some(dir.to_string_lossy().into_owned()),
+            disk_key: Some(AesGcmCodec::generate_key()),
+            max_entries: 1,
+            ..CacheConfig::default()
+        };
+        let cache = HttpCache::new(config.clone()).unwrap();
+        // Files that are not cache entries are never evicted or cleared
+        let unrelated = dir.join("notes.txt");
+        std::fs::write(&unrelated, "keep").unwrap();
+        cache.complete(&HttpRequest::get(URL), response(200, &[("Cache-Control", "max-age=60")], "secret card"));
+        let file = std::fs::read_dir(&dir).unwrap().flatten().find(is_cache_file).unwrap().path();
+        assert!(!String::from_utf8_lossy(&std::fs::read(&file).unwrap()).contains("secret"));
+        // File names are keyed, so they cannot be matched against hashes of known URLs
+        let unkeyed = hex::encode(<Sha256 as sha2::Digest>::digest(URL));
+        assert_ne!(file.file_name().unwrap().to_string_lossy(), unkeyed);
+        let other_key = CacheConfig { disk_key: Some(AesGcmCodec::generate_key()), ..config.clone() };
+        assert!(matches!(HttpCache::new(other_key).unwrap().lookup(&mut HttpRequest::get(URL), None), CacheLookup::Miss));
+
+        let reopened = HttpCache::new(config).unwrap();
+        assert!(matches!(reopened.lookup(&mut HttpRequest::get(URL), None), CacheLookup::Fresh(_)));
+        // An entry copied to another URL's file name is not served for that URL
+        let other_url = "https://agent.example.com/other";
+        let name_key = match &*reopened.store.lock().unwrap() {
+            Store::Disk { name_key, .. } => *name_key,
+            Store::Memory { .. } => unreachable!(),
+        };
+        std::fs::copy(&file, dir.join(file_name(&name_key, other_url))).unwrap();
+        assert!(matches!(reopened.lookup(&mut HttpRequest::get(other_url), None), CacheLookup::Miss));
+        assert!(matches!(reopened.lookup(&mut HttpRequest::get(URL), None), CacheLookup::Fresh(_)));
+        reopened.clear().unwrap();
+        assert!(matches!(reopened.lookup(&mut HttpRequest::get(URL), None), CacheLookup::Miss));
+        assert_eq!(std::fs::read_to_string(&unrelated).unwrap(), "keep");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Payload Codec Module
+//!
+//! This module implements the payload obfuscation methods listed in the spec
+//! (`base64`, `xor`, `aes-gcm`). Only AES-256-GCM provides confidentiality; `base64`
+//! and `xor` are transport obfuscation and must not be relied on for secrecy.
+//!
+//! # Features
+//! - Base64 (standard alphabet)
+//! - Repeating-key XOR
+//! - AES-256-GCM with a random 96-bit nonce prepended to the ciphertext
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// AES-GCM nonce length in bytes
+const NONCE_LEN: usize = 12;
+
+/// Obfuscation method names as used in the JSON config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodecMethod {
+    #[serde(rename = "base64")]
+    Base64,
+    #[serde(rename = "xor")]
+    Xor,
+    #[serde(rename = "aes-gcm")]
+    AesGcm,
+}
+
+/// AES-256-GCM codec
+#[derive(Clone)]
+pub struct AesGcmCodec {
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for AesGcmCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AesGcmCodec(<redacted>)")
+    }
+}
+
+impl AesGcmCodec {
+    /// Create from a 32-byte key
+    pub fn new(key: &[u8]) -> Result<Self, String> {
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|_| format!("Invalid AES-256 key length: expected 32, got {}", key.len()))?;
+        Ok(Self { cipher })
+    }
+
+    /// Create from a base64-encoded 32-byte key
+    pub fn from_base64(key: &str) -> Result<Self, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(key.trim())
+            .map_err(|e| format!("Invalid base64 key: {}", e))?;
+        Self::new(&bytes)
+    }
+
+    /// Generate a random key, base64-encoded
+    pub fn generate_key() -> String {
+        let key = Aes256Gcm::generate_key(&mut OsRng);
+        base64::engine::general_purpose::STANDARD.encode(key)
+    }
+
+    /// Encrypt, returning `nonce || ciphertext || tag`
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "AES-GCM encryption failed".to_string())?;
+        let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&ciphertext);
+        Ok(output)
+    }
+
+    /// Decrypt data produced by [`AesGcmCodec::encrypt`]
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        if data.len() < NONCE_LEN {
+            return Err("Ciphertext too short".to_string());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().map_err(|_| "Invalid nonce".to_string())?;
+        self.cipher
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| "AES-GCM decryption failed (wrong key or tampered data)".to_string())
+    }
+}
+
+/// Payload codec
+#[derive(Debug, Clone)]
+pub enum PayloadCodec {
+    Base64,
+    Xor(Vec<u8>),
+    AesGcm(Box<AesGcmCodec>),
+}
+
+impl PayloadCodec {
+    /// Build a codec from a method and key material.
+    /// `xor` uses the raw key bytes; `aes-gcm` expects a base64-encoded 32-byte key.
+    pub fn from_method(method: CodecMethod, key: Option<&str>) -> Result<Self, String> {
+        match method {
+            CodecMethod::Base64 => Ok(PayloadCodec::Base64),
+            CodecMethod::Xor => {
+                let key = key.filter(|k| !k.is_empty()).ok_or("XOR codec requires a key")?;
+                Ok(PayloadCodec::Xor(key.as_bytes().to_vec()))
+            }
+            CodecMethod::AesGcm => {
+                let key = key.ok_or("AES-GCM codec requires a key")?;
+                Ok(PayloadCodec::AesGcm(Box::new(AesGcmCodec::from_base64(key)?)))
+            }
+        }
+    }
+
+    /// Method implemented by this codec
+    pub fn method(&self) -> CodecMethod {
+        match self {
+            PayloadCodec::Base64 => CodecMethod::Base64,
+            PayloadCodec::Xor(_) => CodecMethod::Xor,
+            PayloadCodec::AesGcm(_) => CodecMethod::AesGcm,
+        }
+    }
+
+    /// Encode a payload
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            PayloadCodec::Base64 => Ok(base64::engine::general_purpose::STANDARD.encode(data).into_bytes()),
+            PayloadCodec::Xor(key) => Ok(xor(data, key)),
+            PayloadCodec::AesGcm(codec) => codec.encrypt(data),
+        }
+    }
+
+    /// Decode a payload
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            PayloadCodec::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("Invalid base64 payload: {}", e)),
+            PayloadCodec::Xor(key) => Ok(xor(data, key)),
+            PayloadCodec::AesGcm(codec) => codec.decrypt(data),
+        }
+    }
+}
+
+fn xor(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter().zip(key.iter().cycle()).map(|(d, k)| d ^ k).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aes_gcm_roundtrip() {
+        let codec = AesGcmCodec::from_base64(&AesGcmCodec::generate_key()).unwrap();
+        let encrypted = codec.encrypt(b"secret image").unwrap();
+        assert_ne!(&encrypted[NONCE_LEN..], b"secret image");
+        assert_eq!(codec.decrypt(&encrypted).unwrap(), b"secret image");
+    }
+
+    #[test]
+    fn test_aes_gcm_rejects_tampering_and_wrong_key() {
+        let codec = AesGcmCodec::new(&[7u8; 32]).unwrap();
+        let mut encrypted = codec.encrypt(b"data").unwrap();
+        let other = AesGcmCodec::new(&[8u8; 32]).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        assert!(codec.decrypt(&encrypted).is_err());
+        assert!(AesGcmCodec::new(&[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn test_base64_and_xor() {
+        let b64 = PayloadCodec::Base64;
+        assert_eq!(b64.encode(b"hi").unwrap(), b"aGk=");
+        assert_eq!(b64.decode(b"aGk=").unwrap(), b"hi");
+
+        let xor = PayloadCodec::from_method(CodecMethod::Xor, Some("k")).unwrap();
+        let encoded = xor.encode(b"abc").unwrap();
+        assert_eq!(xor.decode(&encoded).unwrap(), b"abc");
+        assert!(PayloadCodec::from_method(CodecMethod::Xor, None).is_err());
+    }
+
+    #[test]
+    fn test_method_names() {
+        let method: CodecMethod = serde_json::from_str("\"aes-gcm\"").unwrap();
+        assert_eq!(method, CodecMethod::AesGcm);
+    }
+}
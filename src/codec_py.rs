@@ -0,0 +1,60 @@
+//! Python PyO3 bindings for payload codecs (`privacy_http_sdk.codecs`)
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use crate::codec::*;
+
+/// Python wrapper for PayloadCodec
+#[pyclass(name = "PayloadCodec", module = "privacy_http_sdk.codecs", frozen)]
+pub struct PyPayloadCodec {
+    inner: PayloadCodec,
+}
+
+#[pymethods]
+impl PyPayloadCodec {
+    /// Codec for `method` ("base64", "xor" or "aes-gcm"). `xor` uses the raw key;
+    /// `aes-gcm` expects a base64-encoded 32-byte key.
+    #[new]
+    #[pyo3(signature = (method, key=None))]
+    fn new(method: &str, key: Option<String>) -> PyResult<Self> {
+        let method: CodecMethod = serde_json::from_value(serde_json::Value::String(method.to_string()))
+            .map_err(|_| pyo3::exceptions::PyValueError::new_err(format!("Unknown codec: {}", method)))?;
+        let inner = PayloadCodec::from_method(method, key.as_deref())
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyPayloadCodec { inner })
+    }
+
+    /// Random base64-encoded key for "aes-gcm"
+    #[staticmethod]
+    fn generate_key() -> String {
+        AesGcmCodec::generate_key()
+    }
+
+    #[getter]
+    fn method(&self) -> String {
+        serde_json::to_value(self.inner.method())
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+
+    fn encode<'py>(&self, py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        let encoded = self.inner.encode(data).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyBytes::new(py, &encoded))
+    }
+
+    fn decode<'py>(&self, py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        let decoded = self.inner.decode(data).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyBytes::new(py, &decoded))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PayloadCodec('{}')", self.method())
+    }
+}
+
+/// Populate the `codecs` submodule
+pub fn init_codec_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyPayloadCodec>()?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Whole-client configuration
+//!
+//! One JSON document configuring an `HttpClient` in a single step, for bindings that
+//! construct a client from a config string instead of calling the `configure_*`
+//! methods one by one:
+//!
+//! ```json
+//! {
+//!   "api_key": "...",
+//!   "privacy": { "tls_enforce": true, "user_agent": "Mozilla/5.0" },
+//!   "retries": { "max_retries": 2 },
+//!   "cache": { "max_entries": 256 },
+//!   "ohttp": { "relay_url": "https://relay.example/", "keys_url": "https://gateway.example/ohttp-keys" },
+//!   "cookie_site": "example.com"
+//! }
+//! ```
+//!
+//! Every section is optional and validated like its own `from_json`.
+
+use serde::Deserialize;
+use serde_json::Value;
+use crate::cache::CacheConfig;
+use crate::ohttp::OhttpConfig;
+use crate::privacy::PrivacyConfig;
+use crate::retry::RetryConfig;
+
+/// `HttpClient` settings
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    pub api_key: String,
+    pub privacy: Option<PrivacyConfig>,
+    pub retries: Option<RetryConfig>,
+    pub cache: Option<CacheConfig>,
+    pub ohttp: Option<OhttpConfig>,
+    pub cookie_site: Option<String>,
+}
+
+/// Sections kept as JSON until validated by their own parsers
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawClientConfig {
+    api_key: String,
+    privacy: Option<Value>,
+    retries: Option<Value>,
+    cache: Option<Value>,
+    ohttp: Option<Value>,
+    cookie_site: Option<String>,
+}
+
+fn section<T>(value: Option<Value>, parse: fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    value.map(|v| parse(&v.to_string())).transpose()
+}
+
+impl ClientConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: RawClientConfig = serde_json::from_str(json).map_err(|e| format!("Invalid client config: {}", e))?;
+        Ok(Self {
+            api_key: raw.api_key,
+            privacy: section(raw.privacy, PrivacyConfig::from_json)?,
+            retries: section(raw.retries, RetryConfig::from_json)?,
+            cache: section(raw.cache, CacheConfig::from_json)?,
+            ohttp: section(raw.ohttp, OhttpConfig::from_json)?,
+            cookie_site: raw.cookie_site,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections_are_validated() {
+        let config = ClientConfig::from_json(r#"{"api_key": "k", "privacy": {"tls_enforce": false}, "retries": {}}"#).unwrap();
+        assert_eq!(config.api_key, "k");
+        assert!(!config.privacy.unwrap().tls_enforce);
+        assert!(config.retries.is_some());
+        assert!(config.cache.is_none());
+
+        assert!(ClientConfig::from_json("{}").is_ok());
+        assert!(ClientConfig::from_json(r#"{"ohttp": {"relay_url": ""}}"#).is_err());
+        assert!(ClientConfig::from_json(r#"{"privacy": {"tls_enforce": "yes"}}"#).is_err());
+        assert!(ClientConfig::from_json(r#"{"retry": {}}"#).is_err());
+    }
+
+    /// Text between each `start` and the following `end`
+    fn snippets<'a>(text: &'a str, start: &str, end: &str) -> Vec<&'a str> {
+        text.split(start).skip(1).map(|rest| rest.split(end).next().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_documented_examples_parse() {
+        let module_doc: String = include_str!("config.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("//!"))
+            .map(|line| format!("{}\n", line.trim_start()))
+            .collect();
+        let spec = include_str!("../spec/spec.md");
+        let cpp = include_str!("../http-c++/main.cpp");
+
+        let mut examples: Vec<String> = snippets(&module_doc, "```json\n", "```").into_iter().map(String::from).collect();
+        examples.extend(snippets(spec, "new_http_client(R\"(", ")\"").into_iter().map(String::from));
+        examples.extend(snippets(spec, "phttp_client_new(\"", "\",").into_iter().map(|c| c.replace("\\\"", "\"")));
+        examples.extend(snippets(cpp, "new_http_client(R\"(", ")\"").into_iter().map(String::from));
+
+        // config.rs, spec.md (cxx and C ABI) and main.cpp each document at least one
+        assert!(examples.len() >= 4, "found only {} examples", examples.len());
+        for example in &examples {
+            if let Err(e) = ClientConfig::from_json(example) {
+                panic!("documented config {} does not parse: {}", example, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! Cookie Jar Module
+//!
+//! Opt-in cookie store for `HttpClient` (the `cookies` section of the privacy config).
+//! Cookies are partitioned by top-level site, so a cookie set while talking to one
+//! site is never sent in the context of another.
+//!
+//! # Features
+//! - Partitioning by top-level site (set with `set_cookie_site`; by default every
+//!   request is its own top-level site)
+//! - Third-party cookies blocked unless `allow_third_party` is set
+//! - Ephemeral by default; persistent cookies can be saved to an AES-GCM encrypted file
+//! - Clearing per partition
+//! - Only active while the privacy policy allows the `Cookie` header
+//!
+//! Sites are registrable domains from the public suffix list (`example.co.uk`), and
+//! cookies with a `Domain` wider than that (e.g. `co.uk`) are rejected.
+
+use crate::codec::AesGcmCodec;
+use crate::transport::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cookie jar configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CookieConfig {
+    /// Send and store cookies of sites other than the top-level site
+    pub allow_third_party: bool,
+    /// Encrypted file keeping persistent cookies across sessions
+    pub persist_path: Option<String>,
+    /// Base64 32-byte AES-GCM key for `persist_path`
+    #[serde(skip_serializing)]
+    pub persist_key: Option<String>,
+}
+
+impl CookieConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.persist_path.is_some() {
+            let key = self.persist_key.as_deref().ok_or("persist_path requires persist_key")?;
+            AesGcmCodec::from_base64(key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stored cookie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Lowercase domain without a leading dot
+    pub domain: String,
+    /// Sent to `domain` only (no `Domain` attribute)
+    pub host_only: bool,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    /// `strict`, `lax` or `none`
+    pub same_site: Option<String>,
+    /// Expiry as a Unix timestamp; `None` for session cookies
+    pub expires: Option<u64>,
+}
+
+impl Cookie {
+    /// Parse a `Set-Cookie` value received from `url`
+    pub fn parse(set_cookie: &str, url: &reqwest::Url) -> Result<Self, String> {
+        let host = url.host_str().ok_or("URL has no host")?.trim_matches(['[', ']']).to_ascii_lowercase();
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts
+            .next()
+            .and_then(|pair| pair.split_once('='))
+            .ok_or_else(|| format!("Invalid Set-Cookie: {}", set_cookie))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Cookie name is empty".to_string());
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url.path()),
+            secure: false,
+            http_only: false,
+            same_site: None,
+            expires: None,
+        };
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_matches(&domain, &host) {
+                        return Err(format!("Cookie domain {} does not match {}", domain, host));
+                    }
+                    // Rejects public suffixes (`co.uk`) unless they are the host itself
+                    if domain.len() < site_of(&host).len() {
+                        return Err(format!("Cookie domain {} is wider than the site", domain));
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => cookie.same_site = Some(value.to_ascii_lowercase()),
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => {
+                    if let Ok(date) = httpdate::parse_http_date(value) {
+                        cookie.expires = Some(unix_secs(date));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Max-Age takes precedence over Expires (RFC 6265, Section 5.3)
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(now_secs().saturating_add_signed(max_age.max(-1)));
+        }
+        if cookie.secure && !is_secure_url(url) {
+            return Err("Secure cookie set over an insecure connection".to_string());
+        }
+        Ok(cookie)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &reqwest::Url, host: &str) -> bool {
+        let domain_ok = if self.host_only { self.domain == host } else { domain_matches(&self.domain, host) };
+        domain_ok && path_matches(&self.path, url.path()) && (!self.secure || is_secure_url(url))
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs()
+}
+
+fn now_secs() -> u64 {
+    unix_secs(SystemTime::now())
+}
+
+fn is_secure_url(url: &reqwest::Url) -> bool {
+    url.scheme() == "https" || crate::transport::is_loopback_url(url.as_str())
+}
+
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => path[..end].to_string(),
+    }
+}
+
+fn domain_matches(domain: &str, host: &str) -> bool {
+    host == domain || (host.ends_with(&format!(".{}", domain)) && host.parse::<IpAddr>().is_err())
+}
+
+fn path_matches(cookie_path: &str, request_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Site of a host: its registrable domain (public suffix plus one label, e.g.
+/// `example.co.uk`), or the host itself for IP addresses and public suffixes
+pub fn site_of(host: &str) -> String {
+    let host = host.trim_matches(['[', ']']).trim_end_matches('.').to_ascii_lowercase();
+    if host.parse::<IpAddr>().is_ok() {
+        return host;
+    }
+    psl::domain_str(&host).map_or_else(|| host.clone(), str::to_string)
+}
+
+/// Partitioned cookie store
+pub struct CookieJar {
+    config: CookieConfig,
+    partitions: Mutex<HashMap<String, Vec<Cookie>>>,
+}
+
+impl std::fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieJar").field("config", &self.config).finish()
+    }
+}
+
+impl CookieJar {
+    /// Create a jar, loading persisted cookies when `persist_path` exists
+    pub fn new(config: CookieConfig) -> Result<Self, String> {
+        config.validate()?;
+        let jar = Self { config, partitions: Mutex::new(HashMap::new()) };
+        if let Some(path) = jar.config.persist_path.as_deref().filter(|p| std::path::Path::new(p).exists()) {
+            let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let json = jar.codec()?.decrypt(&data)?;
+            let partitions = serde_json::from_slice(&json).map_err(|e| format!("Invalid cookie file {}: {}", path, e))?;
+            *jar.partitions.lock().unwrap() = partitions;
+        }
+        Ok(jar)
+    }
+
+    pub fn config(&self) -> &CookieConfig {
+        &self.config
+    }
+
+    fn codec(&self) -> Result<AesGcmCodec, String> {
+        AesGcmCodec::from_base64(self.config.persist_key.as_deref().unwrap_or_default())
+    }
+
+    /// Partition for a request to `url` made in the context of `top_level_site`, or
+    /// `None` when it is a blocked third-party request
+    pub fn partition_for(&self, url: &reqwest::Url, top_level_site: Option<&str>) -> Option<String> {
+        let site = site_of(url.host_str()?);
+        let top = top_level_site.map(site_of).unwrap_or_else(|| site.clone());
+        (top == site || self.config.allow_third_party).then_some(top)
+    }
+
+    /// `Cookie` header value for a request
+    pub fn cookie_header(&self, url: &str, top_level_site: Option<&str>) -> Option<String> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let partition = self.partition_for(&url, top_level_site)?;
+        let host = url.host_str()?.trim_matches(['[', ']']).to_ascii_lowercase();
+        let cross_site = partition != site_of(&host);
+        let now = now_secs();
+
+        let mut partitions = self.partitions.lock().unwrap();
+        let cookies = partitions.get_mut(&partition)?;
+        cookies.retain(|c| !c.is_expired(now));
+        let mut matching: Vec<&Cookie> = cookies
+            .iter()
+            .filter(|c| c.matches(&url, &host))
+            // Cross-site requests only carry `SameSite=None` cookies
+            .filter(|c| !cross_site || c.same_site.as_deref() == Some("none"))
+            .collect();
+        // Longer paths first (RFC 6265, Section 5.4)
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        let header: Vec<String> = matching.iter().map(|c| format!("{}={}", c.name, c.value)).collect();
+        (!header.is_empty()).then(|| header.join("; "))
+    }
+
+    /// Add stored cookies to a request (merged with an existing `Cookie` header)
+    pub fn attach(&self, request: &mut HttpRequest, top_level_site: Option<&str>) {
+        let Some(stored) = self.cookie_header(&request.url, top_level_site) else {
+            return;
+        };
+        match request.headers.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case("cookie")) {
+            Some((_, value)) => *value = format!("{}; {}", value, stored),
+            None => request.headers.push(("Cookie".to_string(), stored)),
+        }
+    }
+
+    /// Store the `Set-Cookie` headers of a response (invalid cookies are ignored)
+    pub fn store(&self, url: &str, top_level_site: Option<&str>, response: &HttpResponse) -> Result<(), String> {
+        let url = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        let Some(partition) = self.partition_for(&url, top_level_site) else {
+            return Ok(());
+        };
+        let set_cookies: Vec<Cookie> = response
+            .headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("set-cookie"))
+            .filter_map(|(_, v)| Cookie::parse(v, &url).ok())
+            .collect();
+        if set_cookies.is_empty() {
+            return Ok(());
+        }
+
+        let now = now_secs();
+        {
+            let mut partitions = self.partitions.lock().unwrap();
+            let cookies = partitions.entry(partition).or_default();
+            for cookie in set_cookies {
+                cookies.retain(|c| !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path));
+                if !cookie.is_expired(now) {
+                    cookies.push(cookie);
+                }
+            }
+        }
+        if self.config.persist_path.is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Remove the cookies of one partition (top-level site), or all cookies
+    pub fn clear(&self, partition: Option<&str>) -> Result<(), String> {
+        {
+            let mut partitions = self.partitions.lock().unwrap();
+            match partition {
+                Some(site) => {
+                    partitions.remove(&site_of(site));
+                }
+                None => partitions.clear(),
+            }
+        }
+        if self.config.persist_path.is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Top-level sites that hold cookies
+    pub fn partitions(&self) -> Vec<String> {
+        let partitions = self.partitions.lock().unwrap();
+        let mut sites: Vec<String> = partitions.iter().filter(|(_, c)| !c.is_empty()).map(|(s, _)| s.clone()).collect();
+        sites.sort();
+        sites
+    }
+
+    /// Write persistent (non-session, unexpired) cookies to the encrypted file
+    pub fn save(&self) -> Result<(), String> {
+        let path = self.config.persist_path.as_deref().ok_or("No persist_path configured")?;
+        let now = now_secs();
+        let persistent: HashMap<String, Vec<Cookie>> = self
+            .partitions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(site, cookies)| {
+                let kept = cookies.iter().filter(|c| c.expires.is_some() && !c.is_expired(now)).cloned().collect();
+                (site.clone(), kept)
+            })
+            .collect();
+        let json = serde_json::to_vec(&persistent).map_err(|e| format!("Failed to serialize cookies: {}", e))?;
+        let data = self.codec()?.encrypt(&json)?;
+        std::fs::write(path, data).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(set_cookies: &[&str]) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            headers: set_cookies.iter().map(|c| ("Set-Cookie".to_string(), c.to_string())).collect(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let url = reqwest::Url::parse("https://api.example.com/v1/login").unwrap();
+        let cookie = Cookie::parse("sid=abc; Domain=.example.com; Path=/v1; Secure; HttpOnly; Max-Age=60", &url).unwrap();
+        assert_eq!((cookie.domain.as_str(), cookie.path.as_str(), cookie.host_only), ("example.com", "/v1", false));
+        assert!(cookie.secure && cookie.http_only && cookie.expires.is_some());
+        assert!(Cookie::parse("a=b; Domain=com", &url).is_err());
+        assert!(Cookie::parse("a=b; Domain=other.org", &url).is_err());
+        assert!(Cookie::parse("a=b; Secure", &reqwest::Url::parse("http://example.com/").unwrap()).is_err());
+
+        let jar = CookieJar::new(CookieConfig::default()).unwrap();
+        jar.store(url.as_str(), None, &response(&["sid=abc; Domain=example.com; Path=/v1", "host=1"])).unwrap();
+        assert_eq!(jar.cookie_header("https://www.example.com/v1/chat", None).as_deref(), Some("sid=abc"));
+        assert_eq!(jar.cookie_header("https://api.example.com/v1/x", None).as_deref(), Some("sid=abc; host=1"));
+        assert_eq!(jar.cookie_header("https://api.example.com/v2", None), None);
+
+        jar.store(url.as_str(), None, &response(&["sid=gone; Domain=example.com; Path=/v1; Max-Age=0"])).unwrap();
+        assert_eq!(jar.cookie_header("https://www.example.com/v1/chat", None), None);
+    }
+
+    #[test]
+    fn test_public_suffix_sites() {
+        assert_eq!(site_of("www.bbc.co.uk"), "bbc.co.uk");
+        assert_eq!(site_of("news.bbc.co.uk"), site_of("bbc.co.uk"));
+        assert_ne!(site_of("bbc.co.uk"), site_of("shop.co.uk"));
+        assert_eq!(site_of("co.uk"), "co.uk");
+        assert_eq!(site_of("127.0.0.1"), "127.0.0.1");
+
+        let url = reqwest::Url::parse("https://www.bbc.co.uk/").unwrap();
+        assert!(Cookie::parse("a=b; Domain=co.uk", &url).is_err());
+        assert!(!Cookie::parse("a=b; Domain=bbc.co.uk", &url).unwrap().host_only);
+
+        // A cookie from another co.uk site is third-party and dropped by default
+        let jar = CookieJar::new(CookieConfig::default()).unwrap();
+        jar.store("https://ads.shop.co.uk/", Some("www.bbc.co.uk"), &response(&["id=1"])).unwrap();
+        jar.store("https://static.bbc.co.uk/", Some("www.bbc.co.uk"), &response(&["own=1"])).unwrap();
+        assert_eq!(jar.partitions(), vec!["bbc.co.uk".to_string()]);
+    }
+
+    #[test]
+    fn test_partitioning_and_third_party() {
+        let jar = CookieJar::new(CookieConfig::default()).unwrap();
+        let tracker = "https://cdn.tracker.net/pixel";
+        jar.store(tracker, Some("news.example"), &response(&["id=1; SameSite=None; Secure"])).unwrap();
+        assert!(jar.partitions().is_empty());
+
+        let jar = CookieJar::new(CookieConfig { allow_third_party: true, ..CookieConfig::default() }).unwrap();
+        jar.store(tracker, Some("news.example"), &response(&["id=1; SameSite=None; Secure", "lax=1"])).unwrap();
+        assert_eq!(jar.cookie_header(tracker, Some("www.news.example")).as_deref(), Some("id=1"));
+        assert_eq!(jar.cookie_header(tracker, Some("shop.example")), None);
+        assert_eq!(jar.cookie_header(tracker, None), None);
+
+        jar.store(tracker, None, &response(&["own=1"])).unwrap();
+        assert_eq!(jar.partitions(), vec!["news.example".to_string(), "tracker.net".to_string()]);
+        jar.clear(Some("news.example")).unwrap();
+        assert_eq!(jar.partitions(), vec!["tracker.net".to_string()]);
+    }
+
+    #[test]
+    fn test_encrypted_persistence() {
+        let path = std::env::temp_dir().join(format!("cookies-{}.bin", std::process::id()));
+        let config = CookieConfig {
+            persist_path: Some(path.to_string_lossy().into_owned()),
+            persist_key: Some(AesGcmCodec::generate_key()),
+            ..CookieConfig::default()
+        };
+        let jar = CookieJar::new(config.clone()).unwrap();
+        jar.store("https://example.com/", None, &response(&["keep=1; Max-Age=3600", "session=1"])).unwrap();
+        assert!(!String::from_utf8_lossy(&std::fs::read(&path).unwrap()).contains("keep"));
+
+        let reloaded = CookieJar::new(config.clone()).unwrap();
+        assert_eq!(reloaded.cookie_header("https://example.com/", None).as_deref(), Some("keep=1"));
+        let wrong_key = CookieConfig { persist_key: Some(AesGcmCodec::generate_key()), ..config };
+        assert!(CookieJar::new(wrong_key).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,684 @@
+//! W3C Verifiable Credentials (VC Data Model 2.0) signed with Nostr keys
+//!
+//! Credentials and presentations carry a Data Integrity proof with the
+//! `bip340-jcs-2025` cryptosuite:
+//!
+//! 1. the proof options (the proof as received minus `proofValue`, with the document's
+//!    `@context`) and the document without `proof` are canonicalized with JCS (RFC 8785),
+//!    so every proof field, including ones this crate does not know, is signed
+//! 2. `hashData = sha256(options) || sha256(document)`
+//! 3. `proofValue` is `z` + base58btc of the BIP-340 signature over `hashData` (made the
+//!    way `NostrSigner` signs: Schnorr over `sha256(hashData)`)
+//!
+//! The verification method is resolved through `DidResolver` and must be listed under
+//! `assertionMethod` (credentials) or `authentication` (presentations).
+//!
+//! Servers can demand a credential on top of a request signature: the client sends a
+//! presentation in `X-Presentation` whose challenge is the request's `X-Signature`, and
+//! `VerificationMiddleware::with_credential_requirement` checks it against a
+//! [`CredentialRequirement`]. The signature covers the request's `X-Timestamp` and
+//! `X-Nonce`, and both that timestamp and the proof's `created` time must fall within
+//! the verifier's window, so a presentation cannot outlive the request it was made for.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use crate::did::{Did, DidMethod, KeyType};
+use crate::did_document::DidResolver;
+use crate::did_nostr::{NostrSigner, SIGNATURE_HEADER, SignatureStamp, unix_now};
+
+/// Context of VC Data Model 2.0 credentials and presentations
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+/// Data Integrity cryptosuite of the proofs created here
+pub const CRYPTOSUITE: &str = "bip340-jcs-2025";
+/// Header carrying a base64url-encoded verifiable presentation
+pub const PRESENTATION_HEADER: &str = "X-Presentation";
+
+/// JSON Canonicalization Scheme (RFC 8785)
+pub fn canonicalize(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            // Keys are sorted by their UTF-16 code units
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Number(number) if number.is_f64() => out.push_str(&format_number(number.as_f64().unwrap_or_default())),
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+// ECMAScript `Number.prototype.toString`: plain notation within [1e-6, 1e21)
+fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if (1e-6..1e21).contains(&n.abs()) {
+        return format!("{}", n);
+    }
+    let exponential = format!("{:e}", n);
+    match exponential.split_once('e') {
+        Some((mantissa, exponent)) if !exponent.starts_with('-') => format!("{}e+{}", mantissa, exponent),
+        _ => exponential,
+    }
+}
+
+/// Current time as an XML Schema `dateTime` (seconds precision, UTC)
+fn timestamp(offset: Duration) -> String {
+    let time = OffsetDateTime::now_utc() + offset;
+    time.replace_nanosecond(0).unwrap_or(time).format(&Rfc3339).unwrap_or_default()
+}
+
+fn parse_timestamp(value: &Value, field: &str) -> Result<Option<OffsetDateTime>, String> {
+    match value.get(field) {
+        None => Ok(None),
+        Some(Value::String(time)) => OffsetDateTime::parse(time, &Rfc3339)
+            .map(Some)
+            .map_err(|e| format!("Invalid {} {}: {}", field, time, e)),
+        Some(_) => Err(format!("{} must be a dateTime string", field)),
+    }
+}
+
+/// Data Integrity proof
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub cryptosuite: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    pub verification_method: String,
+    /// `assertionMethod` (credentials) or `authentication` (presentations)
+    pub proof_purpose: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default, rename = "@context", skip_serializing_if = "Option::is_none")]
+    pub context: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_value: Option<String>,
+}
+
+// sha256(JCS(proof options)) || sha256(JCS(document))
+fn hash_data(unsecured: &Value, options: &Value) -> Vec<u8> {
+    let mut hash = Sha256::digest(canonicalize(options).as_bytes()).to_vec();
+    hash.extend(Sha256::digest(canonicalize(unsecured).as_bytes()));
+    hash
+}
+
+/// Add a `bip340-jcs-2025` proof by `signer`'s `did:nostr` key to `document`
+pub fn add_proof(
+    document: &Value,
+    signer: &NostrSigner,
+    purpose: &str,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+) -> Result<Value, String> {
+    let Value::Object(fields) = document else {
+        return Err("Document must be a JSON object".to_string());
+    };
+    let mut unsecured = fields.clone();
+    unsecured.remove("proof");
+    let unsecured = Value::Object(unsecured);
+
+    let mut proof = DataIntegrityProof {
+        proof_type: "DataIntegrityProof".to_string(),
+        cryptosuite: CRYPTOSUITE.to_string(),
+        created: Some(timestamp(Duration::ZERO)),
+        verification_method: format!("{}#key1", signer.did()),
+        proof_purpose: purpose.to_string(),
+        challenge: challenge.map(str::to_string),
+        domain: domain.map(str::to_string),
+        context: unsecured.get("@context").cloned(),
+        proof_value: None,
+    };
+    let options = serde_json::to_value(&proof).map_err(|e| format!("Invalid proof options: {}", e))?;
+    let signature = signer.sign_bytes(&hash_data(&unsecured, &options));
+    let signature = hex::decode(signature.as_hex()).map_err(|e| format!("Invalid signature: {}", e))?;
+    proof.proof_value = Some(format!("z{}", bs58::encode(signature).into_string()));
+
+    let mut secured = unsecured;
+    secured["proof"] = serde_json::to_value(&proof).map_err(|e| format!("Invalid proof: {}", e))?;
+    Ok(secured)
+}
+
+/// Check the proof of `document`, returning it and the DID that made it
+pub fn verify_proof(
+    document: &Value,
+    resolver: &DidResolver,
+    purpose: &str,
+    challenge: Option<&str>,
+    domain: Option<&str>,
+) -> Result<(DataIntegrityProof, Did), String> {
+    let Value::Object(fields) = document else {
+        return Err("Document must be a JSON object".to_string());
+    };
+    let Some(Value::Object(received)) = fields.get("proof") else {
+        return Err("Document has no proof object".to_string());
+    };
+    let mut proof: DataIntegrityProof =
+        serde_json::from_value(Value::Object(received.clone())).map_err(|e| format!("Invalid proof: {}", e))?;
+    if proof.proof_type != "DataIntegrityProof" || proof.cryptosuite != CRYPTOSUITE {
+        return Err(format!("Unsupported proof: {} {}", proof.proof_type, proof.cryptosuite));
+    }
+    if proof.proof_purpose != purpose {
+        return Err(format!("Proof purpose is {}, expected {}", proof.proof_purpose, purpose));
+    }
+    if challenge.is_some() && proof.challenge.as_deref() != challenge {
+        return Err("Proof challenge does not match".to_string());
+    }
+    if domain.is_some() && proof.domain.as_deref() != domain {
+        return Err("Proof domain does not match".to_string());
+    }
+    if proof.context.as_ref().is_some_and(|context| fields.get("@context") != Some(context)) {
+        return Err("Proof @context does not match the document".to_string());
+    }
+    if parse_timestamp(&Value::Object(received.clone()), "expires")?.is_some_and(|expires| OffsetDateTime::now_utc() >= expires) {
+        return Err("Proof has expired".to_string());
+    }
+
+    let proof_value = proof.proof_value.take().ok_or_else(|| "Proof has no proofValue".to_string())?;
+    let signature = proof_value
+        .strip_prefix('z')
+        .and_then(|encoded| bs58::decode(encoded).into_vec().ok())
+        .ok_or_else(|| "proofValue must be base58btc multibase".to_string())?;
+    let mut unsecured = fields.clone();
+    unsecured.remove("proof");
+    let mut options = received.clone();
+    options.remove("proofValue");
+    let hash = hash_data(&Value::Object(unsecured), &Value::Object(options));
+
+    let (did, _) = proof.verification_method.split_once('#').unwrap_or((&proof.verification_method, ""));
+    let did = Did::parse(did)?;
+    let did_document = did.resolve(resolver)?;
+    let relationship = match purpose {
+        "assertionMethod" => &did_document.assertion_method,
+        "authentication" => &did_document.authentication,
+        _ => return Err(format!("Unsupported proof purpose: {}", purpose)),
+    };
+    let method_id = did_document.absolute_id(&proof.verification_method);
+    let method = relationship
+        .iter()
+        .filter_map(|entry| did_document.relationship_method(entry))
+        .find(|method| did_document.absolute_id(&method.id) == method_id)
+        .ok_or_else(|| match did_document.verification_method(&proof.verification_method) {
+            Some(method) => format!("{} is not authorized for {}", method.id, purpose),
+            None => format!("{} is not in the DID document", proof.verification_method),
+        })?;
+    let key = method.public_key()?;
+    if key.key_type() != KeyType::Secp256k1 {
+        return Err(format!("{} requires a secp256k1 key", CRYPTOSUITE));
+    }
+    key.verify(&hash, &signature)?;
+    proof.proof_value = Some(proof_value);
+    Ok((proof, did))
+}
+
+/// Unsigned credential
+#[derive(Debug, Clone)]
+pub struct Credential {
+    id: Option<String>,
+    types: Vec<String>,
+    subject: Value,
+    valid_for: Option<Duration>,
+}
+
+impl Credential {
+    /// Credential of `credential_type` (besides `VerifiableCredential`) about `subject`;
+    /// `subject.id` is normally the holder's DID
+    pub fn new(credential_type: &str, subject: Value) -> Self {
+        Self {
+            id: None,
+            types: vec!["VerifiableCredential".to_string(), credential_type.to_string()],
+            subject,
+            valid_for: None,
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Set `validUntil` this far after issuance
+    pub fn with_validity(mut self, valid_for: Duration) -> Self {
+        self.valid_for = Some(valid_for);
+        self
+    }
+
+    /// Sign as `issuer`, valid from now
+    pub fn issue(&self, issuer: &NostrSigner) -> Result<Value, String> {
+        let mut credential = json!({
+            "@context": [CREDENTIALS_CONTEXT],
+            "type": self.types,
+            "issuer": issuer.did().to_string(),
+            "validFrom": timestamp(Duration::ZERO),
+            "credentialSubject": self.subject,
+        });
+        if let Some(id) = &self.id {
+            credential["id"] = json!(id);
+        }
+        if let Some(valid_for) = self.valid_for {
+            credential["validUntil"] = json!(timestamp(valid_for));
+        }
+        add_proof(&credential, issuer, "assertionMethod", None, None)
+    }
+}
+
+/// Credential whose proof, issuer and validity period were checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedCredential {
+    pub id: Option<String>,
+    pub issuer: String,
+    pub types: Vec<String>,
+    pub subject: Value,
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(item)) => vec![item.clone()],
+        Some(Value::Array(items)) => items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// `issuer`/`holder` may be a DID string or an object with an `id`
+fn identifier(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(Value::Object(object)) => object.get("id").and_then(Value::as_str).map(str::to_string),
+        _ => None,
+    }
+}
+
+fn check_envelope(document: &Value, expected_type: &str) -> Result<Vec<String>, String> {
+    let contexts = string_list(document.get("@context"));
+    if contexts.first().map(String::as_str) != Some(CREDENTIALS_CONTEXT) {
+        return Err(format!("@context must start with {}", CREDENTIALS_CONTEXT));
+    }
+    let types = string_list(document.get("type"));
+    if !types.iter().any(|t| t == expected_type) {
+        return Err(format!("type must include {}", expected_type));
+    }
+    Ok(types)
+}
+
+/// Check a credential's proof (made by its issuer) and validity period
+pub fn verify_credential(credential: &Value, resolver: &DidResolver) -> Result<VerifiedCredential, String> {
+    let types = check_envelope(credential, "VerifiableCredential")?;
+    let issuer = identifier(credential.get("issuer")).ok_or_else(|| "Credential has no issuer".to_string())?;
+    let (_, signer) = verify_proof(credential, resolver, "assertionMethod", None, None)?;
+    if signer.to_string() != issuer {
+        return Err(format!("Credential issued by {} but signed by {}", issuer, signer));
+    }
+    let now = OffsetDateTime::now_utc();
+    if parse_timestamp(credential, "validFrom")?.is_some_and(|from| now < from) {
+        return Err("Credential is not valid yet".to_string());
+    }
+    if parse_timestamp(credential, "validUntil")?.is_some_and(|until| now >= until) {
+        return Err("Credential has expired".to_string());
+    }
+    let subject = credential
+        .get("credentialSubject")
+        .cloned()
+        .ok_or_else(|| "Credential has no credentialSubject".to_string())?;
+    Ok(VerifiedCredential {
+        id: credential.get("id").and_then(Value::as_str).map(str::to_string),
+        issuer,
+        types,
+        subject,
+    })
+}
+
+/// Presentation of `credentials` by `holder`, bound to a verifier's `challenge`
+/// (and optionally its `domain`)
+pub fn create_presentation(
+    holder: &NostrSigner,
+    credentials: &[Value],
+    challenge: &str,
+    domain: Option<&str>,
+) -> Result<Value, String> {
+    let presentation = json!({
+        "@context": [CREDENTIALS_CONTEXT],
+        "type": ["VerifiablePresentation"],
+        "holder": holder.did().to_string(),
+        "verifiableCredential": credentials,
+    });
+    add_proof(&presentation, holder, "authentication", Some(challenge), domain)
+}
+
+/// Presentation whose holder proof and credentials were checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedPresentation {
+    pub holder: String,
+    pub credentials: Vec<VerifiedCredential>,
+}
+
+/// Check a presentation's proof against `challenge`/`domain` and every credential in it
+pub fn verify_presentation(
+    presentation: &Value,
+    resolver: &DidResolver,
+    challenge: &str,
+    domain: Option<&str>,
+) -> Result<VerifiedPresentation, String> {
+    check_envelope(presentation, "VerifiablePresentation")?;
+    let holder = identifier(presentation.get("holder")).ok_or_else(|| "Presentation has no holder".to_string())?;
+    let (_, signer) = verify_proof(presentation, resolver, "authentication", Some(challenge), domain)?;
+    if signer.to_string() != holder {
+        return Err(format!("Presentation by {} but signed by {}", holder, signer));
+    }
+    let credentials = match presentation.get("verifiableCredential") {
+        None => Vec::new(),
+        Some(Value::Array(credentials)) => credentials.iter().collect(),
+        Some(credential) => vec![credential],
+    };
+    let credentials = credentials
+        .into_iter()
+        .map(|credential| verify_credential(credential, resolver))
+        .collect::<Result<_, _>>()?;
+    Ok(VerifiedPresentation { holder, credentials })
+}
+
+/// Credential a server demands, e.g. an org membership:
+///
+/// ```json
+/// {"credential_type": "OrgMemberCredential", "issuers": ["did:nostr:<org>"], "claims": {"memberOf": "Example Org"}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CredentialRequirement {
+    /// Required credential `type`
+    pub credential_type: String,
+    /// Trusted issuer DIDs (empty: any issuer)
+    pub issuers: Vec<String>,
+    /// Subject properties that must match exactly
+    pub claims: Map<String, Value>,
+    /// Expected presentation `domain`
+    pub domain: Option<String>,
+}
+
+impl CredentialRequirement {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let requirement: Self = serde_json::from_str(json).map_err(|e| format!("Invalid credential requirement: {}", e))?;
+        requirement.validate()?;
+        Ok(requirement)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.credential_type.is_empty() {
+            return Err("credential_type is required".to_string());
+        }
+        if let Some(issuer) = self.issuers.iter().find(|issuer| Did::parse(issuer).is_err()) {
+            return Err(format!("Invalid issuer DID: {}", issuer));
+        }
+        Ok(())
+    }
+
+    /// First credential of the presentation about its holder that satisfies the requirement
+    pub fn check<'a>(&self, presentation: &'a VerifiedPresentation) -> Result<&'a VerifiedCredential, String> {
+        presentation
+            .credentials
+            .iter()
+            .find(|credential| {
+                credential.types.contains(&self.credential_type)
+                    && (self.issuers.is_empty() || self.issuers.contains(&credential.issuer))
+                    && credential.subject.get("id").and_then(Value::as_str) == Some(presentation.holder.as_str())
+                    && self.claims.iter().all(|(name, value)| credential.subject.get(name) == Some(value))
+            })
+            .ok_or_else(|| format!("No valid {} credential presented", self.credential_type))
+    }
+
+    /// Verify the `X-Presentation` header of a signed request: its challenge must be the
+    /// request's `X-Signature`, and the request's `X-Timestamp` and the proof's `created`
+    /// time must be within `max_age_secs` of now
+    pub fn verify_header(
+        &self,
+        header: &str,
+        request_headers: &[(String, String)],
+        resolver: &DidResolver,
+        max_age_secs: u64,
+    ) -> Result<VerifiedPresentation, String> {
+        let signature = request_headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(SIGNATURE_HEADER))
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| format!("Missing {} header", SIGNATURE_HEADER))?;
+        SignatureStamp::from_headers(request_headers)?.check_age(unix_now(), max_age_secs)?;
+        let json = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(header.trim())
+            .map_err(|e| format!("Invalid {} header: {}", PRESENTATION_HEADER, e))?;
+        let presentation: Value = serde_json::from_slice(&json).map_err(|e| format!("Invalid presentation: {}", e))?;
+        let verified = verify_presentation(&presentation, resolver, signature, self.domain.as_deref())?;
+        let created = parse_timestamp(&presentation["proof"], "created")?
+            .ok_or_else(|| "Presentation proof has no created time".to_string())?;
+        if (OffsetDateTime::now_utc() - created).whole_seconds().unsigned_abs() > max_age_secs {
+            return Err(format!("Presentation was created outside the {}s window", max_age_secs));
+        }
+        self.check(&verified)?;
+        Ok(verified)
+    }
+}
+
+/// Sign a request and present `credentials`: `X-DID`, `X-Signature` and an
+/// `X-Presentation` bound to that signature
+pub fn sign_request_with_credentials(
+    signer: &NostrSigner,
+    credentials: &[Value],
+    method: &str,
+    path: &str,
+    body: &[u8],
+    domain: Option<&str>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut headers = signer.sign_request(method, path, body);
+    let signature = headers
+        .iter()
+        .find(|(name, _)| name == SIGNATURE_HEADER)
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default();
+    let presentation = create_presentation(signer, credentials, &signature, domain)?;
+    let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(presentation.to_string());
+    headers.push((PRESENTATION_HEADER.to_string(), encoded));
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did_nostr::VerificationMiddleware;
+
+    /// `document` with a proof made from arbitrary `options`, signed the way `add_proof` signs
+    fn prove_with(document: &Value, signer: &NostrSigner, options: Value) -> Value {
+        let signature = hex::decode(signer.sign_bytes(&hash_data(document, &options)).as_hex()).unwrap();
+        let mut secured = document.clone();
+        secured["proof"] = options;
+        secured["proof"]["proofValue"] = json!(format!("z{}", bs58::encode(signature).into_string()));
+        secured
+    }
+
+    fn time_ago(secs: i64) -> String {
+        (OffsetDateTime::now_utc() - time::Duration::seconds(secs)).format(&Rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_jcs_rfc8785_example() {
+        let value: Value = serde_json::from_str(
+            r#"{"numbers":[333333333.33333329,1E30,4.50,2e-3,0.000000000000000000000000001],"string":"€$\u000F\u000aA'B\"\\\\\"\/","literals":[null,true,false]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            canonicalize(&value),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+        // UTF-16 ordering puts U+1F600 (a surrogate pair) before U+FB33
+        let value = json!({"\u{fb33}": 1, "\u{1f600}": 2, "1": 3});
+        assert_eq!(canonicalize(&value), "{\"1\":3,\"\u{1f600}\":2,\"\u{fb33}\":1}");
+    }
+
+    #[test]
+    fn test_issue_and_verify_credential() {
+        let (org, alice) = (NostrSigner::generate(), NostrSigner::generate());
+        let credential = Credential::new("OrgMemberCredential", json!({"id": alice.did().to_string(), "memberOf": "Example Org"}))
+            .with_id("urn:uuid:0b2d3e52-7b1f-4b8e-9c3a-2f6f6a0d1e11")
+            .with_validity(Duration::from_secs(3600))
+            .issue(&org)
+            .unwrap();
+        assert_eq!(credential["proof"]["cryptosuite"], CRYPTOSUITE);
+        assert_eq!(credential["proof"]["verificationMethod"], format!("{}#key1", org.did()));
+
+        let resolver = DidResolver::new();
+        let verified = verify_credential(&credential, &resolver).unwrap();
+        assert_eq!(verified.issuer, org.did().to_string());
+        assert_eq!(verified.subject["memberOf"], "Example Org");
+
+        // Tampering with any field breaks the proof
+        let mut tampered = credential.clone();
+        tampered["credentialSubject"]["memberOf"] = json!("Other Org");
+        assert!(verify_credential(&tampered, &resolver).unwrap_err().contains("verification failed"));
+        // Signed by someone other than the issuer
+        let mut forged = credential.clone();
+        forged["issuer"] = json!(alice.did().to_string());
+        let forged = add_proof(&forged, &org, "assertionMethod", None, None).unwrap();
+        assert!(verify_credential(&forged, &resolver).unwrap_err().contains("signed by"));
+
+        let expired = Credential::new("OrgMemberCredential", json!({"id": alice.did().to_string()}))
+            .with_validity(Duration::ZERO)
+            .issue(&org)
+            .unwrap();
+        assert_eq!(verify_credential(&expired, &resolver).unwrap_err(), "Credential has expired");
+    }
+
+    #[test]
+    fn test_every_proof_field_is_signed() {
+        let org = NostrSigner::generate();
+        let credential = Credential::new("OrgMemberCredential", json!({"memberOf": "Example Org"})).issue(&org).unwrap();
+        let resolver = DidResolver::new();
+
+        // A field added to the proof after signing breaks it
+        let mut extended = credential.clone();
+        extended["proof"]["nonce"] = json!("abc");
+        assert!(verify_credential(&extended, &resolver).unwrap_err().contains("verification failed"));
+
+        let mut unsecured = credential.clone();
+        let mut options = unsecured.as_object_mut().unwrap().remove("proof").unwrap();
+        options.as_object_mut().unwrap().remove("proofValue");
+        options["nonce"] = json!("abc");
+        assert!(verify_credential(&prove_with(&unsecured, &org, options.clone()), &resolver).is_ok());
+        options["expires"] = json!(time_ago(60));
+        assert_eq!(verify_credential(&prove_with(&unsecured, &org, options), &resolver).unwrap_err(), "Proof has expired");
+    }
+
+    #[test]
+    fn test_presentation_challenge_and_domain() {
+        let (org, alice) = (NostrSigner::generate(), NostrSigner::generate());
+        let credential = Credential::new("OrgMemberCredential", json!({"id": alice.did().to_string()})).issue(&org).unwrap();
+        let presentation = create_presentation(&alice, &[credential], "nonce-1", Some("api.example.com")).unwrap();
+
+        let resolver = DidResolver::new();
+        let verified = verify_presentation(&presentation, &resolver, "nonce-1", Some("api.example.com")).unwrap();
+        assert_eq!(verified.holder, alice.did().to_string());
+        assert_eq!(verified.credentials.len(), 1);
+        assert!(verify_presentation(&presentation, &resolver, "nonce-2", None).unwrap_err().contains("challenge"));
+        assert!(verify_presentation(&presentation, &resolver, "nonce-1", Some("evil.example")).unwrap_err().contains("domain"));
+        // A credential proof cannot stand in for a presentation proof
+        let wrong_purpose = add_proof(&presentation, &alice, "assertionMethod", Some("nonce-1"), None).unwrap();
+        assert!(verify_presentation(&wrong_purpose, &resolver, "nonce-1", None).unwrap_err().contains("purpose"));
+    }
+
+    #[test]
+    fn test_middleware_demands_credential() {
+        let (org, alice, mallory) = (NostrSigner::generate(), NostrSigner::generate(), NostrSigner::generate());
+        let requirement = CredentialRequirement::from_json(&format!(
+            r#"{{"credential_type": "OrgMemberCredential", "issuers": ["{}"], "claims": {{"memberOf": "Example Org"}}}}"#,
+            org.did()
+        ))
+        .unwrap();
+        let middleware = VerificationMiddleware::new().with_credential_requirement(requirement);
+        let membership = |signer: &NostrSigner, issuer: &NostrSigner| {
+            Credential::new("OrgMemberCredential", json!({"id": signer.did().to_string(), "memberOf": "Example Org"}))
+                .issue(issuer)
+                .unwrap()
+        };
+
+        let headers = sign_request_with_credentials(&alice, &[membership(&alice, &org)], "POST", "/rpc", b"{}", None).unwrap();
+        assert_eq!(middleware.verify("POST", "/rpc", &headers, b"{}").unwrap(), crate::did::Did::Nostr(alice.did()));
+
+        // Signature only, self-issued credential, someone else's credential
+        assert_eq!(middleware.verify("POST", "/rpc", &alice.sign_request("POST", "/rpc", b"{}"), b"{}").unwrap_err().status, 403);
+        let self_issued = sign_request_with_credentials(&mallory, &[membership(&mallory, &mallory)], "POST", "/rpc", b"{}", None).unwrap();
+        assert_eq!(middleware.verify("POST", "/rpc", &self_issued, b"{}").unwrap_err().status, 403);
+        let borrowed = sign_request_with_credentials(&mallory, &[membership(&alice, &org)], "POST", "/rpc", b"{}", None).unwrap();
+        assert_eq!(middleware.verify("POST", "/rpc", &borrowed, b"{}").unwrap_err().status, 403);
+
+        // The presentation is bound to the request signature
+        let mut replayed = alice.sign_request("POST", "/admin", b"{}");
+        replayed.extend(headers.iter().filter(|(name, _)| name == PRESENTATION_HEADER).cloned());
+        assert_eq!(middleware.verify("POST", "/admin", &replayed, b"{}").unwrap_err().status, 403);
+
+        // A presentation must be as fresh as the request it is bound to
+        let signed = alice.sign_request("POST", "/rpc", b"{}");
+        let signature = signed.iter().find(|(name, _)| name == SIGNATURE_HEADER).unwrap().1.clone();
+        let presentation = json!({
+            "@context": [CREDENTIALS_CONTEXT],
+            "type": ["VerifiablePresentation"],
+            "holder": alice.did().to_string(),
+            "verifiableCredential": [membership(&alice, &org)],
+        });
+        let options = json!({
+            "type": "DataIntegrityProof",
+            "cryptosuite": CRYPTOSUITE,
+            "created": time_ago(3600),
+            "verificationMethod": format!("{}#key1", alice.did()),
+            "proofPurpose": "authentication",
+            "challenge": signature,
+            "@context": [CREDENTIALS_CONTEXT],
+        });
+        let old = prove_with(&presentation, &alice, options);
+        let mut stale = signed.clone();
+        stale.push((PRESENTATION_HEADER.to_string(), base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(old.to_string())));
+        let rejected = middleware.verify("POST", "/rpc", &stale, b"{}").unwrap_err();
+        assert!(rejected.error.contains("Presentation was created outside"), "{}", rejected.error);
+
+        // Checked on its own, the header still demands a fresh request timestamp
+        let requirement = CredentialRequirement { credential_type: "OrgMemberCredential".to_string(), ..Default::default() };
+        let presented = headers.iter().find(|(name, _)| name == PRESENTATION_HEADER).unwrap().1.clone();
+        let resolver = DidResolver::new();
+        assert!(requirement.verify_header(&presented, &headers, &resolver, 300).is_ok());
+        let mut old_request = headers.clone();
+        old_request.retain(|(name, _)| name != crate::did_nostr::TIMESTAMP_HEADER);
+        old_request.push((crate::did_nostr::TIMESTAMP_HEADER.to_string(), (unix_now() - 3600).to_string()));
+        assert!(requirement.verify_header(&presented, &old_request, &resolver, 300).unwrap_err().contains("window"));
+
+        assert!(CredentialRequirement::from_json(r#"{"issuers": ["did:nostr:abc"]}"#).is_err());
+    }
+}
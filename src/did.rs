@@ -0,0 +1,614 @@
+//! Multi-method DIDs: `did:nostr`, `did:key` and `did:web`
+//!
+//! `Did` parses any supported identifier and each method implements [`DidMethod`]:
+//!
+//! - `did:nostr:<hex>`: document derived from the x-only key (see `did_document`)
+//! - `did:key:z...`: multibase (base58btc) multicodec key, `secp256k1-pub` (`0xe7`,
+//!   33-byte compressed) or `ed25519-pub` (`0xed`, 32 bytes)
+//! - `did:web:<host>[:path...]`: `did.json` fetched through the resolver's transport
+//!   (normally the privacy `HttpClient`). `https` is required except for loopback hosts,
+//!   which (like private and link-local hosts) are refused unless the resolver allows them.
+//!
+//! `DidVerifier` resolves the signer's DID and accepts the signature if any of its
+//! `authentication` keys verifies it: BIP-340 Schnorr for secp256k1 keys (as `NostrSigner`
+//! signs), Ed25519 for Ed25519 keys.
+
+use ed25519_dalek::Verifier as _;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use std::fmt;
+use std::str::FromStr;
+use crate::did_document::{DidDocument, DidResolver, PublicKeyJwk};
+use crate::did_nostr::{DEFAULT_MAX_AGE_SECS, DID_HEADER, DidNostr, NostrPublicKey, RequestCanonicalizer, SIGNATURE_HEADER, SignatureStamp, unix_now};
+use crate::transport::{HttpRequest, Transport, is_loopback_url, is_public_url};
+use base64::Engine;
+
+/// Methods `Did::parse` accepts, in `IdentityConfig.methods` form
+pub const SUPPORTED_METHODS: [&str; 3] = ["did:nostr", "did:key", "did:web"];
+
+const SECP256K1_CODEC: [u8; 2] = [0xe7, 0x01];
+const ED25519_CODEC: [u8; 2] = [0xed, 0x01];
+
+/// A DID method: how an identifier resolves to its document
+pub trait DidMethod: fmt::Display {
+    /// Method name as it appears in the DID (`nostr`, `key`, `web`)
+    fn method(&self) -> &'static str;
+
+    /// DID document for this identifier
+    fn resolve(&self, resolver: &DidResolver) -> Result<DidDocument, String>;
+}
+
+impl DidMethod for DidNostr {
+    fn method(&self) -> &'static str {
+        "nostr"
+    }
+
+    fn resolve(&self, resolver: &DidResolver) -> Result<DidDocument, String> {
+        resolver.nostr_document(self)
+    }
+}
+
+/// Key type of a verification method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyType {
+    Secp256k1,
+    Ed25519,
+}
+
+/// Public key of a verification method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicKey {
+    Secp256k1(k256::PublicKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl PublicKey {
+    /// Key from multicodec bytes (`secp256k1-pub` or `ed25519-pub` prefix)
+    pub fn from_multicodec(bytes: &[u8]) -> Result<Self, String> {
+        match bytes.split_at_checked(2) {
+            Some((codec, key)) if codec == SECP256K1_CODEC => k256::PublicKey::from_sec1_bytes(key)
+                .map(PublicKey::Secp256k1)
+                .map_err(|_| "Invalid secp256k1 public key".to_string()),
+            Some((codec, key)) if codec == ED25519_CODEC => key
+                .try_into()
+                .ok()
+                .and_then(|key| ed25519_dalek::VerifyingKey::from_bytes(key).ok())
+                .map(PublicKey::Ed25519)
+                .ok_or_else(|| "Invalid Ed25519 public key".to_string()),
+            _ => Err("Unsupported key type (expected secp256k1 or Ed25519 multicodec)".to_string()),
+        }
+    }
+
+    /// Key from a `publicKeyMultibase` value (base58btc `z` or base16 `f`)
+    pub fn from_multibase(multibase: &str) -> Result<Self, String> {
+        let bytes = match multibase.split_at_checked(1) {
+            Some(("z", encoded)) => bs58::decode(encoded).into_vec().map_err(|e| format!("Invalid base58: {}", e))?,
+            Some(("f", encoded)) => hex::decode(encoded).map_err(|e| format!("Invalid base16: {}", e))?,
+            _ => return Err(format!("Unsupported multibase encoding: {}", multibase)),
+        };
+        Self::from_multicodec(&bytes)
+    }
+
+    /// Key from a JWK (`EC`/`secp256k1` or `OKP`/`Ed25519`)
+    pub fn from_jwk(jwk: &PublicKeyJwk) -> Result<Self, String> {
+        let decode = |value: &str| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|e| format!("Invalid JWK coordinate: {}", e))
+        };
+        match (jwk.kty.as_str(), jwk.crv.as_str(), &jwk.y) {
+            ("EC", "secp256k1", Some(y)) => {
+                let mut sec1 = vec![0x04];
+                sec1.extend(decode(&jwk.x)?);
+                sec1.extend(decode(y)?);
+                k256::PublicKey::from_sec1_bytes(&sec1)
+                    .map(PublicKey::Secp256k1)
+                    .map_err(|_| "Invalid secp256k1 JWK".to_string())
+            }
+            ("OKP", "Ed25519", None) => decode(&jwk.x)?
+                .as_slice()
+                .try_into()
+                .ok()
+                .and_then(|key| ed25519_dalek::VerifyingKey::from_bytes(key).ok())
+                .map(PublicKey::Ed25519)
+                .ok_or_else(|| "Invalid Ed25519 JWK".to_string()),
+            (kty, crv, _) => Err(format!("Unsupported JWK: kty {} crv {}", kty, crv)),
+        }
+    }
+
+    /// Key of a NOSTR x-only public key (even y)
+    pub fn from_nostr(pubkey: &NostrPublicKey) -> Result<Self, String> {
+        let mut sec1 = vec![0x02];
+        sec1.extend(hex::decode(pubkey.as_hex()).map_err(|e| format!("Invalid public key hex: {}", e))?);
+        k256::PublicKey::from_sec1_bytes(&sec1)
+            .map(PublicKey::Secp256k1)
+            .map_err(|_| "Public key is not a valid secp256k1 x-only key".to_string())
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            PublicKey::Secp256k1(_) => KeyType::Secp256k1,
+            PublicKey::Ed25519(_) => KeyType::Ed25519,
+        }
+    }
+
+    /// Multicodec prefix + key (compressed for secp256k1)
+    pub fn to_multicodec(&self) -> Vec<u8> {
+        match self {
+            PublicKey::Secp256k1(key) => [&SECP256K1_CODEC[..], key.to_encoded_point(true).as_bytes()].concat(),
+            PublicKey::Ed25519(key) => [&ED25519_CODEC[..], key.as_bytes()].concat(),
+        }
+    }
+
+    /// `publicKeyMultibase` value (base58btc)
+    pub fn to_multibase(&self) -> String {
+        format!("z{}", bs58::encode(self.to_multicodec()).into_string())
+    }
+
+    pub fn to_jwk(&self) -> PublicKeyJwk {
+        let b64 = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        match self {
+            PublicKey::Secp256k1(key) => {
+                let point = key.to_encoded_point(false);
+                PublicKeyJwk {
+                    kty: "EC".to_string(),
+                    crv: "secp256k1".to_string(),
+                    x: b64(point.x().map(|x| &x[..]).unwrap_or_default()),
+                    y: Some(b64(point.y().map(|y| &y[..]).unwrap_or_default())),
+                }
+            }
+            PublicKey::Ed25519(key) => PublicKeyJwk {
+                kty: "OKP".to_string(),
+                crv: "Ed25519".to_string(),
+                x: b64(key.as_bytes()),
+                y: None,
+            },
+        }
+    }
+
+    /// Verify a signature over `message`: BIP-340 Schnorr (over `sha256(message)`, as
+    /// `NostrSigner` signs) for secp256k1, Ed25519 for Ed25519
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), String> {
+        match self {
+            PublicKey::Secp256k1(key) => {
+                let point = key.to_encoded_point(true);
+                let key = k256::schnorr::VerifyingKey::from_bytes(&point.as_bytes()[1..])
+                    .map_err(|_| "Invalid secp256k1 public key".to_string())?;
+                let signature = k256::schnorr::Signature::try_from(signature)
+                    .map_err(|_| "Invalid signature encoding".to_string())?;
+                k256::schnorr::signature::Verifier::verify(&key, message, &signature)
+                    .map_err(|_| "Signature verification failed".to_string())
+            }
+            PublicKey::Ed25519(key) => {
+                let signature = ed25519_dalek::Signature::from_slice(signature)
+                    .map_err(|_| "Invalid signature encoding".to_string())?;
+                key.verify(message, &signature)
+                    .map_err(|_| "Signature verification failed".to_string())
+            }
+        }
+    }
+}
+
+/// `did:key` identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DidKey {
+    /// Multibase-encoded multicodec key (`z...`)
+    multibase: String,
+}
+
+impl DidKey {
+    pub fn from_public_key(key: &PublicKey) -> Self {
+        Self { multibase: key.to_multibase() }
+    }
+
+    /// Parse `did:key:z...`
+    pub fn parse(did: &str) -> Result<Self, String> {
+        let multibase = did
+            .strip_prefix("did:key:")
+            .ok_or_else(|| format!("Invalid DID format: {}", did))?;
+        if !multibase.starts_with('z') {
+            return Err(format!("did:key must be base58btc multibase: {}", did));
+        }
+        PublicKey::from_multibase(multibase)?;
+        Ok(Self { multibase: multibase.to_string() })
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_multibase(&self.multibase).expect("validated when parsed")
+    }
+}
+
+impl fmt::Display for DidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "did:key:{}", self.multibase)
+    }
+}
+
+impl DidMethod for DidKey {
+    fn method(&self) -> &'static str {
+        "key"
+    }
+
+    fn resolve(&self, resolver: &DidResolver) -> Result<DidDocument, String> {
+        let id = self.to_string();
+        let key_id = format!("{}#{}", id, self.multibase);
+        Ok(resolver.key_document(id, key_id, self.multibase.clone(), &self.public_key()))
+    }
+}
+
+/// `did:web` identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DidWeb {
+    /// `host[%3Aport]` followed by `:`-separated path segments
+    id: String,
+}
+
+impl DidWeb {
+    /// Parse `did:web:example.com`, `did:web:localhost%3A8080:users:alice`, ...
+    pub fn parse(did: &str) -> Result<Self, String> {
+        let id = did
+            .strip_prefix("did:web:")
+            .ok_or_else(|| format!("Invalid DID format: {}", did))?;
+        let mut segments = id.split(':');
+        let host = segments.next().unwrap_or_default().to_ascii_lowercase().replace("%3a", ":");
+        let (name, port) = host.split_once(':').unwrap_or((&host, ""));
+        let valid_host = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+            && port.chars().all(|c| c.is_ascii_digit());
+        let valid_path = segments.all(|s| {
+            !s.is_empty() && s != "." && s != ".." && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~' | '%'))
+        });
+        if !valid_host || !valid_path {
+            return Err(format!("Invalid did:web identifier: {}", did));
+        }
+        Ok(Self { id: id.to_string() })
+    }
+
+    /// URL of the DID document: `/.well-known/did.json` for a bare host, else
+    /// `/<path>/did.json`
+    pub fn document_url(&self) -> String {
+        let mut segments = self.id.split(':');
+        let host = segments.next().unwrap_or_default().replace("%3A", ":").replace("%3a", ":");
+        let path: Vec<&str> = segments.collect();
+        let scheme = if is_loopback_url(&format!("http://{}/", host)) { "http" } else { "https" };
+        if path.is_empty() {
+            format!("{}://{}/.well-known/did.json", scheme, host)
+        } else {
+            format!("{}://{}/{}/did.json", scheme, host, path.join("/"))
+        }
+    }
+
+    /// Document at [`Self::document_url`], refusing non-public hosts unless `private_hosts`
+    fn fetch(&self, transport: &(dyn Transport + Send + Sync), private_hosts: bool) -> Result<DidDocument, String> {
+        let url = self.document_url();
+        if !private_hosts && !is_public_url(&url) {
+            return Err(format!("{} is not a public host", url));
+        }
+        let request = HttpRequest::get(&url).with_headers(&[("Accept".to_string(), "application/did+json, application/json".to_string())]);
+        let response = transport.send(request)?;
+        if response.status != 200 {
+            return Err(format!("{} returned HTTP {}", url, response.status));
+        }
+        let document: DidDocument = serde_json::from_slice(&response.body)
+            .map_err(|e| format!("Invalid DID document at {}: {}", url, e))?;
+        if document.id != self.to_string() {
+            return Err(format!("DID document at {} is for {}", url, document.id));
+        }
+        Ok(document)
+    }
+}
+
+impl fmt::Display for DidWeb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "did:web:{}", self.id)
+    }
+}
+
+impl DidMethod for DidWeb {
+    fn method(&self) -> &'static str {
+        "web"
+    }
+
+    fn resolve(&self, resolver: &DidResolver) -> Result<DidDocument, String> {
+        let transport = resolver
+            .transport()
+            .ok_or_else(|| "did:web resolution needs a transport (DidResolver::with_transport)".to_string())?;
+        resolver.cached(&self.to_string(), || self.fetch(transport, resolver.allows_private_hosts()))
+    }
+}
+
+/// Any supported DID
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Did {
+    Nostr(DidNostr),
+    Key(DidKey),
+    Web(DidWeb),
+}
+
+impl Did {
+    /// Parse a DID of any method in [`SUPPORTED_METHODS`]
+    pub fn parse(did: &str) -> Result<Self, String> {
+        match did.split(':').nth(1) {
+            _ if !did.starts_with("did:") => Err(format!("Invalid DID format: {}", did)),
+            Some("nostr") => DidNostr::from_str(did).map(Did::Nostr),
+            Some("key") => DidKey::parse(did).map(Did::Key),
+            Some("web") => DidWeb::parse(did).map(Did::Web),
+            _ => Err(format!("Unsupported DID method: {}", did)),
+        }
+    }
+
+    /// Whether `did` names a method this crate cannot resolve
+    pub fn is_unsupported_method(did: &str) -> bool {
+        did.starts_with("did:") && !SUPPORTED_METHODS.iter().any(|m| did.starts_with(&format!("{}:", m)))
+    }
+
+    fn inner(&self) -> &dyn DidMethod {
+        match self {
+            Did::Nostr(did) => did,
+            Did::Key(did) => did,
+            Did::Web(did) => did,
+        }
+    }
+}
+
+impl fmt::Display for Did {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner().fmt(f)
+    }
+}
+
+impl FromStr for Did {
+    type Err = String;
+
+    fn from_str(did: &str) -> Result<Self, Self::Err> {
+        Did::parse(did)
+    }
+}
+
+impl DidMethod for Did {
+    fn method(&self) -> &'static str {
+        self.inner().method()
+    }
+
+    fn resolve(&self, resolver: &DidResolver) -> Result<DidDocument, String> {
+        self.inner().resolve(resolver)
+    }
+}
+
+/// Signature verification for any supported DID method
+#[derive(Debug, Clone)]
+pub struct DidVerifier {
+    resolver: DidResolver,
+    methods: Vec<String>,
+}
+
+impl DidVerifier {
+    /// Verifier accepting all supported methods
+    pub fn new(resolver: DidResolver) -> Self {
+        Self { resolver, methods: SUPPORTED_METHODS.iter().map(|m| m.to_string()).collect() }
+    }
+
+    /// Only accept DIDs of `methods` (e.g. `IdentityConfig.methods`)
+    pub fn with_methods(mut self, methods: &[String]) -> Self {
+        self.methods = methods.to_vec();
+        self
+    }
+
+    /// Resolve DIDs with `resolver`, keeping the accepted methods
+    pub fn with_resolver(mut self, resolver: DidResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    pub fn resolver(&self) -> &DidResolver {
+        &self.resolver
+    }
+
+    /// Check a hex signature over `message` by any of the DID's authentication keys
+    pub fn verify(&self, did: &str, message: &str, signature_hex: &str) -> Result<Did, String> {
+        let did = Did::parse(did)?;
+        let method = format!("did:{}", did.method());
+        if !self.methods.contains(&method) {
+            return Err(format!("DID method {} is not accepted", method));
+        }
+        let document = did.resolve(&self.resolver)?;
+        let signature = hex::decode(signature_hex).map_err(|_| "Invalid signature encoding".to_string())?;
+        let mut error = format!("{} has no authentication key", did);
+        for method in document.authentication_methods() {
+            match method.public_key().and_then(|key| key.verify(message.as_bytes(), &signature)) {
+                Ok(()) => return Ok(did),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+
+    /// Verify a request carrying `X-DID`, `X-Timestamp`, `X-Nonce` and `X-Signature`
+    /// headers, signed over the canonical form of [`RequestCanonicalizer::canonicalize_signed`]
+    /// no more than [`DEFAULT_MAX_AGE_SECS`] away from now
+    pub fn verify_request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<Did, String> {
+        self.verify_request_with_max_age(method, path, headers, body, DEFAULT_MAX_AGE_SECS)
+    }
+
+    /// [`Self::verify_request`] with a custom timestamp window
+    pub fn verify_request_with_max_age(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        max_age_secs: u64,
+    ) -> Result<Did, String> {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        let (Some(did), Some(signature)) = (header(DID_HEADER), header(SIGNATURE_HEADER)) else {
+            return Err("Missing DID or Signature headers".to_string());
+        };
+        let stamp = SignatureStamp::from_headers(headers)?;
+        stamp.check_age(unix_now(), max_age_secs)?;
+        let canonical = RequestCanonicalizer::canonicalize_signed(method, path, did, &stamp, body);
+        self.verify(did, &canonical, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did_document::{KeyFormat, VerificationMethodRef};
+    use crate::did_nostr::NostrSigner;
+    use crate::HttpClient;
+    use ed25519_dalek::Signer as _;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    // did:key test vector from the did:key spec
+    const ED25519_DID: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    #[test]
+    fn test_parse_methods() {
+        let nostr = NostrSigner::generate().did().to_string();
+        assert!(matches!(Did::parse(&nostr), Ok(Did::Nostr(_))));
+        let key = Did::parse(ED25519_DID).unwrap();
+        assert_eq!(key.to_string(), ED25519_DID);
+        let Did::Key(did_key) = &key else { panic!("expected did:key") };
+        assert_eq!(did_key.public_key().key_type(), KeyType::Ed25519);
+        assert_eq!(DidKey::from_public_key(&did_key.public_key()), *did_key);
+
+        let web = DidWeb::parse("did:web:example.com").unwrap();
+        assert_eq!(web.document_url(), "https://example.com/.well-known/did.json");
+        let web = DidWeb::parse("did:web:localhost%3A8080:users:alice").unwrap();
+        assert_eq!(web.document_url(), "http://localhost:8080/users/alice/did.json");
+        assert!(DidWeb::parse("did:web:example.com:..").is_err());
+        for internal in ["http://localhost:8080/", "https://10.0.0.1/", "https://169.254.169.254/", "https://100.64.0.1/", "https://[fd00::1]/", "https://[::ffff:192.168.0.1]/"] {
+            assert!(!is_public_url(internal), "{}", internal);
+        }
+        assert!(is_public_url("https://93.184.215.14/"));
+
+        assert!(Did::parse("did:key:zInvalid").is_err());
+        assert!(Did::parse("did:example:123").is_err());
+        assert!(Did::is_unsupported_method("did:example:123"));
+        assert!(!Did::is_unsupported_method("did:key:zInvalid"));
+    }
+
+    #[test]
+    fn test_verify_picks_algorithm_from_key() {
+        let verifier = DidVerifier::new(DidResolver::new());
+
+        // secp256k1 did:key signed with a NOSTR key (BIP-340)
+        let signer = NostrSigner::generate();
+        let did = DidKey::from_public_key(&PublicKey::from_nostr(signer.pubkey()).unwrap()).to_string();
+        let signature = signer.sign("hello");
+        assert!(verifier.verify(&did, "hello", signature.as_hex()).is_ok());
+        assert!(verifier.verify(&did, "hullo", signature.as_hex()).is_err());
+
+        // Ed25519 did:key
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let did = DidKey::from_public_key(&PublicKey::Ed25519(key.verifying_key())).to_string();
+        assert!(did.starts_with("did:key:z6Mk"));
+        let stamp = SignatureStamp::now();
+        let canonical = RequestCanonicalizer::canonicalize_signed("post", "/tasks", &did, &stamp, b"{}");
+        let mut headers = vec![(DID_HEADER.to_string(), did.clone())];
+        headers.extend(stamp.headers());
+        headers.push((SIGNATURE_HEADER.to_string(), hex::encode(key.sign(canonical.as_bytes()).to_bytes())));
+        assert_eq!(verifier.verify_request("POST", "/tasks", &headers, b"{}").unwrap().to_string(), did);
+        assert!(verifier.verify_request("POST", "/other", &headers, b"{}").is_err());
+
+        // did:nostr still verifies, and methods can be restricted
+        let headers = signer.sign_request("GET", "/", b"");
+        assert!(matches!(verifier.verify_request("GET", "/", &headers, b""), Ok(Did::Nostr(_))));
+        let nostr_only = verifier.with_methods(&["did:nostr".to_string()]);
+        let error = nostr_only.verify(&did, "x", &"0".repeat(128)).unwrap_err();
+        assert!(error.contains("not accepted"));
+    }
+
+    #[test]
+    fn test_did_key_document_formats() {
+        let did = Did::parse(ED25519_DID).unwrap();
+        let document = did.resolve(&DidResolver::new()).unwrap();
+        let method = &document.verification_method[0];
+        assert_eq!(method.id, format!("{}#{}", ED25519_DID, &ED25519_DID[8..]));
+        assert_eq!(method.public_key_multibase.as_deref(), Some(&ED25519_DID[8..]));
+        assert_eq!(document.authentication, vec![VerificationMethodRef::Reference(method.id.clone())]);
+
+        let jwk_resolver = DidResolver::new().with_key_format(KeyFormat::JsonWebKey);
+        let document = did.resolve(&jwk_resolver).unwrap();
+        let jwk = document.verification_method[0].public_key_jwk.clone().unwrap();
+        assert_eq!((jwk.kty.as_str(), jwk.crv.as_str(), jwk.y.is_none()), ("OKP", "Ed25519", true));
+        assert_eq!(PublicKey::from_jwk(&jwk).unwrap(), PublicKey::from_multibase(&ED25519_DID[8..]).unwrap());
+    }
+
+    /// Serve `document` at `/.well-known/did.json` and 404 elsewhere
+    fn serve_did_json(listener: TcpListener, document: String) {
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let response = if request.starts_with(b"GET /.well-known/did.json ") {
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: application/did+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", document.len(), document)
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_did_web_resolution_and_verification() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let did = format!("did:web:127.0.0.1%3A{}", listener.local_addr().unwrap().port());
+        let key = ed25519_dalek::SigningKey::from_bytes(&[9; 32]);
+        let public_key = PublicKey::Ed25519(key.verifying_key());
+        let old_key = PublicKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&[8; 32]).verifying_key());
+        // The signing key is the second authentication key, embedded rather than referenced
+        let document = serde_json::json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": did,
+            "verificationMethod": [{
+                "id": format!("{}#old", did),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": old_key.to_jwk(),
+            }],
+            "authentication": ["#old", {
+                "id": format!("{}#owner", did),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": public_key.to_jwk(),
+            }],
+        });
+        serve_did_json(listener, document.to_string());
+        let transport: Arc<dyn Transport + Send + Sync> = Arc::new(HttpClient::new(String::new()));
+        let refused = DidResolver::new().with_transport(Arc::clone(&transport)).resolve(&did);
+        assert!(refused.did_resolution_metadata.error_message.unwrap().contains("not a public host"));
+        let resolver = DidResolver::new().with_transport(transport).with_private_hosts(true);
+
+        let result = resolver.resolve(&did);
+        assert_eq!(result.did_document.unwrap().id, did);
+        let missing = resolver.resolve(&format!("{}:nobody", did));
+        assert_eq!(missing.did_resolution_metadata.error.as_deref(), Some("notFound"));
+
+        let verifier = DidVerifier::new(resolver);
+        let signature = hex::encode(key.sign(b"hello").to_bytes());
+        assert!(matches!(verifier.verify(&did, "hello", &signature), Ok(Did::Web(_))));
+        assert!(verifier.verify(&did, "hullo", &signature).is_err());
+
+        let unresolvable = DidVerifier::new(DidResolver::new());
+        assert!(unresolvable.verify(&did, "hello", &signature).unwrap_err().contains("transport"));
+    }
+}
@@ -0,0 +1,523 @@
+//! W3C DID Documents for `did:nostr`
+//!
+//! `DidResolver` turns a `did:nostr` identifier into a DID Core document and wraps it in
+//! a DID Resolution result (`didDocument`, `didResolutionMetadata`, `didDocumentMetadata`).
+//! The document has a single secp256k1 verification method (`#key1`), referenced by
+//! `authentication` and `assertionMethod`, in either form:
+//!
+//! - `Multikey`: `publicKeyMultibase` is base16 (`f`) + the `secp256k1-pub` multicodec
+//!   (`e701`) + the compressed key. BIP-340 keys have an even y, so it is `02` + x.
+//! - `JsonWebKey`: `publicKeyJwk` with `kty: EC`, `crv: secp256k1` and the recovered y.
+//!
+//! Relays and a NIP-05 identifier can be registered per DID and are listed as services.
+//! `did:key` and `did:web` identifiers resolve through the same resolver (see `did`).
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::did::{Did, DidMethod, PublicKey};
+use crate::did_nostr::DidNostr;
+use crate::transport::Transport;
+
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const MULTIKEY_CONTEXT: &str = "https://w3id.org/security/multikey/v1";
+const JWK_CONTEXT: &str = "https://w3id.org/security/jwk/v1";
+const RESOLUTION_CONTEXT: &str = "https://w3id.org/did-resolution/v1";
+/// Media type of a resolved document
+pub const DID_LD_JSON: &str = "application/did+ld+json";
+/// Multibase prefix of a compressed secp256k1 key with even y (base16, `secp256k1-pub`, `02`)
+const MULTIKEY_PREFIX: &str = "fe70102";
+const KEY_FRAGMENT: &str = "key1";
+/// Resolved documents kept by a [`DidResolver`] cache
+const DOCUMENT_CACHE_CAPACITY: usize = 1024;
+/// Upper bound on how long a failed resolution is remembered
+const FAILURE_TTL: Duration = Duration::from_secs(30);
+
+/// How the verification method encodes the key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyFormat {
+    #[default]
+    Multikey,
+    JsonWebKey,
+}
+
+impl KeyFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_ascii_lowercase().as_str() {
+            "multikey" => Ok(KeyFormat::Multikey),
+            "jsonwebkey" | "jwk" => Ok(KeyFormat::JsonWebKey),
+            _ => Err(format!("Unknown key format: {} (expected Multikey or JsonWebKey)", format)),
+        }
+    }
+}
+
+/// Public key as a JWK (`EC`/`secp256k1` or `OKP`/`Ed25519`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyJwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// Entry of `verificationMethod`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_multibase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_jwk: Option<PublicKeyJwk>,
+}
+
+impl VerificationMethod {
+    /// Key from `publicKeyMultibase` or `publicKeyJwk`
+    pub fn public_key(&self) -> Result<PublicKey, String> {
+        match (&self.public_key_multibase, &self.public_key_jwk) {
+            (Some(multibase), _) => PublicKey::from_multibase(multibase),
+            (None, Some(jwk)) => PublicKey::from_jwk(jwk),
+            (None, None) => Err(format!("Verification method {} has no supported key", self.id)),
+        }
+    }
+}
+
+/// Entry of `service`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub service_type: String,
+    pub service_endpoint: String,
+}
+
+/// Entry of `authentication` or `assertionMethod`: a reference (absolute or `#fragment`)
+/// into `verificationMethod`, or a method embedded in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerificationMethodRef {
+    Reference(String),
+    Embedded(VerificationMethod),
+}
+
+// `@context` may be a single string in fetched documents
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(context) => vec![context],
+        OneOrMany::Many(contexts) => contexts,
+    })
+}
+
+/// DID Core document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context", deserialize_with = "one_or_many")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(default)]
+    pub verification_method: Vec<VerificationMethod>,
+    #[serde(default)]
+    pub authentication: Vec<VerificationMethodRef>,
+    #[serde(default)]
+    pub assertion_method: Vec<VerificationMethodRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub service: Vec<Service>,
+}
+
+impl DidDocument {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// `id` with a bare `#fragment` made absolute against the document id
+    pub fn absolute_id(&self, id: &str) -> String {
+        match id.strip_prefix('#') {
+            Some(fragment) => format!("{}#{}", self.id, fragment),
+            None => id.to_string(),
+        }
+    }
+
+    /// Verification method referenced by `reference` (`did#key` or `#key`)
+    pub fn verification_method(&self, reference: &str) -> Option<&VerificationMethod> {
+        let absolute = self.absolute_id(reference);
+        self.verification_method.iter().find(|method| self.absolute_id(&method.id) == absolute)
+    }
+
+    /// Method an `authentication` or `assertionMethod` entry stands for
+    pub fn relationship_method<'a>(&'a self, entry: &'a VerificationMethodRef) -> Option<&'a VerificationMethod> {
+        match entry {
+            VerificationMethodRef::Reference(reference) => self.verification_method(reference),
+            VerificationMethodRef::Embedded(method) => Some(method),
+        }
+    }
+
+    /// First key usable for `authentication`
+    pub fn authentication_method(&self) -> Option<&VerificationMethod> {
+        self.authentication_methods().next()
+    }
+
+    /// Every key usable for `authentication`, in document order
+    pub fn authentication_methods(&self) -> impl Iterator<Item = &VerificationMethod> {
+        self.authentication.iter().filter_map(|entry| self.relationship_method(entry))
+    }
+}
+
+/// Relays and NIP-05 identifier published for a DID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NostrServices {
+    /// `wss://` (or `ws://`) relay URLs
+    pub relays: Vec<String>,
+    /// NIP-05 identifier (`name@domain`)
+    pub nip05: Option<String>,
+}
+
+impl NostrServices {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let services: Self = serde_json::from_str(json).map_err(|e| format!("Invalid services JSON: {}", e))?;
+        services.validate()?;
+        Ok(services)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(relay) = self.relays.iter().find(|r| !r.starts_with("wss://") && !r.starts_with("ws://")) {
+            return Err(format!("Relay URL must use ws:// or wss://: {}", relay));
+        }
+        if let Some(nip05) = &self.nip05 {
+            nip05_url(nip05)?;
+        }
+        Ok(())
+    }
+}
+
+/// `https://<domain>/.well-known/nostr.json?name=<name>` for a NIP-05 identifier
+fn nip05_url(identifier: &str) -> Result<String, String> {
+    let (name, domain) = identifier
+        .split_once('@')
+        .ok_or_else(|| format!("Invalid NIP-05 identifier: {}", identifier))?;
+    let valid_name = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    let valid_domain = domain.contains('.')
+        && domain.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'));
+    if !valid_name || !valid_domain {
+        return Err(format!("Invalid NIP-05 identifier: {}", identifier));
+    }
+    Ok(format!("https://{}/.well-known/nostr.json?name={}", domain.to_ascii_lowercase(), name.to_ascii_lowercase()))
+}
+
+/// `didResolutionMetadata`: `contentType` on success, `error` otherwise
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// `invalidDid`, `notFound` or `methodNotSupported`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// DID Resolution result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub did_document: Option<DidDocument>,
+    pub did_resolution_metadata: ResolutionMetadata,
+    /// Always empty: documents carry no version or update metadata
+    pub did_document_metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ResolutionResult {
+    fn failure(error: &str, message: String) -> Self {
+        Self {
+            context: RESOLUTION_CONTEXT.to_string(),
+            did_document: None,
+            did_resolution_metadata: ResolutionMetadata {
+                content_type: None,
+                error: Some(error.to_string()),
+                error_message: Some(message),
+            },
+            did_document_metadata: serde_json::Map::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Fetched document (or failure) and its expiry
+type CachedDocument = (Instant, Result<DidDocument, String>);
+
+/// Fetched documents by DID
+#[derive(Debug)]
+struct DocumentCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedDocument>>,
+}
+
+impl DocumentCache {
+    fn get(&self, did: &str) -> Option<Result<DidDocument, String>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(did).filter(|(expires, _)| *expires > Instant::now()).map(|(_, result)| result.clone())
+    }
+
+    fn insert(&self, did: &str, result: Result<DidDocument, String>) {
+        let now = Instant::now();
+        let ttl = if result.is_ok() { self.ttl } else { self.ttl.min(FAILURE_TTL) };
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= DOCUMENT_CACHE_CAPACITY && !entries.contains_key(did) {
+            entries.retain(|_, (expires, _)| *expires > now);
+            if let Some(oldest) = entries.iter().min_by_key(|(_, (expires, _))| *expires).map(|(k, _)| k.clone()) {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(did.to_string(), (now + ttl, result));
+    }
+}
+
+/// Builds DID documents for `did:nostr` and `did:key`, fetches them for `did:web`
+#[derive(Clone, Default)]
+pub struct DidResolver {
+    key_format: KeyFormat,
+    services: HashMap<DidNostr, NostrServices>,
+    transport: Option<Arc<dyn Transport + Send + Sync>>,
+    /// Fetch `did:web` documents from loopback, private and link-local hosts
+    private_hosts: bool,
+    cache: Option<Arc<DocumentCache>>,
+}
+
+impl fmt::Debug for DidResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DidResolver")
+            .field("key_format", &self.key_format)
+            .field("services", &self.services)
+            .field("transport", &self.transport.is_some())
+            .field("private_hosts", &self.private_hosts)
+            .field("cache_ttl", &self.cache.as_ref().map(|cache| cache.ttl))
+            .finish()
+    }
+}
+
+impl DidResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_key_format(mut self, key_format: KeyFormat) -> Self {
+        self.key_format = key_format;
+        self
+    }
+
+    /// Fetch `did:web` documents through `transport` (normally the privacy `HttpClient`)
+    pub fn with_transport(mut self, transport: Arc<dyn Transport + Send + Sync>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    pub(crate) fn transport(&self) -> Option<&(dyn Transport + Send + Sync)> {
+        self.transport.as_deref()
+    }
+
+    /// Allow `did:web` hosts on loopback, private and link-local addresses. They are
+    /// refused by default, so a caller-chosen DID cannot make a server fetch internal URLs.
+    pub fn with_private_hosts(mut self, allow: bool) -> Self {
+        self.private_hosts = allow;
+        self
+    }
+
+    pub(crate) fn allows_private_hosts(&self) -> bool {
+        self.private_hosts
+    }
+
+    /// Keep fetched `did:web` documents for `ttl` (failures for at most 30 seconds)
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(DocumentCache { ttl, entries: Mutex::new(HashMap::new()) }));
+        self
+    }
+
+    /// Cached result for `did`, or the result of `fetch`
+    pub(crate) fn cached(&self, did: &str, fetch: impl FnOnce() -> Result<DidDocument, String>) -> Result<DidDocument, String> {
+        let Some(cache) = &self.cache else {
+            return fetch();
+        };
+        if let Some(result) = cache.get(did) {
+            return result;
+        }
+        let result = fetch();
+        cache.insert(did, result.clone());
+        result
+    }
+
+    /// Publish relays and a NIP-05 identifier in the document of `did`
+    pub fn add_services(&mut self, did: DidNostr, services: NostrServices) -> Result<(), String> {
+        services.validate()?;
+        self.services.insert(did, services);
+        Ok(())
+    }
+
+    /// Document for `did`
+    pub fn document<M: DidMethod + ?Sized>(&self, did: &M) -> Result<DidDocument, String> {
+        did.resolve(self)
+    }
+
+    /// Single-key document in the configured key format
+    pub(crate) fn key_document(&self, id: String, key_id: String, multibase: String, key: &PublicKey) -> DidDocument {
+        let mut method = VerificationMethod {
+            id: key_id.clone(),
+            method_type: String::new(),
+            controller: id.clone(),
+            public_key_multibase: None,
+            public_key_jwk: None,
+        };
+        let key_context = match self.key_format {
+            KeyFormat::Multikey => {
+                method.method_type = "Multikey".to_string();
+                method.public_key_multibase = Some(multibase);
+                MULTIKEY_CONTEXT
+            }
+            KeyFormat::JsonWebKey => {
+                method.method_type = "JsonWebKey".to_string();
+                method.public_key_jwk = Some(key.to_jwk());
+                JWK_CONTEXT
+            }
+        };
+        DidDocument {
+            context: vec![DID_CONTEXT.to_string(), key_context.to_string()],
+            id,
+            verification_method: vec![method],
+            authentication: vec![VerificationMethodRef::Reference(key_id.clone())],
+            assertion_method: vec![VerificationMethodRef::Reference(key_id)],
+            service: Vec::new(),
+        }
+    }
+
+    /// `did:nostr` document; fails if the key is not a valid BIP-340 public key
+    pub(crate) fn nostr_document(&self, did: &DidNostr) -> Result<DidDocument, String> {
+        let key = PublicKey::from_nostr(did.pubkey()).map_err(|e| format!("{}: {}", did, e))?;
+        let id = did.to_string();
+        let key_id = format!("{}#{}", id, KEY_FRAGMENT);
+        let multibase = format!("{}{}", MULTIKEY_PREFIX, did.pubkey().as_hex());
+        let mut document = self.key_document(id.clone(), key_id, multibase, &key);
+
+        if let Some(services) = self.services.get(did) {
+            for (i, relay) in services.relays.iter().enumerate() {
+                document.service.push(Service {
+                    id: format!("{}#relay{}", id, i + 1),
+                    service_type: "Relay".to_string(),
+                    service_endpoint: relay.clone(),
+                });
+            }
+            if let Some(nip05) = &services.nip05 {
+                document.service.push(Service {
+                    id: format!("{}#nip05", id),
+                    service_type: "Nip05".to_string(),
+                    service_endpoint: nip05_url(nip05)?,
+                });
+            }
+        }
+        Ok(document)
+    }
+
+    /// Resolve a DID string. Failures are reported in `didResolutionMetadata`.
+    pub fn resolve(&self, did: &str) -> ResolutionResult {
+        let parsed = match Did::parse(did) {
+            Ok(parsed) => parsed,
+            Err(e) if Did::is_unsupported_method(did) => return ResolutionResult::failure("methodNotSupported", e),
+            Err(e) => return ResolutionResult::failure("invalidDid", e),
+        };
+        let document = match parsed.resolve(self) {
+            Ok(document) => document,
+            Err(e) if matches!(parsed, Did::Web(_)) => return ResolutionResult::failure("notFound", e),
+            Err(e) => return ResolutionResult::failure("invalidDid", e),
+        };
+        ResolutionResult {
+            context: RESOLUTION_CONTEXT.to_string(),
+            did_document: Some(document),
+            did_resolution_metadata: ResolutionMetadata {
+                content_type: Some(DID_LD_JSON.to_string()),
+                ..Default::default()
+            },
+            did_document_metadata: serde_json::Map::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use crate::did_nostr::NostrSigner;
+
+    #[test]
+    fn test_multikey_document_with_services() {
+        let did = NostrSigner::generate().did();
+        let mut resolver = DidResolver::new();
+        let services = NostrServices::from_json(r#"{"relays": ["wss://relay.example.com"], "nip05": "Bob@Example.com"}"#).unwrap();
+        resolver.add_services(did.clone(), services).unwrap();
+
+        let result = resolver.resolve(&did.to_string());
+        assert_eq!(result.did_resolution_metadata.content_type.as_deref(), Some(DID_LD_JSON));
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        let document = &json["didDocument"];
+        let key_id = format!("{}#key1", did);
+        assert_eq!(document["id"], did.to_string());
+        assert_eq!(document["verificationMethod"][0]["type"], "Multikey");
+        assert_eq!(document["verificationMethod"][0]["publicKeyMultibase"], format!("fe70102{}", did.pubkey().as_hex()));
+        assert_eq!(document["authentication"][0], key_id);
+        assert_eq!(document["assertionMethod"][0], key_id);
+        assert_eq!(document["service"][0]["serviceEndpoint"], "wss://relay.example.com");
+        assert_eq!(document["service"][1]["serviceEndpoint"], "https://example.com/.well-known/nostr.json?name=bob");
+        assert_eq!(json["didDocumentMetadata"], serde_json::json!({}));
+
+        assert!(NostrServices::from_json(r#"{"relays": ["https://relay.example.com"]}"#).is_err());
+        assert!(NostrServices::from_json(r#"{"nip05": "bob"}"#).is_err());
+    }
+
+    #[test]
+    fn test_jwk_document_matches_key() {
+        let signer = NostrSigner::generate();
+        let resolver = DidResolver::new().with_key_format(KeyFormat::parse("jwk").unwrap());
+        let document = resolver.document(&signer.did()).unwrap();
+        assert!(document.service.is_empty());
+        let jwk = document.verification_method[0].public_key_jwk.clone().unwrap();
+        assert_eq!(jwk.crv, "secp256k1");
+        let x = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(&jwk.x).unwrap();
+        let y = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(jwk.y.unwrap()).unwrap();
+        assert_eq!(hex::encode(x), signer.pubkey().as_hex());
+        assert_eq!(y.len(), 32);
+        assert_eq!(y[31] % 2, 0);
+    }
+
+    #[test]
+    fn test_resolution_errors() {
+        let resolver = DidResolver::new();
+        let error = |did: &str| resolver.resolve(did).did_resolution_metadata.error;
+        assert_eq!(error("did:example:123").as_deref(), Some("methodNotSupported"));
+        assert_eq!(error("did:key:z123").as_deref(), Some("invalidDid"));
+        assert_eq!(error("did:nostr:xyz").as_deref(), Some("invalidDid"));
+        assert_eq!(error("nostr").as_deref(), Some("invalidDid"));
+        // x = 5 is not on secp256k1
+        let off_curve = format!("did:nostr:{:0>64}", "5");
+        let result = resolver.resolve(&off_curve);
+        assert_eq!(result.did_resolution_metadata.error.as_deref(), Some("invalidDid"));
+        assert!(result.did_document.is_none());
+    }
+}
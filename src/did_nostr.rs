@@ -6,24 +6,54 @@
 //! # Features
 //! - Parse DID from `did:nostr:...` format
 //! - Extract NOSTR public key from DID
-//! - Verify NOSTR signatures using secp256k1
+//! - Verify NOSTR signatures using secp256k1 (BIP-340 Schnorr)
+//! - Sign messages and requests with a NOSTR secret key (requests carry a signed
+//!   timestamp and nonce, so captured requests cannot be replayed)
 //! - Create DID from public key
-//! - Middleware integration for request verification
-
+//! - Middleware integration for request verification (with per-DID rate limiting)
+
+use k256::schnorr::signature::{Signer, Verifier};
+use k256::schnorr::{Signature, SigningKey, VerifyingKey};
+use crate::credential::{CredentialRequirement, PRESENTATION_HEADER};
+use crate::did::{Did, DidVerifier};
+use crate::did_document::DidResolver;
+use crate::ratelimit::{LimitKey, RateLimiter};
+use k256::elliptic_curve::rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Header carrying the caller's DID
+pub const DID_HEADER: &str = "X-DID";
+/// Header carrying the hex Schnorr signature over the canonical request
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// Header carrying the signing time (Unix seconds)
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+/// Header carrying a random hex nonce, unique per signed request
+pub const NONCE_HEADER: &str = "X-Nonce";
+/// How far (in seconds) a request timestamp may be from the verifier's clock
+pub const DEFAULT_MAX_AGE_SECS: u64 = 300;
+/// How many nonces the verifier remembers before rejecting new requests
+pub const DEFAULT_REPLAY_CAPACITY: usize = 100_000;
 
 /// DID-NOSTR Configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct IdentityConfig {
     /// Enable identity verification
     pub enabled: bool,
-    /// Accepted DID methods (e.g., "did:nostr")
+    /// Accepted DID methods (e.g., "did:nostr"; see `did::DidVerifier::with_methods`)
     pub methods: Vec<String>,
     /// Require valid signature for all requests
     pub require_signature: bool,
     /// Log verified identities (redacted)
     pub log_identities: bool,
+    /// Credential callers must present in `X-Presentation` (see `credential`)
+    pub credential: Option<CredentialRequirement>,
 }
 
 impl Default for IdentityConfig {
@@ -33,12 +63,13 @@ impl Default for IdentityConfig {
             methods: vec!["did:nostr".to_string()],
             require_signature: false,
             log_identities: false,
+            credential: None,
         }
     }
 }
 
 /// NOSTR Public Key (hex-encoded)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NostrPublicKey(pub String);
 
 impl NostrPublicKey {
@@ -66,7 +97,7 @@ impl fmt::Display for NostrPublicKey {
 }
 
 /// DID-NOSTR Identifier
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DidNostr {
     /// Underlying NOSTR public key
     pubkey: NostrPublicKey,
@@ -79,6 +110,7 @@ impl DidNostr {
     }
 
     /// Parse DID from string (e.g., "did:nostr:npub1..." or "did:nostr:hexkey")
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(did: &str) -> Result<Self, String> {
         if !did.starts_with("did:nostr:") {
             return Err(format!("Invalid DID format: {}", did));
@@ -117,21 +149,24 @@ impl DidNostr {
     pub fn pubkey(&self) -> &NostrPublicKey {
         &self.pubkey
     }
-
-    /// Get DID as string
-    pub fn to_string(&self) -> String {
-        format!("did:nostr:{}", self.pubkey.as_hex())
-    }
 }
 
 impl fmt::Display for DidNostr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "did:nostr:{}", self.pubkey.as_hex())
+    }
+}
+
+impl FromStr for DidNostr {
+    type Err = String;
+
+    fn from_str(did: &str) -> Result<Self, Self::Err> {
+        DidNostr::from_str(did)
     }
 }
 
 /// NOSTR Signature (hex-encoded)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NostrSignature(pub String);
 
 impl NostrSignature {
@@ -196,49 +231,422 @@ impl NostrVerifier {
     /// Verify a NOSTR signature
     ///
     /// # Arguments
-    /// * `pubkey` - The NOSTR public key (hex-encoded)
+    /// * `pubkey` - The NOSTR public key (hex-encoded, x-only)
     /// * `message` - The canonicalized message that was signed
     /// * `signature` - The NOSTR signature (hex-encoded)
     ///
     /// # Returns
     /// Result indicating whether the signature is valid
     ///
-    /// # Implementation Note
-    /// This is a stub that requires the `secp256k1` crate for actual verification.
-    /// In production, use:
-    /// ```ignore
-    /// use secp256k1::{Secp256k1, PublicKey, Message};
-    /// ```
+    /// Signatures are BIP-340 Schnorr signatures over `sha256(message)`, the same
+    /// construction NOSTR uses for event ids.
     pub fn verify(
         pubkey: &NostrPublicKey,
         message: &str,
         signature: &NostrSignature,
     ) -> VerificationResult {
-        // TODO: Implement actual secp256k1 verification
-        // This requires the secp256k1 crate:
-        //
-        // use secp256k1::{Secp256k1, PublicKey, Message};
-        // use std::str::FromStr;
-        //
-        // let secp = Secp256k1::new();
-        // let pubkey_bytes = hex::decode(pubkey.as_hex())
-        //     .map_err(|e| VerificationResult::failure(e.to_string()))?;
-        // let pk = PublicKey::from_slice(&pubkey_bytes)
-        //     .map_err(|e| VerificationResult::failure(e.to_string()))?;
-        // let msg = Message::from_slice(message.as_bytes())
-        //     .map_err(|e| VerificationResult::failure(e.to_string()))?;
-        // let sig_bytes = hex::decode(signature.as_hex())
-        //     .map_err(|e| VerificationResult::failure(e.to_string()))?;
-        // let sig = Signature::from_compact(&sig_bytes)
-        //     .map_err(|e| VerificationResult::failure(e.to_string()))?;
-        //
-        // match secp.verify_ecdsa(&msg, &sig, &pk) {
-        //     Ok(_) => VerificationResult::success(DidNostr::from_pubkey(pubkey.clone())),
-        //     Err(e) => VerificationResult::failure(e.to_string()),
-        // }
+        let key = match hex::decode(pubkey.as_hex())
+            .ok()
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        {
+            Some(key) => key,
+            None => return VerificationResult::failure("Invalid public key".to_string()),
+        };
+        let sig = match hex::decode(signature.as_hex())
+            .ok()
+            .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+        {
+            Some(sig) => sig,
+            None => return VerificationResult::failure("Invalid signature encoding".to_string()),
+        };
+
+        match key.verify(message.as_bytes(), &sig) {
+            Ok(()) => VerificationResult::success(DidNostr::from_pubkey(pubkey.clone())),
+            Err(_) => VerificationResult::failure("Signature verification failed".to_string()),
+        }
+    }
+
+    /// Verify a signed HTTP request carrying `X-DID`, `X-Timestamp`, `X-Nonce` and
+    /// `X-Signature` headers (see [`NostrSigner::sign_request`]). Requests signed more
+    /// than [`DEFAULT_MAX_AGE_SECS`] away from now are rejected.
+    pub fn verify_request(
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> VerificationResult {
+        Self::verify_request_with_max_age(method, path, headers, body, DEFAULT_MAX_AGE_SECS)
+    }
 
-        // Placeholder implementation
-        VerificationResult::success(DidNostr::from_pubkey(pubkey.clone()))
+    /// [`NostrVerifier::verify_request`] with an explicit timestamp window in seconds
+    pub fn verify_request_with_max_age(
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        max_age_secs: u64,
+    ) -> VerificationResult {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        let (Some(did), Some(signature)) = (header(DID_HEADER), header(SIGNATURE_HEADER)) else {
+            return VerificationResult::failure("Missing DID or Signature headers".to_string());
+        };
+        let did = match DidNostr::from_str(did) {
+            Ok(did) => did,
+            Err(e) => return VerificationResult::failure(e),
+        };
+        let signature = match NostrSignature::from_hex(signature) {
+            Ok(signature) => signature,
+            Err(e) => return VerificationResult::failure(e),
+        };
+        let stamp = match SignatureStamp::from_headers(headers).and_then(|stamp| stamp.check_age(unix_now(), max_age_secs).map(|_| stamp)) {
+            Ok(stamp) => stamp,
+            Err(e) => return VerificationResult::failure(e),
+        };
+        let canonical = RequestCanonicalizer::canonicalize_signed(method, path, &did, &stamp, body);
+        Self::verify(did.pubkey(), &canonical, &signature)
+    }
+}
+
+/// Current Unix time in seconds
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Freshness fields covered by a request signature (`X-Timestamp` and `X-Nonce`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureStamp {
+    /// Signing time in Unix seconds
+    pub timestamp: u64,
+    /// Random hex nonce
+    pub nonce: String,
+}
+
+impl SignatureStamp {
+    /// Stamp for a request signed now, with a fresh 128-bit nonce
+    pub fn now() -> Self {
+        let mut nonce = [0u8; 16];
+        OsRng.fill_bytes(&mut nonce);
+        Self { timestamp: unix_now(), nonce: hex::encode(nonce) }
+    }
+
+    /// Read the stamp from request headers
+    pub fn from_headers(headers: &[(String, String)]) -> Result<Self, String> {
+        let header = |name: &str| headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.trim());
+        let timestamp = header(TIMESTAMP_HEADER)
+            .ok_or_else(|| format!("Missing {} header", TIMESTAMP_HEADER))?
+            .parse()
+            .map_err(|_| format!("Invalid {} header", TIMESTAMP_HEADER))?;
+        let nonce = header(NONCE_HEADER).ok_or_else(|| format!("Missing {} header", NONCE_HEADER))?;
+        if nonce.is_empty() || nonce.len() > 64 || !nonce.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid {} header", NONCE_HEADER));
+        }
+        Ok(Self { timestamp, nonce: nonce.to_ascii_lowercase() })
+    }
+
+    /// Reject stamps more than `max_age_secs` before or after `now`
+    pub fn check_age(&self, now: u64, max_age_secs: u64) -> Result<(), String> {
+        if self.timestamp.abs_diff(now) > max_age_secs {
+            return Err(format!("Request timestamp is outside the {}s window", max_age_secs));
+        }
+        Ok(())
+    }
+
+    /// `X-Timestamp` and `X-Nonce` headers
+    pub fn headers(&self) -> Vec<(String, String)> {
+        vec![
+            (TIMESTAMP_HEADER.to_string(), self.timestamp.to_string()),
+            (NONCE_HEADER.to_string(), self.nonce.clone()),
+        ]
+    }
+}
+
+/// Rejected incoming request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejection {
+    /// HTTP status to answer with (401, 403, 429 or 503)
+    pub status: u16,
+    pub error: String,
+    /// Seconds for a `Retry-After` header
+    pub retry_after: Option<u64>,
+}
+
+/// Server-side verification of signed requests by DIDs of the accepted methods (only
+/// `did:nostr` unless [`Self::with_methods`] says otherwise), optionally throttled per DID
+/// (the `identities`, `default_identity` and `session` limits of the budget apply),
+/// optionally demanding a verifiable credential. Nonces seen within the timestamp
+/// window are remembered, so each signed request is accepted once; when the replay
+/// cache is full, new requests are refused with 503 until its oldest window expires.
+pub struct VerificationMiddleware {
+    limiter: Option<Arc<RateLimiter>>,
+    credential: Option<CredentialRequirement>,
+    verifier: DidVerifier,
+    max_age_secs: u64,
+    seen: Mutex<ReplayCache>,
+}
+
+/// `(did, nonce)` of accepted requests, grouped into windows of `max_age_secs` by their
+/// signed timestamp. A replay carries the original timestamp, so it is looked up in one
+/// window, and a window is dropped as a whole once none of its timestamps is accepted.
+struct ReplayCache {
+    capacity: usize,
+    len: usize,
+    windows: BTreeMap<u64, HashSet<(String, String)>>,
+}
+
+/// Outcome of recording a nonce
+enum ReplayCheck {
+    Fresh,
+    Replayed,
+    /// Full; seconds until the oldest window expires
+    Full(u64),
+}
+
+impl ReplayCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, len: 0, windows: BTreeMap::new() }
+    }
+
+    fn insert(&mut self, did: &Did, stamp: &SignatureStamp, now: u64, max_age_secs: u64) -> ReplayCheck {
+        let window_secs = max_age_secs.max(1);
+        let live = self.windows.split_off(&(now.saturating_sub(max_age_secs) / window_secs));
+        let expired = std::mem::replace(&mut self.windows, live);
+        self.len -= expired.values().map(HashSet::len).sum::<usize>();
+
+        let window = stamp.timestamp / window_secs;
+        let key = (did.to_string(), stamp.nonce.clone());
+        if self.windows.get(&window).is_some_and(|seen| seen.contains(&key)) {
+            return ReplayCheck::Replayed;
+        }
+        if self.len >= self.capacity {
+            let oldest = self.windows.keys().next().copied().unwrap_or(window);
+            let expires = (oldest + 1).saturating_mul(window_secs).saturating_add(max_age_secs);
+            return ReplayCheck::Full(expires.saturating_sub(now).max(1));
+        }
+        self.windows.entry(window).or_default().insert(key);
+        self.len += 1;
+        ReplayCheck::Fresh
+    }
+}
+
+impl Default for VerificationMiddleware {
+    fn default() -> Self {
+        Self {
+            limiter: None,
+            credential: None,
+            verifier: DidVerifier::new(DidResolver::default()).with_methods(&IdentityConfig::default().methods),
+            max_age_secs: DEFAULT_MAX_AGE_SECS,
+            seen: Mutex::new(ReplayCache::new(DEFAULT_REPLAY_CAPACITY)),
+        }
+    }
+}
+
+impl VerificationMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept request timestamps up to `secs` away from now (default [`DEFAULT_MAX_AGE_SECS`])
+    pub fn with_max_age(mut self, secs: u64) -> Self {
+        self.max_age_secs = secs;
+        self
+    }
+
+    /// Remember up to `capacity` nonces (default [`DEFAULT_REPLAY_CAPACITY`])
+    pub fn with_replay_capacity(mut self, capacity: usize) -> Self {
+        self.seen = Mutex::new(ReplayCache::new(capacity));
+        self
+    }
+
+    /// Throttle verified callers (the limiter may be shared with other middleware)
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Demand an `X-Presentation` of a matching credential, bound to the request signature
+    pub fn with_credential_requirement(mut self, requirement: CredentialRequirement) -> Self {
+        self.credential = Some(requirement);
+        self
+    }
+
+    /// Accept callers of these DID methods (e.g. `IdentityConfig.methods`)
+    pub fn with_methods(mut self, methods: &[String]) -> Self {
+        self.verifier = self.verifier.with_methods(methods);
+        self
+    }
+
+    /// Resolver for callers and credential issuers (e.g. one with a transport for `did:web`)
+    pub fn with_resolver(mut self, resolver: DidResolver) -> Self {
+        self.verifier = self.verifier.with_resolver(resolver);
+        self
+    }
+
+    /// Charge the session bucket for an incoming request, before any verification work
+    pub fn admit(&self) -> Result<(), Rejection> {
+        self.charge(&[LimitKey::Session])
+    }
+
+    /// [`Self::admit`] the request, then [`Self::verify_admitted`] it
+    pub fn verify(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<Did, Rejection> {
+        self.admit()?;
+        self.verify_admitted(method, path, headers, body)
+    }
+
+    /// Verify the caller's signature, its freshness and required credential, then charge
+    /// its identity bucket. Only verified DIDs are charged, so unsigned traffic cannot
+    /// drain a caller's budget.
+    pub fn verify_admitted(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<Did, Rejection> {
+        let did = self
+            .verifier
+            .verify_request_with_max_age(method, path, headers, body, self.max_age_secs)
+            .map_err(|error| Rejection { status: 401, error, retry_after: None })?;
+        // The signature covers the stamp, so it parsed above
+        let stamp = SignatureStamp::from_headers(headers).map_err(|error| Rejection { status: 401, error, retry_after: None })?;
+        self.remember_nonce(&did, &stamp)?;
+        if let Some(requirement) = &self.credential {
+            let header = |name: &str| headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str());
+            let presentation = header(PRESENTATION_HEADER)
+                .ok_or_else(|| format!("Missing {} header", PRESENTATION_HEADER))
+                .and_then(|presentation| {
+                    requirement.verify_header(presentation, headers, self.verifier.resolver(), self.max_age_secs)
+                });
+            match presentation {
+                Ok(presentation) if presentation.holder == did.to_string() => {}
+                Ok(presentation) => {
+                    return Err(Rejection {
+                        status: 403,
+                        error: format!("Presentation by {} for a request signed by {}", presentation.holder, did),
+                        retry_after: None,
+                    });
+                }
+                Err(error) => return Err(Rejection { status: 403, error, retry_after: None }),
+            }
+        }
+        self.charge(&[LimitKey::Identity(did.to_string())])?;
+        Ok(did)
+    }
+
+    fn charge(&self, keys: &[LimitKey]) -> Result<(), Rejection> {
+        let Some(limiter) = &self.limiter else {
+            return Ok(());
+        };
+        limiter.try_acquire(keys).map_err(|e| Rejection {
+            status: 429,
+            error: e.to_string(),
+            retry_after: Some(e.retry_after.as_secs_f64().ceil() as u64),
+        })
+    }
+
+    /// Record a request's nonce, rejecting replays and refusing new nonces when full
+    fn remember_nonce(&self, did: &Did, stamp: &SignatureStamp) -> Result<(), Rejection> {
+        match self.seen.lock().unwrap().insert(did, stamp, unix_now(), self.max_age_secs) {
+            ReplayCheck::Fresh => Ok(()),
+            ReplayCheck::Replayed => Err(Rejection { status: 401, error: "Replayed request".to_string(), retry_after: None }),
+            ReplayCheck::Full(retry_after) => Err(Rejection {
+                status: 503,
+                error: "Too many recent requests to check for replays".to_string(),
+                retry_after: Some(retry_after),
+            }),
+        }
+    }
+}
+
+/// NOSTR Signer (holds a secp256k1 secret key)
+pub struct NostrSigner {
+    key: SigningKey,
+    pubkey: NostrPublicKey,
+}
+
+impl fmt::Debug for NostrSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NostrSigner").field("pubkey", &self.pubkey).finish()
+    }
+}
+
+impl Clone for NostrSigner {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            pubkey: self.pubkey.clone(),
+        }
+    }
+}
+
+impl NostrSigner {
+    /// Create from a hex-encoded 32-byte secret key
+    pub fn from_secret_hex(secret_hex: &str) -> Result<Self, String> {
+        let bytes = hex::decode(secret_hex.trim()).map_err(|e| format!("Invalid secret key hex: {}", e))?;
+        let key = SigningKey::from_bytes(&bytes).map_err(|_| "Invalid secret key".to_string())?;
+        Ok(Self::from_signing_key(key))
+    }
+
+    /// Generate a fresh random keypair
+    pub fn generate() -> Self {
+        Self::from_signing_key(SigningKey::random(&mut k256::elliptic_curve::rand_core::OsRng))
+    }
+
+    fn from_signing_key(key: SigningKey) -> Self {
+        let pubkey = NostrPublicKey(hex::encode(key.verifying_key().to_bytes()));
+        Self { key, pubkey }
+    }
+
+    /// Get the x-only public key
+    pub fn pubkey(&self) -> &NostrPublicKey {
+        &self.pubkey
+    }
+
+    /// Get the DID for this key
+    pub fn did(&self) -> DidNostr {
+        DidNostr::from_pubkey(self.pubkey.clone())
+    }
+
+    /// Hex-encoded secret key (handle with care)
+    pub fn secret_hex(&self) -> String {
+        hex::encode(self.key.to_bytes())
+    }
+
+    /// Sign a message (BIP-340 Schnorr over `sha256(message)`)
+    pub fn sign(&self, message: &str) -> NostrSignature {
+        self.sign_bytes(message.as_bytes())
+    }
+
+    /// Sign raw bytes (BIP-340 Schnorr over `sha256(message)`)
+    pub fn sign_bytes(&self, message: &[u8]) -> NostrSignature {
+        let signature: Signature = self.key.sign(message);
+        NostrSignature(hex::encode(signature.to_bytes()))
+    }
+
+    /// Produce the `X-DID`, `X-Timestamp`, `X-Nonce` and `X-Signature` headers for a
+    /// request signed now
+    pub fn sign_request(&self, method: &str, path: &str, body: &[u8]) -> Vec<(String, String)> {
+        self.sign_request_with_stamp(method, path, body, &SignatureStamp::now())
+    }
+
+    /// [`NostrSigner::sign_request`] with an explicit timestamp and nonce
+    pub fn sign_request_with_stamp(&self, method: &str, path: &str, body: &[u8], stamp: &SignatureStamp) -> Vec<(String, String)> {
+        let did = self.did();
+        let canonical = RequestCanonicalizer::canonicalize_signed(method, path, &did, stamp, body);
+        let mut headers = vec![(DID_HEADER.to_string(), did.to_string())];
+        headers.extend(stamp.headers());
+        headers.push((SIGNATURE_HEADER.to_string(), self.sign(&canonical).as_hex().to_string()));
+        headers
     }
 }
 
@@ -269,6 +677,16 @@ impl RequestCanonicalizer {
 
         format!("{}\n{}\n{}\n{}", method, path, headers_str, body)
     }
+
+    /// Canonical form used by [`NostrSigner::sign_request`] (and by `did::DidVerifier`
+    /// for any DID method): only the `X-DID`, `X-Nonce` and `X-Timestamp` headers are
+    /// covered, so intermediaries adding or rewriting headers do not break signatures.
+    /// The body is covered by its hex SHA-256 over the raw bytes.
+    pub fn canonicalize_signed(method: &str, path: &str, did: impl fmt::Display, stamp: &SignatureStamp, body: &[u8]) -> String {
+        let mut headers = vec![(DID_HEADER.to_string(), did.to_string())];
+        headers.extend(stamp.headers());
+        Self::canonicalize(&method.to_uppercase(), path, &headers, &hex::encode(Sha256::digest(body)))
+    }
 }
 
 #[cfg(test)]
@@ -322,4 +740,150 @@ mod tests {
         assert!(canonical.contains("/api/test"));
         assert!(canonical.contains("Authorization:Bearer token"));
     }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = NostrSigner::generate();
+        let signature = signer.sign("hello");
+        assert!(NostrVerifier::verify(signer.pubkey(), "hello", &signature).valid);
+        assert!(!NostrVerifier::verify(signer.pubkey(), "hello!", &signature).valid);
+        let other = NostrSigner::generate();
+        assert!(!NostrVerifier::verify(other.pubkey(), "hello", &signature).valid);
+    }
+
+    #[test]
+    fn test_bip340_test_vector() {
+        // BIP-340 test vector 1
+        let signer = NostrSigner::from_secret_hex(
+            "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+        )
+        .unwrap();
+        assert_eq!(
+            signer.pubkey().as_hex(),
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify_request() {
+        let signer = NostrSigner::generate();
+        let mut headers = signer.sign_request("post", "/mcp", b"{}");
+        headers.push(("Host".to_string(), "example.com".to_string()));
+        let result = NostrVerifier::verify_request("POST", "/mcp", &headers, b"{}");
+        assert!(result.valid);
+        assert_eq!(result.did.unwrap(), signer.did());
+        assert!(!NostrVerifier::verify_request("POST", "/other", &headers, b"{}").valid);
+        assert!(!NostrVerifier::verify_request("POST", "/mcp", &[], b"{}").valid);
+
+        // Raw body bytes are signed, so invalid UTF-8 cannot be swapped
+        let headers = signer.sign_request("POST", "/mcp", &[0xff]);
+        assert!(NostrVerifier::verify_request("POST", "/mcp", &headers, &[0xff]).valid);
+        assert!(!NostrVerifier::verify_request("POST", "/mcp", &headers, &[0xfe]).valid);
+    }
+
+    #[test]
+    fn test_stale_and_tampered_stamps_rejected() {
+        let signer = NostrSigner::generate();
+        let stale = SignatureStamp { timestamp: unix_now() - DEFAULT_MAX_AGE_SECS - 60, nonce: "ab".to_string() };
+        let headers = signer.sign_request_with_stamp("GET", "/", b"", &stale);
+        let result = NostrVerifier::verify_request("GET", "/", &headers, b"");
+        assert!(result.error.unwrap().contains("window"));
+        assert!(NostrVerifier::verify_request_with_max_age("GET", "/", &headers, b"", 3600).valid);
+
+        let mut moved = signer.sign_request("GET", "/", b"");
+        for (name, value) in moved.iter_mut() {
+            if name == TIMESTAMP_HEADER {
+                *value = (value.parse::<u64>().unwrap() + 1).to_string();
+            }
+        }
+        assert!(!NostrVerifier::verify_request("GET", "/", &moved, b"").valid);
+        let unstamped: Vec<_> = signer.sign_request("GET", "/", b"").into_iter().filter(|(k, _)| k != NONCE_HEADER).collect();
+        assert!(!NostrVerifier::verify_request("GET", "/", &unstamped, b"").valid);
+    }
+
+    #[test]
+    fn test_middleware_throttles_per_did() {
+        let config = crate::ratelimit::RateLimitConfig::from_json(r#"{"default_identity": {"rate": 0.1, "burst": 2}}"#);
+        let limiter = Arc::new(RateLimiter::new(config.unwrap()).unwrap());
+        let middleware = VerificationMiddleware::new().with_rate_limiter(limiter);
+        let (alice, bob) = (NostrSigner::generate(), NostrSigner::generate());
+        let alice_headers = alice.sign_request("POST", "/rpc", b"{}");
+
+        assert_eq!(middleware.verify("POST", "/rpc", &alice_headers, b"{}").unwrap(), Did::Nostr(alice.did()));
+        assert!(middleware.verify("POST", "/rpc", &alice.sign_request("POST", "/rpc", b"{}"), b"{}").is_ok());
+        let rejected = middleware.verify("POST", "/rpc", &alice.sign_request("POST", "/rpc", b"{}"), b"{}").unwrap_err();
+        assert_eq!((rejected.status, rejected.retry_after), (429, Some(10)));
+        assert!(middleware.verify("POST", "/rpc", &bob.sign_request("POST", "/rpc", b"{}"), b"{}").is_ok());
+        assert_eq!(middleware.verify("POST", "/rpc", &[], b"{}").unwrap_err().status, 401);
+    }
+
+    #[test]
+    fn test_middleware_rejects_replays() {
+        let middleware = VerificationMiddleware::new();
+        let signer = NostrSigner::generate();
+        let headers = signer.sign_request("POST", "/rpc", b"{}");
+        assert!(middleware.verify("POST", "/rpc", &headers, b"{}").is_ok());
+        let replayed = middleware.verify("POST", "/rpc", &headers, b"{}").unwrap_err();
+        assert_eq!((replayed.status, replayed.error.as_str()), (401, "Replayed request"));
+        assert!(middleware.verify("POST", "/rpc", &signer.sign_request("POST", "/rpc", b"{}"), b"{}").is_ok());
+    }
+
+    #[test]
+    fn test_replay_cache_is_bounded_and_expires_by_window() {
+        let middleware = VerificationMiddleware::new().with_replay_capacity(2);
+        let signer = NostrSigner::generate();
+        for _ in 0..2 {
+            assert!(middleware.verify("POST", "/rpc", &signer.sign_request("POST", "/rpc", b"{}"), b"{}").is_ok());
+        }
+        let full = middleware.verify("POST", "/rpc", &signer.sign_request("POST", "/rpc", b"{}"), b"{}").unwrap_err();
+        assert_eq!(full.status, 503);
+        assert!(full.retry_after.is_some_and(|secs| secs <= 2 * DEFAULT_MAX_AGE_SECS));
+
+        // Once no timestamp of a window is accepted any more, the window is dropped
+        let did = Did::Nostr(signer.did());
+        let mut cache = ReplayCache::new(2);
+        let stamp = |timestamp| SignatureStamp { timestamp, nonce: hex::encode(timestamp.to_be_bytes()) };
+        assert!(matches!(cache.insert(&did, &stamp(1_000), 1_000, 300), ReplayCheck::Fresh));
+        assert!(matches!(cache.insert(&did, &stamp(1_000), 1_000, 300), ReplayCheck::Replayed));
+        assert!(matches!(cache.insert(&did, &stamp(1_200), 1_200, 300), ReplayCheck::Fresh));
+        assert!(matches!(cache.insert(&did, &stamp(1_250), 1_250, 300), ReplayCheck::Full(_)));
+        assert!(matches!(cache.insert(&did, &stamp(1_600), 1_600, 300), ReplayCheck::Fresh));
+        assert_eq!((cache.len, cache.windows.len()), (2, 2));
+    }
+
+    #[test]
+    fn test_middleware_charges_the_session_before_verifying() {
+        let config = crate::ratelimit::RateLimitConfig::from_json(r#"{"session": {"rate": 0.1, "burst": 1}}"#);
+        let middleware = VerificationMiddleware::new().with_rate_limiter(Arc::new(RateLimiter::new(config.unwrap()).unwrap()));
+        assert_eq!(middleware.verify("POST", "/rpc", &[], b"{}").unwrap_err().status, 401);
+        let signer = NostrSigner::generate();
+        let throttled = middleware.verify("POST", "/rpc", &signer.sign_request("POST", "/rpc", b"{}"), b"{}").unwrap_err();
+        assert_eq!(throttled.status, 429);
+        assert_eq!(middleware.seen.lock().unwrap().len, 0);
+    }
+
+    #[test]
+    fn test_middleware_accepts_configured_methods() {
+        use crate::did::{DidKey, PublicKey};
+        use ed25519_dalek::Signer as _;
+
+        let key = ed25519_dalek::SigningKey::from_bytes(&[5; 32]);
+        let did = DidKey::from_public_key(&PublicKey::Ed25519(key.verifying_key())).to_string();
+        let signed = || {
+            let stamp = SignatureStamp::now();
+            let canonical = RequestCanonicalizer::canonicalize_signed("POST", "/rpc", &did, &stamp, b"{}");
+            let mut headers = vec![(DID_HEADER.to_string(), did.clone())];
+            headers.extend(stamp.headers());
+            headers.push((SIGNATURE_HEADER.to_string(), hex::encode(key.sign(canonical.as_bytes()).to_bytes())));
+            headers
+        };
+
+        let nostr_only = VerificationMiddleware::new();
+        assert!(nostr_only.verify("POST", "/rpc", &signed(), b"{}").unwrap_err().error.contains("not accepted"));
+        let methods = ["did:nostr".to_string(), "did:key".to_string()];
+        let middleware = VerificationMiddleware::new().with_methods(&methods);
+        assert_eq!(middleware.verify("POST", "/rpc", &signed(), b"{}").unwrap().to_string(), did);
+        let signer = NostrSigner::generate();
+        assert!(middleware.verify("POST", "/rpc", &signer.sign_request("POST", "/rpc", b"{}"), b"{}").is_ok());
+    }
 }
@@ -1,112 +1,184 @@
-//! C++ FFI bindings for DID-NOSTR functionality
+//! C FFI bindings for DID-NOSTR functionality
+//!
+//! Same conventions as `c_api`: a `PHTTP_*` status code is returned, the result is
+//! written through `out` and must be released with `did_nostr_free` (or
+//! `phttp_string_free`), and failures are described by `phttp_last_error()`.
 
-use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use crate::c_api::{PHTTP_ERR_INVALID, check_out, ffi_call, out_string, read_str};
 use crate::did_nostr::*;
+use crate::nostr_event::{NostrEvent, UnsignedEvent};
+
+/// Write `result` (or its error) through `out`
+///
+/// # Safety
+/// `input` must be NULL or a valid NUL-terminated string; `out` must be writable
+unsafe fn did_call(input: *const c_char, name: &str, out: *mut *mut c_char, f: fn(&str) -> Result<String, String>) -> i32 {
+    ffi_call(|| {
+        check_out(out)?;
+        let input = unsafe { read_str(input, name) }?;
+        let result = f(input).map_err(|e| (PHTTP_ERR_INVALID, e))?;
+        unsafe { *out = out_string(&result, "Result")?.into_raw() };
+        Ok(())
+    })
+}
 
 /// Create a DID from NOSTR public key (hex)
 /// # Safety
-/// Caller must ensure pubkey_hex is a valid null-terminated string
-#[no_mangle]
-pub unsafe extern "C" fn did_nostr_create(
-    pubkey_hex: *const c_char,
-) -> *mut c_char {
-    if pubkey_hex.is_null() {
-        let err = CString::new("null pointer").unwrap();
-        return err.into_raw();
+/// Caller must ensure pubkey_hex is a valid null-terminated string and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_create(pubkey_hex: *const c_char, out: *mut *mut c_char) -> i32 {
+    unsafe {
+        did_call(pubkey_hex, "pubkey_hex", out, |hex| {
+            Ok(DidNostr::from_pubkey(NostrPublicKey::from_hex(hex)?).to_string())
+        })
     }
+}
 
-    let hex_cstr = match CStr::from_ptr(pubkey_hex).to_str() {
-        Ok(s) => s,
-        Err(_) => {
-            let err = CString::new("invalid UTF-8").unwrap();
-            return err.into_raw();
-        }
-    };
-
-    match NostrPublicKey::from_hex(hex_cstr) {
-        Ok(pubkey) => {
-            let did = DidNostr::from_pubkey(pubkey);
-            let result = CString::new(did.to_string()).unwrap();
-            result.into_raw()
-        }
-        Err(e) => {
-            let err = CString::new(format!("error: {}", e)).unwrap();
-            err.into_raw()
-        }
-    }
+/// Parse DID from string (normalized form)
+/// # Safety
+/// Caller must ensure did_str is a valid null-terminated string and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_parse(did_str: *const c_char, out: *mut *mut c_char) -> i32 {
+    unsafe { did_call(did_str, "did_str", out, |did| Ok(DidNostr::from_str(did)?.to_string())) }
 }
 
-/// Parse DID from string
+/// Extract public key (hex) from DID
 /// # Safety
-/// Caller must ensure did_str is a valid null-terminated string
-#[no_mangle]
-pub unsafe extern "C" fn did_nostr_parse(
-    did_str: *const c_char,
-) -> *mut c_char {
-    if did_str.is_null() {
-        let err = CString::new("null pointer").unwrap();
-        return err.into_raw();
-    }
+/// Caller must ensure did_str is a valid null-terminated string and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_get_pubkey(did_str: *const c_char, out: *mut *mut c_char) -> i32 {
+    unsafe { did_call(did_str, "did_str", out, |did| Ok(DidNostr::from_str(did)?.pubkey().as_hex().to_string())) }
+}
 
-    let did_cstr = match CStr::from_ptr(did_str).to_str() {
-        Ok(s) => s,
-        Err(_) => {
-            let err = CString::new("invalid UTF-8").unwrap();
-            return err.into_raw();
-        }
-    };
-
-    match DidNostr::from_str(did_cstr) {
-        Ok(did) => {
-            let result = CString::new(did.to_string()).unwrap();
-            result.into_raw()
-        }
-        Err(e) => {
-            let err = CString::new(format!("error: {}", e)).unwrap();
-            err.into_raw()
-        }
-    }
+/// Sign an unsigned event (`{"kind": 1, "content": "...", "tags": [...], "created_at": ...}`,
+/// `created_at` defaults to now) with a hex secret key; writes the NIP-01 event JSON
+/// # Safety
+/// Caller must ensure secret_hex and unsigned_json are valid null-terminated strings and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_event_sign(secret_hex: *const c_char, unsigned_json: *const c_char, out: *mut *mut c_char) -> i32 {
+    ffi_call(|| {
+        check_out(out)?;
+        let secret_hex = unsafe { read_str(secret_hex, "secret_hex") }?;
+        let unsigned_json = unsafe { read_str(unsigned_json, "unsigned_json") }?;
+        let signer = NostrSigner::from_secret_hex(secret_hex).map_err(|e| (PHTTP_ERR_INVALID, e))?;
+        let event = UnsignedEvent::from_json(unsigned_json).map_err(|e| (PHTTP_ERR_INVALID, e))?;
+        unsafe { *out = out_string(&event.sign(&signer).to_json(), "Event")?.into_raw() };
+        Ok(())
+    })
 }
 
-/// Extract public key from DID
+/// Strictly parse and verify an event (id and signature); writes the author's DID
 /// # Safety
-/// Caller must ensure did_str is a valid null-terminated string
-#[no_mangle]
-pub unsafe extern "C" fn did_nostr_get_pubkey(
-    did_str: *const c_char,
-) -> *mut c_char {
-    if did_str.is_null() {
-        let err = CString::new("null pointer").unwrap();
-        return err.into_raw();
+/// Caller must ensure event_json is a valid null-terminated string and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_event_verify(event_json: *const c_char, out: *mut *mut c_char) -> i32 {
+    unsafe {
+        did_call(event_json, "event_json", out, |json| {
+            let event = NostrEvent::from_json(json)?;
+            event.verify()?;
+            Ok(event.did()?.to_string())
+        })
     }
+}
 
-    let did_cstr = match CStr::from_ptr(did_str).to_str() {
-        Ok(s) => s,
-        Err(_) => {
-            let err = CString::new("invalid UTF-8").unwrap();
-            return err.into_raw();
-        }
-    };
-
-    match DidNostr::from_str(did_cstr) {
-        Ok(did) => {
-            let result = CString::new(did.pubkey().as_hex()).unwrap();
-            result.into_raw()
-        }
-        Err(e) => {
-            let err = CString::new(format!("error: {}", e)).unwrap();
-            err.into_raw()
-        }
-    }
+/// Compute the id of an event from its fields (hex)
+/// # Safety
+/// Caller must ensure event_json is a valid null-terminated string and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_event_id(event_json: *const c_char, out: *mut *mut c_char) -> i32 {
+    unsafe { did_call(event_json, "event_json", out, |json| Ok(NostrEvent::from_json(json)?.compute_id())) }
+}
+
+/// Value of the first tag named `name`; PHTTP_ERR_INVALID when there is none or it
+/// contains a NUL byte
+/// # Safety
+/// Caller must ensure event_json and name are valid null-terminated strings and out is writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn did_nostr_event_tag_value(event_json: *const c_char, name: *const c_char, out: *mut *mut c_char) -> i32 {
+    ffi_call(|| {
+        check_out(out)?;
+        let event_json = unsafe { read_str(event_json, "event_json") }?;
+        let name = unsafe { read_str(name, "name") }?;
+        let event = NostrEvent::from_json(event_json).map_err(|e| (PHTTP_ERR_INVALID, e))?;
+        let value = event.tag_value(name).ok_or_else(|| (PHTTP_ERR_INVALID, format!("No {} tag", name)))?;
+        unsafe { *out = out_string(value, "Tag value")?.into_raw() };
+        Ok(())
+    })
 }
 
 /// Free a string allocated by the Rust library
 /// # Safety
 /// Caller must ensure ptr is a valid pointer returned by did_nostr_* functions
-#[no_mangle]
+#[unsafe(no_mangle)]
 pub unsafe extern "C" fn did_nostr_free(ptr: *mut c_char) {
-    if !ptr.is_null() {
-        let _ = CString::from_raw(ptr);
+    unsafe { crate::c_api::phttp_string_free(ptr) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c_api::{PHTTP_OK, c_string, phttp_last_error};
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_results_and_errors_are_separate() {
+        let hex = "a".repeat(64);
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_create(c_string(&hex).as_ptr(), &mut out) }, PHTTP_OK);
+        let did = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { did_nostr_free(out) };
+        assert_eq!(did, format!("did:nostr:{}", hex));
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_get_pubkey(c_string(&did).as_ptr(), &mut out) }, PHTTP_OK);
+        assert_eq!(unsafe { CStr::from_ptr(out) }.to_str(), Ok(hex.as_str()));
+        unsafe { did_nostr_free(out) };
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_parse(c_string("did:web:example.com").as_ptr(), &mut out) }, PHTTP_ERR_INVALID);
+        assert!(out.is_null());
+        assert!(!unsafe { CStr::from_ptr(phttp_last_error()) }.to_bytes().is_empty());
+    }
+
+    #[test]
+    fn test_event_sign_and_verify() {
+        let signer = NostrSigner::generate();
+        let secret = c_string(&signer.secret_hex());
+        let unsigned = c_string(r#"{"kind": 1, "content": "hello", "tags": [["t", "rust"]]}"#);
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_sign(secret.as_ptr(), unsigned.as_ptr(), &mut out) }, PHTTP_OK);
+        let event = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { did_nostr_free(out) };
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_verify(c_string(&event).as_ptr(), &mut out) }, PHTTP_OK);
+        assert_eq!(unsafe { CStr::from_ptr(out) }.to_str(), Ok(signer.did().to_string().as_str()));
+        unsafe { did_nostr_free(out) };
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_tag_value(c_string(&event).as_ptr(), c_string("t").as_ptr(), &mut out) }, PHTTP_OK);
+        assert_eq!(unsafe { CStr::from_ptr(out) }.to_str(), Ok("rust"));
+        unsafe { did_nostr_free(out) };
+
+        let tampered = c_string(&event.replace("hello", "bye"));
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_verify(tampered.as_ptr(), &mut out) }, PHTTP_ERR_INVALID);
+        assert!(out.is_null());
+    }
+
+    #[test]
+    fn test_results_with_nul_bytes_are_refused() {
+        let secret = c_string(&NostrSigner::generate().secret_hex());
+        let unsigned = c_string(r#"{"kind": 1, "content": "", "tags": [["t", "a\u0000b"]]}"#);
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_sign(secret.as_ptr(), unsigned.as_ptr(), &mut out) }, PHTTP_OK);
+        let event = c_string(unsafe { CStr::from_ptr(out) }.to_str().unwrap());
+        unsafe { did_nostr_free(out) };
+
+        let mut out = std::ptr::null_mut();
+        assert_eq!(unsafe { did_nostr_event_tag_value(event.as_ptr(), c_string("t").as_ptr(), &mut out) }, PHTTP_ERR_INVALID);
+        assert!(out.is_null());
+        assert_eq!(unsafe { CStr::from_ptr(phttp_last_error()) }.to_str(), Ok("Tag value contains a NUL byte"));
     }
 }
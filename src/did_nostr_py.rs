@@ -1,10 +1,20 @@
-//! Python PyO3 bindings for DID-NOSTR functionality
+//! Python PyO3 bindings for DID-NOSTR functionality (`privacy_http_sdk.identity`)
+//!
+//! Keys, DIDs and signatures are immutable values: they compare by value, hash (so
+//! they work in sets and as dict keys) and pickle. `NostrSigner` holds a secret key
+//! and deliberately does not pickle.
 
 use pyo3::prelude::*;
+use pyo3::types::PyType;
+use crate::did_document::{DidResolver, KeyFormat, NostrServices};
 use crate::did_nostr::*;
+use crate::nostr_event::{NostrEvent, UnsignedEvent};
+use crate::HttpClient;
+use std::sync::Arc;
 
 /// Python wrapper for NostrPublicKey
-#[pyclass]
+#[pyclass(name = "NostrPublicKey", module = "privacy_http_sdk.identity", frozen, eq, hash, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PyNostrPublicKey {
     inner: NostrPublicKey,
 }
@@ -14,7 +24,7 @@ impl PyNostrPublicKey {
     #[new]
     fn new(hex: String) -> PyResult<Self> {
         let inner = NostrPublicKey::from_hex(&hex)
-            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e))?;
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
         Ok(PyNostrPublicKey { inner })
     }
 
@@ -22,23 +32,34 @@ impl PyNostrPublicKey {
         self.inner.as_hex().to_string()
     }
 
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (String,)) {
+        (slf.get_type(), (slf.get().inner.as_hex().to_string(),))
+    }
+
     fn __str__(&self) -> String {
         self.inner.to_string()
     }
 
     fn __repr__(&self) -> String {
-        format!("PyNostrPublicKey('{}')", self.inner.as_hex())
+        format!("NostrPublicKey('{}')", self.inner.as_hex())
     }
 }
 
 /// Python wrapper for DidNostr
-#[pyclass]
+#[pyclass(name = "DidNostr", module = "privacy_http_sdk.identity", frozen, eq, hash, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PyDidNostr {
     inner: DidNostr,
 }
 
 #[pymethods]
 impl PyDidNostr {
+    /// Parse a `did:nostr:...` string
+    #[new]
+    fn new(did_str: String) -> PyResult<Self> {
+        Self::from_str(did_str)
+    }
+
     /// Create DID from public key
     #[staticmethod]
     fn from_pubkey(pubkey: &PyNostrPublicKey) -> Self {
@@ -51,7 +72,7 @@ impl PyDidNostr {
     #[staticmethod]
     fn from_str(did_str: String) -> PyResult<Self> {
         let inner = DidNostr::from_str(&did_str)
-            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e))?;
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
         Ok(PyDidNostr { inner })
     }
 
@@ -63,21 +84,27 @@ impl PyDidNostr {
     }
 
     /// Get DID as string
-    fn to_string(&self) -> String {
+    #[pyo3(name = "to_string")]
+    fn to_did_string(&self) -> String {
         self.inner.to_string()
     }
 
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (String,)) {
+        (slf.get_type(), (slf.get().inner.to_string(),))
+    }
+
     fn __str__(&self) -> String {
         self.inner.to_string()
     }
 
     fn __repr__(&self) -> String {
-        format!("PyDidNostr('{}')", self.inner.to_string())
+        format!("DidNostr('{}')", self.inner)
     }
 }
 
 /// Python wrapper for NostrSignature
-#[pyclass]
+#[pyclass(name = "NostrSignature", module = "privacy_http_sdk.identity", frozen, eq, hash, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PyNostrSignature {
     inner: NostrSignature,
 }
@@ -87,7 +114,7 @@ impl PyNostrSignature {
     #[new]
     fn new(hex: String) -> PyResult<Self> {
         let inner = NostrSignature::from_hex(&hex)
-            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e))?;
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
         Ok(PyNostrSignature { inner })
     }
 
@@ -95,13 +122,22 @@ impl PyNostrSignature {
         self.inner.as_hex().to_string()
     }
 
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (String,)) {
+        (slf.get_type(), (slf.get().inner.as_hex().to_string(),))
+    }
+
     fn __str__(&self) -> String {
         self.inner.to_string()
     }
+
+    fn __repr__(&self) -> String {
+        format!("NostrSignature('{}')", self.inner.as_hex())
+    }
 }
 
 /// Python wrapper for VerificationResult
-#[pyclass]
+#[pyclass(name = "VerificationResult", module = "privacy_http_sdk.identity", frozen, skip_from_py_object)]
+#[derive(Clone)]
 pub struct PyVerificationResult {
     valid: bool,
     did: Option<PyDidNostr>,
@@ -125,11 +161,14 @@ impl PyVerificationResult {
         self.error.clone()
     }
 
+    fn __bool__(&self) -> bool {
+        self.valid
+    }
+
     fn __repr__(&self) -> String {
-        if self.valid {
-            format!("VerificationResult(valid=True, did='{}')", self.did.as_ref().unwrap().inner.to_string())
-        } else {
-            format!("VerificationResult(valid=False, error='{}')", self.error.as_ref().unwrap_or(&"Unknown".to_string()))
+        match (&self.did, &self.error) {
+            (Some(did), _) if self.valid => format!("VerificationResult(valid=True, did='{}')", did.inner),
+            (_, error) => format!("VerificationResult(valid=False, error='{}')", error.as_deref().unwrap_or("Unknown")),
         }
     }
 }
@@ -145,26 +184,8 @@ impl From<VerificationResult> for PyVerificationResult {
     }
 }
 
-impl Clone for PyDidNostr {
-    fn clone(&self) -> Self {
-        PyDidNostr {
-            inner: self.inner.clone(),
-        }
-    }
-}
-
-impl Clone for PyVerificationResult {
-    fn clone(&self) -> Self {
-        PyVerificationResult {
-            valid: self.valid,
-            did: self.did.clone(),
-            error: self.error.clone(),
-        }
-    }
-}
-
 /// Python wrapper for NostrVerifier
-#[pyclass]
+#[pyclass(name = "NostrVerifier", module = "privacy_http_sdk.identity")]
 pub struct PyNostrVerifier;
 
 #[pymethods]
@@ -179,10 +200,69 @@ impl PyNostrVerifier {
         let result = NostrVerifier::verify(&pubkey.inner, &message, &signature.inner);
         result.into()
     }
+
+    /// Verify a request signed with `NostrSigner.sign_request`
+    #[staticmethod]
+    fn verify_request(
+        method: String,
+        path: String,
+        headers: Vec<(String, String)>,
+        body: String,
+    ) -> PyVerificationResult {
+        NostrVerifier::verify_request(&method, &path, &headers, body.as_bytes()).into()
+    }
+}
+
+/// Python wrapper for NostrSigner
+#[pyclass(name = "NostrSigner", module = "privacy_http_sdk.identity", frozen)]
+pub struct PyNostrSigner {
+    pub(crate) inner: NostrSigner,
+}
+
+#[pymethods]
+impl PyNostrSigner {
+    /// Signer from a hex-encoded secret key
+    #[new]
+    fn new(secret_hex: String) -> PyResult<Self> {
+        let inner = NostrSigner::from_secret_hex(&secret_hex)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyNostrSigner { inner })
+    }
+
+    /// Fresh random key
+    #[staticmethod]
+    fn generate() -> Self {
+        PyNostrSigner { inner: NostrSigner::generate() }
+    }
+
+    fn did(&self) -> PyDidNostr {
+        PyDidNostr { inner: self.inner.did() }
+    }
+
+    fn pubkey(&self) -> PyNostrPublicKey {
+        PyNostrPublicKey { inner: self.inner.pubkey().clone() }
+    }
+
+    fn secret_hex(&self) -> String {
+        self.inner.secret_hex()
+    }
+
+    fn sign(&self, message: String) -> PyNostrSignature {
+        PyNostrSignature { inner: self.inner.sign(&message) }
+    }
+
+    /// Signed `X-DID`, `X-Timestamp`, `X-Nonce` and `X-Signature` headers for a request
+    fn sign_request(&self, method: String, path: String, body: String) -> Vec<(String, String)> {
+        self.inner.sign_request(&method, &path, body.as_bytes())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("NostrSigner(did='{}')", self.inner.did())
+    }
 }
 
 /// Python wrapper for RequestCanonicalizer
-#[pyclass]
+#[pyclass(name = "RequestCanonicalizer", module = "privacy_http_sdk.identity")]
 pub struct PyRequestCanonicalizer;
 
 #[pymethods]
@@ -199,15 +279,169 @@ impl PyRequestCanonicalizer {
     }
 }
 
-/// Python module initialization
-pub fn init_did_nostr_module(py: Python<'_>, parent_module: &pyo3::types::PyModule) -> PyResult<()> {
-    let submodule = pyo3::types::PyModule::new(py, "did_nostr")?;
-    submodule.add_class::<PyNostrPublicKey>()?;
-    submodule.add_class::<PyDidNostr>()?;
-    submodule.add_class::<PyNostrSignature>()?;
-    submodule.add_class::<PyVerificationResult>()?;
-    submodule.add_class::<PyNostrVerifier>()?;
-    submodule.add_class::<PyRequestCanonicalizer>()?;
-    parent_module.add_submodule(submodule)?;
+/// Python wrapper for DidResolver (documents and results are JSON strings).
+/// `did:web` documents are fetched through a default privacy `HttpClient`.
+#[pyclass(name = "DidResolver", module = "privacy_http_sdk.identity")]
+pub struct PyDidResolver {
+    inner: DidResolver,
+}
+
+#[pymethods]
+impl PyDidResolver {
+    /// `key_format` is "Multikey" or "JsonWebKey"
+    #[new]
+    #[pyo3(signature = (key_format="Multikey"))]
+    fn new(key_format: &str) -> PyResult<Self> {
+        let key_format = KeyFormat::parse(key_format).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let resolver = DidResolver::new()
+            .with_key_format(key_format)
+            .with_transport(Arc::new(HttpClient::new(String::new())));
+        Ok(PyDidResolver { inner: resolver })
+    }
+
+    /// Publish relays and a NIP-05 identifier (`name@domain`) in the document of `did`
+    #[pyo3(signature = (did, relays=Vec::new(), nip05=None))]
+    fn add_services(&mut self, did: &PyDidNostr, relays: Vec<String>, nip05: Option<String>) -> PyResult<()> {
+        self.inner
+            .add_services(did.inner.clone(), NostrServices { relays, nip05 })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// DID document as JSON
+    fn document(&self, did: &PyDidNostr) -> PyResult<String> {
+        self.inner
+            .document(&did.inner)
+            .map(|document| document.to_json())
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// DID resolution result as JSON for any supported method (`did:nostr`, `did:key`,
+    /// `did:web`); errors are reported in `didResolutionMetadata`
+    fn resolve(&self, py: Python<'_>, did: &str) -> String {
+        py.detach(|| self.inner.resolve(did).to_json())
+    }
+}
+
+/// Python wrapper for a signed NIP-01 NostrEvent
+#[pyclass(name = "NostrEvent", module = "privacy_http_sdk.identity", frozen, eq, hash, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PyNostrEvent {
+    inner: NostrEvent,
+}
+
+#[pymethods]
+impl PyNostrEvent {
+    /// Strictly parse event JSON (call `verify` before trusting it)
+    #[new]
+    fn new(json: &str) -> PyResult<Self> {
+        Self::from_json(json)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = NostrEvent::from_json(json).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyNostrEvent { inner })
+    }
+
+    /// Sign a new event; `created_at` defaults to now
+    #[staticmethod]
+    #[pyo3(signature = (signer, kind, content, tags=Vec::new(), created_at=None))]
+    fn sign(signer: &PyNostrSigner, kind: u16, content: &str, tags: Vec<Vec<String>>, created_at: Option<u64>) -> Self {
+        let mut event = UnsignedEvent::new(kind, content);
+        event.tags = tags;
+        if let Some(created_at) = created_at {
+            event = event.with_created_at(created_at);
+        }
+        PyNostrEvent { inner: event.sign(&signer.inner) }
+    }
+
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id.clone()
+    }
+
+    #[getter]
+    fn pubkey(&self) -> String {
+        self.inner.pubkey.clone()
+    }
+
+    #[getter]
+    fn created_at(&self) -> u64 {
+        self.inner.created_at
+    }
+
+    #[getter]
+    fn kind(&self) -> u16 {
+        self.inner.kind
+    }
+
+    #[getter]
+    fn tags(&self) -> Vec<Vec<String>> {
+        self.inner.tags.clone()
+    }
+
+    #[getter]
+    fn content(&self) -> String {
+        self.inner.content.clone()
+    }
+
+    #[getter]
+    fn sig(&self) -> String {
+        self.inner.sig.clone()
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Id computed from the event's fields
+    fn compute_id(&self) -> String {
+        self.inner.compute_id()
+    }
+
+    /// Raise `ValueError` unless the id and signature are valid
+    fn verify(&self) -> PyResult<()> {
+        self.inner.verify().map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Author's DID
+    fn did(&self) -> PyResult<PyDidNostr> {
+        let inner = self.inner.did().map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyDidNostr { inner })
+    }
+
+    /// First tag named `name`, including the name
+    fn tag(&self, name: &str) -> Option<Vec<String>> {
+        self.inner.tag(name).map(<[String]>::to_vec)
+    }
+
+    fn tag_value(&self, name: &str) -> Option<String> {
+        self.inner.tag_value(name).map(str::to_string)
+    }
+
+    fn tag_values(&self, name: &str) -> Vec<String> {
+        self.inner.tag_values(name).into_iter().map(str::to_string).collect()
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (String,)) {
+        (slf.get_type(), (slf.get().inner.to_json(),))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("NostrEvent(id='{}', kind={})", self.inner.id, self.inner.kind)
+    }
+}
+
+/// Populate the `identity` submodule
+pub fn init_did_nostr_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyNostrPublicKey>()?;
+    module.add_class::<PyDidNostr>()?;
+    module.add_class::<PyNostrSignature>()?;
+    module.add_class::<PyVerificationResult>()?;
+    module.add_class::<PyNostrVerifier>()?;
+    module.add_class::<PyNostrSigner>()?;
+    module.add_class::<PyRequestCanonicalizer>()?;
+    module.add_class::<PyDidResolver>()?;
+    module.add_class::<PyNostrEvent>()?;
     Ok(())
 }
@@ -1,7 +1,9 @@
 //! WASM bindings for DID-NOSTR functionality via wasm-bindgen
 
 use wasm_bindgen::prelude::*;
+use crate::did_document::{DidResolver, KeyFormat, NostrServices};
 use crate::did_nostr::*;
+use crate::nostr_event::{NostrEvent, UnsignedEvent};
 
 /// JavaScript wrapper for NostrPublicKey
 #[wasm_bindgen]
@@ -25,6 +27,7 @@ impl JsNostrPublicKey {
     }
 
     /// Get display string (redacted)
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         self.inner.to_string()
     }
@@ -46,6 +49,7 @@ impl JsDidNostr {
     }
 
     /// Parse DID from string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(did_str: String) -> Result<JsDidNostr, JsValue> {
         let inner = DidNostr::from_str(&did_str)
             .map_err(|e| JsValue::from_str(&e))?;
@@ -60,6 +64,7 @@ impl JsDidNostr {
     }
 
     /// Get DID as string
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         self.inner.to_string()
     }
@@ -87,6 +92,7 @@ impl JsNostrSignature {
     }
 
     /// Get display string (redacted)
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         self.inner.to_string()
     }
@@ -183,3 +189,126 @@ impl JsRequestCanonicalizer {
         RequestCanonicalizer::canonicalize(&method, &path, &headers_vec, &body)
     }
 }
+
+/// JavaScript wrapper for DidResolver (documents and results are JSON strings)
+#[wasm_bindgen]
+pub struct JsDidResolver {
+    inner: DidResolver,
+}
+
+#[wasm_bindgen]
+impl JsDidResolver {
+    /// Create a resolver; `key_format` is "Multikey" (default) or "JsonWebKey"
+    #[wasm_bindgen(constructor)]
+    pub fn new(key_format: Option<String>) -> Result<JsDidResolver, JsValue> {
+        let key_format = match key_format {
+            Some(format) => KeyFormat::parse(&format).map_err(|e| JsValue::from_str(&e))?,
+            None => KeyFormat::default(),
+        };
+        Ok(JsDidResolver { inner: DidResolver::new().with_key_format(key_format) })
+    }
+
+    /// Publish relays and a NIP-05 identifier for a DID (`{"relays": [...], "nip05": "..."}`)
+    pub fn add_services(&mut self, did: &JsDidNostr, services_json: &str) -> Result<(), JsValue> {
+        let services = NostrServices::from_json(services_json).map_err(|e| JsValue::from_str(&e))?;
+        self.inner
+            .add_services(did.inner.clone(), services)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// DID document as JSON
+    pub fn document(&self, did: &JsDidNostr) -> Result<String, JsValue> {
+        self.inner
+            .document(&did.inner)
+            .map(|document| document.to_json())
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// DID resolution result as JSON (errors are reported in `didResolutionMetadata`)
+    pub fn resolve(&self, did: &str) -> String {
+        self.inner.resolve(did).to_json()
+    }
+}
+
+/// JavaScript wrapper for a signed NIP-01 NostrEvent (tags are JSON strings)
+#[wasm_bindgen]
+pub struct JsNostrEvent {
+    inner: NostrEvent,
+}
+
+#[wasm_bindgen]
+impl JsNostrEvent {
+    /// Strictly parse event JSON (call `verify` before trusting it)
+    pub fn from_json(json: &str) -> Result<JsNostrEvent, JsValue> {
+        let inner = NostrEvent::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        Ok(JsNostrEvent { inner })
+    }
+
+    /// Sign `{"kind": 1, "content": "...", "tags": [...], "created_at": ...}` with a
+    /// hex-encoded secret key (`created_at` defaults to now)
+    pub fn sign(secret_hex: &str, unsigned_json: &str) -> Result<JsNostrEvent, JsValue> {
+        let signer = NostrSigner::from_secret_hex(secret_hex).map_err(|e| JsValue::from_str(&e))?;
+        let event = UnsignedEvent::from_json(unsigned_json).map_err(|e| JsValue::from_str(&e))?;
+        Ok(JsNostrEvent { inner: event.sign(&signer) })
+    }
+
+    pub fn id(&self) -> String {
+        self.inner.id.clone()
+    }
+
+    pub fn pubkey(&self) -> String {
+        self.inner.pubkey.clone()
+    }
+
+    /// Unix seconds
+    pub fn created_at(&self) -> f64 {
+        self.inner.created_at as f64
+    }
+
+    pub fn kind(&self) -> u16 {
+        self.inner.kind
+    }
+
+    /// Tags as a JSON array of string arrays
+    pub fn tags(&self) -> String {
+        serde_json::to_string(&self.inner.tags).unwrap_or_default()
+    }
+
+    pub fn content(&self) -> String {
+        self.inner.content.clone()
+    }
+
+    pub fn sig(&self) -> String {
+        self.inner.sig.clone()
+    }
+
+    pub fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Id computed from the event's fields
+    pub fn compute_id(&self) -> String {
+        self.inner.compute_id()
+    }
+
+    /// Throw unless the id and signature are valid
+    pub fn verify(&self) -> Result<(), JsValue> {
+        self.inner.verify().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Author's DID
+    pub fn did(&self) -> Result<JsDidNostr, JsValue> {
+        let inner = self.inner.did().map_err(|e| JsValue::from_str(&e))?;
+        Ok(JsDidNostr { inner })
+    }
+
+    /// Value of the first tag named `name`
+    pub fn tag_value(&self, name: &str) -> Option<String> {
+        self.inner.tag_value(name).map(str::to_string)
+    }
+
+    /// Values of all tags named `name`
+    pub fn tag_values(&self, name: &str) -> Vec<String> {
+        self.inner.tag_values(name).into_iter().map(str::to_string).collect()
+    }
+}
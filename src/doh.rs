@@ -0,0 +1,442 @@
+//! DNS-over-HTTPS Module
+//!
+//! This module provides a reqwest DNS resolver that sends lookups to DoH servers
+//! (RFC 8484) instead of the system resolver, so destination hosts are not leaked to
+//! the local network's DNS. It is configured in the `dns` section of the privacy config.
+//!
+//! # Features
+//! - RFC 8484 `application/dns-message` GET queries (A and AAAA)
+//! - Multiple resolver URLs, tried in order
+//! - TTL-respecting in-memory cache with min/max clamps
+//! - Static host overrides (also used to bootstrap resolver hostnames)
+//! - Fail-closed mode: never fall back to the system resolver
+
+use base64::Engine;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Content type of DNS wire-format messages
+pub const DNS_MESSAGE_CONTENT_TYPE: &str = "application/dns-message";
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// DoH resolver configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DohConfig {
+    /// Resolver URLs, e.g. `https://1.1.1.1/dns-query`
+    pub resolvers: Vec<String>,
+    /// Static host overrides (exact host names)
+    pub hosts: HashMap<String, Vec<IpAddr>>,
+    /// Refuse to fall back to the system resolver
+    pub fail_closed: bool,
+    /// Also query AAAA records
+    pub ipv6: bool,
+    /// Lower bound for cached TTLs, in seconds
+    pub min_ttl_secs: u64,
+    /// Upper bound for cached TTLs, in seconds
+    pub max_ttl_secs: u64,
+    /// Per-query timeout, in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for DohConfig {
+    fn default() -> Self {
+        Self {
+            resolvers: vec![
+                "https://1.1.1.1/dns-query".to_string(),
+                "https://9.9.9.9/dns-query".to_string(),
+            ],
+            hosts: HashMap::new(),
+            fail_closed: true,
+            ipv6: true,
+            min_ttl_secs: 30,
+            max_ttl_secs: 3600,
+            timeout_secs: 5,
+        }
+    }
+}
+
+impl DohConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: DohConfig = serde_json::from_str(json).map_err(|e| format!("Invalid DNS config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check resolver URLs: HTTPS (or loopback), and resolvable without the system
+    /// resolver when failing closed
+    pub fn validate(&self) -> Result<(), String> {
+        if self.resolvers.is_empty() {
+            return Err("DNS config requires at least one resolver".to_string());
+        }
+        for resolver in &self.resolvers {
+            let url = reqwest::Url::parse(resolver).map_err(|e| format!("Invalid resolver URL {}: {}", resolver, e))?;
+            if url.scheme() != "https" && !crate::transport::is_loopback_url(resolver) {
+                return Err(format!("Resolver must use HTTPS: {}", resolver));
+            }
+            let host = url.host_str().unwrap_or_default().trim_matches(['[', ']']);
+            let bootstrapped = host.parse::<IpAddr>().is_ok() || self.hosts.contains_key(host);
+            if self.fail_closed && !bootstrapped {
+                return Err(format!("Resolver host {} needs an IP address or a hosts entry in fail-closed mode", host));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encode an RFC 1035 query (id 0, as recommended by RFC 8484)
+pub fn encode_query(name: &str, qtype: u16) -> Result<Vec<u8>, String> {
+    let mut out = vec![0, 0, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid DNS name: {}", name));
+        }
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+    out.extend_from_slice(&qtype.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(out)
+}
+
+/// Address records from a DNS response, with their TTLs
+pub fn decode_answers(message: &[u8]) -> Result<Vec<(IpAddr, u32)>, String> {
+    let truncated = || "Truncated DNS response".to_string();
+    let u16_at = |pos: usize| -> Result<u16, String> {
+        message.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]])).ok_or_else(truncated)
+    };
+    let flags = u16_at(2)?;
+    match flags & 0x000f {
+        0 => {}
+        RCODE_NXDOMAIN => return Err("Name not found (NXDOMAIN)".to_string()),
+        rcode => return Err(format!("DNS server error (rcode {})", rcode)),
+    }
+    let questions = u16_at(4)?;
+    let answers = u16_at(6)?;
+
+    let skip_name = |mut pos: usize| -> Result<usize, String> {
+        loop {
+            let len = *message.get(pos).ok_or_else(truncated)?;
+            match len {
+                0 => return Ok(pos + 1),
+                l if l & 0xc0 == 0xc0 => return Ok(pos + 2),
+                l => pos += 1 + l as usize,
+            }
+        }
+    };
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(pos)? + 4;
+    }
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(pos)?;
+        let rtype = u16_at(pos)?;
+        let ttl_bytes = message.get(pos + 4..pos + 8).ok_or_else(truncated)?;
+        let ttl = u32::from_be_bytes([ttl_bytes[0], ttl_bytes[1], ttl_bytes[2], ttl_bytes[3]]);
+        let rdlength = u16_at(pos + 8)? as usize;
+        let rdata = message.get(pos + 10..pos + 10 + rdlength).ok_or_else(truncated)?;
+        match (rtype, rdlength) {
+            (TYPE_A, 4) => records.push((IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3])), ttl)),
+            (TYPE_AAAA, 16) => {
+                let octets: [u8; 16] = rdata.try_into().map_err(|_| truncated())?;
+                records.push((IpAddr::V6(Ipv6Addr::from(octets)), ttl));
+            }
+            _ => {}
+        }
+        pos += 10 + rdlength;
+    }
+    Ok(records)
+}
+
+struct CacheEntry {
+    addrs: Vec<IpAddr>,
+    expires: Instant,
+}
+
+struct DohInner {
+    config: DohConfig,
+    client: reqwest::Client,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+/// DoH resolver for reqwest (`ClientBuilder::dns_resolver`)
+#[derive(Clone)]
+pub struct DohResolver {
+    inner: Arc<DohInner>,
+}
+
+impl std::fmt::Debug for DohResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DohResolver").field("config", &self.inner.config).finish()
+    }
+}
+
+impl DohResolver {
+    pub fn new(config: DohConfig) -> Result<Self, String> {
+        config.validate()?;
+        // Resolver hostnames are bootstrapped from the static hosts, never from DoH itself
+        let mut builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .no_proxy();
+        for (host, addrs) in &config.hosts {
+            let addrs: Vec<SocketAddr> = addrs.iter().map(|ip| SocketAddr::new(*ip, 0)).collect();
+            builder = builder.resolve_to_addrs(host, &addrs);
+        }
+        let client = builder.build().map_err(|e| format!("Failed to create DoH client: {}", e))?;
+        Ok(Self { inner: Arc::new(DohInner { config, client, cache: Mutex::new(HashMap::new()) }) })
+    }
+
+    pub fn config(&self) -> &DohConfig {
+        &self.inner.config
+    }
+
+    /// Number of cached names (expired entries included until next lookup)
+    pub fn cache_len(&self) -> usize {
+        self.inner.cache.lock().unwrap().len()
+    }
+
+    /// Forget all cached answers
+    pub fn clear_cache(&self) {
+        self.inner.cache.lock().unwrap().clear();
+    }
+
+    /// Resolve a host name to IP addresses
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, String> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        if let Some(addrs) = self.inner.config.hosts.get(&host) {
+            return Ok(addrs.clone());
+        }
+        if let Some(entry) = self.inner.cache.lock().unwrap().get(&host)
+            && entry.expires > Instant::now()
+        {
+            return Ok(entry.addrs.clone());
+        }
+
+        match self.query_resolvers(&host).await {
+            Ok(addrs) => Ok(addrs),
+            Err(e) if self.inner.config.fail_closed => Err(e),
+            Err(_) => tokio::net::lookup_host((host.as_str(), 0))
+                .await
+                .map(|addrs| addrs.map(|a| a.ip()).collect())
+                .map_err(|e| format!("System lookup of {} failed: {}", host, e)),
+        }
+    }
+
+    async fn query_resolvers(&self, host: &str) -> Result<Vec<IpAddr>, String> {
+        let mut last_error = String::new();
+        for resolver in &self.inner.config.resolvers {
+            match self.query(resolver, host).await {
+                Ok(records) if !records.is_empty() => {
+                    let config = &self.inner.config;
+                    let ttl = records.iter().map(|(_, ttl)| u64::from(*ttl)).min().unwrap_or(0);
+                    let ttl = ttl.clamp(config.min_ttl_secs, config.max_ttl_secs.max(config.min_ttl_secs));
+                    let addrs: Vec<IpAddr> = records.into_iter().map(|(ip, _)| ip).collect();
+                    self.inner.cache.lock().unwrap().insert(
+                        host.to_string(),
+                        CacheEntry { addrs: addrs.clone(), expires: Instant::now() + Duration::from_secs(ttl) },
+                    );
+                    return Ok(addrs);
+                }
+                Ok(_) => last_error = format!("No address records for {}", host),
+                // NXDOMAIN is authoritative; other resolvers would give the same answer
+                Err(e) if e.contains("NXDOMAIN") => return Err(format!("{}: {}", host, e)),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(format!("DoH lookup of {} failed: {}", host, last_error))
+    }
+
+    async fn query(&self, resolver: &str, host: &str) -> Result<Vec<(IpAddr, u32)>, String> {
+        let mut qtypes = vec![TYPE_A];
+        if self.inner.config.ipv6 {
+            qtypes.push(TYPE_AAAA);
+        }
+        let mut records = Vec::new();
+        for qtype in qtypes {
+            let query = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(encode_query(host, qtype)?);
+            let mut url = reqwest::Url::parse(resolver).map_err(|e| format!("Invalid resolver URL {}: {}", resolver, e))?;
+            url.query_pairs_mut().append_pair("dns", &query);
+            let response = self
+                .inner
+                .client
+                .get(url)
+                .header("Accept", DNS_MESSAGE_CONTENT_TYPE)
+                .send()
+                .await
+                .map_err(|e| format!("DoH request to {} failed: {}", resolver, e))?;
+            if !response.status().is_success() {
+                return Err(format!("DoH resolver {} returned HTTP {}", resolver, response.status()));
+            }
+            let body = response.bytes().await.map_err(|e| format!("Failed to read DoH response: {}", e))?;
+            records.extend(decode_answers(&body)?);
+        }
+        Ok(records)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Build a response to `query` with one A record per address
+    fn answer(query: &[u8], addrs: &[Ipv4Addr], ttl: u32, rcode: u16) -> Vec<u8> {
+        let mut out = query.to_vec();
+        out[2] = 0x81;
+        out[3] = 0x80 | rcode as u8;
+        let qtype = u16::from_be_bytes([query[query.len() - 4], query[query.len() - 3]]);
+        let addrs = if qtype == TYPE_A && rcode == 0 { addrs } else { &[] };
+        out[6..8].copy_from_slice(&(addrs.len() as u16).to_be_bytes());
+        for addr in addrs {
+            out.extend_from_slice(&[0xc0, 0x0c]);
+            out.extend_from_slice(&TYPE_A.to_be_bytes());
+            out.extend_from_slice(&CLASS_IN.to_be_bytes());
+            out.extend_from_slice(&ttl.to_be_bytes());
+            out.extend_from_slice(&4u16.to_be_bytes());
+            out.extend_from_slice(&addr.octets());
+        }
+        out
+    }
+
+    /// Local DoH stand-in: every name resolves to 127.0.0.1 except `missing.test`
+    async fn doh_server(queries: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/dns-query", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let queries = Arc::clone(&queries);
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    let n = stream.read(&mut buf).await.unwrap();
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let param = request.split("dns=").nth(1).unwrap().split([' ', '&']).next().unwrap();
+                    let query = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(param).unwrap();
+                    queries.fetch_add(1, Ordering::SeqCst);
+                    let rcode = if query.windows(7).any(|w| w == b"missing") { RCODE_NXDOMAIN } else { 0 };
+                    let body = answer(&query, &[Ipv4Addr::LOCALHOST], 300, rcode);
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+                        DNS_MESSAGE_CONTENT_TYPE,
+                        body.len()
+                    );
+                    stream.write_all(head.as_bytes()).await.unwrap();
+                    stream.write_all(&body).await.unwrap();
+                });
+            }
+        });
+        url
+    }
+
+    fn config(resolver: &str) -> DohConfig {
+        DohConfig { resolvers: vec![resolver.to_string()], ipv6: false, ..DohConfig::default() }
+    }
+
+    #[test]
+    fn test_query_encoding_and_answer_parsing() {
+        let query = encode_query("example.com.", TYPE_A).unwrap();
+        assert_eq!(&query[12..25], b"\x07example\x03com\x00");
+        let response = answer(&query, &[Ipv4Addr::new(93, 184, 216, 34)], 120, 0);
+        assert_eq!(decode_answers(&response).unwrap(), vec![(IpAddr::from([93, 184, 216, 34]), 120)]);
+        assert!(decode_answers(&answer(&query, &[], 0, RCODE_NXDOMAIN)).unwrap_err().contains("NXDOMAIN"));
+        assert!(encode_query("a..b", TYPE_A).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(DohConfig::default().validate().is_ok());
+        assert!(DohConfig::from_json(r#"{"resolvers": ["http://dns.example/dns-query"]}"#).is_err());
+        assert!(DohConfig::from_json(r#"{"resolvers": ["https://dns.google/dns-query"]}"#).is_err());
+        let bootstrapped = r#"{"resolvers": ["https://dns.google/dns-query"], "hosts": {"dns.google": ["8.8.8.8"]}}"#;
+        assert!(DohConfig::from_json(bootstrapped).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_cache_and_overrides() {
+        let queries = Arc::new(AtomicUsize::new(0));
+        let url = doh_server(Arc::clone(&queries)).await;
+        let mut config = config(&url);
+        config.hosts.insert("pinned.test".to_string(), vec![IpAddr::from([10, 0, 0, 1])]);
+        let resolver = DohResolver::new(config).unwrap();
+
+        assert_eq!(resolver.lookup("api.example.com").await.unwrap(), vec![IpAddr::from([127, 0, 0, 1])]);
+        assert_eq!(resolver.lookup("API.example.com.").await.unwrap(), vec![IpAddr::from([127, 0, 0, 1])]);
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+        assert_eq!(resolver.lookup("pinned.test").await.unwrap(), vec![IpAddr::from([10, 0, 0, 1])]);
+        assert!(resolver.lookup("missing.test").await.unwrap_err().contains("NXDOMAIN"));
+        assert_eq!(resolver.cache_len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fail_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead = format!("http://{}/dns-query", listener.local_addr().unwrap());
+        drop(listener);
+
+        let closed = DohResolver::new(config(&dead)).unwrap();
+        assert!(closed.lookup("localhost").await.is_err());
+        let open = DohResolver::new(DohConfig { fail_closed: false, ..config(&dead) }).unwrap();
+        assert!(!open.lookup("localhost").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_uses_resolver() {
+        let queries = Arc::new(AtomicUsize::new(0));
+        let url = doh_server(Arc::clone(&queries)).await;
+        let target = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = target.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = target.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                .await
+                .unwrap();
+        });
+
+        let client = reqwest::Client::builder()
+            .dns_resolver(DohResolver::new(config(&url)).unwrap())
+            .no_proxy()
+            .build()
+            .unwrap();
+        let body = client
+            .get(format!("http://service.internal.test:{}/", port))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "ok");
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,462 @@
+//! Forward Proxy Module
+//!
+//! A local HTTP/CONNECT forward proxy that applies the privacy policy to traffic from
+//! any application, used by the `privacy-http-proxy` binary.
+//!
+//! # Features
+//! - Header filtering, IP masking and User-Agent normalization on plain-HTTP requests
+//! - Tracker blocking for plain-HTTP requests and `CONNECT` tunnels (`403`)
+//! - `CONNECT` tunnels routed through the configured proxy / DoH resolver
+//! - Optional HTTPS interception (MITM) with a local CA (see [`crate::mitm`]), so the
+//!   policy also applies inside TLS
+
+use crate::doh::DohResolver;
+use crate::http1::{error_response, read_request, strip_hop_by_hop, wants_close, write_response};
+use crate::mitm::CertificateAuthority;
+use crate::privacy::{LoggingMode, PrivacyPolicy};
+use crate::proxy::{self, ProxyRouter};
+use crate::transport::{self, HttpRequest, HttpResponse};
+use reqwest::{Client, Url};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Forward proxy applying a privacy policy
+#[derive(Debug)]
+pub struct ForwardProxy {
+    policy: PrivacyPolicy,
+    client: Client,
+    onion_client: Option<Client>,
+    router: Option<Arc<ProxyRouter>>,
+    resolver: Option<DohResolver>,
+    ca: Option<Arc<CertificateAuthority>>,
+}
+
+impl ForwardProxy {
+    /// Proxy for a policy; upstream connections follow its proxy and DNS settings
+    pub fn new(policy: PrivacyPolicy) -> Result<Self, String> {
+        let router = match &policy.config().proxy {
+            Some(config) => Some(Arc::new(ProxyRouter::new(config.clone())?)),
+            None => None,
+        };
+        let resolver = match &policy.config().dns {
+            Some(config) => Some(DohResolver::new(config.clone())?),
+            None => None,
+        };
+        // Redirects are passed back to the client; plain HTTP is checked per request
+        let client = proxy::client_builder(router.as_ref(), resolver.as_ref(), false)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to create reqwest client: {}", e))?;
+        // Onion hosts are resolved by the proxy (socks5h), never locally
+        let onion_client = match &router {
+            Some(router) if router.config().allow_onion => Some(
+                proxy::client_builder(Some(router), None, false)
+                    .redirect(reqwest::redirect::Policy::none())
+                    .build()
+                    .map_err(|e| format!("Failed to create reqwest client: {}", e))?,
+            ),
+            _ => None,
+        };
+        Ok(Self { policy, client, onion_client, router, resolver, ca: None })
+    }
+
+    /// Intercept HTTPS with certificates issued by `ca`
+    pub fn with_mitm(mut self, ca: Arc<CertificateAuthority>) -> Self {
+        self.ca = Some(ca);
+        self
+    }
+
+    /// Replace the upstream client (e.g. to trust additional roots)
+    pub fn with_upstream(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Policy applied to proxied requests
+    pub fn policy(&self) -> &PrivacyPolicy {
+        &self.policy
+    }
+
+    /// Accept and serve connections until the listener fails
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), String> {
+        loop {
+            let (stream, _) = listener.accept().await.map_err(|e| format!("Failed to accept connection: {}", e))?;
+            let proxy = Arc::clone(&self);
+            tokio::spawn(async move {
+                let _ = proxy.handle(stream).await;
+            });
+        }
+    }
+
+    async fn handle(&self, stream: TcpStream) -> Result<(), String> {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let request = match read_request(&mut reader).await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(()),
+                Err(e) => return write_response(&mut reader, &error_response(400, &e), false, true).await,
+            };
+            if request.method.eq_ignore_ascii_case("CONNECT") {
+                let pending = reader.buffer().to_vec();
+                return self.connect(reader.into_inner(), &request.url, pending).await;
+            }
+            if !self.respond(&mut reader, request, None).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Forward one request and write its response; returns whether to keep the connection
+    async fn respond<S: AsyncWrite + Unpin>(
+        &self,
+        writer: &mut S,
+        mut request: HttpRequest,
+        authority: Option<&str>,
+    ) -> Result<bool, String> {
+        let close = wants_close(&request);
+        let head_only = request.method.eq_ignore_ascii_case("HEAD");
+        if let Some(authority) = authority.filter(|_| request.url.starts_with('/')) {
+            request.url = format!("https://{}{}", authority, request.url);
+        }
+        let response = self.forward(request).await;
+        write_response(writer, &response, head_only, close).await?;
+        Ok(!close)
+    }
+
+    /// Apply the policy to a proxied request and send it upstream
+    pub async fn forward(&self, mut request: HttpRequest) -> HttpResponse {
+        let url = match Url::parse(&request.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => url,
+            _ => return error_response(400, &format!("Unsupported request target: {}", request.url)),
+        };
+        let host = url.host_str().unwrap_or_default().to_string();
+        if self.policy.blocks_host(&host) {
+            return self.log(&request, &host, error_response(403, &format!("Blocked tracker: {}", host)));
+        }
+        if url.scheme() == "http" && self.policy.config().tls_enforce && !transport::is_loopback_url(url.as_str()) {
+            return self.log(&request, &host, error_response(403, "Plain HTTP is disabled by tls_enforce"));
+        }
+
+        strip_hop_by_hop(&mut request.headers);
+        request
+            .headers
+            .retain(|(k, _)| !k.eq_ignore_ascii_case("host") && !k.eq_ignore_ascii_case("content-length"));
+        self.policy.apply(&mut request);
+        let response = match self.send(request.clone()).await {
+            Ok(response) => response,
+            Err(e) => error_response(502, &e),
+        };
+        self.log(&request, &host, response)
+    }
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        let client = if proxy::is_onion_url(&request.url) {
+            let router = self.router.as_ref().ok_or("Onion destinations require a proxy")?;
+            router.check(&request.url)?;
+            self.onion_client.as_ref().ok_or("Onion destinations are disabled")?
+        } else {
+            &self.client
+        };
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|e| format!("Invalid method {}: {}", request.method, e))?;
+        let mut req = client.request(method, &request.url);
+        for (key, value) in &request.headers {
+            req = req.header(key.as_str(), value.as_str());
+        }
+        if !request.body.is_empty() {
+            req = req.body(request.body);
+        }
+        let response = req.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.as_str().to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+            .collect();
+        let body = response.bytes().await.map_err(|e| format!("Failed to read response body: {}", e))?;
+        Ok(HttpResponse { status, headers, body: body.to_vec() })
+    }
+
+    /// Metadata log line (method, host, status); nothing else is ever logged
+    fn log(&self, request: &HttpRequest, host: &str, response: HttpResponse) -> HttpResponse {
+        self.log_status(&request.method, host, response.status);
+        response
+    }
+
+    fn log_status(&self, method: &str, host: &str, status: u16) {
+        if self.policy.config().logging != LoggingMode::None {
+            eprintln!("{} {} {}", method, host, status);
+        }
+    }
+
+    async fn connect(&self, mut stream: TcpStream, target: &str, pending: Vec<u8>) -> Result<(), String> {
+        let Some((host, port)) = split_authority(target) else {
+            return write_response(&mut stream, &error_response(400, "Invalid CONNECT target"), false, true).await;
+        };
+        let request = HttpRequest::new("CONNECT", target);
+        if self.policy.blocks_host(&host) {
+            let response = self.log(&request, &host, error_response(403, &format!("Blocked tracker: {}", host)));
+            return write_response(&mut stream, &response, false, true).await;
+        }
+
+        if let Some(ca) = &self.ca {
+            let acceptor = ca.acceptor(&host)?;
+            stream
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await
+                .map_err(|e| format!("Failed to write response: {}", e))?;
+            // A ClientHello pipelined with the CONNECT head is already in `pending`
+            let tls = acceptor
+                .accept(Prefixed::new(pending, stream))
+                .await
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+            let authority = if port == 443 { host_for_url(&host) } else { format!("{}:{}", host_for_url(&host), port) };
+            return self.serve_tls(tls, &authority).await;
+        }
+
+        let mut upstream = match proxy::connect_tunnel(self.router.as_deref(), self.resolver.as_ref(), &host, port).await {
+            Ok(upstream) => upstream,
+            Err(e) => return write_response(&mut stream, &self.log(&request, &host, error_response(502, &e)), false, true).await,
+        };
+        self.log_status("CONNECT", &host, 200);
+        stream
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await
+            .map_err(|e| format!("Failed to write response: {}", e))?;
+        if !pending.is_empty() {
+            upstream.write_all(&pending).await.map_err(|e| format!("Tunnel failed: {}", e))?;
+        }
+        tokio::io::copy_bidirectional(&mut stream, &mut upstream)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Tunnel failed: {}", e))
+    }
+
+    async fn serve_tls<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S, authority: &str) -> Result<(), String> {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let request = match read_request(&mut reader).await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(()),
+                Err(e) => return write_response(&mut reader, &error_response(400, &e), false, true).await,
+            };
+            if !self.respond(&mut reader, request, Some(authority)).await? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Stream that yields bytes already read from `inner` before reading from it again
+struct Prefixed<S> {
+    prefix: Vec<u8>,
+    offset: usize,
+    inner: S,
+}
+
+impl<S> Prefixed<S> {
+    fn new(prefix: Vec<u8>, inner: S) -> Self {
+        Self { prefix, offset: 0, inner }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Prefixed<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        if self.offset < self.prefix.len() {
+            let n = buf.remaining().min(self.prefix.len() - self.offset);
+            buf.put_slice(&self.prefix[self.offset..self.offset + n]);
+            self.offset += n;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Prefixed<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Split a `CONNECT` target (`host:port` or `[v6]:port`)
+fn split_authority(target: &str) -> Option<(String, u16)> {
+    let (host, port) = target.rsplit_once(':')?;
+    let host = host.trim_matches(['[', ']']);
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_ascii_lowercase(), port.parse().ok()?))
+}
+
+fn host_for_url(host: &str) -> String {
+    if host.contains(':') { format!("[{}]", host) } else { host.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::PrivacyConfig;
+    use tokio::io::AsyncReadExt;
+
+    /// Origin answering every request with its headers, one per line
+    async fn echo_origin(acceptor: Option<tokio_native_tls::TlsAcceptor>) -> u16 {
+        async fn reply<S: AsyncRead + AsyncWrite + Unpin>(stream: S) {
+            let mut reader = BufReader::new(stream);
+            let request = read_request(&mut reader).await.unwrap().unwrap();
+            let body: String = request.headers.iter().map(|(k, v)| format!("{}: {}\n", k.to_ascii_lowercase(), v)).collect();
+            let response = HttpResponse { status: 200, headers: Vec::new(), body: body.into_bytes() };
+            write_response(&mut reader, &response, false, true).await.unwrap();
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                match &acceptor {
+                    Some(acceptor) => reply(acceptor.accept(stream).await.unwrap()).await,
+                    None => reply(stream).await,
+                }
+            }
+        });
+        port
+    }
+
+    async fn start(proxy: ForwardProxy) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(Arc::new(proxy).serve(listener));
+        address
+    }
+
+    fn policy() -> PrivacyPolicy {
+        PrivacyPolicy::new(PrivacyConfig::from_json(r#"{"user_agent": "Mozilla/5.0"}"#).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_policy() {
+        let origin = echo_origin(None).await;
+        let address = start(ForwardProxy::new(policy()).unwrap()).await;
+        let client = Client::builder().proxy(reqwest::Proxy::http(&address).unwrap()).build().unwrap();
+
+        let seen = client
+            .get(format!("http://127.0.0.1:{}/", origin))
+            .header("Cookie", "id=1")
+            .header("X-Forwarded-For", "10.0.0.1")
+            .header("User-Agent", "curl/8.0")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(seen.contains("user-agent: Mozilla/5.0"));
+        assert!(!seen.contains("cookie") && !seen.contains("x-forwarded-for") && !seen.contains("proxy-"));
+
+        let blocked = client.get("http://www.google-analytics.com/collect").send().await.unwrap();
+        assert_eq!(blocked.status().as_u16(), 403);
+        let plain = client.get("http://example.com/").send().await.unwrap();
+        assert_eq!(plain.status().as_u16(), 403);
+    }
+
+    #[tokio::test]
+    async fn test_connect_tunnel_and_mitm() {
+        // Plain tunnel to an echo server
+        let echo = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let echo_port = echo.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = echo.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            stream.write_all(&buf).await.unwrap();
+        });
+        let address = start(ForwardProxy::new(policy()).unwrap()).await;
+        let mut stream = TcpStream::connect(address.trim_start_matches("http://")).await.unwrap();
+        stream.write_all(format!("CONNECT 127.0.0.1:{} HTTP/1.1\r\n\r\nping", echo_port).as_bytes()).await.unwrap();
+        let mut reply = vec![0u8; "HTTP/1.1 200 Connection Established\r\n\r\nping".len()];
+        stream.read_exact(&mut reply).await.unwrap();
+        assert!(reply.ends_with(b"\r\n\r\nping"));
+
+        // Intercepted HTTPS: the policy applies inside TLS
+        let ca = Arc::new(CertificateAuthority::generate("Test Privacy CA"));
+        let root = reqwest::Certificate::from_pem(ca.cert_pem().as_bytes()).unwrap();
+        let origin = echo_origin(Some(ca.acceptor("localhost").unwrap())).await;
+        let upstream = Client::builder().add_root_certificate(root.clone()).no_proxy().build().unwrap();
+        let proxy = ForwardProxy::new(policy()).unwrap().with_mitm(Arc::clone(&ca)).with_upstream(upstream);
+        let address = start(proxy).await;
+        let client = Client::builder()
+            .proxy(reqwest::Proxy::https(&address).unwrap())
+            .add_root_certificate(root)
+            .build()
+            .unwrap();
+        let seen = client
+            .get(format!("https://localhost:{}/", origin))
+            .header("Referer", "https://private.example/")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(seen.contains("user-agent: Mozilla/5.0"));
+        assert!(!seen.contains("referer"));
+
+        let blocked = client.get("https://www.google-analytics.com/collect").send().await;
+        assert!(blocked.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mitm_client_hello_pipelined_with_connect() {
+        let ca = Arc::new(CertificateAuthority::generate("Test Privacy CA"));
+        let root = reqwest::Certificate::from_pem(ca.cert_pem().as_bytes()).unwrap();
+        let origin = echo_origin(Some(ca.acceptor("localhost").unwrap())).await;
+        let upstream = Client::builder().add_root_certificate(root).no_proxy().build().unwrap();
+        let proxy = ForwardProxy::new(policy()).unwrap().with_mitm(Arc::clone(&ca)).with_upstream(upstream);
+        let address = start(proxy).await;
+
+        // TLS client over an in-memory pipe, so its ClientHello can be captured first
+        let (tls_side, mut wire) = tokio::io::duplex(64 * 1024);
+        let root = tokio_native_tls::native_tls::Certificate::from_pem(ca.cert_pem().as_bytes()).unwrap();
+        let connector = tokio_native_tls::native_tls::TlsConnector::builder().add_root_certificate(root).build().unwrap();
+        let connector = tokio_native_tls::TlsConnector::from(connector);
+        let client = tokio::spawn(async move {
+            let mut tls = connector.connect("localhost", tls_side).await.unwrap();
+            let request = format!("GET / HTTP/1.1\r\nHost: localhost:{}\r\nUser-Agent: curl/8.0\r\nConnection: close\r\n\r\n", origin);
+            tls.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            tls.read_to_string(&mut response).await.unwrap();
+            response
+        });
+        let mut hello = vec![0u8; 5];
+        wire.read_exact(&mut hello).await.unwrap();
+        let length = u16::from_be_bytes([hello[3], hello[4]]) as usize;
+        hello.resize(5 + length, 0);
+        wire.read_exact(&mut hello[5..]).await.unwrap();
+
+        let mut stream = TcpStream::connect(address.trim_start_matches("http://")).await.unwrap();
+        let mut head = format!("CONNECT localhost:{} HTTP/1.1\r\nHost: localhost:{}\r\n\r\n", origin, origin).into_bytes();
+        head.extend_from_slice(&hello);
+        stream.write_all(&head).await.unwrap();
+        let mut established = vec![0u8; "HTTP/1.1 200 Connection Established\r\n\r\n".len()];
+        stream.read_exact(&mut established).await.unwrap();
+        assert!(established.starts_with(b"HTTP/1.1 200"));
+        tokio::spawn(async move {
+            let _ = tokio::io::copy_bidirectional(&mut wire, &mut stream).await;
+        });
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(10), client).await.unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("user-agent: Mozilla/5.0"));
+    }
+}
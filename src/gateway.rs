@@ -0,0 +1,548 @@
+//! Gateway Module
+//!
+//! An inbound reverse proxy placed in front of the `http-js`, `http-py` and `http-c++`
+//! servers, used by the `privacy-http-gateway` binary. DID checks and header hygiene
+//! happen once here instead of in every server.
+//!
+//! # Features
+//! - TLS termination (PEM certificate chain and PKCS#8 key)
+//! - DID signature verification for the `identity.methods` (`did:nostr`, `did:key`,
+//!   `did:web`; see [`crate::did`]). `did:web` documents are fetched through the privacy
+//!   `HttpClient` without redirects, cached, and refused on loopback, private and
+//!   link-local hosts unless `allow_private_did_web` is set
+//! - `X-Verified-DID` injected upstream; client-supplied values are always dropped
+//! - Client-identifying header stripping
+//! - Per-DID and session rate limits (the budget's `identities`, `default_identity`
+//!   and `session` limits). The session limit is charged before any verification, and
+//!   at most 32 `did:web` documents are fetched at once
+//! - Redacted access logs (no client address or query string; DIDs only when
+//!   `log_identities` is set, and then truncated)
+
+use crate::did::{Did, DidMethod, SUPPORTED_METHODS};
+use crate::did_document::DidResolver;
+use crate::did_nostr::{DID_HEADER, IdentityConfig, Rejection, SIGNATURE_HEADER, VerificationMiddleware};
+use crate::http1::{error_response, read_request, strip_hop_by_hop, wants_close, write_response};
+use crate::privacy::IpMaskingConfig;
+use crate::ratelimit::{RateLimitConfig, RateLimiter};
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+use crate::HttpClient;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+
+/// Header carrying the verified caller DID to the upstream server
+pub const VERIFIED_DID_HEADER: &str = "X-Verified-DID";
+
+/// How long a fetched `did:web` document is reused
+const DID_DOCUMENT_TTL: Duration = Duration::from_secs(300);
+
+/// `did:web` documents fetched at once; further callers are refused until one finishes
+const MAX_CONCURRENT_RESOLUTIONS: usize = 32;
+
+/// TLS termination settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayTlsConfig {
+    /// PEM certificate chain (leaf first)
+    pub cert_path: String,
+    /// PEM PKCS#8 private key
+    pub key_path: String,
+}
+
+/// Gateway configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GatewayConfig {
+    /// Upstream base URL (e.g. `http://127.0.0.1:3000`)
+    pub upstream: String,
+    /// TLS termination; `None` serves plain HTTP
+    pub tls: Option<GatewayTlsConfig>,
+    pub identity: IdentityConfig,
+    /// Headers removed before forwarding, in addition to hop-by-hop headers
+    pub strip_headers: Vec<String>,
+    /// Per-DID and session limits; `None` is unlimited
+    pub budget: Option<RateLimitConfig>,
+    /// Write a redacted access log line per request to stderr
+    pub access_log: bool,
+    /// Resolve `did:web` callers on loopback, private and link-local hosts (local
+    /// development only)
+    pub allow_private_did_web: bool,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            upstream: "http://127.0.0.1:3000".to_string(),
+            tls: None,
+            identity: IdentityConfig { enabled: true, require_signature: true, ..IdentityConfig::default() },
+            strip_headers: IpMaskingConfig::default().remove_headers,
+            budget: None,
+            access_log: true,
+            allow_private_did_web: false,
+        }
+    }
+}
+
+impl GatewayConfig {
+    /// Parse from JSON (missing fields fall back to defaults)
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: GatewayConfig = serde_json::from_str(json).map_err(|e| format!("Invalid gateway config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let upstream = Url::parse(&self.upstream).map_err(|e| format!("Invalid upstream {}: {}", self.upstream, e))?;
+        if !matches!(upstream.scheme(), "http" | "https") {
+            return Err(format!("Upstream must be an http(s) URL: {}", self.upstream));
+        }
+        if let Some(method) = self.identity.methods.iter().find(|m| !SUPPORTED_METHODS.contains(&m.as_str())) {
+            return Err(format!("Unsupported DID method: {}", method));
+        }
+        if let Some(requirement) = &self.identity.credential {
+            if !self.identity.require_signature {
+                return Err("identity.credential requires identity.require_signature".to_string());
+            }
+            requirement.validate()?;
+        }
+        if let Some(budget) = &self.budget {
+            budget.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Load from a JSON file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_json(&json)
+    }
+}
+
+/// Reverse proxy verifying callers before forwarding to one upstream
+pub struct Gateway {
+    config: GatewayConfig,
+    client: Client,
+    middleware: Arc<VerificationMiddleware>,
+    tls: Option<tokio_native_tls::TlsAcceptor>,
+}
+
+impl std::fmt::Debug for Gateway {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gateway").field("config", &self.config).finish()
+    }
+}
+
+impl Gateway {
+    pub fn new(config: GatewayConfig) -> Result<Self, String> {
+        config.validate()?;
+        let client = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to create reqwest client: {}", e))?;
+        let resolver = DidResolver::new()
+            .with_transport(Arc::new(ResolverTransport::new(config.allow_private_did_web)?))
+            .with_private_hosts(config.allow_private_did_web)
+            .with_cache(DID_DOCUMENT_TTL);
+        let mut middleware = VerificationMiddleware::new().with_methods(&config.identity.methods).with_resolver(resolver);
+        if let Some(budget) = &config.budget {
+            middleware = middleware.with_rate_limiter(Arc::new(RateLimiter::new(budget.clone())?));
+        }
+        if let Some(requirement) = &config.identity.credential {
+            middleware = middleware.with_credential_requirement(requirement.clone());
+        }
+        let tls = match &config.tls {
+            Some(tls) => Some(load_acceptor(tls)?),
+            None => None,
+        };
+        Ok(Self { config, client, middleware: Arc::new(middleware), tls })
+    }
+
+    /// Gateway configuration
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    /// Accept and serve connections until the listener fails
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), String> {
+        loop {
+            let (stream, _) = listener.accept().await.map_err(|e| format!("Failed to accept connection: {}", e))?;
+            let gateway = Arc::clone(&self);
+            tokio::spawn(async move {
+                let _ = match &gateway.tls {
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(tls) => gateway.handle(tls).await,
+                        Err(e) => Err(format!("TLS handshake failed: {}", e)),
+                    },
+                    None => gateway.handle(stream).await,
+                };
+            });
+        }
+    }
+
+    async fn handle<S: AsyncRead + AsyncWrite + Unpin>(&self, stream: S) -> Result<(), String> {
+        let mut reader = BufReader::new(stream);
+        loop {
+            let request = match read_request(&mut reader).await {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(()),
+                Err(e) => return write_response(&mut reader, &error_response(400, &e), false, true).await,
+            };
+            let close = wants_close(&request);
+            let head_only = request.method.eq_ignore_ascii_case("HEAD");
+            let response = self.handle_request(request).await;
+            write_response(&mut reader, &response, head_only, close).await?;
+            if close {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Verify, sanitize and forward one request (target in origin-form)
+    pub async fn handle_request(&self, mut request: HttpRequest) -> HttpResponse {
+        let started = Instant::now();
+        if !request.url.starts_with('/') {
+            return error_response(400, "Request target must be a path");
+        }
+        let did = match self.authenticate(&request).await {
+            Ok(did) => did,
+            Err(rejection) => {
+                let mut response = error_response(rejection.status, &rejection.error);
+                if let Some(seconds) = rejection.retry_after {
+                    response.headers.push(("Retry-After".to_string(), seconds.to_string()));
+                }
+                self.log(&request, &response, None, started);
+                return response;
+            }
+        };
+
+        let path = request.url.clone();
+        strip_hop_by_hop(&mut request.headers);
+        request.headers.retain(|(k, _)| {
+            !k.eq_ignore_ascii_case("host")
+                && !k.eq_ignore_ascii_case("content-length")
+                && !k.eq_ignore_ascii_case(VERIFIED_DID_HEADER)
+                && !self.config.strip_headers.iter().any(|h| h.eq_ignore_ascii_case(k))
+        });
+        if let Some(did) = &did {
+            request.headers.push((VERIFIED_DID_HEADER.to_string(), did.to_string()));
+        }
+        request.url = format!("{}{}", self.config.upstream.trim_end_matches('/'), path);
+        let response = match self.send(&request).await {
+            Ok(response) => response,
+            Err(e) => error_response(502, &e),
+        };
+        request.url = path;
+        self.log(&request, &response, did.as_ref(), started);
+        response
+    }
+
+    /// Verified caller, `None` for anonymous requests when signatures are optional
+    async fn authenticate(&self, request: &HttpRequest) -> Result<Option<Did>, Rejection> {
+        let identity = &self.config.identity;
+        // Charged before any verification work, so requests that fail verification
+        // cannot trigger unlimited did:web fetches
+        self.middleware.admit()?;
+        let signed = request.header(DID_HEADER).is_some() || request.header(SIGNATURE_HEADER).is_some();
+        if identity.enabled && (signed || identity.require_signature) {
+            // Resolving a did:web caller or issuer blocks on its document
+            let middleware = Arc::clone(&self.middleware);
+            let request = request.clone();
+            let verified = tokio::task::spawn_blocking(move || {
+                middleware.verify_admitted(&request.method, &request.url, &request.headers, &request.body)
+            })
+            .await
+            .map_err(|e| Rejection { status: 500, error: format!("Verification failed: {}", e), retry_after: None })?;
+            return verified.map(Some);
+        }
+        Ok(None)
+    }
+
+    async fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        forward(&self.client, request).await
+    }
+
+    fn log(&self, request: &HttpRequest, response: &HttpResponse, did: Option<&Did>, started: Instant) {
+        if self.config.access_log {
+            let did = did.filter(|_| self.config.identity.log_identities);
+            eprintln!("{}", access_log_line(request, response.status, did, started.elapsed().as_millis()));
+        }
+    }
+}
+
+/// Fetches `did:web` documents with a privacy client that does not follow redirects
+/// and, unless private hosts are allowed, only connects to public addresses; called on
+/// the blocking pool, at most [`MAX_CONCURRENT_RESOLUTIONS`] at once
+struct ResolverTransport {
+    client: Option<HttpClient>,
+    permits: Semaphore,
+}
+
+impl ResolverTransport {
+    fn new(allow_private_hosts: bool) -> Result<Self, String> {
+        let mut client = HttpClient::new(String::new());
+        client.set_follow_redirects(false)?;
+        client.set_public_hosts_only(!allow_private_hosts)?;
+        Ok(Self { client: Some(client), permits: Semaphore::new(MAX_CONCURRENT_RESOLUTIONS) })
+    }
+}
+
+impl Transport for ResolverTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        let _permit = self.permits.try_acquire().map_err(|_| "Too many concurrent did:web resolutions".to_string())?;
+        self.client.as_ref().ok_or_else(|| "DID resolution client is closed".to_string())?.send(request)
+    }
+}
+
+impl Drop for ResolverTransport {
+    // The client owns a runtime, which may not be dropped from within the gateway's
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            std::thread::spawn(move || drop(client));
+        }
+    }
+}
+
+async fn forward(client: &Client, request: &HttpRequest) -> Result<HttpResponse, String> {
+    let method = reqwest::Method::from_bytes(request.method.as_bytes())
+        .map_err(|e| format!("Invalid method {}: {}", request.method, e))?;
+    let mut req = client.request(method, &request.url);
+    for (key, value) in &request.headers {
+        req = req.header(key.as_str(), value.as_str());
+    }
+    if !request.body.is_empty() {
+        req = req.body(request.body.clone());
+    }
+    let response = req.send().await.map_err(|e| format!("Upstream request failed: {}", e))?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.as_str().to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+        .collect();
+    let body = response.bytes().await.map_err(|e| format!("Failed to read upstream body: {}", e))?;
+    Ok(HttpResponse { status, headers, body: body.to_vec() })
+}
+
+/// Access log line: method, path without query, status, latency and (optionally) a
+/// truncated DID. Client addresses and query strings are never logged.
+pub fn access_log_line(request: &HttpRequest, status: u16, did: Option<&Did>, elapsed_ms: u128) -> String {
+    let path = request.url.split(['?', '#']).next().unwrap_or_default();
+    let mut line = format!("{} {} {} {}ms", request.method, path, status, elapsed_ms);
+    if let Some(did) = did {
+        let id = did.to_string();
+        let tail = id.char_indices().rev().nth(7).map_or(0, |(i, _)| i);
+        line.push_str(&format!(" did:{}:***{}", did.method(), &id[tail..]));
+    }
+    line
+}
+
+fn load_acceptor(tls: &GatewayTlsConfig) -> Result<tokio_native_tls::TlsAcceptor, String> {
+    let cert = std::fs::read(&tls.cert_path).map_err(|e| format!("Failed to read {}: {}", tls.cert_path, e))?;
+    let key = std::fs::read(&tls.key_path).map_err(|e| format!("Failed to read {}: {}", tls.key_path, e))?;
+    let identity = tokio_native_tls::native_tls::Identity::from_pkcs8(&cert, &key)
+        .map_err(|e| format!("Invalid TLS certificate or key: {}", e))?;
+    let acceptor = tokio_native_tls::native_tls::TlsAcceptor::new(identity).map_err(|e| format!("Failed to create TLS acceptor: {}", e))?;
+    Ok(tokio_native_tls::TlsAcceptor::from(acceptor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did_nostr::NostrSigner;
+
+    /// Upstream answering every request with its headers, one per line
+    async fn echo_upstream() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    while let Ok(Some(request)) = read_request(&mut reader).await {
+                        let body: String = request
+                            .headers
+                            .iter()
+                            .map(|(k, v)| format!("{}: {}\n", k.to_ascii_lowercase(), v))
+                            .collect();
+                        let response = HttpResponse { status: 200, headers: Vec::new(), body: body.into_bytes() };
+                        write_response(&mut reader, &response, false, false).await.unwrap();
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_verified_did_forwarded() {
+        let config = GatewayConfig {
+            upstream: echo_upstream().await,
+            budget: Some(RateLimitConfig::from_json(r#"{"default_identity": {"rate": 0.1, "burst": 1}}"#).unwrap()),
+            access_log: false,
+            ..GatewayConfig::default()
+        };
+        let gateway = Gateway::new(config).unwrap();
+        let signer = NostrSigner::generate();
+
+        let request = HttpRequest::post("/rpc?x=1", "{}")
+            .with_headers(&signer.sign_request("POST", "/rpc?x=1", b"{}"))
+            .with_header("X-Forwarded-For", "10.0.0.1")
+            .with_header("X-Verified-DID", "did:nostr:spoofed");
+        let response = gateway.handle_request(request.clone()).await;
+        let seen = response.text();
+        assert_eq!(response.status, 200, "{}", seen);
+        assert!(seen.contains(&format!("x-verified-did: {}\n", signer.did())));
+        assert!(!seen.contains("spoofed") && !seen.contains("x-forwarded-for"));
+
+        let replayed = gateway.handle_request(request).await;
+        assert_eq!(replayed.status, 401);
+        let resigned = HttpRequest::post("/rpc?x=1", "{}").with_headers(&signer.sign_request("POST", "/rpc?x=1", b"{}"));
+        let throttled = gateway.handle_request(resigned).await;
+        assert_eq!((throttled.status, throttled.header("retry-after")), (429, Some("10")));
+        let unsigned = gateway.handle_request(HttpRequest::post("/rpc", "{}")).await;
+        assert_eq!(unsigned.status, 401);
+        let tampered = HttpRequest::post("/rpc", "{\"a\":1}").with_headers(&signer.sign_request("POST", "/rpc", b"{}"));
+        assert_eq!(gateway.handle_request(tampered).await.status, 401);
+    }
+
+    #[tokio::test]
+    async fn test_optional_signatures_and_log_redaction() {
+        let config = GatewayConfig::from_json(&format!(
+            r#"{{"upstream": "{}", "identity": {{"enabled": true, "methods": ["did:nostr"], "require_signature": false, "log_identities": true}}, "access_log": false}}"#,
+            echo_upstream().await
+        ))
+        .unwrap();
+        let gateway = Gateway::new(config).unwrap();
+        let anonymous = gateway.handle_request(HttpRequest::get("/health").with_header("X-Verified-DID", "did:nostr:x")).await;
+        assert_eq!(anonymous.status, 200);
+        assert!(!anonymous.text().contains("x-verified-did"));
+
+        let did = NostrSigner::generate().did();
+        let line = access_log_line(&HttpRequest::get("/search?q=secret"), 200, Some(&Did::Nostr(did.clone())), 12);
+        let key = did.pubkey().as_hex();
+        assert_eq!(line, format!("GET /search 200 12ms did:nostr:***{}", &key[56..]));
+        assert!(GatewayConfig::from_json(r#"{"upstream": "ftp://example.com"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_budget_is_charged_before_resolving() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Count document fetches, answering none of them
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let did = format!("did:web:127.0.0.1%3A{}", listener.local_addr().unwrap().port());
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&fetches);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counted.fetch_add(1, Ordering::SeqCst);
+                drop(stream);
+            }
+        });
+
+        let json = format!(
+            r#"{{"upstream": "{}", "identity": {{"enabled": true, "methods": ["did:web"], "require_signature": true}},
+                "budget": {{"session": {{"rate": 0.001, "burst": 1}}}}, "access_log": false, "allow_private_did_web": true}}"#,
+            echo_upstream().await
+        );
+        let gateway = Gateway::new(GatewayConfig::from_json(&json).unwrap()).unwrap();
+        let forged = || {
+            HttpRequest::post("/rpc", "{}")
+                .with_header(DID_HEADER, &did)
+                .with_headers(&crate::did_nostr::SignatureStamp::now().headers())
+                .with_header(SIGNATURE_HEADER, &"0".repeat(128))
+        };
+        assert_eq!(gateway.handle_request(forged()).await.status, 401);
+        let throttled = gateway.handle_request(forged()).await;
+        assert_eq!(throttled.status, 429);
+        assert!(throttled.headers.iter().any(|(k, _)| k == "Retry-After"));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_resolutions_are_capped() {
+        let transport = ResolverTransport::new(true).unwrap();
+        let _held = transport.permits.try_acquire_many(MAX_CONCURRENT_RESOLUTIONS as u32).unwrap();
+        let error = transport.send(HttpRequest::get("http://127.0.0.1:1/.well-known/did.json")).unwrap_err();
+        assert_eq!(error, "Too many concurrent did:web resolutions");
+    }
+
+    #[tokio::test]
+    async fn test_did_web_callers() {
+        use crate::did::PublicKey;
+        use crate::did_nostr::{RequestCanonicalizer, SignatureStamp};
+        use ed25519_dalek::Signer as _;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Serve the caller's DID document
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let did = format!("did:web:127.0.0.1%3A{}", listener.local_addr().unwrap().port());
+        let key = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let document = serde_json::json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": did,
+            "verificationMethod": [{
+                "id": format!("{}#owner", did),
+                "type": "JsonWebKey2020",
+                "controller": did,
+                "publicKeyJwk": PublicKey::Ed25519(key.verifying_key()).to_jwk(),
+            }],
+            "authentication": ["#owner"],
+        })
+        .to_string();
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let served = Arc::clone(&fetches);
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+                if let Ok(Some(_)) = read_request(&mut reader).await {
+                    served.fetch_add(1, Ordering::SeqCst);
+                    let response = HttpResponse { status: 200, headers: Vec::new(), body: document.clone().into_bytes() };
+                    let _ = write_response(&mut reader, &response, false, true).await;
+                }
+            }
+        });
+
+        let json = format!(
+            r#"{{"upstream": "{}", "identity": {{"enabled": true, "methods": ["did:web"], "require_signature": true}}, "access_log": false}}"#,
+            echo_upstream().await
+        );
+        let signed = |did: &str| {
+            let stamp = SignatureStamp::now();
+            let canonical = RequestCanonicalizer::canonicalize_signed("POST", "/rpc", did, &stamp, b"{}");
+            HttpRequest::post("/rpc", "{}")
+                .with_header(DID_HEADER, did)
+                .with_headers(&stamp.headers())
+                .with_header(SIGNATURE_HEADER, &hex::encode(key.sign(canonical.as_bytes()).to_bytes()))
+        };
+
+        // Loopback documents are refused unless the gateway opts in
+        let strict = Gateway::new(GatewayConfig::from_json(&json).unwrap()).unwrap();
+        let refused = strict.handle_request(signed(&did)).await;
+        assert_eq!(refused.status, 401);
+        assert!(refused.text().contains("not a public host"), "{}", refused.text());
+        assert_eq!(fetches.load(Ordering::SeqCst), 0);
+
+        let mut config = GatewayConfig::from_json(&json).unwrap();
+        config.allow_private_did_web = true;
+        let gateway = Gateway::new(config).unwrap();
+        for _ in 0..2 {
+            let response = gateway.handle_request(signed(&did)).await;
+            assert_eq!(response.status, 200, "{}", response.text());
+            assert!(response.text().contains(&format!("x-verified-did: {}\n", did)));
+        }
+        // The second request reuses the cached document
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // did:nostr is not among this gateway's methods
+        let signer = NostrSigner::generate();
+        let nostr = HttpRequest::post("/rpc", "{}").with_headers(&signer.sign_request("POST", "/rpc", b"{}"));
+        assert_eq!(gateway.handle_request(nostr).await.status, 401);
+        assert!(GatewayConfig::from_json(r#"{"identity": {"methods": ["did:example"]}}"#).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! HTTP/1.1 Wire Module
+//!
+//! Minimal HTTP/1.1 message reading and writing over async streams, used by the
+//! proxy binaries. Bodies are buffered: `Content-Length` and chunked request bodies
+//! are supported, and responses are always written with a `Content-Length`.
+
+use crate::transport::{HttpRequest, HttpResponse};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest accepted header section
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// Largest accepted request body
+pub const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// Hop-by-hop headers (RFC 9110, Section 7.6.1) plus proxy credentials
+pub const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R, budget: &mut usize) -> Result<String, String> {
+    let mut line = String::new();
+    let n = (&mut *reader)
+        .take(*budget as u64 + 1)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("Failed to read request: {}", e))?;
+    if n > *budget {
+        return Err("Request head too large".to_string());
+    }
+    *budget -= n;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Read one request; `None` when the peer closed the connection between requests.
+/// The URL is the request target as sent (absolute-form, origin-form or authority-form).
+pub async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<HttpRequest>, String> {
+    let mut budget = MAX_HEAD_BYTES;
+    let mut request_line = read_line(reader, &mut budget).await?;
+    while request_line.is_empty() {
+        if reader.fill_buf().await.map_err(|e| e.to_string())?.is_empty() {
+            return Ok(None);
+        }
+        request_line = read_line(reader, &mut budget).await?;
+    }
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(format!("Malformed request line: {}", request_line));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(format!("Unsupported protocol: {}", version));
+    }
+
+    let mut request = HttpRequest::new(method, target);
+    loop {
+        let line = read_line(reader, &mut budget).await?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').ok_or_else(|| format!("Malformed header: {}", line))?;
+        request.headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let chunked = request
+        .header("transfer-encoding")
+        .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+    if chunked {
+        request.body = read_chunked(reader).await?;
+    } else if let Some(length) = request.header("content-length") {
+        let length: usize = length.trim().parse().map_err(|_| "Invalid Content-Length".to_string())?;
+        if length > MAX_BODY_BYTES {
+            return Err("Request body too large".to_string());
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body).await.map_err(|e| format!("Failed to read body: {}", e))?;
+    }
+    Ok(Some(request))
+}
+
+async fn read_chunked<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let mut budget = MAX_HEAD_BYTES;
+        let size_line = read_line(reader, &mut budget).await?;
+        let size = usize::from_str_radix(size_line.split(';').next().unwrap_or_default().trim(), 16)
+            .map_err(|_| format!("Invalid chunk size: {}", size_line))?;
+        if size == 0 {
+            // Skip trailers
+            while !read_line(reader, &mut budget).await?.is_empty() {}
+            return Ok(body);
+        }
+        if body.len().checked_add(size).is_none_or(|total| total > MAX_BODY_BYTES) {
+            return Err("Request body too large".to_string());
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).await.map_err(|e| format!("Failed to read chunk: {}", e))?;
+        read_line(reader, &mut budget).await?;
+    }
+}
+
+/// Whether the client asked to close the connection after this request
+pub fn wants_close(request: &HttpRequest) -> bool {
+    request.header("connection").is_some_and(|c| c.eq_ignore_ascii_case("close"))
+        || request.header("proxy-connection").is_some_and(|c| c.eq_ignore_ascii_case("close"))
+}
+
+/// Remove hop-by-hop headers, including those named in `Connection`
+pub fn strip_hop_by_hop(headers: &mut Vec<(String, String)>) {
+    let listed: Vec<String> = headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("connection"))
+        .flat_map(|(_, v)| v.split(',').map(|t| t.trim().to_ascii_lowercase()).collect::<Vec<_>>())
+        .collect();
+    headers.retain(|(k, _)| {
+        let k = k.to_ascii_lowercase();
+        !HOP_BY_HOP.contains(&k.as_str()) && !listed.contains(&k)
+    });
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "",
+    }
+}
+
+/// Write a response. Hop-by-hop headers are dropped and `Content-Length` is set from
+/// the body, except for `HEAD` responses, which keep the upstream length.
+pub async fn write_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    response: &HttpResponse,
+    head_only: bool,
+    close: bool,
+) -> Result<(), String> {
+    let mut headers = response.headers.clone();
+    strip_hop_by_hop(&mut headers);
+    if !head_only {
+        headers.retain(|(k, _)| !k.eq_ignore_ascii_case("content-length"));
+    }
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason(response.status));
+    for (name, value) in &headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if !head_only && !matches!(response.status, 204 | 304) {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    if close {
+        head.push_str("Connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes()).await.map_err(|e| format!("Failed to write response: {}", e))?;
+    if !head_only {
+        writer.write_all(&response.body).await.map_err(|e| format!("Failed to write response: {}", e))?;
+    }
+    writer.flush().await.map_err(|e| format!("Failed to write response: {}", e))
+}
+
+/// Plain-text error response
+pub fn error_response(status: u16, message: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        headers: vec![("Content-Type".to_string(), "text/plain; charset=utf-8".to_string())],
+        body: format!("{}\n", message).into_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn test_read_requests() {
+        let wire = b"GET http://example.com/a HTTP/1.1\r\nHost: example.com\r\nConnection: keep-alive, X-Trace\r\nX-Trace: 1\r\n\r\n\
+POST /b HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2;ext=1\r\nde\r\n0\r\n\r\n";
+        let mut reader = BufReader::new(&wire[..]);
+        let mut first = read_request(&mut reader).await.unwrap().unwrap();
+        assert_eq!((first.method.as_str(), first.url.as_str()), ("GET", "http://example.com/a"));
+        strip_hop_by_hop(&mut first.headers);
+        assert_eq!(first.headers, vec![("Host".to_string(), "example.com".to_string())]);
+
+        let second = read_request(&mut reader).await.unwrap().unwrap();
+        assert_eq!((second.url.as_str(), second.body.as_slice()), ("/b", &b"abcde"[..]));
+        assert!(read_request(&mut reader).await.unwrap().is_none());
+        assert!(read_request(&mut BufReader::new(&b"NONSENSE\r\n\r\n"[..])).await.is_err());
+
+        // A chunk size near usize::MAX must not wrap around the body limit
+        let huge = b"POST /c HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\nffffffffffffffff\r\n";
+        let err = read_request(&mut BufReader::new(&huge[..])).await.unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_write_response() {
+        let mut out = Vec::new();
+        let response = HttpResponse {
+            status: 200,
+            headers: vec![
+                ("Transfer-Encoding".to_string(), "chunked".to_string()),
+                ("Content-Type".to_string(), "text/plain".to_string()),
+            ],
+            body: b"hi".to_vec(),
+        };
+        write_response(&mut out, &response, false, true).await.unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\nConnection: close\r\n\r\nhi"
+        );
+    }
+}
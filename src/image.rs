@@ -0,0 +1,871 @@
+//! Image Generation Module
+//!
+//! This module provides a single [`ImageProvider`] abstraction over the Stable Diffusion
+//! style backends used by the SDK. The WASM, Python and C++ bindings all go through
+//! `HttpClient::generate_images`, which delegates to the configured provider.
+//!
+//! # Providers
+//! - Stability AI v1 (`/v1/generation/{engine}/text-to-image`)
+//! - Stability AI v2 (`/v2beta/stable-image/generate/{model}`, multipart)
+//! - AUTOMATIC1111-compatible local servers (`/sdapi/v1/txt2img`)
+//! - ComfyUI local servers (`/prompt` + `/history` + `/view`)
+//! - OpenAI Images (`/v1/images/generations`)
+
+use aes_gcm::aead::OsRng;
+use aes_gcm::aead::rand_core::RngCore;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fmt;
+use std::time::Duration;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+/// Engine used by the Stability v1 API when none is configured
+pub const DEFAULT_STABILITY_V1_ENGINE: &str = "stable-diffusion-xl-beta-v2-2-2";
+
+/// Output image encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    /// Parse from "png" or "webp"
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_ascii_lowercase().as_str() {
+            "png" => Ok(ImageFormat::Png),
+            "webp" => Ok(ImageFormat::Webp),
+            other => Err(format!("Unsupported image format: {}", other)),
+        }
+    }
+
+    /// File extension / API value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    /// MIME type
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// Text-to-image request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageRequest {
+    pub prompt: String,
+    pub negative_prompt: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub steps: u32,
+    pub seed: Option<u64>,
+    pub cfg_scale: Option<f32>,
+    pub sampler: Option<String>,
+    /// Number of images to generate
+    pub count: u32,
+    pub format: ImageFormat,
+}
+
+impl Default for ImageRequest {
+    fn default() -> Self {
+        Self {
+            prompt: String::new(),
+            negative_prompt: None,
+            width: 1024,
+            height: 1024,
+            steps: 30,
+            seed: None,
+            cfg_scale: None,
+            sampler: None,
+            count: 1,
+            format: ImageFormat::Png,
+        }
+    }
+}
+
+impl ImageRequest {
+    /// Create a request with the basic parameters shared by all bindings
+    pub fn new(prompt: &str, width: u32, height: u32, steps: u32) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            width,
+            height,
+            steps,
+            ..Self::default()
+        }
+    }
+
+    /// Parse from JSON (missing fields fall back to defaults)
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid image request: {}", e))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.prompt.trim().is_empty() {
+            return Err("Image prompt must not be empty".to_string());
+        }
+        if self.count == 0 {
+            return Err("Image count must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A generated image
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    /// Seed reported by the backend, if any
+    pub seed: Option<u64>,
+}
+
+impl GeneratedImage {
+    /// Base64 (standard alphabet) encoding of the image bytes
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(&self.data)
+    }
+}
+
+/// Text-to-image backend
+pub trait ImageProvider: fmt::Debug + Send + Sync {
+    /// Short provider name (e.g. "stability_v1")
+    fn name(&self) -> &'static str;
+
+    /// Generate images, sending requests through `transport`
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String>;
+}
+
+/// Provider selection, deserializable from JSON (`{"provider": "automatic1111", ...}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum ImageProviderConfig {
+    StabilityV1 {
+        api_key: String,
+        #[serde(default)]
+        engine: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    StabilityV2 {
+        api_key: String,
+        /// "core", "ultra" or "sd3"
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    Automatic1111 {
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+    #[serde(rename = "comfyui")]
+    ComfyUi {
+        #[serde(default)]
+        base_url: Option<String>,
+        #[serde(default)]
+        checkpoint: Option<String>,
+    },
+    #[serde(rename = "openai")]
+    OpenAi {
+        api_key: String,
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+}
+
+impl ImageProviderConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid image provider config: {}", e))
+    }
+
+    /// Build the configured provider
+    pub fn build(self) -> Box<dyn ImageProvider> {
+        match self {
+            ImageProviderConfig::StabilityV1 { api_key, engine, base_url } => {
+                let mut provider = StabilityV1::new(&api_key);
+                if let Some(engine) = engine {
+                    provider.engine = engine;
+                }
+                if let Some(base_url) = base_url {
+                    provider.base_url = base_url;
+                }
+                Box::new(provider)
+            }
+            ImageProviderConfig::StabilityV2 { api_key, model, base_url } => {
+                let mut provider = StabilityV2::new(&api_key);
+                if let Some(model) = model {
+                    provider.model = model;
+                }
+                if let Some(base_url) = base_url {
+                    provider.base_url = base_url;
+                }
+                Box::new(provider)
+            }
+            ImageProviderConfig::Automatic1111 { base_url } => {
+                Box::new(Automatic1111::new(base_url.as_deref().unwrap_or(Automatic1111::DEFAULT_URL)))
+            }
+            ImageProviderConfig::ComfyUi { base_url, checkpoint } => {
+                let mut provider = ComfyUi::new(base_url.as_deref().unwrap_or(ComfyUi::DEFAULT_URL));
+                if let Some(checkpoint) = checkpoint {
+                    provider.checkpoint = checkpoint;
+                }
+                Box::new(provider)
+            }
+            ImageProviderConfig::OpenAi { api_key, model, base_url } => {
+                let mut provider = OpenAiImages::new(&api_key);
+                if let Some(model) = model {
+                    provider.model = model;
+                }
+                if let Some(base_url) = base_url {
+                    provider.base_url = base_url;
+                }
+                Box::new(provider)
+            }
+        }
+    }
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+fn redacted(api_key: &str) -> String {
+    let skip = api_key.chars().count().saturating_sub(4);
+    format!("***{}", api_key.chars().skip(skip).collect::<String>())
+}
+
+/// Stability AI v1 REST API
+#[derive(Clone)]
+pub struct StabilityV1 {
+    pub api_key: String,
+    pub engine: String,
+    pub base_url: String,
+}
+
+impl fmt::Debug for StabilityV1 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StabilityV1")
+            .field("api_key", &redacted(&self.api_key))
+            .field("engine", &self.engine)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl StabilityV1 {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            engine: DEFAULT_STABILITY_V1_ENGINE.to_string(),
+            base_url: "https://api.stability.ai".to_string(),
+        }
+    }
+
+    fn build_request(&self, request: &ImageRequest) -> HttpRequest {
+        let mut prompts = vec![json!({ "text": request.prompt, "weight": 1.0 })];
+        if let Some(negative) = &request.negative_prompt {
+            prompts.push(json!({ "text": negative, "weight": -1.0 }));
+        }
+        let mut body = json!({
+            "text_prompts": prompts,
+            "width": request.width,
+            "height": request.height,
+            "steps": request.steps,
+            "samples": request.count,
+        });
+        if let Some(seed) = request.seed {
+            body["seed"] = json!(seed);
+        }
+        if let Some(cfg_scale) = request.cfg_scale {
+            body["cfg_scale"] = json!(cfg_scale);
+        }
+        if let Some(sampler) = &request.sampler {
+            body["sampler"] = json!(sampler);
+        }
+        let url = format!("{}/v1/generation/{}/text-to-image", self.base_url.trim_end_matches('/'), self.engine);
+        HttpRequest::post_json(&url, &body)
+            .with_header("Authorization", &format!("Bearer {}", self.api_key))
+            .with_header("Accept", "application/json")
+    }
+
+    fn parse_response(response: &HttpResponse) -> Result<Vec<GeneratedImage>, String> {
+        let json = response.json()?;
+        let artifacts = json["artifacts"]
+            .as_array()
+            .ok_or_else(|| "No image data in response".to_string())?;
+        artifacts
+            .iter()
+            .map(|artifact| {
+                let data = artifact["base64"]
+                    .as_str()
+                    .or_else(|| artifact["binary"].as_str())
+                    .ok_or_else(|| "No image data in response".to_string())?;
+                Ok(GeneratedImage {
+                    data: decode_base64(data)?,
+                    format: ImageFormat::Png,
+                    seed: artifact["seed"].as_u64(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ImageProvider for StabilityV1 {
+    fn name(&self) -> &'static str {
+        "stability_v1"
+    }
+
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        request.validate()?;
+        if request.format != ImageFormat::Png {
+            return Err("Stability v1 only returns PNG images".to_string());
+        }
+        let response = transport.send(self.build_request(request))?.error_for_status()?;
+        Self::parse_response(&response)
+    }
+}
+
+/// Stability AI v2beta Stable Image API (one image per call)
+#[derive(Clone)]
+pub struct StabilityV2 {
+    pub api_key: String,
+    /// "core", "ultra" or "sd3"
+    pub model: String,
+    pub base_url: String,
+}
+
+impl fmt::Debug for StabilityV2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StabilityV2")
+            .field("api_key", &redacted(&self.api_key))
+            .field("model", &self.model)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl StabilityV2 {
+    const ASPECT_RATIOS: [(&'static str, f64); 9] = [
+        ("21:9", 21.0 / 9.0),
+        ("16:9", 16.0 / 9.0),
+        ("3:2", 3.0 / 2.0),
+        ("5:4", 5.0 / 4.0),
+        ("1:1", 1.0),
+        ("4:5", 4.0 / 5.0),
+        ("2:3", 2.0 / 3.0),
+        ("9:16", 9.0 / 16.0),
+        ("9:21", 9.0 / 21.0),
+    ];
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            model: "core".to_string(),
+            base_url: "https://api.stability.ai".to_string(),
+        }
+    }
+
+    /// v2 takes an aspect ratio instead of pixel dimensions
+    fn aspect_ratio(width: u32, height: u32) -> &'static str {
+        let ratio = width.max(1) as f64 / height.max(1) as f64;
+        Self::ASPECT_RATIOS
+            .iter()
+            .min_by(|a, b| (a.1 - ratio).abs().total_cmp(&(b.1 - ratio).abs()))
+            .map(|(name, _)| *name)
+            .unwrap_or("1:1")
+    }
+
+    fn build_request(&self, request: &ImageRequest, seed: Option<u64>) -> HttpRequest {
+        let mut fields = vec![
+            ("prompt", request.prompt.clone()),
+            ("aspect_ratio", Self::aspect_ratio(request.width, request.height).to_string()),
+            ("output_format", request.format.as_str().to_string()),
+        ];
+        if let Some(negative) = &request.negative_prompt {
+            fields.push(("negative_prompt", negative.clone()));
+        }
+        if let Some(seed) = seed {
+            fields.push(("seed", seed.to_string()));
+        }
+        if let Some(cfg_scale) = request.cfg_scale.filter(|_| self.model.starts_with("sd3")) {
+            fields.push(("cfg_scale", cfg_scale.to_string()));
+        }
+        let endpoint = if self.model.starts_with("sd3") { "sd3" } else { self.model.as_str() };
+        if endpoint == "sd3" && self.model != "sd3" {
+            // Specific SD3 variants (e.g. "sd3.5-large") are selected with a form field
+            fields.push(("model", self.model.clone()));
+        }
+        let (content_type, body) = multipart_form(&fields);
+        let url = format!("{}/v2beta/stable-image/generate/{}", self.base_url.trim_end_matches('/'), endpoint);
+        HttpRequest::post(&url, body)
+            .with_header("Authorization", &format!("Bearer {}", self.api_key))
+            .with_header("Accept", "application/json")
+            .with_header("Content-Type", &content_type)
+    }
+}
+
+impl ImageProvider for StabilityV2 {
+    fn name(&self) -> &'static str {
+        "stability_v2"
+    }
+
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        request.validate()?;
+        let mut images = Vec::new();
+        for i in 0..request.count {
+            let seed = request.seed.map(|s| s.wrapping_add(i as u64));
+            let response = transport.send(self.build_request(request, seed))?.error_for_status()?;
+            let json = response.json()?;
+            let data = json["image"]
+                .as_str()
+                .ok_or_else(|| "No image data in response".to_string())?;
+            images.push(GeneratedImage {
+                data: decode_base64(data)?,
+                format: request.format,
+                seed: json["seed"].as_u64().or(seed),
+            });
+        }
+        Ok(images)
+    }
+}
+
+/// AUTOMATIC1111 / Forge compatible local server
+#[derive(Debug, Clone)]
+pub struct Automatic1111 {
+    pub base_url: String,
+}
+
+impl Automatic1111 {
+    pub const DEFAULT_URL: &'static str = "http://127.0.0.1:7860";
+
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.to_string() }
+    }
+
+    fn build_request(&self, request: &ImageRequest) -> HttpRequest {
+        let mut body = json!({
+            "prompt": request.prompt,
+            "negative_prompt": request.negative_prompt.clone().unwrap_or_default(),
+            "width": request.width,
+            "height": request.height,
+            "steps": request.steps,
+            "seed": request.seed.map(|s| s as i64).unwrap_or(-1),
+            "batch_size": request.count,
+            "n_iter": 1,
+            "override_settings": { "samples_format": request.format.as_str() },
+        });
+        if let Some(cfg_scale) = request.cfg_scale {
+            body["cfg_scale"] = json!(cfg_scale);
+        }
+        if let Some(sampler) = &request.sampler {
+            body["sampler_name"] = json!(sampler);
+        }
+        let url = format!("{}/sdapi/v1/txt2img", self.base_url.trim_end_matches('/'));
+        HttpRequest::post_json(&url, &body)
+    }
+
+    fn parse_response(response: &HttpResponse, format: ImageFormat) -> Result<Vec<GeneratedImage>, String> {
+        let json = response.json()?;
+        // `info` is a JSON document encoded as a string
+        let info: Value = json["info"]
+            .as_str()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        let seeds = info["all_seeds"].as_array().cloned().unwrap_or_default();
+        let images = json["images"]
+            .as_array()
+            .ok_or_else(|| "No image data in response".to_string())?;
+        images
+            .iter()
+            .enumerate()
+            .map(|(i, image)| {
+                let data = image.as_str().ok_or_else(|| "No image data in response".to_string())?;
+                // Some servers prefix the payload with a data URL header
+                let data = data.split_once("base64,").map(|(_, d)| d).unwrap_or(data);
+                Ok(GeneratedImage {
+                    data: decode_base64(data)?,
+                    format,
+                    seed: seeds.get(i).and_then(|s| s.as_u64()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ImageProvider for Automatic1111 {
+    fn name(&self) -> &'static str {
+        "automatic1111"
+    }
+
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        request.validate()?;
+        let response = transport.send(self.build_request(request))?.error_for_status()?;
+        Self::parse_response(&response, request.format)
+    }
+}
+
+/// ComfyUI local server driven by a built-in txt2img workflow
+#[derive(Debug, Clone)]
+pub struct ComfyUi {
+    pub base_url: String,
+    /// Checkpoint file name passed to `CheckpointLoaderSimple`
+    pub checkpoint: String,
+    /// Maximum number of `/history` polls before giving up
+    pub max_polls: u32,
+    pub poll_interval: Duration,
+}
+
+impl ComfyUi {
+    pub const DEFAULT_URL: &'static str = "http://127.0.0.1:8188";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            checkpoint: "sd_xl_base_1.0.safetensors".to_string(),
+            max_polls: 600,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+
+    fn workflow(&self, request: &ImageRequest, seed: u64) -> Value {
+        let save_node = match request.format {
+            ImageFormat::Png => json!({
+                "class_type": "SaveImage",
+                "inputs": { "filename_prefix": "privacy_http", "images": ["6", 0] }
+            }),
+            ImageFormat::Webp => json!({
+                "class_type": "SaveAnimatedWEBP",
+                "inputs": {
+                    "filename_prefix": "privacy_http", "images": ["6", 0],
+                    "fps": 1.0, "lossless": false, "quality": 90, "method": "default"
+                }
+            }),
+        };
+        json!({
+            "1": { "class_type": "CheckpointLoaderSimple", "inputs": { "ckpt_name": self.checkpoint } },
+            "2": { "class_type": "CLIPTextEncode", "inputs": { "text": request.prompt, "clip": ["1", 1] } },
+            "3": {
+                "class_type": "CLIPTextEncode",
+                "inputs": { "text": request.negative_prompt.clone().unwrap_or_default(), "clip": ["1", 1] }
+            },
+            "4": {
+                "class_type": "EmptyLatentImage",
+                "inputs": { "width": request.width, "height": request.height, "batch_size": request.count }
+            },
+            "5": {
+                "class_type": "KSampler",
+                "inputs": {
+                    "model": ["1", 0], "positive": ["2", 0], "negative": ["3", 0], "latent_image": ["4", 0],
+                    "seed": seed, "steps": request.steps,
+                    "cfg": request.cfg_scale.unwrap_or(7.0),
+                    "sampler_name": request.sampler.clone().unwrap_or_else(|| "euler".to_string()),
+                    "scheduler": "normal", "denoise": 1.0
+                }
+            },
+            "6": { "class_type": "VAEDecode", "inputs": { "samples": ["5", 0], "vae": ["1", 2] } },
+            "7": save_node
+        })
+    }
+}
+
+impl ImageProvider for ComfyUi {
+    fn name(&self) -> &'static str {
+        "comfyui"
+    }
+
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        request.validate()?;
+        let base = self.base_url.trim_end_matches('/');
+        // ComfyUI has no "random" seed value, so pick one here and report it
+        let seed = request.seed.unwrap_or_else(|| OsRng.next_u64());
+        let queued = transport
+            .send(HttpRequest::post_json(&format!("{}/prompt", base), &json!({ "prompt": self.workflow(request, seed) })))?
+            .error_for_status()?
+            .json()?;
+        let prompt_id = queued["prompt_id"]
+            .as_str()
+            .ok_or_else(|| format!("ComfyUI did not queue the prompt: {}", queued))?
+            .to_string();
+
+        let mut outputs = None;
+        for _ in 0..self.max_polls {
+            let history = transport
+                .send(HttpRequest::get(&format!("{}/history/{}", base, prompt_id)))?
+                .error_for_status()?
+                .json()?;
+            if let Some(entry) = history.get(&prompt_id) {
+                outputs = Some(entry["outputs"].clone());
+                break;
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+        let outputs = outputs.ok_or_else(|| "Timed out waiting for ComfyUI".to_string())?;
+
+        let files = outputs["7"]["images"]
+            .as_array()
+            .ok_or_else(|| "No image data in response".to_string())?;
+        files
+            .iter()
+            .map(|file| {
+                let url = format!(
+                    "{}/view?filename={}&subfolder={}&type={}",
+                    base,
+                    encode_query(file["filename"].as_str().unwrap_or_default()),
+                    encode_query(file["subfolder"].as_str().unwrap_or_default()),
+                    encode_query(file["type"].as_str().unwrap_or("output")),
+                );
+                let image = transport.send(HttpRequest::get(&url))?.error_for_status()?;
+                Ok(GeneratedImage { data: image.body, format: request.format, seed: Some(seed) })
+            })
+            .collect()
+    }
+}
+
+/// OpenAI Images API (`gpt-image-1`, `dall-e-3`, ...)
+#[derive(Clone)]
+pub struct OpenAiImages {
+    pub api_key: String,
+    pub model: String,
+    pub base_url: String,
+}
+
+impl fmt::Debug for OpenAiImages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenAiImages")
+            .field("api_key", &redacted(&self.api_key))
+            .field("model", &self.model)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+impl OpenAiImages {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            model: "gpt-image-1".to_string(),
+            base_url: "https://api.openai.com".to_string(),
+        }
+    }
+
+    fn build_request(&self, request: &ImageRequest) -> HttpRequest {
+        // The Images API has no negative prompt; fold it into the prompt text
+        let prompt = match &request.negative_prompt {
+            Some(negative) => format!("{}\nAvoid: {}", request.prompt, negative),
+            None => request.prompt.clone(),
+        };
+        let mut body = json!({
+            "model": self.model,
+            "prompt": prompt,
+            "n": request.count,
+            "size": format!("{}x{}", request.width, request.height),
+        });
+        if self.model.starts_with("dall-e") {
+            body["response_format"] = json!("b64_json");
+        } else {
+            body["output_format"] = json!(request.format.as_str());
+        }
+        let url = format!("{}/v1/images/generations", self.base_url.trim_end_matches('/'));
+        HttpRequest::post_json(&url, &body).with_header("Authorization", &format!("Bearer {}", self.api_key))
+    }
+}
+
+impl ImageProvider for OpenAiImages {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn generate(&self, transport: &dyn Transport, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        request.validate()?;
+        if self.model.starts_with("dall-e") && request.format != ImageFormat::Png {
+            return Err("DALL-E models only return PNG images".to_string());
+        }
+        let json = transport.send(self.build_request(request))?.error_for_status()?.json()?;
+        let data = json["data"]
+            .as_array()
+            .ok_or_else(|| "No image data in response".to_string())?;
+        data.iter()
+            .map(|item| {
+                let b64 = item["b64_json"]
+                    .as_str()
+                    .ok_or_else(|| "No image data in response".to_string())?;
+                Ok(GeneratedImage { data: decode_base64(b64)?, format: request.format, seed: None })
+            })
+            .collect()
+    }
+}
+
+/// Encode text fields as `multipart/form-data`, returning (content type, body)
+fn multipart_form(fields: &[(&str, String)]) -> (String, Vec<u8>) {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let boundary = format!("----privacy-http-{:x}", nanos);
+    let mut body = String::new();
+    for (name, value) in fields {
+        body.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary, name, value
+        ));
+    }
+    body.push_str(&format!("--{}--\r\n", boundary));
+    (format!("multipart/form-data; boundary={}", boundary), body.into_bytes())
+}
+
+/// Percent-encode a query parameter value
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Records requests and replays canned responses in order
+    struct MockTransport {
+        requests: RefCell<Vec<HttpRequest>>,
+        responses: RefCell<Vec<HttpResponse>>,
+    }
+
+    impl MockTransport {
+        fn new(bodies: Vec<Vec<u8>>) -> Self {
+            let responses = bodies
+                .into_iter()
+                .rev()
+                .map(|body| HttpResponse { status: 200, headers: Vec::new(), body })
+                .collect();
+            Self { requests: RefCell::new(Vec::new()), responses: RefCell::new(responses) }
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+            self.requests.borrow_mut().push(request);
+            self.responses.borrow_mut().pop().ok_or_else(|| "no response".to_string())
+        }
+    }
+
+    fn b64(data: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_stability_v1_request_and_response() {
+        let body = json!({ "artifacts": [{ "base64": b64(b"png1"), "seed": 42 }] }).to_string();
+        let transport = MockTransport::new(vec![body.into_bytes()]);
+        let request = ImageRequest {
+            negative_prompt: Some("blurry".to_string()),
+            seed: Some(42),
+            ..ImageRequest::new("a cat", 512, 512, 20)
+        };
+        let images = StabilityV1::new("key").generate(&transport, &request).unwrap();
+        assert_eq!(images, vec![GeneratedImage { data: b"png1".to_vec(), format: ImageFormat::Png, seed: Some(42) }]);
+
+        let sent = &transport.requests.borrow()[0];
+        assert!(sent.url.ends_with("/v1/generation/stable-diffusion-xl-beta-v2-2-2/text-to-image"));
+        let sent_body: Value = serde_json::from_slice(&sent.body).unwrap();
+        assert_eq!(sent_body["text_prompts"][1]["weight"], -1.0);
+        assert_eq!(sent_body["seed"], 42);
+    }
+
+    #[test]
+    fn test_stability_v2_multipart_per_image() {
+        let body = json!({ "image": b64(b"webp"), "seed": 7 }).to_string().into_bytes();
+        let transport = MockTransport::new(vec![body.clone(), body]);
+        let request = ImageRequest { count: 2, format: ImageFormat::Webp, ..ImageRequest::new("a dog", 1344, 768, 30) };
+        let images = StabilityV2::new("key").generate(&transport, &request).unwrap();
+        assert_eq!(images.len(), 2);
+        let sent = transport.requests.borrow();
+        assert!(sent[0].header("Content-Type").unwrap().starts_with("multipart/form-data; boundary="));
+        let form = String::from_utf8(sent[0].body.clone()).unwrap();
+        assert!(form.contains("name=\"aspect_ratio\"\r\n\r\n16:9"));
+        assert!(form.contains("name=\"output_format\"\r\n\r\nwebp"));
+    }
+
+    #[test]
+    fn test_automatic1111_batch() {
+        let body = json!({
+            "images": [b64(b"one"), format!("data:image/png;base64,{}", b64(b"two"))],
+            "info": json!({ "all_seeds": [1, 2] }).to_string()
+        });
+        let transport = MockTransport::new(vec![body.to_string().into_bytes()]);
+        let request = ImageRequest { count: 2, sampler: Some("DPM++ 2M".to_string()), ..ImageRequest::new("x", 512, 512, 10) };
+        let images = Automatic1111::new(Automatic1111::DEFAULT_URL).generate(&transport, &request).unwrap();
+        assert_eq!(images[1].data, b"two".to_vec());
+        assert_eq!(images[1].seed, Some(2));
+        let sent: Value = serde_json::from_slice(&transport.requests.borrow()[0].body).unwrap();
+        assert_eq!(sent["sampler_name"], "DPM++ 2M");
+        assert_eq!(sent["seed"], -1);
+    }
+
+    #[test]
+    fn test_comfyui_queue_poll_view() {
+        let transport = MockTransport::new(vec![
+            json!({ "prompt_id": "abc" }).to_string().into_bytes(),
+            json!({}).to_string().into_bytes(),
+            json!({ "abc": { "outputs": { "7": { "images": [{ "filename": "a b.png", "subfolder": "", "type": "output" }] } } } })
+                .to_string()
+                .into_bytes(),
+            b"pngbytes".to_vec(),
+        ]);
+        let provider = ComfyUi { poll_interval: Duration::from_millis(1), ..ComfyUi::new(ComfyUi::DEFAULT_URL) };
+        let images = provider.generate(&transport, &ImageRequest::new("x", 512, 512, 10)).unwrap();
+        assert_eq!(images[0].data, b"pngbytes".to_vec());
+        assert!(transport.requests.borrow()[3].url.contains("filename=a%20b.png"));
+
+        // Without a requested seed, a random one is sent and reported back
+        let sent: Value = serde_json::from_slice(&transport.requests.borrow()[0].body).unwrap();
+        let seed = sent["prompt"]["5"]["inputs"]["seed"].as_u64().unwrap();
+        assert_eq!(images[0].seed, Some(seed));
+    }
+
+    #[test]
+    fn test_openai_images() {
+        let body = json!({ "data": [{ "b64_json": b64(b"img") }] }).to_string();
+        let transport = MockTransport::new(vec![body.into_bytes()]);
+        let images = OpenAiImages::new("key").generate(&transport, &ImageRequest::new("x", 1024, 1024, 0)).unwrap();
+        assert_eq!(images[0].data, b"img".to_vec());
+        let sent: Value = serde_json::from_slice(&transport.requests.borrow()[0].body).unwrap();
+        assert_eq!(sent["size"], "1024x1024");
+        assert_eq!(sent["output_format"], "png");
+    }
+
+    #[test]
+    fn test_provider_config_from_json() {
+        let provider = ImageProviderConfig::from_json(r#"{"provider": "automatic1111"}"#).unwrap().build();
+        assert_eq!(provider.name(), "automatic1111");
+        assert!(ImageProviderConfig::from_json(r#"{"provider": "unknown"}"#).is_err());
+        assert!(format!("{:?}", StabilityV1::new("sk-secret-1234")).contains("***1234"));
+    }
+
+    #[test]
+    fn test_redacted_key() {
+        assert_eq!(redacted("sk-abcdef"), "***cdef");
+        assert_eq!(redacted("ab"), "***ab");
+        assert_eq!(redacted("clé-secrète"), "***rète");
+    }
+}
@@ -0,0 +1,561 @@
+//! Interceptor Module
+//!
+//! An ordered chain of request/response hooks run by `HttpClient` around every request.
+//! The built-in privacy features are interceptors themselves, so user steps can be
+//! placed before them (their headers are still filtered) or after them (they see the
+//! final request, e.g. for signing).
+//!
+//! `on_request` hooks run in chain order and may short-circuit with a response;
+//! `on_response` / `on_error` hooks then run in reverse order for every interceptor
+//! whose `on_request` ran, so each step sees the response to the request it produced.
+//! Hooks that need to await (e.g. JavaScript Promises) override the `*_async` forms,
+//! which the chain awaits when it sends.
+//!
+//! # Features
+//! - [`Interceptor`] trait with `on_request`, `on_response` and `on_error` hooks
+//! - Built-ins: privacy policy (tracker blocking, header rules), PII scrubbing, cookie
+//!   jar, response cache and metadata logging
+//! - Opt-in: Nostr request signing and payload obfuscation
+
+use crate::cache::{CacheLookup, HttpCache};
+use crate::codec::PayloadCodec;
+use crate::cookies::CookieJar;
+use crate::did_nostr::{DID_HEADER, NONCE_HEADER, NostrSigner, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::pii::{PiiScrubber, PiiVault};
+use crate::privacy::{LoggingMode, PrivacyPolicy};
+use crate::transport::{HttpRequest, HttpResponse};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+/// Header naming the obfuscation codec of a request or response body
+pub const PAYLOAD_CODEC_HEADER: &str = "X-Payload-Codec";
+
+/// Future returned by the asynchronous hook forms
+pub type HookFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// Request/response hook
+pub trait Interceptor: Send + Sync {
+    /// Name shown in chain listings
+    fn name(&self) -> &str;
+
+    /// Inspect or rewrite an outgoing request. Returning a response short-circuits the
+    /// chain (later interceptors and the network are skipped); an error aborts it.
+    fn on_request(&self, _request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        Ok(None)
+    }
+
+    /// Inspect or rewrite a response to the request as this interceptor left it
+    fn on_response(&self, _request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        Ok(response)
+    }
+
+    /// Handle a failure further down the chain; returning a response recovers from it
+    fn on_error(&self, _request: &HttpRequest, error: String) -> Result<HttpResponse, String> {
+        Err(error)
+    }
+
+    /// Awaited form of `on_request` used when the chain sends; defaults to `on_request`
+    fn on_request_async<'a>(&'a self, request: &'a mut HttpRequest) -> HookFuture<'a, Option<HttpResponse>> {
+        Box::pin(std::future::ready(self.on_request(request)))
+    }
+
+    /// Awaited form of `on_response` used when the chain sends; defaults to `on_response`
+    fn on_response_async<'a>(&'a self, request: &'a HttpRequest, response: HttpResponse) -> HookFuture<'a, HttpResponse> {
+        Box::pin(std::future::ready(self.on_response(request, response)))
+    }
+}
+
+impl fmt::Debug for dyn Interceptor + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Interceptor({})", self.name())
+    }
+}
+
+/// Where a user interceptor runs relative to the built-in privacy steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterceptorStage {
+    /// Before the built-ins: sees the caller's request, and its headers are filtered
+    Outer,
+    /// After the built-ins: sees the final request as sent (e.g. signing), and runs
+    /// again for every retry
+    Inner,
+}
+
+impl InterceptorStage {
+    pub fn parse(stage: &str) -> Result<Self, String> {
+        match stage {
+            "outer" => Ok(Self::Outer),
+            "inner" => Ok(Self::Inner),
+            other => Err(format!("Unknown interceptor stage: {} (expected outer or inner)", other)),
+        }
+    }
+}
+
+/// Ordered interceptors for one request
+#[derive(Debug, Default)]
+pub struct InterceptorChain<'a> {
+    steps: Vec<&'a dyn Interceptor>,
+}
+
+impl<'a> InterceptorChain<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an interceptor
+    pub fn push(&mut self, interceptor: &'a dyn Interceptor) {
+        self.steps.push(interceptor);
+    }
+
+    /// Interceptor names in order
+    pub fn names(&self) -> Vec<String> {
+        self.steps.iter().map(|step| step.name().to_string()).collect()
+    }
+
+    /// Run only the request hooks, ignoring short-circuits (for dry runs)
+    pub fn prepare(&self, mut request: HttpRequest) -> Result<HttpRequest, String> {
+        for step in &self.steps {
+            step.on_request(&mut request)?;
+        }
+        Ok(request)
+    }
+
+    /// Run the chain around `send`, returning the final request and its outcome
+    pub async fn run<F, Fut>(&self, mut request: HttpRequest, send: F) -> (HttpRequest, Result<HttpResponse, String>)
+    where
+        F: FnOnce(HttpRequest) -> Fut,
+        Fut: Future<Output = Result<HttpResponse, String>>,
+    {
+        let mut unwind = self.steps.len();
+        let mut early = None;
+        for (index, step) in self.steps.iter().enumerate() {
+            let outcome = match step.on_request_async(&mut request).await {
+                Ok(None) => continue,
+                Ok(Some(response)) => Ok(response),
+                Err(e) => Err(e),
+            };
+            // A short-circuiting or failing step does not see its own outcome
+            unwind = index;
+            early = Some(outcome);
+            break;
+        }
+        let mut result = match early {
+            Some(outcome) => outcome,
+            None => send(request.clone()).await,
+        };
+        for step in self.steps[..unwind].iter().rev() {
+            result = match result {
+                Ok(response) => step.on_response_async(&request, response).await,
+                Err(error) => step.on_error(&request, error),
+            };
+        }
+        (request, result)
+    }
+}
+
+impl Interceptor for PrivacyPolicy {
+    fn name(&self) -> &str {
+        "privacy"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        if self.blocks_url(&request.url) {
+            return Err(format!("Blocked tracker: {}", request.url));
+        }
+        self.apply(request);
+        Ok(None)
+    }
+}
+
+/// PII step: replaces personal data in outgoing bodies with placeholders from the
+/// client's vault and restores them in the response, so originals never leave the process
+#[derive(Debug)]
+pub struct PiiInterceptor<'a> {
+    scrubber: &'a PiiScrubber,
+    vault: &'a Mutex<PiiVault>,
+}
+
+impl<'a> PiiInterceptor<'a> {
+    pub fn new(scrubber: &'a PiiScrubber, vault: &'a Mutex<PiiVault>) -> Self {
+        Self { scrubber, vault }
+    }
+}
+
+impl Interceptor for PiiInterceptor<'_> {
+    fn name(&self) -> &str {
+        "pii"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        if let Some(body) = self.scrubber.scrub_body(&request.body, &mut self.vault.lock().unwrap()) {
+            request.body = body;
+        }
+        Ok(None)
+    }
+
+    fn on_response(&self, _request: &HttpRequest, mut response: HttpResponse) -> Result<HttpResponse, String> {
+        if let Ok(text) = std::str::from_utf8(&response.body) {
+            let rehydrated = self.vault.lock().unwrap().rehydrate(text);
+            if rehydrated != text {
+                response.body = rehydrated.into_bytes();
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Cookie jar step for one top-level site
+#[derive(Debug)]
+pub struct CookieInterceptor<'a> {
+    jar: &'a CookieJar,
+    top_level_site: Option<&'a str>,
+}
+
+impl<'a> CookieInterceptor<'a> {
+    pub fn new(jar: &'a CookieJar, top_level_site: Option<&'a str>) -> Self {
+        Self { jar, top_level_site }
+    }
+}
+
+impl Interceptor for CookieInterceptor<'_> {
+    fn name(&self) -> &str {
+        "cookies"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        self.jar.attach(request, self.top_level_site);
+        Ok(None)
+    }
+
+    fn on_response(&self, request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        // Like transcripts, failing to persist cookies never fails the request
+        let _ = self.jar.store(&request.url, self.top_level_site, &response);
+        Ok(response)
+    }
+}
+
+/// Response cache step: fresh entries short-circuit, stale ones are revalidated
+#[derive(Debug)]
+pub struct CacheInterceptor<'a> {
+    cache: &'a HttpCache,
+    top_level_site: Option<&'a str>,
+}
+
+impl<'a> CacheInterceptor<'a> {
+    pub fn new(cache: &'a HttpCache, top_level_site: Option<&'a str>) -> Self {
+        Self { cache, top_level_site }
+    }
+}
+
+impl Interceptor for CacheInterceptor<'_> {
+    fn name(&self) -> &str {
+        "cache"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        match self.cache.lookup(request, self.top_level_site) {
+            CacheLookup::Fresh(response) => Ok(Some(response)),
+            CacheLookup::Stale | CacheLookup::Miss => Ok(None),
+        }
+    }
+
+    fn on_response(&self, request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        Ok(self.cache.complete(request, response))
+    }
+}
+
+/// Logging step honouring the policy's logging mode. `metadata` logs method, host and
+/// status; `redacted` adds the path (without query) and header names, never values.
+#[derive(Debug, Clone, Copy)]
+pub struct LoggingInterceptor {
+    mode: LoggingMode,
+}
+
+impl LoggingInterceptor {
+    pub fn new(mode: LoggingMode) -> Self {
+        Self { mode }
+    }
+
+    /// Log line for a request outcome; `None` when logging is off
+    pub fn line(&self, request: &HttpRequest, outcome: Result<u16, &str>) -> Option<String> {
+        let url = reqwest::Url::parse(&request.url).ok();
+        let host = url.as_ref().and_then(|u| u.host_str()).unwrap_or("-");
+        let outcome = match outcome {
+            Ok(status) => status.to_string(),
+            Err(_) => "error".to_string(),
+        };
+        match self.mode {
+            LoggingMode::None => None,
+            LoggingMode::Metadata => Some(format!("{} {} {}", request.method, host, outcome)),
+            LoggingMode::Redacted => {
+                let path = url.as_ref().map(|u| u.path()).unwrap_or("-");
+                let names: Vec<&str> = request.headers.iter().map(|(k, _)| k.as_str()).collect();
+                Some(format!("{} {}{} {} [{}]", request.method, host, path, outcome, names.join(", ")))
+            }
+        }
+    }
+}
+
+impl Interceptor for LoggingInterceptor {
+    fn name(&self) -> &str {
+        "logging"
+    }
+
+    fn on_response(&self, request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        if let Some(line) = self.line(request, Ok(response.status)) {
+            eprintln!("{}", line);
+        }
+        Ok(response)
+    }
+
+    fn on_error(&self, request: &HttpRequest, error: String) -> Result<HttpResponse, String> {
+        if let Some(line) = self.line(request, Err(&error)) {
+            eprintln!("{}", line);
+        }
+        Err(error)
+    }
+}
+
+/// Signs requests with `X-DID`, `X-Timestamp`, `X-Nonce` and `X-Signature` (see
+/// [`NostrSigner::sign_request`]). Add it at the inner stage so the signature covers
+/// the final request.
+#[derive(Debug, Clone)]
+pub struct SigningInterceptor {
+    signer: NostrSigner,
+}
+
+impl SigningInterceptor {
+    pub fn new(signer: NostrSigner) -> Self {
+        Self { signer }
+    }
+}
+
+impl Interceptor for SigningInterceptor {
+    fn name(&self) -> &str {
+        "nostr-signing"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        let url = reqwest::Url::parse(&request.url).map_err(|e| format!("Invalid URL {}: {}", request.url, e))?;
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        request.headers.retain(|(k, _)| {
+            ![DID_HEADER, TIMESTAMP_HEADER, NONCE_HEADER, SIGNATURE_HEADER].iter().any(|h| k.eq_ignore_ascii_case(h))
+        });
+        let headers = self.signer.sign_request(&request.method, &path, &request.body);
+        request.headers.extend(headers);
+        Ok(None)
+    }
+}
+
+/// Encodes request bodies with the policy's obfuscation codec and decodes responses
+/// that carry [`PAYLOAD_CODEC_HEADER`]. Both ends must understand the codec, so this is
+/// opt-in rather than enabled by `obfuscation.enabled` alone.
+#[derive(Debug, Clone)]
+pub struct ObfuscationInterceptor {
+    codec: PayloadCodec,
+}
+
+impl ObfuscationInterceptor {
+    pub fn new(codec: PayloadCodec) -> Self {
+        Self { codec }
+    }
+
+    /// Interceptor for the policy's codec, when obfuscation is enabled
+    pub fn from_policy(policy: &PrivacyPolicy) -> Result<Option<Self>, String> {
+        Ok(policy.codec()?.map(Self::new))
+    }
+
+    fn method_name(&self) -> String {
+        serde_json::to_value(self.codec.method())
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+impl Interceptor for ObfuscationInterceptor {
+    fn name(&self) -> &str {
+        "obfuscation"
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        if !request.body.is_empty() {
+            request.body = self.codec.encode(&request.body)?;
+            request.headers.push((PAYLOAD_CODEC_HEADER.to_string(), self.method_name()));
+        }
+        Ok(None)
+    }
+
+    fn on_response(&self, _request: &HttpRequest, mut response: HttpResponse) -> Result<HttpResponse, String> {
+        if response.header(PAYLOAD_CODEC_HEADER).is_some_and(|m| m.eq_ignore_ascii_case(&self.method_name())) {
+            response.body = self.codec.decode(&response.body)?;
+            response.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(PAYLOAD_CODEC_HEADER));
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::PrivacyConfig;
+    use std::sync::Mutex;
+
+    /// Records hook calls into a shared trace
+    struct Probe {
+        name: &'static str,
+        trace: &'static Mutex<Vec<String>>,
+        short_circuit: bool,
+    }
+
+    impl Interceptor for Probe {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+            self.trace.lock().unwrap().push(format!("{}:request", self.name));
+            request.headers.push(("X-Probe".to_string(), self.name.to_string()));
+            Ok(self.short_circuit.then(|| HttpResponse { status: 299, headers: Vec::new(), body: Vec::new() }))
+        }
+
+        fn on_response(&self, _request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+            self.trace.lock().unwrap().push(format!("{}:response", self.name));
+            Ok(response)
+        }
+
+        fn on_error(&self, _request: &HttpRequest, error: String) -> Result<HttpResponse, String> {
+            self.trace.lock().unwrap().push(format!("{}:error", self.name));
+            Err(error)
+        }
+    }
+
+    fn ok(status: u16) -> Result<HttpResponse, String> {
+        Ok(HttpResponse { status, headers: Vec::new(), body: Vec::new() })
+    }
+
+    #[tokio::test]
+    async fn test_chain_order_and_short_circuit() {
+        static TRACE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        let (a, b) = (Probe { name: "a", trace: &TRACE, short_circuit: false }, Probe { name: "b", trace: &TRACE, short_circuit: false });
+        let policy = PrivacyPolicy::new(PrivacyConfig::from_json(r#"{"filter_headers": {"mode": "blacklist", "headers": ["X-Probe"]}}"#).unwrap());
+        let mut chain = InterceptorChain::new();
+        chain.push(&a);
+        chain.push(&policy);
+        chain.push(&b);
+        assert_eq!(chain.names(), vec!["a", "privacy", "b"]);
+
+        let (sent, result) = chain.run(HttpRequest::get("https://example.com/"), |_| async { ok(200) }).await;
+        assert_eq!(result.unwrap().status, 200);
+        // The policy filtered the outer probe's header, not the inner one's
+        assert_eq!(sent.headers, vec![("X-Probe".to_string(), "b".to_string())]);
+        assert_eq!(*TRACE.lock().unwrap(), vec!["a:request", "b:request", "b:response", "a:response"]);
+
+        TRACE.lock().unwrap().clear();
+        let blocked = HttpRequest::get("https://www.google-analytics.com/collect");
+        let (_, result) = chain.run(blocked, |_| async { ok(200) }).await;
+        assert!(result.unwrap_err().starts_with("Blocked tracker"));
+        assert_eq!(*TRACE.lock().unwrap(), vec!["a:request", "a:error"]);
+
+        TRACE.lock().unwrap().clear();
+        let cached = Probe { name: "cached", trace: &TRACE, short_circuit: true };
+        let mut chain = InterceptorChain::new();
+        chain.push(&a);
+        chain.push(&cached);
+        chain.push(&b);
+        let (_, result) = chain.run(HttpRequest::get("https://example.com/"), |_| async { ok(200) }).await;
+        assert_eq!(result.unwrap().status, 299);
+        assert_eq!(*TRACE.lock().unwrap(), vec!["a:request", "cached:request", "a:response"]);
+    }
+
+    /// Hook that only finishes after yielding to the runtime
+    struct Deferred;
+
+    impl Interceptor for Deferred {
+        fn name(&self) -> &str {
+            "deferred"
+        }
+
+        fn on_request(&self, _request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+            Err("deferred hooks must be awaited".to_string())
+        }
+
+        fn on_request_async<'a>(&'a self, request: &'a mut HttpRequest) -> HookFuture<'a, Option<HttpResponse>> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                request.headers.push(("X-Deferred".to_string(), "1".to_string()));
+                Ok(None)
+            })
+        }
+
+        fn on_response_async<'a>(&'a self, _request: &'a HttpRequest, mut response: HttpResponse) -> HookFuture<'a, HttpResponse> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                response.status += 1;
+                Ok(response)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_hooks_are_awaited() {
+        let deferred = Deferred;
+        let mut chain = InterceptorChain::new();
+        chain.push(&deferred);
+        let (sent, result) = chain.run(HttpRequest::get("https://example.com/"), |_| async { ok(200) }).await;
+        assert_eq!(sent.header("X-Deferred"), Some("1"));
+        assert_eq!(result.unwrap().status, 201);
+        // Dry runs only use the synchronous forms
+        assert!(chain.prepare(HttpRequest::get("https://example.com/")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_signing_and_obfuscation() {
+        let signer = NostrSigner::generate();
+        let (signing, obfuscation) = (SigningInterceptor::new(signer.clone()), ObfuscationInterceptor::new(PayloadCodec::Base64));
+        let mut chain = InterceptorChain::new();
+        chain.push(&obfuscation);
+        chain.push(&signing);
+
+        let request = HttpRequest::post("https://example.com/rpc?v=1", "hello");
+        let (sent, result) = chain
+            .run(request, |sent| async move {
+                assert_eq!(sent.body, b"aGVsbG8=");
+                let verified = crate::did_nostr::NostrVerifier::verify_request("POST", "/rpc?v=1", &sent.headers, b"aGVsbG8=");
+                assert!(verified.valid);
+                Ok(HttpResponse { status: 200, headers: vec![(PAYLOAD_CODEC_HEADER.to_string(), "base64".to_string())], body: b"d29ybGQ=".to_vec() })
+            })
+            .await;
+        assert_eq!(sent.header(PAYLOAD_CODEC_HEADER), Some("base64"));
+        assert_eq!(result.unwrap().body, b"world");
+
+        let logging = LoggingInterceptor::new(LoggingMode::Redacted);
+        let line = logging.line(&sent, Ok(200)).unwrap();
+        assert!(line.starts_with("POST example.com/rpc 200 ["));
+        assert!(!line.contains("v=1") && !line.contains(&signer.did().to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_pii_scrubbing_and_rehydration() {
+        let scrubber = PiiScrubber::new(&crate::pii::PiiConfig::default()).unwrap();
+        let vault = Mutex::new(PiiVault::new());
+        let pii = PiiInterceptor::new(&scrubber, &vault);
+        let mut chain = InterceptorChain::new();
+        chain.push(&pii);
+
+        let request = HttpRequest::post_json("https://api.example/v1", &serde_json::json!({"prompt": "email dave@example.com"}));
+        let (sent, result) = chain
+            .run(request, |sent| async move {
+                assert!(!String::from_utf8_lossy(&sent.body).contains("dave@example.com"));
+                Ok(HttpResponse { status: 200, headers: Vec::new(), body: b"Sent to [[EMAIL_1]]".to_vec() })
+            })
+            .await;
+        assert_eq!(sent.body, br#"{"prompt":"email [[EMAIL_1]]"}"#);
+        assert_eq!(result.unwrap().body, b"Sent to dave@example.com");
+    }
+}
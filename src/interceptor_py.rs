@@ -0,0 +1,208 @@
+//! Python PyO3 bindings for callback interceptors
+//!
+//! Python callables registered on `HttpClientPy` run as request/response hooks. Each
+//! hook receives a mutable view; it may edit it in place, call `block(reason)` (or
+//! return `False`) to stop the request, or `respond(...)` to answer it without sending.
+//! Exceptions raised by a hook fail the request with the exception message.
+//!
+//! Hooks re-acquire the GIL themselves, so the client releases it while requests are
+//! in flight.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBool;
+use crate::interceptor::Interceptor;
+use crate::transport::{HttpRequest, HttpResponse};
+
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+}
+
+fn set_header_value(headers: &mut Vec<(String, String)>, name: &str, value: &str) {
+    headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+    headers.push((name.to_string(), value.to_string()));
+}
+
+/// Mutable view of an outgoing request
+#[pyclass(name = "RequestView", module = "privacy_http_sdk.http")]
+pub struct PyRequestView {
+    #[pyo3(get, set)]
+    method: String,
+    #[pyo3(get, set)]
+    url: String,
+    #[pyo3(get, set)]
+    headers: Vec<(String, String)>,
+    #[pyo3(get, set)]
+    body: Vec<u8>,
+    blocked: Option<String>,
+    response: Option<HttpResponse>,
+}
+
+#[pymethods]
+impl PyRequestView {
+    /// First value of a header (case-insensitive)
+    fn header(&self, name: &str) -> Option<String> {
+        header_value(&self.headers, name).map(str::to_string)
+    }
+
+    /// Replace a header
+    fn set_header(&mut self, name: &str, value: &str) {
+        set_header_value(&mut self.headers, name, value);
+    }
+
+    /// Remove a header
+    fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+    }
+
+    /// Stop the request; the client raises an error with this reason
+    fn block(&mut self, reason: String) {
+        self.blocked = Some(reason);
+    }
+
+    /// Answer the request without sending it
+    #[pyo3(signature = (status, body=Vec::new(), headers=Vec::new()))]
+    fn respond(&mut self, status: u16, body: Vec<u8>, headers: Vec<(String, String)>) {
+        self.response = Some(HttpResponse { status, headers, body });
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RequestView({} {})", self.method, self.url)
+    }
+}
+
+/// Mutable view of a response, with the request it answers
+#[pyclass(name = "ResponseView", module = "privacy_http_sdk.http")]
+pub struct PyResponseView {
+    #[pyo3(get)]
+    method: String,
+    #[pyo3(get)]
+    url: String,
+    #[pyo3(get)]
+    request_headers: Vec<(String, String)>,
+    #[pyo3(get, set)]
+    status: u16,
+    #[pyo3(get, set)]
+    headers: Vec<(String, String)>,
+    #[pyo3(get, set)]
+    body: Vec<u8>,
+    blocked: Option<String>,
+}
+
+#[pymethods]
+impl PyResponseView {
+    /// First value of a response header (case-insensitive)
+    fn header(&self, name: &str) -> Option<String> {
+        header_value(&self.headers, name).map(str::to_string)
+    }
+
+    /// Replace a response header
+    fn set_header(&mut self, name: &str, value: &str) {
+        set_header_value(&mut self.headers, name, value);
+    }
+
+    /// Remove a response header
+    fn remove_header(&mut self, name: &str) {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+    }
+
+    /// Body decoded as UTF-8 (lossy)
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Reject the response; the client raises an error with this reason
+    fn block(&mut self, reason: String) {
+        self.blocked = Some(reason);
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ResponseView({} {} -> {})", self.method, self.url, self.status)
+    }
+}
+
+/// Interceptor calling Python hooks
+pub struct PyCallbackInterceptor {
+    name: String,
+    on_request: Option<Py<PyAny>>,
+    on_response: Option<Py<PyAny>>,
+}
+
+impl PyCallbackInterceptor {
+    pub fn new(name: String, on_request: Option<Py<PyAny>>, on_response: Option<Py<PyAny>>) -> Self {
+        Self { name, on_request, on_response }
+    }
+
+    /// Call a hook; `Ok(false)` when it returned `False`
+    fn call(&self, py: Python<'_>, hook: &Py<PyAny>, view: Py<PyAny>) -> Result<bool, String> {
+        let result = hook.call1(py, (view,)).map_err(|e| format!("Interceptor {} failed: {}", self.name, e))?;
+        let result = result.bind(py);
+        Ok(!result.cast::<PyBool>().is_ok_and(|b| !b.is_true()))
+    }
+}
+
+impl Interceptor for PyCallbackInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        let Some(hook) = &self.on_request else {
+            return Ok(None);
+        };
+        Python::attach(|py| {
+            let view = PyRequestView {
+                method: request.method.clone(),
+                url: request.url.clone(),
+                headers: request.headers.clone(),
+                body: request.body.clone(),
+                blocked: None,
+                response: None,
+            };
+            let view = Py::new(py, view).map_err(|e| e.to_string())?;
+            let proceed = self.call(py, hook, view.clone_ref(py).into_any())?;
+            let mut view = view.borrow_mut(py);
+            if let Some(reason) = view.blocked.take() {
+                return Err(format!("Blocked by {}: {}", self.name, reason));
+            }
+            if !proceed {
+                return Err(format!("Blocked by {}", self.name));
+            }
+            request.method = std::mem::take(&mut view.method).to_uppercase();
+            request.url = std::mem::take(&mut view.url);
+            request.headers = std::mem::take(&mut view.headers);
+            request.body = std::mem::take(&mut view.body);
+            Ok(view.response.take())
+        })
+    }
+
+    fn on_response(&self, request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        let Some(hook) = &self.on_response else {
+            return Ok(response);
+        };
+        Python::attach(|py| {
+            let view = PyResponseView {
+                method: request.method.clone(),
+                url: request.url.clone(),
+                request_headers: request.headers.clone(),
+                status: response.status,
+                headers: response.headers,
+                body: response.body,
+                blocked: None,
+            };
+            let view = Py::new(py, view).map_err(|e| e.to_string())?;
+            let proceed = self.call(py, hook, view.clone_ref(py).into_any())?;
+            let mut view = view.borrow_mut(py);
+            if let Some(reason) = view.blocked.take() {
+                return Err(format!("Blocked by {}: {}", self.name, reason));
+            }
+            if !proceed {
+                return Err(format!("Blocked by {}", self.name));
+            }
+            Ok(HttpResponse {
+                status: view.status,
+                headers: std::mem::take(&mut view.headers),
+                body: std::mem::take(&mut view.body),
+            })
+        })
+    }
+}
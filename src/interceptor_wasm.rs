@@ -0,0 +1,373 @@
+//! WASM bindings for callback interceptors via wasm-bindgen
+//!
+//! JavaScript functions registered on `HttpClient` run as request/response hooks. Each
+//! hook receives a plain object view (`method`, `url`, `headers` as `[name, value]`
+//! pairs, `body` as a string or `Uint8Array`) that it may edit in place or replace by
+//! returning a new object. Setting `blocked = "reason"` or returning `false` stops the
+//! request; a request hook may set `response = {status, headers, body}` to answer it
+//! without sending. A thrown error (or rejected Promise) fails the request with its
+//! message, and so does a view whose `headers` are not `[name, value]` string pairs.
+//!
+//! Hooks may be `async` (return a Promise): the async client methods (`get_async`,
+//! `post_async`) await them. The `*_sync` methods cannot wait for the JavaScript event
+//! loop, so there a Promise fails the request with an explicit error instead of being
+//! mistaken for a replacement view.
+
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use crate::interceptor::{HookFuture, Interceptor};
+use crate::transport::{HttpRequest, HttpResponse};
+
+/// Interceptor calling JavaScript hooks
+pub struct JsCallbackInterceptor {
+    name: String,
+    on_request: Option<Function>,
+    on_response: Option<Function>,
+}
+
+// SAFETY: `Function` wraps a handle into the JS heap, which is only valid on the thread
+// that created it. An interceptor is created by `HttpClient::register_interceptor` and
+// stored in that client; wasm-bindgen exports are `!Send` handles, so the client (and
+// with it the interceptor) is only ever used from the JS thread that owns it. On wasm32
+// without threads there is no other thread at all. The bounds exist because
+// `Interceptor` requires them for the native bindings, where this type is never built.
+unsafe impl Send for JsCallbackInterceptor {}
+unsafe impl Sync for JsCallbackInterceptor {}
+
+/// Future holding JS values across an await
+struct JsThreadFuture<F>(F);
+
+// SAFETY: as for `JsCallbackInterceptor`, the future is created and polled on the JS
+// thread owning its values; `HookFuture` only requires `Send` for native interceptors.
+unsafe impl<F> Send for JsThreadFuture<F> {}
+
+impl<F: Future> Future for JsThreadFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: the inner future is structurally pinned and never moved out
+        unsafe { self.map_unchecked_mut(|f| &mut f.0) }.poll(cx)
+    }
+}
+
+fn is_promise(value: &JsValue) -> bool {
+    get(value, "then").is_function()
+}
+
+impl JsCallbackInterceptor {
+    pub fn new(name: String, on_request: Option<Function>, on_response: Option<Function>) -> Self {
+        Self { name, on_request, on_response }
+    }
+
+    fn invoke(&self, hook: &Function, view: &Object) -> Result<JsValue, String> {
+        hook.call1(&JsValue::NULL, view).map_err(|e| self.failed(&e))
+    }
+
+    /// Call a hook that must finish synchronously
+    fn call(&self, hook: &Function, view: &Object) -> Result<Object, String> {
+        let result = self.invoke(hook, view)?;
+        if is_promise(&result) {
+            return Err(format!("Interceptor {} returned a Promise; use the async request methods", self.name));
+        }
+        self.view_from(result, view)
+    }
+
+    /// Call a hook, awaiting it when it returns a Promise
+    async fn call_async(&self, hook: &Function, view: &Object) -> Result<Object, String> {
+        let mut result = self.invoke(hook, view)?;
+        if is_promise(&result) {
+            result = JsFuture::from(Promise::resolve(&result)).await.map_err(|e| self.failed(&e))?;
+        }
+        self.view_from(result, view)
+    }
+
+    fn failed(&self, error: &JsValue) -> String {
+        format!("Interceptor {} failed: {}", self.name, js_message(error))
+    }
+
+    /// The view to read back, or an error for blocking results
+    fn view_from(&self, result: JsValue, view: &Object) -> Result<Object, String> {
+        if result.is_undefined() || result.is_null() || result == JsValue::TRUE {
+            return Ok(view.clone());
+        }
+        if result == JsValue::FALSE {
+            return Err(format!("Blocked by {}", self.name));
+        }
+        result.dyn_into::<Object>().map_err(|_| format!("Interceptor {} returned an invalid view", self.name))
+    }
+
+    fn check_blocked(&self, view: &Object) -> Result<(), String> {
+        let blocked = get(view, "blocked");
+        if blocked.is_undefined() || blocked.is_null() || blocked == JsValue::FALSE {
+            return Ok(());
+        }
+        match blocked.as_string() {
+            Some(reason) => Err(format!("Blocked by {}: {}", self.name, reason)),
+            None => Err(format!("Blocked by {}", self.name)),
+        }
+    }
+}
+
+fn js_message(error: &JsValue) -> String {
+    error
+        .as_string()
+        .or_else(|| get(error, "message").as_string())
+        .unwrap_or_else(|| "Unknown error".to_string())
+}
+
+fn get(target: &JsValue, key: &str) -> JsValue {
+    Reflect::get(target, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn set(target: &Object, key: &str, value: &JsValue) {
+    let _ = Reflect::set(target, &JsValue::from_str(key), value);
+}
+
+fn headers_to_js(headers: &[(String, String)]) -> Array {
+    headers
+        .iter()
+        .map(|(k, v)| -> JsValue { Array::of2(&JsValue::from_str(k), &JsValue::from_str(v)).into() })
+        .collect()
+}
+
+fn headers_from_js(value: &JsValue) -> Result<Vec<(String, String)>, String> {
+    if !Array::is_array(value) {
+        return Err("Interceptor view headers must be an array of [name, value] pairs".to_string());
+    }
+    Array::from(value)
+        .iter()
+        .map(|pair| {
+            let pair = Array::is_array(&pair).then(|| Array::from(&pair)).filter(|p| p.length() == 2);
+            match pair.map(|p| (p.get(0).as_string(), p.get(1).as_string())) {
+                Some((Some(name), Some(value))) => Ok((name, value)),
+                _ => Err("Interceptor view header must be a [name, value] pair of strings".to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Body as exposed to hooks: text when it is valid UTF-8, bytes otherwise
+fn body_to_js(body: &[u8]) -> JsValue {
+    match std::str::from_utf8(body) {
+        Ok(text) => JsValue::from_str(text),
+        Err(_) => Uint8Array::from(body).into(),
+    }
+}
+
+fn body_from_js(value: &JsValue, original: Vec<u8>) -> Vec<u8> {
+    if let Some(text) = value.as_string() {
+        return text.into_bytes();
+    }
+    if value.is_instance_of::<Uint8Array>() {
+        return Uint8Array::from(value.clone()).to_vec();
+    }
+    if value.is_undefined() || value.is_null() { Vec::new() } else { original }
+}
+
+fn response_from_js(value: &JsValue) -> Result<HttpResponse, String> {
+    let headers = get(value, "headers");
+    Ok(HttpResponse {
+        status: get(value, "status").as_f64().unwrap_or(200.0) as u16,
+        // A short-circuit response may leave out its headers
+        headers: if headers.is_undefined() || headers.is_null() { Vec::new() } else { headers_from_js(&headers)? },
+        body: body_from_js(&get(value, "body"), Vec::new()),
+    })
+}
+
+fn request_view(request: &HttpRequest) -> Object {
+    let view = Object::new();
+    set(&view, "method", &JsValue::from_str(&request.method));
+    set(&view, "url", &JsValue::from_str(&request.url));
+    set(&view, "headers", &headers_to_js(&request.headers));
+    set(&view, "body", &body_to_js(&request.body));
+    view
+}
+
+fn response_view(request: &HttpRequest, response: &HttpResponse) -> Object {
+    let request_view = Object::new();
+    set(&request_view, "method", &JsValue::from_str(&request.method));
+    set(&request_view, "url", &JsValue::from_str(&request.url));
+    set(&request_view, "headers", &headers_to_js(&request.headers));
+    let view = Object::new();
+    set(&view, "status", &JsValue::from_f64(response.status as f64));
+    set(&view, "headers", &headers_to_js(&response.headers));
+    set(&view, "body", &body_to_js(&response.body));
+    set(&view, "request", &request_view);
+    view
+}
+
+impl JsCallbackInterceptor {
+    /// Copy an edited request view back, returning its short-circuit response if set
+    fn apply_request_view(&self, view: &Object, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        self.check_blocked(view)?;
+        let headers = headers_from_js(&get(view, "headers"))?;
+        if let Some(method) = get(view, "method").as_string() {
+            request.method = method.to_uppercase();
+        }
+        if let Some(url) = get(view, "url").as_string() {
+            request.url = url;
+        }
+        request.headers = headers;
+        request.body = body_from_js(&get(view, "body"), std::mem::take(&mut request.body));
+        let response = get(view, "response");
+        if response.is_undefined() || response.is_null() {
+            return Ok(None);
+        }
+        response_from_js(&response).map(Some)
+    }
+
+    fn apply_response_view(&self, view: &Object, response: HttpResponse) -> Result<HttpResponse, String> {
+        self.check_blocked(view)?;
+        Ok(HttpResponse {
+            status: get(view, "status").as_f64().map(|s| s as u16).unwrap_or(response.status),
+            headers: headers_from_js(&get(view, "headers"))?,
+            body: body_from_js(&get(view, "body"), response.body),
+        })
+    }
+}
+
+impl Interceptor for JsCallbackInterceptor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+        let Some(hook) = &self.on_request else {
+            return Ok(None);
+        };
+        let view = self.call(hook, &request_view(request))?;
+        self.apply_request_view(&view, request)
+    }
+
+    fn on_response(&self, request: &HttpRequest, response: HttpResponse) -> Result<HttpResponse, String> {
+        let Some(hook) = &self.on_response else {
+            return Ok(response);
+        };
+        let view = self.call(hook, &response_view(request, &response))?;
+        self.apply_response_view(&view, response)
+    }
+
+    fn on_request_async<'a>(&'a self, request: &'a mut HttpRequest) -> HookFuture<'a, Option<HttpResponse>> {
+        Box::pin(JsThreadFuture(async move {
+            let Some(hook) = &self.on_request else {
+                return Ok(None);
+            };
+            let view = self.call_async(hook, &request_view(request)).await?;
+            self.apply_request_view(&view, request)
+        }))
+    }
+
+    fn on_response_async<'a>(&'a self, request: &'a HttpRequest, response: HttpResponse) -> HookFuture<'a, HttpResponse> {
+        Box::pin(JsThreadFuture(async move {
+            let Some(hook) = &self.on_response else {
+                return Ok(response);
+            };
+            let view = self.call_async(hook, &response_view(request, &response)).await?;
+            self.apply_response_view(&view, response)
+        }))
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn hook(body: &str) -> Function {
+        Function::new_with_args("view", body)
+    }
+
+    fn interceptor(on_request: Option<&str>, on_response: Option<&str>) -> JsCallbackInterceptor {
+        JsCallbackInterceptor::new("js".to_string(), on_request.map(hook), on_response.map(hook))
+    }
+
+    fn request() -> HttpRequest {
+        let mut request = HttpRequest::new("GET", "https://example.com/");
+        request.headers.push(("Accept".to_string(), "*/*".to_string()));
+        request
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse { status: 200, headers: vec![("Server".to_string(), "test".to_string())], body: b"ok".to_vec() }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_request_view_edits_apply() {
+        let hooks = interceptor(
+            Some("view.method = 'post'; view.headers.push(['X-Test', '1']); view.body = 'hello';"),
+            None,
+        );
+        let mut request = request();
+        assert!(hooks.on_request(&mut request).unwrap().is_none());
+        assert_eq!(request.method, "POST");
+        assert!(request.headers.contains(&("X-Test".to_string(), "1".to_string())));
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_response_view_edits_apply() {
+        let hooks = interceptor(None, Some("return {status: 201, headers: [], body: view.body + '!'};"));
+        let response = hooks.on_response(&request(), response()).unwrap();
+        assert_eq!(response.status, 201);
+        assert!(response.headers.is_empty());
+        assert_eq!(response.body, b"ok!");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_block_and_false_stop_request() {
+        let blocked = interceptor(Some("view.blocked = 'no trackers';"), None);
+        assert_eq!(blocked.on_request(&mut request()).unwrap_err(), "Blocked by js: no trackers");
+        let refused = interceptor(Some("return false;"), None);
+        assert_eq!(refused.on_request(&mut request()).unwrap_err(), "Blocked by js");
+        let response_blocked = interceptor(None, Some("return false;"));
+        assert!(response_blocked.on_response(&request(), response()).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_respond_short_circuits() {
+        let hooks = interceptor(Some("view.response = {status: 204, headers: [['X-Local', 'yes']]};"), None);
+        let response = hooks.on_request(&mut request()).unwrap().unwrap();
+        assert_eq!(response.status, 204);
+        assert_eq!(response.headers, vec![("X-Local".to_string(), "yes".to_string())]);
+        assert!(response.body.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_thrown_error_fails_request() {
+        let hooks = interceptor(Some("throw new Error('boom');"), None);
+        assert_eq!(hooks.on_request(&mut request()).unwrap_err(), "Interceptor js failed: boom");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_malformed_headers_are_rejected() {
+        let object = interceptor(Some("view.headers = {Accept: 'text/html'};"), None);
+        assert!(object.on_request(&mut request()).unwrap_err().contains("must be an array"));
+        let pair = interceptor(Some("view.headers = [['Accept']];"), None);
+        assert!(pair.on_request(&mut request()).unwrap_err().contains("[name, value] pair"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_promise_rejected_by_sync_hooks() {
+        let hooks = interceptor(Some("return Promise.resolve(view);"), None);
+        assert!(hooks.on_request(&mut request()).unwrap_err().contains("returned a Promise"));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_async_hooks_are_awaited() {
+        let hooks = interceptor(
+            Some("return Promise.resolve().then(() => { view.url = 'https://example.org/'; return view; });"),
+            Some("return new Promise(resolve => setTimeout(() => resolve(false), 0));"),
+        );
+        let mut request = request();
+        assert!(hooks.on_request_async(&mut request).await.unwrap().is_none());
+        assert_eq!(request.url, "https://example.org/");
+        assert_eq!(hooks.on_response_async(&request, response()).await.unwrap_err(), "Blocked by js");
+
+        let rejected = interceptor(Some("return Promise.reject(new Error('denied'));"), None);
+        assert_eq!(rejected.on_request_async(&mut request).await.unwrap_err(), "Interceptor js failed: denied");
+    }
+}
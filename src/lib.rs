@@ -6,14 +6,83 @@ use wasm_bindgen::prelude::*;
 use pyo3::types::PyModule;
 use pyo3::{Python, PyResult as PyResultType, Bound};
 use serde_json::json;
-use base64::Engine;
+use std::sync::Mutex;
+
+pub mod a2a;
+pub mod bhttp;
+pub mod c_api;
+pub mod cache;
+pub mod codec;
+pub mod codec_py;
+pub mod config;
+pub mod cookies;
+pub mod credential;
+pub mod did;
+pub mod did_document;
+pub mod did_nostr;
+pub mod did_nostr_ffi;
+pub mod did_nostr_py;
+pub mod did_nostr_wasm;
+pub mod doh;
+pub mod forward_proxy;
+pub mod gateway;
+pub mod http1;
+pub mod image;
+pub mod interceptor;
+pub mod interceptor_py;
+pub mod interceptor_wasm;
+pub mod mcp;
+pub mod mcp_py;
+pub mod mcp_wasm;
+pub mod mitm;
+pub mod nostr_event;
+pub mod ohttp;
+pub mod pii;
+pub mod pii_py;
+pub mod pii_wasm;
+pub mod privacy;
+pub mod privacy_py;
+pub mod proxy;
+pub mod ratelimit;
+pub mod retry;
+pub mod sse;
+pub mod storage;
+pub mod transport;
+pub mod uniffi_api;
+
+// Namespaced apart from the maturin package, which is also called `privacy_http_sdk`
+uniffi::setup_scaffolding!("privacy_http_uniffi");
+
+use a2a::{A2aClient, CardVerification, Message, SendResult};
+use cache::{CacheConfig, HttpCache};
+use config::ClientConfig;
+use cookies::CookieJar;
+use doh::DohResolver;
+use interceptor::{CacheInterceptor, CookieInterceptor, Interceptor, InterceptorChain, InterceptorStage, LoggingInterceptor, PiiInterceptor};
+use image::{GeneratedImage, ImageFormat, ImageProvider, ImageProviderConfig, ImageRequest, StabilityV1};
+use ohttp::{OhttpConfig, OhttpRoute};
+use pii::{PiiConfig, PiiScrubber, PiiVault};
+use privacy::{PolicyReport, PrivacyConfig, PrivacyPolicy};
+use proxy::ProxyRouter;
+use ratelimit::{LimitKey, RateLimiter};
+use retry::{AttemptError, RetryConfig, RetryPolicy};
+use std::collections::HashMap;
+use std::sync::Arc;
+use storage::{ArtifactStore, S3Location, StorageConfig, StoredArtifact};
+use transport::{ChunkSink, HttpRequest, HttpResponse, Transport};
 
 // HttpClient for Rust/WASM usage
 #[wasm_bindgen]
 #[derive(Debug)]
 pub struct HttpClient {
     client: Client,
+    /// Client for loopback destinations (local model servers), exempt from HTTPS-only
+    local_client: Client,
+    /// Client for `.onion` destinations (proxied, exempt from HTTPS-only)
+    onion_client: Option<Client>,
+    proxy_router: Option<Arc<ProxyRouter>>,
     runtime: Runtime,
+    #[allow(dead_code)]
     api_key: String,
     #[allow(dead_code)]
     openai_url: String,
@@ -35,8 +104,7 @@ pub struct HttpClient {
     qwen_client: Client,
     #[allow(dead_code)]
     deepseek_api_key: String,
-    #[allow(dead_code)]
-    s3_client: Client,
+    artifact_store: Option<Arc<ArtifactStore>>,
     #[allow(dead_code)]
     xai_api_key: String,
     #[allow(dead_code)]
@@ -45,18 +113,37 @@ pub struct HttpClient {
     claude_api_key: String,
     #[allow(dead_code)]
     ollama_api_key: String,
-    #[allow(dead_code)]
+    /// Base URL of the default A2A agent
     a2_a_server: String,
+    /// Discovered A2A agents by base URL, so the card is fetched once
+    a2a_clients: Mutex<HashMap<String, Arc<A2aClient>>>,
     #[allow(dead_code)]
     request: String,
     #[allow(dead_code)]
     response: String,
-    #[allow(dead_code)]
+    /// Base URL of the default MCP endpoint
     mcp_server: String,
     #[allow(dead_code)]
     base_url_:String,
     #[allow(dead_code)]
-    kimi_api_key: String
+    kimi_api_key: String,
+    pii_scrubber: Option<PiiScrubber>,
+    pii_vault: Mutex<PiiVault>,
+    image_provider: Box<dyn ImageProvider>,
+    privacy_policy: PrivacyPolicy,
+    ohttp: Option<OhttpRoute>,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
+    cookie_jar: Option<CookieJar>,
+    /// Top-level site partitioning the cookie jar
+    cookie_site: Option<String>,
+    http_cache: Option<HttpCache>,
+    /// User interceptors and their stage
+    interceptors: Vec<(InterceptorStage, Arc<dyn Interceptor>)>,
+    /// Follow redirects (see `set_follow_redirects`)
+    follow_redirects: bool,
+    /// Only connect to public addresses (see `set_public_hosts_only`)
+    public_hosts_only: bool,
 }
 #[wasm_bindgen]
 impl HttpClient {
@@ -66,12 +153,16 @@ impl HttpClient {
             .https_only(true) // Enforce HTTPS for privacy
             .build()
             .expect("Failed to create reqwest client");
+        let local_client = Client::new();
         let runtime = Runtime::new().expect("Failed to create Tokio runtime");
         let deepseek_client = Client::new();
         let qwen_client = Client::new();
 
         Self {
             client,
+            local_client,
+            onion_client: None,
+            proxy_router: None,
             runtime,
             api_key: api_key.clone(),
             openai_url: String::new(),
@@ -85,16 +176,30 @@ impl HttpClient {
             qwen_api_key: api_key.clone(),
             qwen_client,
             a2_a_server: String::new(),
+            a2a_clients: Mutex::new(HashMap::new()),
             deepseek_api_key: api_key.clone(),
-            s3_client: Client::new(),
+            artifact_store: None,
             xai_api_key: api_key.clone(),
             claude_api_key: api_key.clone(),
-            ollama_api_key: api_key,
+            ollama_api_key: api_key.clone(),
             request: String::new(),
             response: String::new(),
             mcp_server: String::new(),
             base_url_: String::new(),
-            kimi_api_key: String::new()
+            kimi_api_key: String::new(),
+            pii_scrubber: None,
+            pii_vault: Mutex::new(PiiVault::new()),
+            image_provider: Box::new(StabilityV1::new(&api_key)),
+            privacy_policy: PrivacyPolicy::default(),
+            ohttp: None,
+            rate_limiter: None,
+            retry_policy: None,
+            cookie_jar: None,
+            cookie_site: None,
+            http_cache: None,
+            interceptors: Vec::new(),
+            follow_redirects: true,
+            public_hosts_only: false,
         }
     }
     pub fn get_sync(&self, url: &str, headers: JsValue) -> Result<String, JsValue> {
@@ -111,77 +216,795 @@ impl HttpClient {
             .map_err(|e| JsValue::from_str(&e))
     }
 
-    // General prompt for AI API
+    /// Like `get_sync`, but returns a Promise and awaits `async` interceptor hooks
+    pub async fn get_async(&self, url: String, headers: JsValue) -> Result<String, JsValue> {
+        let headers_vec = Self::js_headers_to_vec(headers)?;
+        self.get(&url, &headers_vec).await.map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Like `post_sync`, but returns a Promise and awaits `async` interceptor hooks
+    pub async fn post_async(&self, url: String, headers: JsValue, body: String) -> Result<String, JsValue> {
+        let headers_vec = Self::js_headers_to_vec(headers)?;
+        self.post(&url, &headers_vec, body).await.map_err(|e| JsValue::from_str(&e))
+    }
+
+    // General prompt for AI API (PII is replaced by placeholders when scrubbing is enabled)
     pub fn prompt(&self, prompt: &str) -> Result<String, JsValue> {
-        let json_data = json!({
-            "prompt": prompt,
-        });
-        Ok(json_data.to_string())
+        Ok(self.prompt_json(prompt))
+    }
+
+    /// Enable PII scrubbing (optional JSON config): prompts, chat messages and request
+    /// bodies get placeholders, and responses are re-hydrated locally
+    pub fn enable_pii_scrubbing(&mut self, config_json: Option<String>) -> Result<(), JsValue> {
+        let config = match config_json {
+            Some(json) => PiiConfig::from_json(&json).map_err(|e| JsValue::from_str(&e))?,
+            None => PiiConfig::default(),
+        };
+        self.set_pii_config(&config).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Disable PII scrubbing and forget stored placeholders
+    pub fn disable_pii_scrubbing(&mut self) {
+        self.pii_scrubber = None;
+        self.pii_vault.lock().unwrap().clear();
+    }
+
+    /// Scrub a JSON array of chat messages (or an object with `messages`)
+    pub fn scrub_chat(&self, messages_json: &str) -> Result<String, JsValue> {
+        self.scrub_chat_json(messages_json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Restore placeholders in a provider response with the original values (local only)
+    pub fn rehydrate(&self, response: &str) -> String {
+        self.pii_vault.lock().unwrap().rehydrate(response)
+    }
+
+    /// Select the image backend from a JSON config, e.g.
+    /// `{"provider": "automatic1111", "base_url": "http://127.0.0.1:7860"}`
+    pub fn set_image_provider(&mut self, config_json: &str) -> Result<(), JsValue> {
+        self.cxx_set_image_provider(config_json).map_err(|e| JsValue::from_str(&e))
     }
 
-    /// Generate an image using Stable Diffusion API (for WASM)
+    /// Generate an image with the configured provider (for WASM), returning base64
     pub fn generate_image_sync(&self, prompt: &str, width: u32, height: u32, steps: u32) -> Result<String, JsValue> {
-        let url = "https://api.stability.ai/v1/generation/stable-diffusion-xl-beta-v2-2-2/text-to-image";
-        let headers = js_sys::Array::new();
-        let auth_header = js_sys::Array::new();
-        auth_header.push(&JsValue::from_str("Authorization"));
-        auth_header.push(&JsValue::from_str(&format!("Bearer {}", self.api_key)));
-        headers.push(&auth_header.into());
-        let content_type = js_sys::Array::new();
-        content_type.push(&JsValue::from_str("Content-Type"));
-        content_type.push(&JsValue::from_str("application/json"));
-        headers.push(&content_type.into());
-
-        let body = json!({
-            "prompt": prompt,
-            "width": width,
-            "height": height,
-            "steps": steps
-        })
-        .to_string();
-
-        self.post_sync(url, headers.into(), body)
-            .map(|response| {
-                let json: serde_json::Value = serde_json::from_str(&response).unwrap_or_default();
-                json["artifacts"][0]["binary"]
-                    .as_str()
-                    .map(|s| s.to_string())
-                    .unwrap_or_default()
-            })
+        let request = ImageRequest::new(prompt, width, height, steps);
+        self.generate_images(&request)
+            .map(|images| images.first().map(|image| image.to_base64()).unwrap_or_default())
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Generate images from a JSON `ImageRequest`, returning a JSON array of
+    /// `{"base64": ..., "format": ..., "seed": ...}` objects
+    pub fn generate_images_sync(&self, request_json: &str) -> Result<String, JsValue> {
+        let request = ImageRequest::from_json(request_json).map_err(|e| JsValue::from_str(&e))?;
+        let images = self.generate_images(&request).map_err(|e| JsValue::from_str(&e))?;
+        let output: Vec<serde_json::Value> = images
+            .iter()
+            .map(|image| json!({
+                "base64": image.to_base64(),
+                "format": image.format.as_str(),
+                "seed": image.seed,
+            }))
+            .collect();
+        Ok(serde_json::Value::Array(output).to_string())
+    }
+
+    /// Apply a JSON privacy config (header filtering, IP masking, TLS enforcement, ...)
+    pub fn set_privacy_config(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = PrivacyConfig::from_json(config_json).map_err(|e| JsValue::from_str(&e))?;
+        self.set_privacy_policy(PrivacyPolicy::new(config))
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Set the top-level site whose cookie partition is used (`None`: each request's own
+    /// site). The HTTP cache only sends stored ETags to this site.
+    pub fn set_cookie_site(&mut self, site: Option<String>) {
+        self.cookie_site = site;
+    }
+
+    /// Clear the cookies of one top-level site, or all cookies
+    pub fn clear_cookies(&self, site: Option<String>) -> Result<(), JsValue> {
+        self.clear_cookie_jar(site.as_deref()).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Top-level sites holding cookies, as a JSON array
+    pub fn cookie_partitions(&self) -> Result<String, JsValue> {
+        self.cookie_partitions_json().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Privacy budget counters and remaining tokens as JSON (`null` without a budget)
+    pub fn rate_limit_stats(&self) -> Result<String, JsValue> {
+        self.rate_limit_stats_json().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Set the base URL of the default A2A agent
+    pub fn set_a2a_server(&mut self, url: &str) {
+        self.a2_a_server = url.to_string();
+    }
+
+    /// Set the default MCP endpoint used when an MCP client is opened without a URL
+    pub fn set_mcp_server(&mut self, url: &str) {
+        self.mcp_server = url.to_string();
+    }
+
+    /// Fetch and validate the A2A agent's card, returning it as JSON
+    pub fn a2a_agent_card_sync(&self) -> Result<String, JsValue> {
+        self.a2a_agent_card_json().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Send a text message to the A2A agent, returning the resulting task or message as JSON
+    pub fn a2a_send_message_sync(&self, text: &str) -> Result<String, JsValue> {
+        self.a2a_send_message_json(text).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Get an A2A task as JSON
+    pub fn a2a_get_task_sync(&self, task_id: &str) -> Result<String, JsValue> {
+        self.a2a_get_task_json(task_id).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Cancel an A2A task, returning it as JSON
+    pub fn a2a_cancel_task_sync(&self, task_id: &str) -> Result<String, JsValue> {
+        self.a2a_cancel_task_json(task_id).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Switch the proxy isolation identity (fresh circuits with `"isolation": "per_identity"`)
+    pub fn set_proxy_identity(&mut self, identity: &str) -> Result<(), JsValue> {
+        self.rotate_proxy_identity(identity).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Send requests through an Oblivious HTTP relay (JSON `OhttpConfig`); the gateway
+    /// key is fetched from `keys_url` through this client when not given inline
+    pub fn configure_ohttp(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = OhttpConfig::from_json(config_json).map_err(|e| JsValue::from_str(&e))?;
+        self.set_ohttp(config).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Stop using Oblivious HTTP
+    pub fn disable_ohttp(&mut self) {
+        self.ohttp = None;
+    }
+
+    /// Retry transient failures with backoff and a per-host circuit breaker (JSON `RetryConfig`)
+    pub fn configure_retries(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = RetryConfig::from_json(config_json).map_err(|e| JsValue::from_str(&e))?;
+        self.set_retry_config(config);
+        Ok(())
+    }
+
+    /// Send every request exactly once
+    pub fn disable_retries(&mut self) {
+        self.retry_policy = None;
+    }
+
+    /// Cache GET responses following `Cache-Control`, `ETag` and `Vary` (JSON `CacheConfig`)
+    pub fn configure_cache(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = CacheConfig::from_json(config_json).map_err(|e| JsValue::from_str(&e))?;
+        self.set_cache_config(config).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Stop caching (stored entries are kept on disk)
+    pub fn disable_cache(&mut self) {
+        self.http_cache = None;
+    }
+
+    /// Remove every cached response
+    pub fn clear_cache(&self) -> Result<(), JsValue> {
+        self.clear_http_cache().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Circuit breaker state per host as JSON (hosts without failures are omitted)
+    pub fn circuit_states(&self) -> Result<String, JsValue> {
+        self.circuit_states_json().map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Register JS request/response hooks (see `interceptor_wasm`). `stage` is
+    /// `"outer"` (default: before the privacy policy, which still filters the request)
+    /// or `"inner"` (after it, seeing the request as sent).
+    pub fn register_interceptor(
+        &mut self,
+        on_request: Option<js_sys::Function>,
+        on_response: Option<js_sys::Function>,
+        stage: Option<String>,
+        name: Option<String>,
+    ) -> Result<(), JsValue> {
+        let stage = InterceptorStage::parse(stage.as_deref().unwrap_or("outer")).map_err(|e| JsValue::from_str(&e))?;
+        let name = name.unwrap_or_else(|| "js".to_string());
+        let interceptor = interceptor_wasm::JsCallbackInterceptor::new(name, on_request, on_response);
+        self.add_interceptor(stage, Arc::new(interceptor));
+        Ok(())
+    }
+
+    /// Remove all user interceptors (the built-in steps remain)
+    pub fn clear_interceptors(&mut self) {
+        self.interceptors.clear();
+    }
+
+    /// Names of the interceptors a request currently runs through, in order
+    pub fn interceptor_names(&self) -> Vec<String> {
+        let builtins = self.builtin_interceptors();
+        let (outer, wire) = self.interceptor_chains(&builtins, None);
+        [outer.names(), wire.names()].concat()
+    }
+
+    /// Configure S3-compatible artifact storage from a JSON `StorageConfig`
+    pub fn configure_storage(&mut self, config_json: &str) -> Result<(), JsValue> {
+        let config = StorageConfig::from_json(config_json).map_err(|e| JsValue::from_str(&e))?;
+        self.set_artifact_storage(config).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Generate images and upload them to storage (`output_uri` overrides the configured
+    /// bucket/prefix; without a configured store, `encryption_key` is required), returning
+    /// a JSON array of `{"uri": ..., "presigned_url": ...}`
+    pub fn generate_images_to_storage_sync(
+        &self,
+        request_json: &str,
+        output_uri: Option<String>,
+        encryption_key: Option<String>,
+    ) -> Result<String, JsValue> {
+        let request = ImageRequest::from_json(request_json).map_err(|e| JsValue::from_str(&e))?;
+        let images = self.generate_images(&request).map_err(|e| JsValue::from_str(&e))?;
+        let stored = self
+            .store_images(&images, output_uri.as_deref(), encryption_key.as_deref())
+            .map_err(|e| JsValue::from_str(&e))?;
+        let output: Vec<serde_json::Value> = stored
+            .iter()
+            .map(|artifact| json!({
+                "uri": artifact.uri(),
+                "presigned_url": artifact.presigned_url,
+                "encrypted": artifact.encrypted,
+            }))
+            .collect();
+        Ok(serde_json::Value::Array(output).to_string())
     }
 }
 
 impl HttpClient {
-    async fn get(&self, url: &str, headers: &[(String, String)]) -> Result<String, String> {
-        let mut req = self.client.get(url);
-        for (key, value) in headers {
-            req = req.header(key.as_str(), value.as_str());
+    /// Build a client from a whole-client config (see `config::ClientConfig`)
+    pub fn from_config(config: ClientConfig) -> Result<Self, String> {
+        let mut client = Self::new(config.api_key);
+        if let Some(privacy) = config.privacy {
+            client.set_privacy_policy(PrivacyPolicy::new(privacy))?;
         }
-        let response = req
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-        let text = response
-            .text()
-            .await
-            .map_err(|e| format!("Failed to parse response body: {}", e))?;
-        Ok(text)
+        client.retry_policy = config.retries.map(RetryPolicy::new);
+        if let Some(cache) = config.cache {
+            client.set_cache_config(cache)?;
+        }
+        client.cookie_site = config.cookie_site;
+        // Last, so a remote key fetch goes through the configured policy
+        if let Some(ohttp) = config.ohttp {
+            client.ohttp = Some(OhttpRoute::new(ohttp, &client)?);
+        }
+        Ok(client)
+    }
+
+    /// Generate images with the configured provider (shared by all bindings)
+    pub fn generate_images(&self, request: &ImageRequest) -> Result<Vec<GeneratedImage>, String> {
+        self.image_provider.generate(self, request)
+    }
+
+    /// Upload images to the configured store. With an `s3://bucket/prefix` URI and no
+    /// configured store, a store using the default AWS credential chain and
+    /// `encryption_key` is created; images are never uploaded unencrypted.
+    pub fn store_images(
+        &self,
+        images: &[GeneratedImage],
+        output_uri: Option<&str>,
+        encryption_key: Option<&str>,
+    ) -> Result<Vec<StoredArtifact>, String> {
+        let location = output_uri.map(S3Location::parse).transpose()?;
+        self.runtime.block_on(async {
+            let adhoc;
+            let store = match (&self.artifact_store, &location) {
+                (Some(store), _) => store.as_ref(),
+                (None, Some(location)) => {
+                    let config = StorageConfig {
+                        bucket: location.bucket.clone(),
+                        prefix: location.prefix.clone(),
+                        encryption_key: encryption_key.map(str::to_string),
+                        ..StorageConfig::default()
+                    };
+                    adhoc = ArtifactStore::connect(config).await?;
+                    &adhoc
+                }
+                (None, None) => return Err("No artifact storage configured".to_string()),
+            };
+            let mut stored = Vec::new();
+            for image in images {
+                stored.push(store.put_image(location.as_ref(), image).await?);
+            }
+            Ok(stored)
+        })
+    }
+
+    /// The default A2A agent (see `set_a2a_server`), discovered on first use
+    pub fn a2a_client(&self) -> Result<Arc<A2aClient>, String> {
+        if self.a2_a_server.is_empty() {
+            return Err("A2A server is not set".to_string());
+        }
+        if let Some(client) = self.a2a_clients.lock().unwrap().get(&self.a2_a_server) {
+            return Ok(Arc::clone(client));
+        }
+        // Discover without holding the lock, since discovery goes through this client
+        let client = Arc::new(A2aClient::discover(self, &self.a2_a_server, &CardVerification::IfSigned)?);
+        let mut clients = self.a2a_clients.lock().unwrap();
+        Ok(Arc::clone(clients.entry(self.a2_a_server.clone()).or_insert(client)))
+    }
+
+    /// Prompt payload as JSON, with PII scrubbed when enabled (shared by the bindings)
+    pub fn prompt_json(&self, prompt: &str) -> String {
+        let prompt = match &self.pii_scrubber {
+            Some(scrubber) => scrubber.scrub(prompt, &mut self.pii_vault.lock().unwrap()),
+            None => prompt.to_string(),
+        };
+        let json_data = json!({
+            "prompt": prompt,
+        });
+        json_data.to_string()
+    }
+
+    /// Scrub PII with `config`, or stop scrubbing when it is disabled (shared by the bindings)
+    pub fn set_pii_config(&mut self, config: &PiiConfig) -> Result<(), String> {
+        self.pii_scrubber = if config.enabled {
+            Some(PiiScrubber::new(config)?)
+        } else {
+            None
+        };
+        self.pii_vault.lock().unwrap().set_capacity(config.vault_capacity);
+        Ok(())
+    }
+
+    /// Scrub a JSON array of chat messages (or an object with `messages`), returning JSON
+    pub fn scrub_chat_json(&self, messages_json: &str) -> Result<String, String> {
+        let mut messages: serde_json::Value = serde_json::from_str(messages_json)
+            .map_err(|e| format!("Invalid messages JSON: {}", e))?;
+        if let Some(scrubber) = &self.pii_scrubber {
+            scrubber.scrub_messages(&mut messages, &mut self.pii_vault.lock().unwrap());
+        }
+        Ok(messages.to_string())
+    }
+
+    /// Clear the cookies of one top-level site, or all cookies (shared by the bindings)
+    pub fn clear_cookie_jar(&self, site: Option<&str>) -> Result<(), String> {
+        match &self.cookie_jar {
+            Some(jar) => jar.clear(site),
+            None => Ok(()),
+        }
+    }
+
+    /// Top-level sites holding cookies as a JSON array (shared by the bindings)
+    pub fn cookie_partitions_json(&self) -> Result<String, String> {
+        let partitions = self.cookie_jar.as_ref().map(CookieJar::partitions).unwrap_or_default();
+        serde_json::to_string(&partitions).map_err(|e| e.to_string())
+    }
+
+    /// Privacy budget counters as JSON, `null` without a budget (shared by the bindings)
+    pub fn rate_limit_stats_json(&self) -> Result<String, String> {
+        let stats = self.rate_limiter.as_ref().map(RateLimiter::stats);
+        serde_json::to_string(&stats).map_err(|e| e.to_string())
+    }
+
+    /// Circuit breaker state per host as JSON (shared by the bindings)
+    pub fn circuit_states_json(&self) -> Result<String, String> {
+        let states = self
+            .retry_policy
+            .as_ref()
+            .and_then(RetryPolicy::breaker)
+            .map(|breaker| breaker.states())
+            .unwrap_or_default();
+        serde_json::to_string(&states).map_err(|e| e.to_string())
+    }
+
+    /// The A2A agent's card as JSON (shared by the bindings)
+    pub fn a2a_agent_card_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.a2a_client()?.card()).map_err(|e| e.to_string())
+    }
+
+    /// Send a text message to the A2A agent, returning the resulting task or message as JSON
+    pub fn a2a_send_message_json(&self, text: &str) -> Result<String, String> {
+        let json = match self.a2a_client()?.send_message(self, &Message::user_text(text))? {
+            SendResult::Task(task) => serde_json::to_string(&task),
+            SendResult::Message(message) => serde_json::to_string(&message),
+        };
+        json.map_err(|e| e.to_string())
+    }
+
+    /// An A2A task as JSON
+    pub fn a2a_get_task_json(&self, task_id: &str) -> Result<String, String> {
+        let task = self.a2a_client()?.get_task(self, task_id)?;
+        serde_json::to_string(&task).map_err(|e| e.to_string())
+    }
+
+    /// Cancel an A2A task, returning it as JSON
+    pub fn a2a_cancel_task_json(&self, task_id: &str) -> Result<String, String> {
+        let task = self.a2a_client()?.cancel_task(self, task_id)?;
+        serde_json::to_string(&task).map_err(|e| e.to_string())
+    }
+
+    /// Switch the proxy isolation identity, keeping the rest of the privacy config
+    pub fn rotate_proxy_identity(&mut self, identity: &str) -> Result<(), String> {
+        let mut config = self.privacy_policy.config().clone();
+        let proxy = config.proxy.as_mut().ok_or("No proxy configured")?;
+        proxy.identity = Some(identity.to_string());
+        self.set_privacy_policy(PrivacyPolicy::new(config))
+    }
+
+    /// Route requests through an Oblivious HTTP relay, fetching the gateway key through
+    /// this client when it is not given inline
+    pub fn set_ohttp(&mut self, config: OhttpConfig) -> Result<(), String> {
+        self.ohttp = Some(OhttpRoute::new(config, self)?);
+        Ok(())
+    }
+
+    /// Retry transient failures with backoff and a per-host circuit breaker
+    pub fn set_retry_config(&mut self, config: RetryConfig) {
+        self.retry_policy = Some(RetryPolicy::new(config));
+    }
+
+    /// Cache GET responses following `Cache-Control`, `ETag` and `Vary`
+    pub fn set_cache_config(&mut self, config: CacheConfig) -> Result<(), String> {
+        self.http_cache = Some(HttpCache::new(config)?);
+        Ok(())
+    }
+
+    /// Remove every cached response (shared by the bindings)
+    pub fn clear_http_cache(&self) -> Result<(), String> {
+        match &self.http_cache {
+            Some(cache) => cache.clear(),
+            None => Ok(()),
+        }
+    }
+
+    /// Connect S3-compatible artifact storage
+    pub fn set_artifact_storage(&mut self, config: StorageConfig) -> Result<(), String> {
+        let store = self.runtime.block_on(ArtifactStore::connect(config))?;
+        self.artifact_store = Some(Arc::new(store));
+        Ok(())
+    }
+
+    /// Resolve an MCP endpoint, falling back to the default (see `set_mcp_server`)
+    pub fn mcp_endpoint(&self, url: Option<&str>) -> Result<String, String> {
+        match url {
+            Some(url) => Ok(url.to_string()),
+            None if self.mcp_server.is_empty() => Err("MCP server is not set".to_string()),
+            None => Ok(self.mcp_server.clone()),
+        }
+    }
+
+    /// Follow redirects (the default) or return them as responses. Clients fetching
+    /// caller-chosen URLs turn them off, so a checked URL cannot redirect elsewhere.
+    pub fn set_follow_redirects(&mut self, follow: bool) -> Result<(), String> {
+        self.follow_redirects = follow;
+        self.local_client = Client::builder()
+            .redirect(self.redirect_policy())
+            .build()
+            .map_err(|e| format!("Failed to create reqwest client: {}", e))?;
+        self.set_privacy_policy(self.privacy_policy.clone())
+    }
+
+    /// Only connect to public addresses: host names are resolved with a resolver that
+    /// drops internal addresses, and internal IP literals and loopback hosts are refused.
+    /// Clients fetching caller-chosen URLs turn this on, so a host checked with
+    /// `transport::is_public_url` cannot be rebound to an internal address before the
+    /// connection. Destinations reached through a proxy are resolved by the proxy.
+    pub fn set_public_hosts_only(&mut self, public_only: bool) -> Result<(), String> {
+        self.public_hosts_only = public_only;
+        self.set_privacy_policy(self.privacy_policy.clone())
+    }
+
+    fn redirect_policy(&self) -> reqwest::redirect::Policy {
+        if self.follow_redirects {
+            reqwest::redirect::Policy::default()
+        } else {
+            reqwest::redirect::Policy::none()
+        }
+    }
+
+    /// Replace the privacy policy applied to every request. The budget and the cookie
+    /// jar are only rebuilt when their sections change, so e.g. a new proxy identity
+    /// neither refills the budget nor drops cookies.
+    pub fn set_privacy_policy(&mut self, policy: PrivacyPolicy) -> Result<(), String> {
+        let (current, config) = (self.privacy_policy.config(), policy.config());
+        let router = match &config.proxy {
+            Some(config) => Some(Arc::new(ProxyRouter::new(config.clone())?)),
+            None => None,
+        };
+        let resolver = match &config.dns {
+            Some(config) => Some(DohResolver::new(config.clone())?),
+            None => None,
+        };
+        let build = |builder: reqwest::ClientBuilder| {
+            builder
+                .redirect(self.redirect_policy())
+                .build()
+                .map_err(|e| format!("Failed to create reqwest client: {}", e))
+        };
+        let mut builder = proxy::client_builder(router.as_ref(), resolver.as_ref(), config.tls_enforce);
+        if self.public_hosts_only {
+            builder = builder.dns_resolver(transport::PublicOnlyResolver::new(resolver.clone()));
+        }
+        let client = build(builder)?;
+        // Onion hosts are resolved by the proxy (socks5h), never locally
+        let onion_client = match &router {
+            Some(router) if router.config().allow_onion => Some(build(proxy::client_builder(Some(router), None, false))?),
+            _ => None,
+        };
+        let rate_limiter = match &config.budget {
+            budget if *budget == current.budget => None,
+            Some(budget) => Some(Some(RateLimiter::new(budget.clone())?)),
+            None => Some(None),
+        };
+        let cookie_jar = match &config.cookies {
+            cookies if *cookies == current.cookies => None,
+            Some(cookies) => Some(Some(CookieJar::new(cookies.clone())?)),
+            None => Some(None),
+        };
+
+        self.client = client;
+        self.onion_client = onion_client;
+        if let Some(rate_limiter) = rate_limiter {
+            self.rate_limiter = rate_limiter;
+        }
+        if let Some(cookie_jar) = cookie_jar {
+            self.cookie_jar = cookie_jar;
+        }
+        self.proxy_router = router;
+        self.privacy_policy = policy;
+        Ok(())
+    }
+
+    /// Client for a destination, enforcing the `.onion` guard
+    fn client_for(&self, url: &str) -> Result<&Client, String> {
+        if proxy::is_onion_url(url) {
+            let router = self.proxy_router.as_ref().ok_or("Onion destinations require a proxy")?;
+            router.check(url)?;
+            return self.onion_client.as_ref().ok_or_else(|| "Onion destinations are disabled".to_string());
+        }
+        if self.public_hosts_only {
+            if transport::is_loopback_url(url) || transport::is_internal_ip_url(url) {
+                return Err(format!("{} is not a public host", url));
+            }
+            Ok(&self.client)
+        } else if transport::is_loopback_url(url) {
+            Ok(&self.local_client)
+        } else {
+            Ok(&self.client)
+        }
+    }
+
+    async fn get(&self, url: &str, headers: &[(String, String)]) -> Result<String, String> {
+        let request = HttpRequest::get(url).with_headers(headers);
+        Ok(self.execute(request, None).await?.text())
     }
 
     async fn post(&self, url: &str, headers: &[(String, String)], body: String) -> Result<String, String> {
-        let mut req = self.client.post(url).body(body);
-        for (key, value) in headers {
+        let request = HttpRequest::post(url, body).with_headers(headers);
+        Ok(self.execute(request, None).await?.text())
+    }
+
+    /// Add an interceptor before (`Outer`) or after (`Inner`) the built-in privacy steps
+    pub fn add_interceptor(&mut self, stage: InterceptorStage, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.push((stage, interceptor));
+    }
+
+    /// Remove the custom interceptors with this name
+    pub fn remove_interceptor(&mut self, name: &str) {
+        self.interceptors.retain(|(_, interceptor)| interceptor.name() != name);
+    }
+
+    fn builtin_interceptors(&self) -> BuiltinInterceptors<'_> {
+        let site = self.cookie_site.as_deref();
+        BuiltinInterceptors {
+            pii: self.pii_scrubber.as_ref().map(|scrubber| PiiInterceptor::new(scrubber, &self.pii_vault)),
+            cookies: self
+                .cookie_jar
+                .as_ref()
+                .filter(|_| self.privacy_policy.allows_header("Cookie"))
+                .map(|jar| CookieInterceptor::new(jar, site)),
+            cache: self.http_cache.as_ref().map(|cache| CacheInterceptor::new(cache, site)),
+            logging: LoggingInterceptor::new(self.privacy_policy.config().logging),
+        }
+    }
+
+    /// The steps run once per request (outer user steps, privacy policy, PII scrubbing,
+    /// cookies, cache) and the wire steps run again for every attempt (inner user steps,
+    /// the per-request `inner` step from `Transport::send_with`, logging), so retries
+    /// are signed afresh
+    fn interceptor_chains<'a>(
+        &'a self,
+        builtins: &'a BuiltinInterceptors<'a>,
+        inner: Option<&'a dyn Interceptor>,
+    ) -> (InterceptorChain<'a>, InterceptorChain<'a>) {
+        let (mut outer, mut wire) = (InterceptorChain::new(), InterceptorChain::new());
+        let stage = |wanted: InterceptorStage| {
+            self.interceptors.iter().filter(move |(s, _)| *s == wanted).map(|(_, i)| i.as_ref())
+        };
+        stage(InterceptorStage::Outer).for_each(|i| outer.push(i));
+        outer.push(&self.privacy_policy);
+        if let Some(pii) = &builtins.pii {
+            outer.push(pii);
+        }
+        if let Some(cookies) = &builtins.cookies {
+            outer.push(cookies);
+        }
+        if let Some(cache) = &builtins.cache {
+            outer.push(cache);
+        }
+        stage(InterceptorStage::Inner).for_each(|i| wire.push(i));
+        if let Some(inner) = inner {
+            wire.push(inner);
+        }
+        wire.push(&builtins.logging);
+        (outer, wire)
+    }
+
+    /// Run a request through the request hooks without sending it. The report covers
+    /// the privacy policy applied to the caller's request.
+    pub fn prepare(&self, request: HttpRequest) -> Result<(HttpRequest, PolicyReport), String> {
+        let report = self.privacy_policy.explain(&mut request.clone());
+        let builtins = self.builtin_interceptors();
+        let (outer, wire) = self.interceptor_chains(&builtins, None);
+        let request = wire.prepare(outer.prepare(request)?)?;
+        Ok((request, report))
+    }
+
+    /// Send a request through the privacy client
+    async fn execute(&self, request: HttpRequest, inner: Option<&dyn Interceptor>) -> Result<HttpResponse, String> {
+        let builtins = self.builtin_interceptors();
+        let (outer, wire) = self.interceptor_chains(&builtins, inner);
+        let (request, result) = outer.run(request, |request| self.send_budgeted(request, &wire)).await;
+        let response = result?;
+        self.archive_transcript(request, &response);
+        Ok(response)
+    }
+
+    /// Like `execute`, but hands body chunks to `on_chunk` as they arrive. Response
+    /// hooks run on the completed response, so their body rewrites are not reflected
+    /// in the chunks. Streams are not retried, since delivered chunks cannot be taken back.
+    async fn execute_streaming(
+        &self,
+        request: HttpRequest,
+        inner: Option<&dyn Interceptor>,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<HttpResponse, String> {
+        let builtins = self.builtin_interceptors();
+        let (outer, wire) = self.interceptor_chains(&builtins, inner);
+        let send = |request: HttpRequest| async move {
+            let budget = self.charge_budget(&request).await?;
+            let response = match self.ohttp.as_ref().filter(|route| route.config.applies_to(&request.url)) {
+                Some(_) => {
+                    // Encapsulated responses only decrypt as a whole
+                    let response = self.send_routed(request).await?;
+                    on_chunk(&response.head(), &response.body)?;
+                    response
+                }
+                None => self.send_direct_streaming(request, on_chunk).await?,
+            };
+            if let Some((limiter, keys)) = &budget {
+                limiter.observe_response(keys, &response);
+            }
+            Ok(response)
+        };
+        let (request, result) = outer
+            .run(request, |request| async { wire.run(request, send).await.1 })
+            .await;
+        let response = result?;
+        self.archive_transcript(request, &response);
+        Ok(response)
+    }
+
+    fn archive_transcript(&self, request: HttpRequest, response: &HttpResponse) {
+        if let Some(store) = self.artifact_store.as_ref().filter(|s| s.config().store_transcripts) {
+            // Transcript upload is best-effort and runs in the background on the client's
+            // runtime (callers such as the wasm futures may not be inside one), so it
+            // neither delays nor fails the request itself
+            let (store, response) = (Arc::clone(store), response.clone());
+            self.runtime.spawn(async move {
+                let _ = store.put_transcript(&request, &response).await;
+            });
+        }
+    }
+
+    /// Charge the privacy budget for a request, returning the limiter and keys to
+    /// report the response to
+    async fn charge_budget(&self, request: &HttpRequest) -> Result<Option<(&RateLimiter, Vec<LimitKey>)>, String> {
+        match &self.rate_limiter {
+            Some(limiter) => {
+                let keys = limiter.keys_for_request(request);
+                limiter.acquire(&keys).await.map_err(|e| e.to_string())?;
+                Ok(Some((limiter, keys)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Send through the wire steps, with retries when configured. Every attempt runs
+    /// the wire steps again (fresh signature timestamp and nonce); the privacy budget
+    /// is charged once, for the first attempt as signed.
+    async fn send_budgeted(&self, request: HttpRequest, wire: &InterceptorChain<'_>) -> Result<HttpResponse, String> {
+        let budget = tokio::sync::OnceCell::new();
+        let budget = &budget;
+        let attempt = move |request: HttpRequest| async move {
+            let mut failure = None;
+            let failed = &mut failure;
+            let (_, result) = wire
+                .run(request, |request| async move {
+                    let budget = budget.get_or_try_init(|| self.charge_budget(&request)).await?;
+                    let response = self.send_routed(request).await.map_err(|error| {
+                        let message = String::from(error.clone());
+                        *failed = Some(error);
+                        message
+                    })?;
+                    if let Some((limiter, keys)) = budget {
+                        limiter.observe_response(keys, &response);
+                    }
+                    Ok(response)
+                })
+                .await;
+            // A network failure the wire steps passed through keeps its classification
+            result.map_err(|message| match failure {
+                Some(AttemptError::Transient(sent)) if sent == message => AttemptError::Transient(message),
+                _ => AttemptError::Fatal(message),
+            })
+        };
+        match &self.retry_policy {
+            Some(policy) => policy.run(&request, attempt).await,
+            None => Ok(attempt(request).await?),
+        }
+    }
+
+    /// Send one attempt, through the Oblivious HTTP relay when configured
+    async fn send_routed(&self, request: HttpRequest) -> Result<HttpResponse, AttemptError> {
+        match self.ohttp.as_ref().filter(|route| route.config.applies_to(&request.url)) {
+            Some(route) => {
+                let (outer, pending) = route.client.encapsulate_request(&route.config.relay_url, &request)?;
+                Ok(pending.decapsulate_response(self.send_direct(outer).await?)?)
+            }
+            None => self.send_direct(request).await,
+        }
+    }
+
+    /// Send a request on the wire (TLS enforcement and proxy routing apply)
+    async fn send_direct(&self, request: HttpRequest) -> Result<HttpResponse, AttemptError> {
+        self.send_direct_streaming(request, &mut |_, _| Ok(())).await
+    }
+
+    /// Send a request on the wire, handing body chunks to `on_chunk` as they arrive
+    async fn send_direct_streaming(&self, request: HttpRequest, on_chunk: &mut ChunkSink<'_>) -> Result<HttpResponse, AttemptError> {
+        let client = self.client_for(&request.url)?;
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|e| format!("Invalid method {}: {}", request.method, e))?;
+        let mut req = client.request(method, &request.url);
+        for (key, value) in &request.headers {
             req = req.header(key.as_str(), value.as_str());
         }
-        let response = req
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-        let text = response
-            .text()
+        if !request.body.is_empty() {
+            req = req.body(request.body);
+        }
+        let mut response = req.send().await.map_err(|e| {
+            let message = format!("Request failed: {}", e);
+            // Builder errors cover invalid URLs and TLS enforcement (`https_only`)
+            if !e.is_builder() && (e.is_connect() || e.is_timeout() || e.is_request()) {
+                AttemptError::Transient(message)
+            } else {
+                AttemptError::Fatal(message)
+            }
+        })?;
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.as_str().to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+            .collect();
+        let head = HttpResponse { status: response.status().as_u16(), headers, body: Vec::new() };
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .map_err(|e| format!("Failed to parse response body: {}", e))?;
-        Ok(text)
+            .map_err(|e| AttemptError::Transient(format!("Failed to parse response body: {}", e)))?
+        {
+            on_chunk(&head, &chunk)?;
+            body.extend_from_slice(&chunk);
+        }
+        Ok(HttpResponse { body, ..head })
     }
 
     fn js_headers_to_vec(headers: JsValue) -> Result<Vec<(String, String)>, JsValue> {
@@ -202,13 +1025,44 @@ impl HttpClient {
     }
 }
 
+/// Built-in steps borrowed from the client for one request
+struct BuiltinInterceptors<'a> {
+    pii: Option<PiiInterceptor<'a>>,
+    cookies: Option<CookieInterceptor<'a>>,
+    cache: Option<CacheInterceptor<'a>>,
+    logging: LoggingInterceptor,
+}
+
+impl Transport for HttpClient {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        self.runtime.block_on(self.execute(request, None))
+    }
+
+    fn send_streaming(&self, request: HttpRequest, on_chunk: &mut ChunkSink<'_>) -> Result<HttpResponse, String> {
+        self.runtime.block_on(self.execute_streaming(request, None, on_chunk))
+    }
+
+    fn send_with(&self, request: HttpRequest, inner: &dyn Interceptor) -> Result<HttpResponse, String> {
+        self.runtime.block_on(self.execute(request, Some(inner)))
+    }
+
+    fn send_streaming_with(
+        &self,
+        request: HttpRequest,
+        inner: &dyn Interceptor,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<HttpResponse, String> {
+        self.runtime.block_on(self.execute_streaming(request, Some(inner), on_chunk))
+    }
+}
+
 #[wasm_bindgen]
 pub fn greet(name: &str) -> String {
     format!("Hello, {}!", name)
 }
 
 // HttpClientPy for Python usage via PyO3
-#[pyclass]
+#[pyclass(module = "privacy_http_sdk.http")]
 pub struct HttpClientPy {
     #[pyo3(get, set)]
     api_key: String,
@@ -217,15 +1071,15 @@ pub struct HttpClientPy {
     inner: HttpClient,
 }
 
-fn headers_to_jsvalue(headers: Vec<(String, String)>) -> JsValue {
-    let array = js_sys::Array::new();
-    for (key, value) in headers {
-        let pair = js_sys::Array::new();
-        pair.push(&JsValue::from_str(&key));
-        pair.push(&JsValue::from_str(&value));
-        array.push(&pair.into());
+// Headers as a dict or a list of `(name, value)` pairs
+fn headers_from_py(headers: Option<&Bound<'_, PyAny>>) -> PyResult<Vec<(String, String)>> {
+    let Some(headers) = headers else {
+        return Ok(Vec::new());
+    };
+    if let Ok(dict) = headers.cast::<pyo3::types::PyDict>() {
+        return dict.iter().map(|(k, v)| Ok((k.extract()?, v.extract()?))).collect();
     }
-    array.into()
+    headers.extract()
 }
 
 #[pymethods]
@@ -240,59 +1094,273 @@ impl HttpClientPy {
         }
     }
 
-    fn get(&self, url: String, headers: Vec<(String, String)>) -> PyResult<String> {
+    // The GIL is released while the request is in flight; Python interceptors
+    // re-acquire it for their hooks
+    #[pyo3(signature = (url, headers=None))]
+    fn get(&self, py: Python<'_>, url: String, headers: Option<&Bound<'_, PyAny>>) -> PyResult<String> {
+        let request = HttpRequest::get(&url).with_headers(&headers_from_py(headers)?);
+        py.detach(|| self.inner.send(request))
+            .map(|response| response.text())
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    #[pyo3(signature = (url, headers=None, body=String::new()))]
+    fn post(&self, py: Python<'_>, url: String, headers: Option<&Bound<'_, PyAny>>, body: String) -> PyResult<String> {
+        let request = HttpRequest::post(&url, body).with_headers(&headers_from_py(headers)?);
+        py.detach(|| self.inner.send(request))
+            .map(|response| response.text())
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Register Python request/response hooks (see `interceptor_py`). `stage` is
+    /// `"outer"` (before the privacy policy, which still filters the request) or
+    /// `"inner"` (after it, seeing the request as sent).
+    #[pyo3(signature = (on_request=None, on_response=None, stage="outer", name="python"))]
+    fn register_interceptor(
+        &mut self,
+        on_request: Option<Py<PyAny>>,
+        on_response: Option<Py<PyAny>>,
+        stage: &str,
+        name: &str,
+    ) -> PyResult<()> {
+        let stage = InterceptorStage::parse(stage).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let interceptor = interceptor_py::PyCallbackInterceptor::new(name.to_string(), on_request, on_response);
+        self.inner.add_interceptor(stage, Arc::new(interceptor));
+        Ok(())
+    }
+
+    /// Remove all registered interceptors
+    fn clear_interceptors(&mut self) {
+        self.inner.clear_interceptors();
+    }
+
+    /// Names of the interceptors a request runs through, in order
+    fn interceptor_names(&self) -> Vec<String> {
+        self.inner.interceptor_names()
+    }
+
+    /// Select the image backend from a JSON config (see `ImageProviderConfig`)
+    fn set_image_provider(&mut self, config_json: String) -> PyResult<()> {
         self.inner
-            .get_sync(&url, headers_to_jsvalue(headers))
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(
-                e.as_string().unwrap_or("Unknown error".to_string())
-            ))
+            .cxx_set_image_provider(&config_json)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
     }
 
-    fn post(&self, url: String, headers: Vec<(String, String)>, body: String) -> PyResult<String> {
+    /// Apply a JSON privacy config (see `PrivacyConfig`)
+    fn set_privacy_config(&mut self, config_json: String) -> PyResult<()> {
+        let config = PrivacyConfig::from_json(&config_json).map_err(pyo3::exceptions::PyValueError::new_err)?;
         self.inner
-            .post_sync(&url, headers_to_jsvalue(headers), body)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(
-                e.as_string().unwrap_or("Unknown error".to_string())
-            ))
-    }
-
-    /// Generate an image using Stable Diffusion API and save it to a file (for Python)
-    fn generate_image(&self, prompt: String, width: u32, height: u32, steps: u32, output_path: String) -> PyResult<()> {
-        let url = "https://api.stability.ai/v1/generation/stable-diffusion-xl-beta-v2-2-2/text-to-image";
-        let headers = vec![
-            ("Authorization".to_string(), format!("Bearer {}", self.api_key)),
-            ("Content-Type".to_string(), "application/json".to_string()),
-            ("Cache-Control".to_string(), "no-cache".to_string()),
-        ];
-        let body = json!({
-            "prompt": prompt,
-            "width": width,
-            "height": height,
-            "steps": steps
-        })
-        .to_string();
-
-        let response = self.inner
-            .post_sync(&url, headers_to_jsvalue(headers), body)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(
-                e.as_string().unwrap_or("Unknown error".to_string())
-            ))?;
-
-        let json: serde_json::Value = serde_json::from_str(&response)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to parse response: {}", e)))?;
-        let image_data = json["artifacts"][0]["binary"]
-            .as_str()
-            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("No image data in response"))?;
-
-        // Decode base64 and save to file
-        let decoded = base64::engine::general_purpose::STANDARD.decode(image_data)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to decode image: {}", e)))?;
-        std::fs::write(&output_path, decoded)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to save image: {}", e)))?;
+            .set_privacy_policy(PrivacyPolicy::new(config))
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
 
+    #[pyo3(signature = (site=None))]
+    fn set_cookie_site(&mut self, site: Option<String>) {
+        self.inner.set_cookie_site(site);
+    }
+
+    #[pyo3(signature = (site=None))]
+    fn clear_cookies(&self, site: Option<String>) -> PyResult<()> {
+        self.inner
+            .clear_cookie_jar(site.as_deref())
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn cookie_partitions(&self) -> PyResult<String> {
+        self.inner
+            .cookie_partitions_json()
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Privacy budget counters and remaining tokens as JSON
+    fn rate_limit_stats(&self) -> PyResult<String> {
+        self.inner
+            .rate_limit_stats_json()
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn set_a2a_server(&mut self, url: String) {
+        self.inner.set_a2a_server(&url);
+    }
+
+    fn set_mcp_server(&mut self, url: String) {
+        self.inner.set_mcp_server(&url);
+    }
+
+    fn a2a_agent_card(&self, py: Python<'_>) -> PyResult<String> {
+        py.detach(|| self.inner.a2a_agent_card_json())
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn a2a_send_message(&self, py: Python<'_>, text: String) -> PyResult<String> {
+        py.detach(|| self.inner.a2a_send_message_json(&text))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn a2a_get_task(&self, py: Python<'_>, task_id: String) -> PyResult<String> {
+        py.detach(|| self.inner.a2a_get_task_json(&task_id))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn a2a_cancel_task(&self, py: Python<'_>, task_id: String) -> PyResult<String> {
+        py.detach(|| self.inner.a2a_cancel_task_json(&task_id))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Switch the proxy isolation identity
+    fn set_proxy_identity(&mut self, identity: String) -> PyResult<()> {
+        self.inner
+            .rotate_proxy_identity(&identity)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    /// Send requests through an Oblivious HTTP relay (see `OhttpConfig`)
+    fn configure_ohttp(&mut self, py: Python<'_>, config_json: String) -> PyResult<()> {
+        let config = OhttpConfig::from_json(&config_json).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        // May fetch the gateway's key configuration
+        let inner = &mut self.inner;
+        py.detach(|| inner.set_ohttp(config))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    fn disable_ohttp(&mut self) {
+        self.inner.disable_ohttp();
+    }
+
+    /// Retry transient failures (see `RetryConfig`)
+    fn configure_retries(&mut self, config_json: String) -> PyResult<()> {
+        let config = RetryConfig::from_json(&config_json).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.inner.set_retry_config(config);
         Ok(())
     }
 
+    fn disable_retries(&mut self) {
+        self.inner.disable_retries();
+    }
+
+    /// Cache GET responses (see `CacheConfig`)
+    fn configure_cache(&mut self, config_json: String) -> PyResult<()> {
+        let config = CacheConfig::from_json(&config_json).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        self.inner.set_cache_config(config).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn disable_cache(&mut self) {
+        self.inner.disable_cache();
+    }
+
+    fn clear_cache(&self) -> PyResult<()> {
+        self.inner.clear_http_cache().map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Circuit breaker state per host as JSON
+    fn circuit_states(&self) -> PyResult<String> {
+        self.inner
+            .circuit_states_json()
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Configure S3-compatible artifact storage from a JSON config
+    fn configure_storage(&mut self, py: Python<'_>, config_json: String) -> PyResult<()> {
+        let config = StorageConfig::from_json(&config_json).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        // Loading AWS credentials may query the network
+        let inner = &mut self.inner;
+        py.detach(|| inner.set_artifact_storage(config))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)
+    }
+
+    /// Generate images with the configured provider and save them (for Python).
+    /// Local files: with `count > 1` files are written as `<stem>_<n>.<ext>` and the paths are returned.
+    /// `output_uri="s3://bucket/prefix"`: images are uploaded encrypted and presigned URLs are
+    /// returned; without `configure_storage`, pass the base64 `encryption_key` to use.
+    #[pyo3(signature = (prompt, width, height, steps, output_path=None, negative_prompt=None, seed=None, cfg_scale=None, sampler=None, count=1, format="png", output_uri=None, encryption_key=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn generate_image(
+        &self,
+        py: Python<'_>,
+        prompt: String,
+        width: u32,
+        height: u32,
+        steps: u32,
+        output_path: Option<String>,
+        negative_prompt: Option<String>,
+        seed: Option<u64>,
+        cfg_scale: Option<f32>,
+        sampler: Option<String>,
+        count: u32,
+        format: &str,
+        output_uri: Option<String>,
+        encryption_key: Option<String>,
+    ) -> PyResult<Vec<String>> {
+        if output_path.is_none() && output_uri.is_none() {
+            return Err(pyo3::exceptions::PyValueError::new_err("output_path or output_uri is required"));
+        }
+        let request = ImageRequest {
+            negative_prompt,
+            seed,
+            cfg_scale,
+            sampler,
+            count,
+            format: ImageFormat::parse(format).map_err(pyo3::exceptions::PyValueError::new_err)?,
+            ..ImageRequest::new(&prompt, width, height, steps)
+        };
+        let images = py
+            .detach(|| self.inner.generate_images(&request))
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+
+        let mut written = Vec::new();
+        if let Some(uri) = &output_uri {
+            let stored = py
+                .detach(|| self.inner.store_images(&images, Some(uri), encryption_key.as_deref()))
+                .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+            written.extend(stored.into_iter().map(|artifact| artifact.presigned_url));
+        }
+        let Some(output_path) = output_path else {
+            return Ok(written);
+        };
+
+        let path = std::path::Path::new(&output_path);
+        for (i, image) in images.iter().enumerate() {
+            let target = if images.len() == 1 {
+                path.to_path_buf()
+            } else {
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+                path.with_file_name(format!("{}_{}.{}", stem, i + 1, image.format.as_str()))
+            };
+            std::fs::write(&target, &image.data)
+                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to save image: {}", e)))?;
+            written.push(target.to_string_lossy().into_owned());
+        }
+
+        Ok(written)
+    }
+
+    fn prompt(&self, py: Python<'_>, prompt: String) -> PyResult<String> {
+        Ok(py.detach(|| self.inner.prompt_json(&prompt)))
+    }
+
+    #[pyo3(signature = (config_json=None))]
+    fn enable_pii_scrubbing(&mut self, config_json: Option<String>) -> PyResult<()> {
+        let config = match config_json {
+            Some(json) => PiiConfig::from_json(&json).map_err(pyo3::exceptions::PyValueError::new_err)?,
+            None => PiiConfig::default(),
+        };
+        self.inner.set_pii_config(&config).map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn disable_pii_scrubbing(&mut self) {
+        self.inner.disable_pii_scrubbing();
+    }
+
+    fn scrub_chat(&self, messages_json: String) -> PyResult<String> {
+        self.inner
+            .scrub_chat_json(&messages_json)
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    fn rehydrate(&self, response: String) -> String {
+        self.inner.rehydrate(&response)
+    }
+
     fn __str__(&self) -> String {
         format!(
             "HttpClientPy(api_key='{}', openai_url='{}')",
@@ -301,18 +1369,240 @@ impl HttpClientPy {
     }
 }
 
+// Create `privacy_http_sdk.<name>` and register it in `sys.modules` so that
+// `import privacy_http_sdk.<name>` works as well as attribute access
+fn add_submodule(
+    parent: &Bound<'_, PyModule>,
+    name: &str,
+    init: impl FnOnce(&Bound<'_, PyModule>) -> PyResultType<()>,
+) -> PyResultType<()> {
+    let py = parent.py();
+    let qualified = format!("privacy_http_sdk.{}", name);
+    let module = PyModule::new(py, name)?;
+    init(&module)?;
+    module.setattr("__name__", &qualified)?;
+    parent.add_submodule(&module)?;
+    py.import("sys")?.getattr("modules")?.set_item(qualified, &module)?;
+    Ok(())
+}
+
+/// Native part of the `privacy_http_sdk` package (`python/privacy_http_sdk`)
 #[pymodule]
-fn http_client_module(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResultType<()> {
+#[pyo3(name = "_native")]
+fn privacy_http_sdk(m: &Bound<'_, PyModule>) -> PyResultType<()> {
     m.add_class::<HttpClientPy>()?;
+    add_submodule(m, "http", |http| {
+        http.add_class::<HttpClientPy>()?;
+        http.add_class::<interceptor_py::PyRequestView>()?;
+        http.add_class::<interceptor_py::PyResponseView>()?;
+        http.add_class::<mcp_py::PyMcpClient>()
+    })?;
+    add_submodule(m, "identity", did_nostr_py::init_did_nostr_module)?;
+    add_submodule(m, "codecs", codec_py::init_codec_module)?;
+    add_submodule(m, "policy", privacy_py::init_privacy_module)?;
     Ok(())
 }
 
-#[cxx::bridge]
+// C++ bindings via cxx: `Result` errors are thrown as `rust::Error` (a `std::exception`)
+#[cxx::bridge(namespace = "privacy_http_sdk")]
 mod ffi {
-    #[allow(dead_code)]
-    unsafe extern "C++" {
-        include!("lib.rs.h");
+    /// Header name/value pair
+    struct Header {
+        key: String,
+        value: String,
+    }
+
+    /// Full HTTP response
+    struct Response {
+        status: u16,
+        headers: Vec<Header>,
+        body: Vec<u8>,
+    }
+
+    extern "Rust" {
+        type HttpClient;
 
         fn greet(name: &str) -> String;
+
+        /// Client from a `ClientConfig` JSON string (empty: defaults)
+        fn new_http_client(config_json: &str) -> Result<Box<HttpClient>>;
+
+        /// Apply a JSON privacy config
+        #[cxx_name = "set_privacy_config"]
+        fn cxx_set_privacy_config(self: &mut HttpClient, config_json: &str) -> Result<()>;
+
+        /// Select the image backend from a JSON config
+        #[cxx_name = "set_image_provider"]
+        fn cxx_set_image_provider(self: &mut HttpClient, config_json: &str) -> Result<()>;
+
+        /// Send a request through the privacy pipeline
+        #[cxx_name = "send"]
+        fn cxx_send(self: &HttpClient, method: &str, url: &str, headers: Vec<Header>, body: &[u8]) -> Result<Response>;
+
+        #[cxx_name = "get"]
+        fn cxx_get(self: &HttpClient, url: &str, headers: Vec<Header>) -> Result<Response>;
+
+        #[cxx_name = "post"]
+        fn cxx_post(self: &HttpClient, url: &str, headers: Vec<Header>, body: &str) -> Result<Response>;
+
+        /// Generate one image and write it to `output_path`
+        #[cxx_name = "generate_image"]
+        fn cxx_generate_image(
+            self: &HttpClient,
+            prompt: &str,
+            width: u32,
+            height: u32,
+            steps: u32,
+            output_path: &str,
+        ) -> Result<()>;
+    }
+}
+
+fn new_http_client(config_json: &str) -> Result<Box<HttpClient>, String> {
+    let config = match config_json.trim() {
+        "" => ClientConfig::default(),
+        json => ClientConfig::from_json(json)?,
+    };
+    HttpClient::from_config(config).map(Box::new)
+}
+
+impl From<HttpResponse> for ffi::Response {
+    fn from(response: HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response
+                .headers
+                .into_iter()
+                .map(|(key, value)| ffi::Header { key, value })
+                .collect(),
+            body: response.body,
+        }
+    }
+}
+
+impl HttpClient {
+    fn cxx_set_privacy_config(&mut self, config_json: &str) -> Result<(), String> {
+        self.set_privacy_policy(PrivacyPolicy::new(PrivacyConfig::from_json(config_json)?))
+    }
+
+    fn cxx_set_image_provider(&mut self, config_json: &str) -> Result<(), String> {
+        self.image_provider = ImageProviderConfig::from_json(config_json)?.build();
+        Ok(())
+    }
+
+    fn cxx_send(&self, method: &str, url: &str, headers: Vec<ffi::Header>, body: &[u8]) -> Result<ffi::Response, String> {
+        let headers: Vec<(String, String)> = headers.into_iter().map(|h| (h.key, h.value)).collect();
+        let request = HttpRequest::new(method, url).with_headers(&headers).with_body(body.to_vec());
+        self.send(request).map(ffi::Response::from)
+    }
+
+    fn cxx_get(&self, url: &str, headers: Vec<ffi::Header>) -> Result<ffi::Response, String> {
+        self.cxx_send("GET", url, headers, &[])
+    }
+
+    fn cxx_post(&self, url: &str, headers: Vec<ffi::Header>, body: &str) -> Result<ffi::Response, String> {
+        self.cxx_send("POST", url, headers, body.as_bytes())
+    }
+
+    fn cxx_generate_image(&self, prompt: &str, width: u32, height: u32, steps: u32, output_path: &str) -> Result<(), String> {
+        let images = self.generate_images(&ImageRequest::new(prompt, width, height, steps))?;
+        let image = images.first().ok_or("No image returned")?;
+        std::fs::write(output_path, &image.data).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did_nostr::{NostrSigner, NostrVerifier};
+
+    #[test]
+    fn test_identity_rotation_keeps_budget_and_cookies() {
+        let config = json!({
+            "proxy": { "url": "socks5h://127.0.0.1:9050", "isolation": "per_identity", "identity": "alice" },
+            "budget": { "session": { "rate": 0.001, "burst": 2 }, "mode": "fail_fast" },
+            "cookies": {}
+        });
+        let mut client = HttpClient::new(String::new());
+        client.set_privacy_config(&config.to_string()).unwrap();
+        // Loopback is reached directly; the closed port fails after the budget is charged
+        let url = "http://127.0.0.1:1/";
+        for _ in 0..2 {
+            assert!(!client.send(HttpRequest::get(url)).unwrap_err().contains("Rate limit"));
+        }
+        let set_cookie = HttpResponse {
+            status: 200,
+            headers: vec![("Set-Cookie".to_string(), "sid=1".to_string())],
+            body: Vec::new(),
+        };
+        client.cookie_jar.as_ref().unwrap().store(url, None, &set_cookie).unwrap();
+
+        client.rotate_proxy_identity("bob").unwrap();
+        assert!(client.send(HttpRequest::get(url)).unwrap_err().contains("Rate limit"));
+        assert_eq!(client.cookie_jar.as_ref().unwrap().partitions().len(), 1);
+
+        // Changing the budget itself starts a fresh one
+        let mut config = config;
+        config["budget"]["session"]["burst"] = json!(3);
+        client.set_privacy_config(&config.to_string()).unwrap();
+        assert!(!client.send(HttpRequest::get(url)).unwrap_err().contains("Rate limit"));
+        assert_eq!(client.cookie_jar.as_ref().unwrap().partitions().len(), 1);
+    }
+
+    #[test]
+    fn test_public_hosts_only_refuses_internal_addresses() {
+        use reqwest::dns::Resolve;
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let _ = stream.read(&mut [0; 1024]);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+            }
+        });
+
+        let mut client = HttpClient::new(String::new());
+        client.set_public_hosts_only(true).unwrap();
+        for url in [format!("http://localhost:{}/", port), format!("http://127.0.0.1:{}/", port), "http://[::1]/".to_string()] {
+            assert!(client.send(HttpRequest::get(&url)).unwrap_err().contains("not a public host"), "{}", url);
+        }
+        // Names resolving to internal addresses are dropped at connect time
+        let resolver = transport::PublicOnlyResolver::default();
+        let resolved = client.runtime.block_on(resolver.resolve("localhost".parse().unwrap()));
+        assert!(resolved.err().unwrap().to_string().contains("does not resolve to a public address"));
+
+        client.set_public_hosts_only(false).unwrap();
+        assert_eq!(client.send(HttpRequest::get(&format!("http://localhost:{}/", port))).unwrap().text(), "ok");
+    }
+
+    /// Signs like `SigningInterceptor`, then records the request instead of sending it
+    struct SignAndRecord(interceptor::SigningInterceptor, std::sync::Mutex<Option<HttpRequest>>);
+
+    impl Interceptor for SignAndRecord {
+        fn name(&self) -> &str {
+            "sign-and-record"
+        }
+
+        fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+            self.0.on_request(request)?;
+            *self.1.lock().unwrap() = Some(request.clone());
+            Ok(Some(HttpResponse { status: 200, headers: Vec::new(), body: Vec::new() }))
+        }
+    }
+
+    #[test]
+    fn test_send_with_signs_the_scrubbed_request() {
+        let mut client = HttpClient::new(String::new());
+        client.enable_pii_scrubbing(None).unwrap();
+        let signer = SignAndRecord(interceptor::SigningInterceptor::new(NostrSigner::generate()), Default::default());
+        let request = HttpRequest::post("https://mcp.example.com/mcp?tenant=a", "mail alice@example.com");
+        client.send_with(request, &signer).unwrap();
+
+        let sent = signer.1.lock().unwrap().take().unwrap();
+        assert!(!String::from_utf8_lossy(&sent.body).contains("alice@example.com"));
+        assert!(NostrVerifier::verify_request("POST", "/mcp?tenant=a", &sent.headers, &sent.body).valid);
     }
 }
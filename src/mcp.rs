@@ -0,0 +1,546 @@
+//! MCP Client Module
+//!
+//! This module implements a Model Context Protocol client (protocol revision
+//! `2025-06-18`) for the MCP tools exposed by our Node/Python servers and third-party
+//! MCP servers.
+//!
+//! # Features
+//! - Streamable HTTP transport over any [`Transport`] (normally `HttpClient`, so all
+//!   traffic goes through the privacy pipeline)
+//! - `application/json` and `text/event-stream` responses, `Mcp-Session-Id` sessions
+//! - Optional DID-NOSTR signing of every HTTP request (`X-DID`, `X-Timestamp`, `X-Nonce`, `X-Signature`)
+//! - stdio transport (newline-delimited JSON-RPC to a child process)
+//! - initialize, tools, resources and prompts, with pagination handled internally
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio as ProcessStdio};
+use crate::did_nostr::NostrSigner;
+use crate::interceptor::SigningInterceptor;
+use crate::sse;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+/// Protocol revision requested by this client
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+/// Protocol revisions this client can talk to
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+/// Session header assigned by Streamable HTTP servers
+pub const SESSION_HEADER: &str = "Mcp-Session-Id";
+/// Negotiated protocol version header sent after initialization
+pub const PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+
+/// Message transport for JSON-RPC traffic
+pub trait McpTransport: Send {
+    /// Send a request and return the JSON-RPC response with the same id
+    fn request(&mut self, message: &Value) -> Result<Value, String>;
+
+    /// Send a notification (no response expected)
+    fn notify(&mut self, message: &Value) -> Result<(), String>;
+
+    /// Record the protocol version negotiated during initialization
+    fn set_protocol_version(&mut self, _version: &str) {}
+
+    /// Terminate the session
+    fn close(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Streamable HTTP transport
+pub struct StreamableHttp {
+    transport: Box<dyn Transport + Send + Sync>,
+    url: String,
+    headers: Vec<(String, String)>,
+    signer: Option<SigningInterceptor>,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+}
+
+impl std::fmt::Debug for StreamableHttp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamableHttp")
+            .field("url", &self.url)
+            .field("signer", &self.signer)
+            .field("session_id", &self.session_id)
+            .field("protocol_version", &self.protocol_version)
+            .finish()
+    }
+}
+
+impl StreamableHttp {
+    /// Create a transport for the MCP endpoint at `url`
+    pub fn new(transport: Box<dyn Transport + Send + Sync>, url: &str) -> Self {
+        Self {
+            transport,
+            url: url.to_string(),
+            headers: Vec::new(),
+            signer: None,
+            session_id: None,
+            protocol_version: None,
+        }
+    }
+
+    /// Extra headers sent with every request (e.g. `Authorization`)
+    pub fn with_headers(mut self, headers: &[(String, String)]) -> Self {
+        self.headers.extend_from_slice(headers);
+        self
+    }
+
+    /// Sign every request with a DID-NOSTR key. The signature is added as the
+    /// transport's innermost step, so it covers the request as sent.
+    pub fn with_signer(mut self, signer: NostrSigner) -> Self {
+        self.signer = Some(SigningInterceptor::new(signer));
+        self
+    }
+
+    /// Session id assigned by the server, if any
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    fn build_request(&self, method: &str, body: Vec<u8>) -> HttpRequest {
+        let mut request = HttpRequest::new(method, &self.url)
+            .with_header("Accept", "application/json, text/event-stream")
+            .with_headers(&self.headers);
+        if !body.is_empty() {
+            request = request.with_header("Content-Type", "application/json");
+        }
+        if let Some(session_id) = &self.session_id {
+            request = request.with_header(SESSION_HEADER, session_id);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.with_header(PROTOCOL_VERSION_HEADER, version);
+        }
+        request.with_body(body)
+    }
+
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        match &self.signer {
+            Some(signer) => self.transport.send_with(request, signer),
+            None => self.transport.send(request),
+        }
+    }
+
+    fn post(&mut self, message: &Value) -> Result<HttpResponse, String> {
+        let request = self.build_request("POST", message.to_string().into_bytes());
+        let response = self.send(request)?;
+        if response.status == 404 && self.session_id.is_some() {
+            self.session_id = None;
+            return Err("MCP session expired".to_string());
+        }
+        let response = response.error_for_status()?;
+        if let Some(session_id) = response.header(SESSION_HEADER) {
+            self.session_id = Some(session_id.to_string());
+        }
+        Ok(response)
+    }
+}
+
+impl McpTransport for StreamableHttp {
+    fn request(&mut self, message: &Value) -> Result<Value, String> {
+        let response = self.post(message)?;
+        let content_type = response.header("Content-Type").unwrap_or_default().to_ascii_lowercase();
+        if !content_type.starts_with("text/event-stream") {
+            return response.json();
+        }
+        // The server may interleave notifications and requests before the response
+        sse::parse_events(&response.body)
+            .into_iter()
+            .filter_map(|event| serde_json::from_str::<Value>(&event.data).ok())
+            .find(|reply| is_response_to(reply, message))
+            .ok_or_else(|| "Event stream ended without a response".to_string())
+    }
+
+    fn notify(&mut self, message: &Value) -> Result<(), String> {
+        self.post(message).map(|_| ())
+    }
+
+    fn set_protocol_version(&mut self, version: &str) {
+        self.protocol_version = Some(version.to_string());
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        if self.session_id.is_none() {
+            return Ok(());
+        }
+        let request = self.build_request("DELETE", Vec::new());
+        let response = self.send(request)?;
+        self.session_id = None;
+        // 405 means the server does not allow clients to terminate sessions
+        if response.is_success() || response.status == 405 {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}: {}", response.status, response.text()))
+        }
+    }
+}
+
+/// stdio transport (spawns the server as a child process)
+#[derive(Debug)]
+pub struct Stdio {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Stdio {
+    /// Spawn `command` with `args`; stderr is inherited for server logs
+    pub fn spawn(command: &str, args: &[String], env: &[(String, String)]) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env.iter().map(|(k, v)| (k, v)))
+            .stdin(ProcessStdio::piped())
+            .stdout(ProcessStdio::piped())
+            .stderr(ProcessStdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to spawn MCP server {}: {}", command, e))?;
+        let stdin = child.stdin.take().ok_or("Failed to open MCP server stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open MCP server stdout")?;
+        Ok(Self { child, stdin, stdout: BufReader::new(stdout) })
+    }
+
+    fn write(&mut self, message: &Value) -> Result<(), String> {
+        writeln!(self.stdin, "{}", message)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Failed to write to MCP server: {}", e))
+    }
+
+    fn read(&mut self) -> Result<Value, String> {
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .map_err(|e| format!("Failed to read from MCP server: {}", e))?;
+            if read == 0 {
+                return Err("MCP server closed the connection".to_string());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(&line).map_err(|e| format!("Invalid message from MCP server: {}", e));
+        }
+    }
+}
+
+impl McpTransport for Stdio {
+    fn request(&mut self, message: &Value) -> Result<Value, String> {
+        self.write(message)?;
+        loop {
+            let reply = self.read()?;
+            if is_response_to(&reply, message) {
+                return Ok(reply);
+            }
+            // Server-to-client requests (sampling, roots, ...) are not supported
+            if reply.get("method").is_some() && reply.get("id").is_some() {
+                self.write(&json!({
+                    "jsonrpc": "2.0",
+                    "id": reply["id"],
+                    "error": { "code": -32601, "message": "Method not found" }
+                }))?;
+            }
+        }
+    }
+
+    fn notify(&mut self, message: &Value) -> Result<(), String> {
+        self.write(message)
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        let _ = self.child.kill();
+        self.child.wait().map(|_| ()).map_err(|e| format!("Failed to stop MCP server: {}", e))
+    }
+}
+
+impl Drop for Stdio {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn is_response_to(reply: &Value, request: &Value) -> bool {
+    reply.get("method").is_none() && reply.get("id") == request.get("id")
+}
+
+/// MCP client
+pub struct McpClient {
+    transport: Box<dyn McpTransport>,
+    next_id: u64,
+    server_info: Option<Value>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("next_id", &self.next_id)
+            .field("server_info", &self.server_info)
+            .finish()
+    }
+}
+
+impl McpClient {
+    pub fn new(transport: Box<dyn McpTransport>) -> Self {
+        Self { transport, next_id: 1, server_info: None }
+    }
+
+    /// Client for a Streamable HTTP endpoint
+    pub fn http(transport: Box<dyn Transport + Send + Sync>, url: &str) -> Self {
+        Self::new(Box::new(StreamableHttp::new(transport, url)))
+    }
+
+    /// Client for a stdio server
+    pub fn stdio(command: &str, args: &[String]) -> Result<Self, String> {
+        Ok(Self::new(Box::new(Stdio::spawn(command, args, &[])?)))
+    }
+
+    /// Initialize result returned by the server (after [`McpClient::initialize`])
+    pub fn server_info(&self) -> Option<&Value> {
+        self.server_info.as_ref()
+    }
+
+    /// Perform the initialization handshake
+    pub fn initialize(&mut self) -> Result<Value, String> {
+        let result = self.call(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+        )?;
+        let version = result["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION);
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+            return Err(format!("Unsupported MCP protocol version: {}", version));
+        }
+        self.transport.set_protocol_version(version);
+        self.transport.notify(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))?;
+        self.server_info = Some(result.clone());
+        Ok(result)
+    }
+
+    /// Check that the server is alive
+    pub fn ping(&mut self) -> Result<(), String> {
+        self.call("ping", json!({})).map(|_| ())
+    }
+
+    /// List all tools
+    pub fn list_tools(&mut self) -> Result<Vec<Value>, String> {
+        self.paginate("tools/list", "tools")
+    }
+
+    /// Call a tool. Tool failures are reported in the result (`isError: true`), not as `Err`.
+    pub fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value, String> {
+        self.call("tools/call", json!({ "name": name, "arguments": arguments }))
+    }
+
+    /// List all resources
+    pub fn list_resources(&mut self) -> Result<Vec<Value>, String> {
+        self.paginate("resources/list", "resources")
+    }
+
+    /// Read a resource by URI
+    pub fn read_resource(&mut self, uri: &str) -> Result<Value, String> {
+        self.call("resources/read", json!({ "uri": uri }))
+    }
+
+    /// List all prompts
+    pub fn list_prompts(&mut self) -> Result<Vec<Value>, String> {
+        self.paginate("prompts/list", "prompts")
+    }
+
+    /// Get a prompt with its arguments filled in
+    pub fn get_prompt(&mut self, name: &str, arguments: Value) -> Result<Value, String> {
+        self.call("prompts/get", json!({ "name": name, "arguments": arguments }))
+    }
+
+    /// Terminate the session (HTTP) or stop the server process (stdio)
+    pub fn close(&mut self) -> Result<(), String> {
+        self.transport.close()
+    }
+
+    /// Send a JSON-RPC request and return its result
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let mut reply = self.transport.request(&message)?;
+        if let Some(error) = reply.get("error") {
+            return Err(format!(
+                "MCP error {}: {}",
+                error["code"],
+                error["message"].as_str().unwrap_or("Unknown error")
+            ));
+        }
+        reply
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| format!("MCP response to {} has no result", method))
+    }
+
+    fn paginate(&mut self, method: &str, field: &str) -> Result<Vec<Value>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let mut result = self.call(method, params)?;
+            if let Some(Value::Array(page)) = result.get_mut(field).map(Value::take) {
+                items.extend(page);
+            }
+            match result["nextCursor"].as_str() {
+                Some(next) if !next.is_empty() => cursor = Some(next.to_string()),
+                _ => return Ok(items),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::did_nostr::NostrVerifier;
+    use std::sync::{Arc, Mutex};
+
+    /// Scripted transport recording every request
+    #[derive(Clone, Default)]
+    struct MockTransport {
+        requests: Arc<Mutex<Vec<HttpRequest>>>,
+        responses: Arc<Mutex<Vec<HttpResponse>>>,
+    }
+
+    impl MockTransport {
+        fn reply(&self, status: u16, headers: &[(&str, &str)], body: &str) {
+            self.responses.lock().unwrap().push(HttpResponse {
+                status,
+                headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+                body: body.as_bytes().to_vec(),
+            });
+        }
+
+        fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+            self.requests.lock().unwrap().push(request);
+            let mut responses = self.responses.lock().unwrap();
+            if responses.is_empty() {
+                return Err("no scripted response".to_string());
+            }
+            Ok(responses.remove(0))
+        }
+    }
+
+    fn initialize_reply(mock: &MockTransport) {
+        mock.reply(
+            200,
+            &[("Content-Type", "application/json"), ("Mcp-Session-Id", "abc")],
+            r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"test","version":"1"}}}"#,
+        );
+        mock.reply(202, &[], "");
+    }
+
+    #[test]
+    fn test_http_initialize_and_session() {
+        let mock = MockTransport::default();
+        initialize_reply(&mock);
+        mock.reply(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo"}],"nextCursor":"p2"}}"#,
+        );
+        mock.reply(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[{"name":"add"}]}}"#,
+        );
+        mock.reply(200, &[], "");
+
+        let mut client = McpClient::http(Box::new(mock.clone()), "https://mcp.example.com/mcp");
+        let info = client.initialize().unwrap();
+        assert_eq!(info["serverInfo"]["name"], "test");
+        let tools = client.list_tools().unwrap();
+        assert_eq!(tools.len(), 2);
+        client.close().unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[0].header(SESSION_HEADER), None);
+        assert!(requests[0].header("Accept").unwrap().contains("text/event-stream"));
+        assert_eq!(requests[1].header(SESSION_HEADER), Some("abc"));
+        assert_eq!(requests[1].header(PROTOCOL_VERSION_HEADER), Some("2025-06-18"));
+        let page2: Value = serde_json::from_slice(&requests[3].body).unwrap();
+        assert_eq!(page2["params"]["cursor"], "p2");
+        assert_eq!(requests[4].method, "DELETE");
+    }
+
+    #[test]
+    fn test_http_event_stream_response() {
+        let mock = MockTransport::default();
+        initialize_reply(&mock);
+        mock.reply(
+            200,
+            &[("Content-Type", "text/event-stream")],
+            "event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{}}\n\n\
+             event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"content\":[{\"type\":\"text\",\"text\":\"hi\"}]}}\n\n",
+        );
+
+        let mut client = McpClient::http(Box::new(mock), "https://mcp.example.com/mcp");
+        client.initialize().unwrap();
+        let result = client.call_tool("echo", json!({ "text": "hi" })).unwrap();
+        assert_eq!(result["content"][0]["text"], "hi");
+    }
+
+    #[test]
+    fn test_json_rpc_error() {
+        let mock = MockTransport::default();
+        mock.reply(
+            200,
+            &[("Content-Type", "application/json")],
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Unknown tool"}}"#,
+        );
+        let mut client = McpClient::http(Box::new(mock), "https://mcp.example.com/mcp");
+        let err = client.call_tool("missing", json!({})).unwrap_err();
+        assert!(err.contains("-32602") && err.contains("Unknown tool"));
+    }
+
+    #[test]
+    fn test_signed_requests() {
+        let mock = MockTransport::default();
+        mock.reply(200, &[("Content-Type", "application/json")], r#"{"jsonrpc":"2.0","id":1,"result":{}}"#);
+        let transport = StreamableHttp::new(Box::new(mock.clone()), "https://mcp.example.com/mcp?tenant=a")
+            .with_signer(NostrSigner::generate());
+        let mut client = McpClient::new(Box::new(transport));
+        client.ping().unwrap();
+
+        let request = &mock.requests()[0];
+        let result = NostrVerifier::verify_request("POST", "/mcp?tenant=a", &request.headers, &request.body);
+        assert!(result.valid);
+    }
+
+    #[test]
+    fn test_stdio_transport() {
+        let script = r#"
+            read line
+            echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-03-26","capabilities":{},"serverInfo":{"name":"sh"}}}'
+            read line
+            read line
+            echo '{"jsonrpc":"2.0","method":"notifications/message","params":{}}'
+            echo '{"jsonrpc":"2.0","id":2,"result":{"contents":[{"uri":"file:///a","text":"A"}]}}'
+        "#;
+        let mut client = McpClient::stdio("sh", &["-c".to_string(), script.to_string()]).unwrap();
+        let info = client.initialize().unwrap();
+        assert_eq!(info["protocolVersion"], "2025-03-26");
+        let resource = client.read_resource("file:///a").unwrap();
+        assert_eq!(resource["contents"][0]["text"], "A");
+        client.close().unwrap();
+    }
+}
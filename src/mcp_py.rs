@@ -0,0 +1,137 @@
+//! Python PyO3 bindings for the MCP client
+
+use pyo3::prelude::*;
+use std::sync::Mutex;
+use crate::did_nostr::NostrSigner;
+use crate::interceptor::Interceptor;
+use crate::mcp::*;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+use crate::HttpClientPy;
+
+/// Python wrapper for McpClient (results are returned as JSON strings)
+#[pyclass(name = "McpClient", module = "privacy_http_sdk.http")]
+pub struct PyMcpClient {
+    inner: Mutex<McpClient>,
+}
+
+/// The caller's client, so its policy, interceptors and budget apply to MCP traffic
+struct SharedClient(Py<HttpClientPy>);
+
+impl Transport for SharedClient {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        Python::attach(|py| {
+            let client = self.0.try_borrow(py).map_err(|e| e.to_string())?;
+            let inner = &client.inner;
+            py.detach(|| inner.send(request))
+        })
+    }
+
+    fn send_with(&self, request: HttpRequest, interceptor: &dyn Interceptor) -> Result<HttpResponse, String> {
+        Python::attach(|py| {
+            let client = self.0.try_borrow(py).map_err(|e| e.to_string())?;
+            let inner = &client.inner;
+            py.detach(|| inner.send_with(request, interceptor))
+        })
+    }
+}
+
+fn runtime_err(e: String) -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err(e)
+}
+
+fn parse_json(json: Option<String>) -> PyResult<serde_json::Value> {
+    match json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid arguments JSON: {}", e))),
+        None => Ok(serde_json::json!({})),
+    }
+}
+
+#[pymethods]
+impl PyMcpClient {
+    /// Connect to a Streamable HTTP endpoint through `client`, defaulting to its
+    /// MCP server. `signer_secret` (hex) enables DID-NOSTR signing of every request.
+    #[staticmethod]
+    #[pyo3(signature = (client, url=None, headers=None, signer_secret=None))]
+    fn http(
+        py: Python<'_>,
+        client: Py<HttpClientPy>,
+        url: Option<String>,
+        headers: Option<Vec<(String, String)>>,
+        signer_secret: Option<String>,
+    ) -> PyResult<Self> {
+        let url = client.borrow(py).inner.mcp_endpoint(url.as_deref())
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        let mut transport = StreamableHttp::new(Box::new(SharedClient(client)), &url)
+            .with_headers(&headers.unwrap_or_default());
+        if let Some(secret) = signer_secret {
+            let signer = NostrSigner::from_secret_hex(&secret).map_err(pyo3::exceptions::PyValueError::new_err)?;
+            transport = transport.with_signer(signer);
+        }
+        Ok(PyMcpClient { inner: Mutex::new(McpClient::new(Box::new(transport))) })
+    }
+
+    /// Spawn a stdio server process
+    #[staticmethod]
+    #[pyo3(signature = (command, args=None, env=None))]
+    fn stdio(
+        command: String,
+        args: Option<Vec<String>>,
+        env: Option<Vec<(String, String)>>,
+    ) -> PyResult<Self> {
+        let transport = Stdio::spawn(&command, &args.unwrap_or_default(), &env.unwrap_or_default())
+            .map_err(runtime_err)?;
+        Ok(PyMcpClient { inner: Mutex::new(McpClient::new(Box::new(transport))) })
+    }
+
+    /// Perform the initialization handshake, returning the server's initialize result
+    fn initialize(&self) -> PyResult<String> {
+        let result = self.inner.lock().unwrap().initialize().map_err(runtime_err)?;
+        Ok(result.to_string())
+    }
+
+    /// List all tools as a JSON array
+    fn list_tools(&self) -> PyResult<String> {
+        let tools = self.inner.lock().unwrap().list_tools().map_err(runtime_err)?;
+        Ok(serde_json::Value::Array(tools).to_string())
+    }
+
+    /// Call a tool with JSON arguments
+    #[pyo3(signature = (name, arguments_json=None))]
+    fn call_tool(&self, name: String, arguments_json: Option<String>) -> PyResult<String> {
+        let arguments = parse_json(arguments_json)?;
+        let result = self.inner.lock().unwrap().call_tool(&name, arguments).map_err(runtime_err)?;
+        Ok(result.to_string())
+    }
+
+    /// List all resources as a JSON array
+    fn list_resources(&self) -> PyResult<String> {
+        let resources = self.inner.lock().unwrap().list_resources().map_err(runtime_err)?;
+        Ok(serde_json::Value::Array(resources).to_string())
+    }
+
+    /// Read a resource by URI
+    fn read_resource(&self, uri: String) -> PyResult<String> {
+        let result = self.inner.lock().unwrap().read_resource(&uri).map_err(runtime_err)?;
+        Ok(result.to_string())
+    }
+
+    /// List all prompts as a JSON array
+    fn list_prompts(&self) -> PyResult<String> {
+        let prompts = self.inner.lock().unwrap().list_prompts().map_err(runtime_err)?;
+        Ok(serde_json::Value::Array(prompts).to_string())
+    }
+
+    /// Get a prompt with JSON arguments
+    #[pyo3(signature = (name, arguments_json=None))]
+    fn get_prompt(&self, name: String, arguments_json: Option<String>) -> PyResult<String> {
+        let arguments = parse_json(arguments_json)?;
+        let result = self.inner.lock().unwrap().get_prompt(&name, arguments).map_err(runtime_err)?;
+        Ok(result.to_string())
+    }
+
+    /// Terminate the session or stop the server process
+    fn close(&self) -> PyResult<()> {
+        self.inner.lock().unwrap().close().map_err(runtime_err)
+    }
+}
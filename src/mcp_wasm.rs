@@ -0,0 +1,85 @@
+//! WASM bindings for the MCP client via wasm-bindgen (Streamable HTTP only)
+
+use wasm_bindgen::prelude::*;
+use crate::did_nostr::NostrSigner;
+use crate::mcp::*;
+use crate::HttpClient;
+
+/// JavaScript wrapper for McpClient (results are returned as JSON strings)
+#[wasm_bindgen]
+pub struct JsMcpClient {
+    inner: McpClient,
+}
+
+fn js_err(e: String) -> JsValue {
+    JsValue::from_str(&e)
+}
+
+fn parse_json(json: Option<String>) -> Result<serde_json::Value, JsValue> {
+    match json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid arguments JSON: {}", e))),
+        None => Ok(serde_json::json!({})),
+    }
+}
+
+#[wasm_bindgen]
+impl JsMcpClient {
+    /// Connect to a Streamable HTTP endpoint through `client`, defaulting to its
+    /// MCP server. The client (with its policy, interceptors and budget) moves
+    /// into the MCP client. `signer_secret` (hex) enables DID-NOSTR signing.
+    #[wasm_bindgen(constructor)]
+    pub fn new(client: HttpClient, url: Option<String>, signer_secret: Option<String>) -> Result<JsMcpClient, JsValue> {
+        let url = client.mcp_endpoint(url.as_deref()).map_err(js_err)?;
+        let mut transport = StreamableHttp::new(Box::new(client), &url);
+        if let Some(secret) = signer_secret {
+            transport = transport.with_signer(NostrSigner::from_secret_hex(&secret).map_err(js_err)?);
+        }
+        Ok(JsMcpClient { inner: McpClient::new(Box::new(transport)) })
+    }
+
+    /// Perform the initialization handshake, returning the server's initialize result
+    pub fn initialize(&mut self) -> Result<String, JsValue> {
+        Ok(self.inner.initialize().map_err(js_err)?.to_string())
+    }
+
+    /// List all tools as a JSON array
+    pub fn list_tools(&mut self) -> Result<String, JsValue> {
+        let tools = self.inner.list_tools().map_err(js_err)?;
+        Ok(serde_json::Value::Array(tools).to_string())
+    }
+
+    /// Call a tool with JSON arguments
+    pub fn call_tool(&mut self, name: &str, arguments_json: Option<String>) -> Result<String, JsValue> {
+        let arguments = parse_json(arguments_json)?;
+        Ok(self.inner.call_tool(name, arguments).map_err(js_err)?.to_string())
+    }
+
+    /// List all resources as a JSON array
+    pub fn list_resources(&mut self) -> Result<String, JsValue> {
+        let resources = self.inner.list_resources().map_err(js_err)?;
+        Ok(serde_json::Value::Array(resources).to_string())
+    }
+
+    /// Read a resource by URI
+    pub fn read_resource(&mut self, uri: &str) -> Result<String, JsValue> {
+        Ok(self.inner.read_resource(uri).map_err(js_err)?.to_string())
+    }
+
+    /// List all prompts as a JSON array
+    pub fn list_prompts(&mut self) -> Result<String, JsValue> {
+        let prompts = self.inner.list_prompts().map_err(js_err)?;
+        Ok(serde_json::Value::Array(prompts).to_string())
+    }
+
+    /// Get a prompt with JSON arguments
+    pub fn get_prompt(&mut self, name: &str, arguments_json: Option<String>) -> Result<String, JsValue> {
+        let arguments = parse_json(arguments_json)?;
+        Ok(self.inner.get_prompt(name, arguments).map_err(js_err)?.to_string())
+    }
+
+    /// Terminate the session
+    pub fn close(&mut self) -> Result<(), JsValue> {
+        self.inner.close().map_err(js_err)
+    }
+}
@@ -0,0 +1,495 @@
+//! Local Certificate Authority Module
+//!
+//! A locally generated CA used by the forward proxy to intercept HTTPS (MITM) so the
+//! privacy policy also applies inside TLS. The CA certificate must be trusted by the
+//! clients using the proxy; its key never leaves the CA directory.
+//!
+//! # Features
+//! - ECDSA P-256 CA and leaf certificates (X.509 v3, DER built in-crate)
+//! - CA persisted as PEM (`ca-cert.pem`, `ca-key.pem`) and reused across runs; a loaded
+//!   CA issues under its certificate's own subject and must match its key
+//! - Per-host leaf certificates with `subjectAltName`, the most recently used
+//!   [`MAX_CACHED_LEAVES`] cached in memory
+
+use base64::Engine;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File name of the CA certificate inside the CA directory
+pub const CA_CERT_FILE: &str = "ca-cert.pem";
+/// File name of the CA private key inside the CA directory
+pub const CA_KEY_FILE: &str = "ca-key.pem";
+/// Leaf certificates kept in memory; older hosts get a fresh leaf on their next visit
+pub const MAX_CACHED_LEAVES: usize = 1024;
+
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_SUBJECT_KEY_ID: &[u8] = &[0x55, 0x1d, 0x0e];
+const OID_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0f];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+const OID_AUTHORITY_KEY_ID: &[u8] = &[0x55, 0x1d, 0x23];
+const OID_EXT_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25];
+const OID_SERVER_AUTH: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01];
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// DER tag-length-value
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn seq(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(0x30, &parts.concat())
+}
+
+fn oid(value: &[u8]) -> Vec<u8> {
+    tlv(0x06, value)
+}
+
+/// Unsigned big-endian integer
+fn uint(bytes: &[u8]) -> Vec<u8> {
+    let trimmed: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    let mut content = if trimmed.is_empty() { vec![0] } else { trimmed };
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    tlv(0x02, &content)
+}
+
+fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(0x03, &[&[0u8][..], bytes].concat())
+}
+
+fn name(common_name: &str) -> Vec<u8> {
+    seq(&[tlv(0x31, &seq(&[oid(OID_COMMON_NAME), tlv(0x0c, common_name.as_bytes())]))])
+}
+
+/// UTCTime (`YYMMDDHHMMSSZ`) for years before 2050, GeneralizedTime after
+fn time(unix: u64) -> Vec<u8> {
+    let days = (unix / DAY) as i64;
+    let secs = unix % DAY;
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let clock = format!("{:02}{:02}{:02}{:02}{:02}Z", month, day, secs / 3600, secs % 3600 / 60, secs % 60);
+    if year < 2050 {
+        tlv(0x17, format!("{:02}{}", year % 100, clock).as_bytes())
+    } else {
+        tlv(0x18, format!("{:04}{}", year, clock).as_bytes())
+    }
+}
+
+fn extension(id: &[u8], critical: bool, value: Vec<u8>) -> Vec<u8> {
+    let mut parts = vec![oid(id)];
+    if critical {
+        parts.push(tlv(0x01, &[0xff]));
+    }
+    parts.push(tlv(0x04, &value));
+    seq(&parts)
+}
+
+fn public_key_info(key: &SigningKey) -> (Vec<u8>, Vec<u8>) {
+    let point = key.verifying_key().to_encoded_point(false);
+    let info = seq(&[seq(&[oid(OID_EC_PUBLIC_KEY), oid(OID_PRIME256V1)]), bit_string(point.as_bytes())]);
+    let key_id = Sha256::digest(point.as_bytes())[..20].to_vec();
+    (info, key_id)
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(der);
+    let lines: Vec<&str> = encoded.as_bytes().chunks(64).map(|c| std::str::from_utf8(c).unwrap_or_default()).collect();
+    format!("-----BEGIN {}-----\n{}\n-----END {}-----\n", label, lines.join("\n"), label)
+}
+
+/// A DER element read from a buffer: `(tag, element, content, rest)`
+type Tlv<'a> = (u8, &'a [u8], &'a [u8], &'a [u8]);
+
+/// Split one DER element off `input`
+fn read_tlv(input: &[u8]) -> Option<Tlv<'_>> {
+    let (&tag, after_tag) = input.split_first()?;
+    let (&first, after_len) = after_tag.split_first()?;
+    let (len, body) = if first < 0x80 {
+        (first as usize, after_len)
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > std::mem::size_of::<usize>() || after_len.len() < n {
+            return None;
+        }
+        let len = after_len[..n].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, &after_len[n..])
+    };
+    if body.len() < len {
+        return None;
+    }
+    let header = input.len() - body.len();
+    Some((tag, &input[..header + len], &body[..len], &body[len..]))
+}
+
+/// Parts of a CA certificate that leaves are issued under
+struct CaCertificateInfo {
+    /// DER subject `Name`, copied verbatim as the leaves' issuer
+    subject: Vec<u8>,
+    /// DER SubjectPublicKeyInfo
+    spki: Vec<u8>,
+    /// subjectKeyIdentifier extension, if present
+    key_id: Option<Vec<u8>>,
+}
+
+fn parse_ca_certificate(der: &[u8]) -> Option<CaCertificateInfo> {
+    let Some((0x30, _, certificate, _)) = read_tlv(der) else { return None };
+    let Some((0x30, _, tbs, _)) = read_tlv(certificate) else { return None };
+    let mut rest = tbs;
+    // Optional version, then serial, signature algorithm, issuer and validity
+    if let Some((0xa0, _, _, next)) = read_tlv(rest) {
+        rest = next;
+    }
+    for _ in 0..4 {
+        rest = read_tlv(rest)?.3;
+    }
+    let Some((0x30, subject, _, rest)) = read_tlv(rest) else { return None };
+    let Some((0x30, spki, _, mut rest)) = read_tlv(rest) else { return None };
+
+    let mut key_id = None;
+    while let Some((tag, _, content, next)) = read_tlv(rest) {
+        if tag == 0xa3 {
+            let mut extensions = read_tlv(content)?.2;
+            while let Some((_, _, extension, next)) = read_tlv(extensions) {
+                let (_, _, id, mut fields) = read_tlv(extension)?;
+                if id == OID_SUBJECT_KEY_ID {
+                    // Skip the optional `critical` flag
+                    if let Some((0x01, _, _, after)) = read_tlv(fields) {
+                        fields = after;
+                    }
+                    let value = read_tlv(fields)?.2;
+                    let Some((0x04, _, identifier, _)) = read_tlv(value) else { return None };
+                    key_id = Some(identifier.to_vec());
+                }
+                extensions = next;
+            }
+        }
+        rest = next;
+    }
+    Some(CaCertificateInfo { subject: subject.to_vec(), spki: spki.to_vec(), key_id })
+}
+
+/// Sign a TBSCertificate and wrap it into a DER certificate
+fn sign_certificate(tbs: Vec<u8>, issuer_key: &SigningKey) -> Vec<u8> {
+    let signature: Signature = issuer_key.sign(&tbs);
+    seq(&[tbs, seq(&[oid(OID_ECDSA_SHA256)]), bit_string(signature.to_der().as_bytes())])
+}
+
+fn serial() -> Vec<u8> {
+    let mut serial = [0u8; 16];
+    OsRng.fill_bytes(&mut serial);
+    serial[0] &= 0x7f;
+    uint(&serial)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Leaf certificate and key, PEM encoded
+#[derive(Clone)]
+pub struct LeafCertificate {
+    /// Leaf certificate followed by the CA certificate
+    pub chain_pem: String,
+    /// PKCS#8 private key
+    pub key_pem: String,
+}
+
+/// Leaf certificates by host, evicting the least recently used
+#[derive(Default)]
+struct LeafCache {
+    leaves: HashMap<String, LeafCertificate>,
+    order: VecDeque<String>,
+}
+
+impl LeafCache {
+    fn get(&mut self, host: &str) -> Option<LeafCertificate> {
+        let leaf = self.leaves.get(host)?.clone();
+        self.order.retain(|h| h != host);
+        self.order.push_back(host.to_string());
+        Some(leaf)
+    }
+
+    fn insert(&mut self, host: String, leaf: LeafCertificate) {
+        self.order.retain(|h| *h != host);
+        self.order.push_back(host.clone());
+        self.leaves.insert(host, leaf);
+        while self.order.len() > MAX_CACHED_LEAVES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.leaves.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Local certificate authority
+pub struct CertificateAuthority {
+    /// DER subject of the CA certificate (issuer of every leaf)
+    subject: Vec<u8>,
+    /// Subject key identifier of the CA certificate (leaves' authorityKeyIdentifier)
+    key_id: Vec<u8>,
+    key: SigningKey,
+    cert_der: Vec<u8>,
+    leaves: Mutex<LeafCache>,
+}
+
+impl std::fmt::Debug for CertificateAuthority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertificateAuthority").field("subject", &hex::encode(&self.subject)).finish()
+    }
+}
+
+impl CertificateAuthority {
+    /// Generate a new CA valid for ten years
+    pub fn generate(common_name: &str) -> Self {
+        let key = SigningKey::random(&mut OsRng);
+        let (spki, key_id) = public_key_info(&key);
+        let subject = name(common_name);
+        let now = now_secs();
+        let tbs = seq(&[
+            tlv(0xa0, &uint(&[2])),
+            serial(),
+            seq(&[oid(OID_ECDSA_SHA256)]),
+            subject.clone(),
+            seq(&[time(now - DAY), time(now + 3650 * DAY)]),
+            subject.clone(),
+            spki,
+            tlv(
+                0xa3,
+                &seq(&[
+                    extension(OID_BASIC_CONSTRAINTS, true, seq(&[tlv(0x01, &[0xff])])),
+                    // keyCertSign | cRLSign
+                    extension(OID_KEY_USAGE, true, tlv(0x03, &[0x01, 0x06])),
+                    extension(OID_SUBJECT_KEY_ID, false, tlv(0x04, &key_id)),
+                ]),
+            ),
+        ]);
+        let cert_der = sign_certificate(tbs, &key);
+        Self { subject, key_id, key, cert_der, leaves: Mutex::new(LeafCache::default()) }
+    }
+
+    /// Load the CA from `dir`, or generate and save one there named `common_name`
+    /// (an existing CA keeps the name in its certificate)
+    pub fn load_or_create(dir: &str, common_name: &str) -> Result<Self, String> {
+        let dir = Path::new(dir);
+        let (cert_path, key_path) = (dir.join(CA_CERT_FILE), dir.join(CA_KEY_FILE));
+        if cert_path.exists() && key_path.exists() {
+            let cert_pem = std::fs::read_to_string(&cert_path).map_err(|e| format!("Failed to read CA certificate: {}", e))?;
+            let key_pem = std::fs::read_to_string(&key_path).map_err(|e| format!("Failed to read CA key: {}", e))?;
+            return Self::from_pem(&cert_pem, &key_pem);
+        }
+
+        let ca = Self::generate(common_name);
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        // The key is owner-only from the moment it exists, and an existing file
+        // (e.g. a planted key without certificate) is never reused or overwritten
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut key_file = options
+            .open(&key_path)
+            .map_err(|e| format!("Failed to create CA key {}: {}", key_path.display(), e))?;
+        key_file
+            .write_all(ca.key_pem()?.as_bytes())
+            .map_err(|e| format!("Failed to write CA key: {}", e))?;
+        std::fs::write(&cert_path, ca.cert_pem()).map_err(|e| format!("Failed to write CA certificate: {}", e))?;
+        Ok(ca)
+    }
+
+    /// Restore a CA from its PEM certificate and PKCS#8 key; the key must be the
+    /// certificate's, and leaves are issued under the certificate's subject
+    pub fn from_pem(cert_pem: &str, key_pem: &str) -> Result<Self, String> {
+        let key = SigningKey::from_pkcs8_pem(key_pem).map_err(|e| format!("Invalid CA key: {}", e))?;
+        let body: String = cert_pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        let cert_der = base64::engine::general_purpose::STANDARD
+            .decode(body.trim())
+            .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+        let info = parse_ca_certificate(&cert_der).ok_or("Invalid CA certificate: malformed DER")?;
+        let (spki, key_id) = public_key_info(&key);
+        if info.spki != spki {
+            return Err("CA key does not match the CA certificate".to_string());
+        }
+        Ok(Self {
+            subject: info.subject,
+            key_id: info.key_id.unwrap_or(key_id),
+            key,
+            cert_der,
+            leaves: Mutex::new(LeafCache::default()),
+        })
+    }
+
+    /// CA certificate to install in client trust stores
+    pub fn cert_pem(&self) -> String {
+        pem("CERTIFICATE", &self.cert_der)
+    }
+
+    fn key_pem(&self) -> Result<String, String> {
+        let pem = self.key.to_pkcs8_pem(LineEnding::LF).map_err(|e| format!("Failed to encode CA key: {}", e))?;
+        Ok(pem.to_string())
+    }
+
+    /// Leaf certificate for a host name or IP address (cached)
+    pub fn leaf(&self, host: &str) -> Result<LeafCertificate, String> {
+        let host = host.trim_matches(['[', ']']).to_ascii_lowercase();
+        if let Some(leaf) = self.leaves.lock().unwrap().get(&host) {
+            return Ok(leaf);
+        }
+
+        let key = SigningKey::random(&mut OsRng);
+        let (spki, _) = public_key_info(&key);
+        let alt_name = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => tlv(0x87, &ip.octets()),
+            Ok(IpAddr::V6(ip)) => tlv(0x87, &ip.octets()),
+            Err(_) => tlv(0x82, host.as_bytes()),
+        };
+        let now = now_secs();
+        let tbs = seq(&[
+            tlv(0xa0, &uint(&[2])),
+            serial(),
+            seq(&[oid(OID_ECDSA_SHA256)]),
+            self.subject.clone(),
+            // Clients reject leaf certificates valid for more than 398 days
+            seq(&[time(now - DAY), time(now + 397 * DAY)]),
+            name(&host),
+            spki,
+            tlv(
+                0xa3,
+                &seq(&[
+                    extension(OID_BASIC_CONSTRAINTS, true, seq(&[])),
+                    // digitalSignature
+                    extension(OID_KEY_USAGE, true, tlv(0x03, &[0x07, 0x80])),
+                    extension(OID_EXT_KEY_USAGE, false, seq(&[oid(OID_SERVER_AUTH)])),
+                    extension(OID_SUBJECT_ALT_NAME, false, seq(&[alt_name])),
+                    extension(OID_AUTHORITY_KEY_ID, false, seq(&[tlv(0x80, &self.key_id)])),
+                ]),
+            ),
+        ]);
+        let cert_der = sign_certificate(tbs, &self.key);
+        let key_pem = key.to_pkcs8_pem(LineEnding::LF).map_err(|e| format!("Failed to encode key: {}", e))?;
+        let leaf = LeafCertificate {
+            chain_pem: format!("{}{}", pem("CERTIFICATE", &cert_der), self.cert_pem()),
+            key_pem: key_pem.to_string(),
+        };
+        self.leaves.lock().unwrap().insert(host, leaf.clone());
+        Ok(leaf)
+    }
+
+    /// TLS acceptor presenting a leaf certificate for `host`
+    pub fn acceptor(&self, host: &str) -> Result<tokio_native_tls::TlsAcceptor, String> {
+        let leaf = self.leaf(host)?;
+        let identity = tokio_native_tls::native_tls::Identity::from_pkcs8(leaf.chain_pem.as_bytes(), leaf.key_pem.as_bytes())
+            .map_err(|e| format!("Invalid leaf certificate: {}", e))?;
+        let acceptor = tokio_native_tls::native_tls::TlsAcceptor::new(identity).map_err(|e| format!("Failed to create TLS acceptor: {}", e))?;
+        Ok(tokio_native_tls::TlsAcceptor::from(acceptor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_der_time() {
+        assert_eq!(time(0), tlv(0x17, b"700101000000Z"));
+        // 2000-02-29 12:34:56 UTC
+        assert_eq!(time(951_827_696), tlv(0x17, b"000229123456Z"));
+        // 2050-01-01 00:00:00 UTC
+        assert_eq!(time(2_524_608_000), tlv(0x18, b"20500101000000Z"));
+    }
+
+    #[test]
+    fn test_from_pem_requires_the_certificate_key() {
+        let ca = CertificateAuthority::generate("Test Privacy CA");
+        let other = CertificateAuthority::generate("Test Privacy CA");
+        let restored = CertificateAuthority::from_pem(&ca.cert_pem(), &ca.key_pem().unwrap()).unwrap();
+        assert_eq!(restored.subject, name("Test Privacy CA"));
+        assert_eq!(restored.key_id, ca.key_id);
+        let err = CertificateAuthority::from_pem(&ca.cert_pem(), &other.key_pem().unwrap()).unwrap_err();
+        assert!(err.contains("does not match"));
+        assert!(CertificateAuthority::from_pem("not a certificate", &ca.key_pem().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_leaf_cache_evicts_least_recently_used() {
+        let leaf = LeafCertificate { chain_pem: String::new(), key_pem: String::new() };
+        let mut cache = LeafCache::default();
+        for i in 0..MAX_CACHED_LEAVES {
+            cache.insert(format!("host{}", i), leaf.clone());
+        }
+        assert!(cache.get("host0").is_some());
+        cache.insert("new".to_string(), leaf);
+        assert_eq!(cache.leaves.len(), MAX_CACHED_LEAVES);
+        assert!(cache.get("host0").is_some() && cache.get("new").is_some());
+        assert!(cache.get("host1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_leaf_accepted_by_tls_client() {
+        let dir = std::env::temp_dir().join(format!("mitm-ca-{}", std::process::id()));
+        let ca = CertificateAuthority::load_or_create(dir.to_str().unwrap(), "Test Privacy CA").unwrap();
+        // Reloaded under another name, leaves are still issued by the stored certificate
+        let reloaded = CertificateAuthority::load_or_create(dir.to_str().unwrap(), "Renamed CA").unwrap();
+        assert_eq!(ca.cert_pem(), reloaded.cert_pem());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join(CA_KEY_FILE)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        // A key without its certificate is not silently replaced
+        std::fs::remove_file(dir.join(CA_CERT_FILE)).unwrap();
+        assert!(CertificateAuthority::load_or_create(dir.to_str().unwrap(), "Test Privacy CA").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = reloaded.acceptor("localhost").unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut tls = acceptor.accept(stream).await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut tls, &mut request).await.unwrap();
+            let response = b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\nsecure";
+            tokio::io::AsyncWriteExt::write_all(&mut tls, response).await.unwrap();
+        });
+
+        let root = reqwest::Certificate::from_pem(ca.cert_pem().as_bytes()).unwrap();
+        let client = reqwest::Client::builder().add_root_certificate(root).no_proxy().build().unwrap();
+        let body = client.get(format!("https://localhost:{}/", port)).send().await.unwrap().text().await.unwrap();
+        assert_eq!(body, "secure");
+    }
+}
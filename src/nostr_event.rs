@@ -0,0 +1,302 @@
+//! Nostr Event Module
+//!
+//! NIP-01 events: building, signing, parsing and verifying them.
+//!
+//! # Features
+//! - Event ids: the sha256 of the serialization
+//!   `[0, <pubkey>, <created_at>, <kind>, <tags>, <content>]`
+//! - BIP-340 signatures over the id; `NostrSigner::sign` over the serialization produces
+//!   exactly this signature, since it signs `sha256(message)`
+//! - Strict parsing: every field must be present with its NIP-01 type, hex fields must
+//!   be lowercase and of the right length, and unknown or duplicate fields are rejected
+//! - A parsed event is not trusted until [`NostrEvent::verify`] succeeds
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::did_nostr::{DidNostr, NostrPublicKey, NostrSignature, NostrSigner, NostrVerifier, unix_now};
+
+/// User metadata (NIP-01)
+pub const KIND_METADATA: u16 = 0;
+/// Short text note (NIP-01)
+pub const KIND_TEXT_NOTE: u16 = 1;
+/// HTTP authorization (NIP-98)
+pub const KIND_HTTP_AUTH: u16 = 27235;
+
+/// Signed Nostr event
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NostrEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub created_at: u64,
+    pub kind: u16,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    pub sig: String,
+}
+
+/// Event before signing; the author's key fills in `pubkey`, `id` and `sig`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnsignedEvent {
+    /// Unix seconds (defaults to now)
+    #[serde(default = "unix_now")]
+    pub created_at: u64,
+    pub kind: u16,
+    #[serde(default)]
+    pub tags: Vec<Vec<String>>,
+    #[serde(default)]
+    pub content: String,
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+// NIP-01 escapes only these characters; everything else is written verbatim
+fn push_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Serialization hashed into the event id
+pub fn serialize_for_id(pubkey: &str, created_at: u64, kind: u16, tags: &[Vec<String>], content: &str) -> String {
+    let mut out = String::from("[0,");
+    push_string(pubkey, &mut out);
+    out.push_str(&format!(",{},{},[", created_at, kind));
+    for (i, tag) in tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('[');
+        for (j, value) in tag.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            push_string(value, &mut out);
+        }
+        out.push(']');
+    }
+    out.push_str("],");
+    push_string(content, &mut out);
+    out.push(']');
+    out
+}
+
+impl UnsignedEvent {
+    /// Event of `kind` created now
+    pub fn new(kind: u16, content: &str) -> Self {
+        Self {
+            created_at: unix_now(),
+            kind,
+            tags: Vec::new(),
+            content: content.to_string(),
+        }
+    }
+
+    pub fn with_created_at(mut self, created_at: u64) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    /// Append a tag, e.g. `["p", <pubkey>]` or `["u", <url>]`
+    pub fn with_tag(mut self, tag: &[&str]) -> Self {
+        self.tags.push(tag.iter().map(|value| value.to_string()).collect());
+        self
+    }
+
+    /// Parse `{"kind": .., "content": .., "tags": [..], "created_at": ..}`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let event: Self = serde_json::from_str(json).map_err(|e| format!("Invalid unsigned event: {}", e))?;
+        if event.tags.iter().any(Vec::is_empty) {
+            return Err("Invalid unsigned event: tags must not be empty".to_string());
+        }
+        Ok(event)
+    }
+
+    /// Sign as `signer`
+    pub fn sign(self, signer: &NostrSigner) -> NostrEvent {
+        let pubkey = signer.pubkey().as_hex().to_string();
+        let serialized = serialize_for_id(&pubkey, self.created_at, self.kind, &self.tags, &self.content);
+        NostrEvent {
+            id: hex::encode(Sha256::digest(serialized.as_bytes())),
+            sig: signer.sign(&serialized).as_hex().to_string(),
+            pubkey,
+            created_at: self.created_at,
+            kind: self.kind,
+            tags: self.tags,
+            content: self.content,
+        }
+    }
+}
+
+impl NostrEvent {
+    /// Strictly parse a signed event (see the module docs); does not check `id` or `sig`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let event: Self = serde_json::from_str(json).map_err(|e| format!("Invalid event: {}", e))?;
+        if !is_lower_hex(&event.id, 64) {
+            return Err("Invalid event: id must be 64 lowercase hex characters".to_string());
+        }
+        if !is_lower_hex(&event.pubkey, 64) {
+            return Err("Invalid event: pubkey must be 64 lowercase hex characters".to_string());
+        }
+        if !is_lower_hex(&event.sig, 128) {
+            return Err("Invalid event: sig must be 128 lowercase hex characters".to_string());
+        }
+        if event.tags.iter().any(Vec::is_empty) {
+            return Err("Invalid event: tags must not be empty".to_string());
+        }
+        Ok(event)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Serialization hashed into the id
+    pub fn serialize_for_id(&self) -> String {
+        serialize_for_id(&self.pubkey, self.created_at, self.kind, &self.tags, &self.content)
+    }
+
+    /// Id computed from the event's fields
+    pub fn compute_id(&self) -> String {
+        hex::encode(Sha256::digest(self.serialize_for_id().as_bytes()))
+    }
+
+    /// Check that `id` matches the fields and `sig` is the author's signature over it
+    pub fn verify(&self) -> Result<(), String> {
+        if self.compute_id() != self.id {
+            return Err("Event id does not match its content".to_string());
+        }
+        let pubkey = NostrPublicKey::from_hex(&self.pubkey)?;
+        let sig = NostrSignature::from_hex(&self.sig)?;
+        let result = NostrVerifier::verify(&pubkey, &self.serialize_for_id(), &sig);
+        match result.valid {
+            true => Ok(()),
+            false => Err(result.error.unwrap_or_else(|| "Signature verification failed".to_string())),
+        }
+    }
+
+    /// Author's DID
+    pub fn did(&self) -> Result<DidNostr, String> {
+        Ok(DidNostr::from_pubkey(NostrPublicKey::from_hex(&self.pubkey)?))
+    }
+
+    /// First tag named `name` (e.g. `"e"`, `"p"`), including the name
+    pub fn tag(&self, name: &str) -> Option<&[String]> {
+        self.tags.iter().find(|tag| tag.first().is_some_and(|n| n == name)).map(Vec::as_slice)
+    }
+
+    /// Value of the first tag named `name`
+    pub fn tag_value(&self, name: &str) -> Option<&str> {
+        self.tag(name).and_then(|tag| tag.get(1)).map(String::as_str)
+    }
+
+    /// Values of all tags named `name`, e.g. the referenced pubkeys for `"p"`
+    pub fn tag_values(&self, name: &str) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|tag| tag.first().is_some_and(|n| n == name))
+            .filter_map(|tag| tag.get(1).map(String::as_str))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialization_for_id() {
+        let tags = vec![vec!["e".to_string(), "abc".to_string()], vec!["t".to_string()]];
+        let content = "line\nquote\" tab\t back\\ bell\u{07} é";
+        assert_eq!(
+            serialize_for_id("ff", 1700000000, 1, &tags, content),
+            "[0,\"ff\",1700000000,1,[[\"e\",\"abc\"],[\"t\"]],\"line\\nquote\\\" tab\\t back\\\\ bell\u{07} é\"]"
+        );
+        assert_eq!(serialize_for_id("ff", 0, 0, &[], ""), "[0,\"ff\",0,0,[],\"\"]");
+    }
+
+    #[test]
+    fn test_verifies_event_from_another_implementation() {
+        // Kind-4 event from the rust-nostr test suite
+        let json = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = NostrEvent::from_json(json).unwrap();
+        assert_eq!(event.compute_id(), "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45");
+        assert!(event.verify().is_ok());
+
+        let mut edited = event.clone();
+        edited.created_at += 1;
+        assert!(edited.verify().is_err());
+    }
+
+    #[test]
+    fn test_sign_verify_and_tags() {
+        let signer = NostrSigner::generate();
+        let event = UnsignedEvent::new(KIND_HTTP_AUTH, "")
+            .with_created_at(1700000000)
+            .with_tag(&["u", "https://api.example.com/rpc"])
+            .with_tag(&["method", "POST"])
+            .with_tag(&["p", "aa"])
+            .with_tag(&["p", "bb"])
+            .sign(&signer);
+        assert_eq!(event.id, event.compute_id());
+        assert_eq!(event.did().unwrap(), signer.did());
+        event.verify().unwrap();
+        assert_eq!(event.tag_value("method"), Some("POST"));
+        assert_eq!(event.tag("u").unwrap()[1], "https://api.example.com/rpc");
+        assert_eq!(event.tag_values("p"), vec!["aa", "bb"]);
+        assert_eq!(event.tag_value("x"), None);
+
+        let parsed = NostrEvent::from_json(&event.to_json()).unwrap();
+        assert_eq!(parsed, event);
+
+        let mut tampered = event.clone();
+        tampered.content = "changed".to_string();
+        assert!(tampered.verify().unwrap_err().contains("id"));
+        // A consistent id does not help without the author's signature
+        tampered.id = tampered.compute_id();
+        assert!(tampered.verify().is_err());
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let event = UnsignedEvent::new(KIND_TEXT_NOTE, "hi").sign(&NostrSigner::generate());
+        let json: serde_json::Value = serde_json::from_str(&event.to_json()).unwrap();
+        let with = |field: &str, value: serde_json::Value| {
+            let mut json = json.clone();
+            json[field] = value;
+            NostrEvent::from_json(&json.to_string())
+        };
+        assert!(with("id", event.id.to_uppercase().into()).is_err());
+        assert!(with("sig", "00".into()).is_err());
+        assert!(with("kind", 70000.into()).is_err());
+        assert!(with("created_at", serde_json::json!(1.5)).is_err());
+        assert!(with("created_at", "1700000000".into()).is_err());
+        assert!(with("tags", serde_json::json!([[]])).is_err());
+        assert!(with("tags", serde_json::json!([["e", 1]])).is_err());
+        assert!(with("extra", true.into()).is_err());
+
+        let mut missing = json.clone();
+        missing.as_object_mut().unwrap().remove("content");
+        assert!(NostrEvent::from_json(&missing.to_string()).is_err());
+        let duplicate = event.to_json().replacen("\"kind\":", "\"kind\":1,\"kind\":", 1);
+        assert!(NostrEvent::from_json(&duplicate).is_err());
+
+        let unsigned = UnsignedEvent::from_json(r#"{"kind": 1, "content": "hi", "tags": [["t", "rust"]]}"#).unwrap();
+        assert_eq!(unsigned.tags, vec![vec!["t".to_string(), "rust".to_string()]]);
+        assert!(UnsignedEvent::from_json(r#"{"kind": 1, "pubkey": "aa"}"#).is_err());
+    }
+}
@@ -0,0 +1,741 @@
+//! Oblivious HTTP Module
+//!
+//! This module implements Oblivious HTTP (RFC 9458): requests are encoded as Binary
+//! HTTP, HPKE-encrypted to a gateway's key and sent through a relay, so the relay
+//! learns the client's IP but not the request, and the gateway/target learn the
+//! request but not the client's IP.
+//!
+//! # Features
+//! - HPKE base mode (RFC 9180): DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM / AES-256-GCM
+//! - KeyConfig and `application/ohttp-keys` encoding
+//! - Client encapsulation / decapsulation ([`OhttpClient`])
+//! - Gateway decapsulation / encapsulation for running our own gateway ([`OhttpGateway`])
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use base64::Engine;
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+use x25519_dalek::{PublicKey, StaticSecret};
+use crate::bhttp;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+/// DHKEM(X25519, HKDF-SHA256)
+pub const KEM_X25519_SHA256: u16 = 0x0020;
+/// HKDF-SHA256
+pub const KDF_HKDF_SHA256: u16 = 0x0001;
+/// AES-128-GCM
+pub const AEAD_AES_128_GCM: u16 = 0x0001;
+/// AES-256-GCM
+pub const AEAD_AES_256_GCM: u16 = 0x0002;
+
+/// Content type of encapsulated requests
+pub const REQUEST_CONTENT_TYPE: &str = "message/ohttp-req";
+/// Content type of encapsulated responses
+pub const RESPONSE_CONTENT_TYPE: &str = "message/ohttp-res";
+/// Content type of gateway key configurations
+pub const KEYS_CONTENT_TYPE: &str = "application/ohttp-keys";
+
+const REQUEST_LABEL: &[u8] = b"message/bhttp request";
+const RESPONSE_LABEL: &[u8] = b"message/bhttp response";
+const NPK: usize = 32;
+const NN: usize = 12;
+
+/// KDF/AEAD pair offered by a gateway
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymmetricSuite {
+    pub kdf_id: u16,
+    pub aead_id: u16,
+}
+
+impl SymmetricSuite {
+    fn is_supported(&self) -> bool {
+        self.kdf_id == KDF_HKDF_SHA256 && matches!(self.aead_id, AEAD_AES_128_GCM | AEAD_AES_256_GCM)
+    }
+}
+
+/// Gateway key configuration (RFC 9458, Section 3)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConfig {
+    pub key_id: u8,
+    pub kem_id: u16,
+    pub public_key: [u8; NPK],
+    pub suites: Vec<SymmetricSuite>,
+}
+
+impl KeyConfig {
+    /// Encode a single KeyConfig
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![self.key_id];
+        out.extend_from_slice(&self.kem_id.to_be_bytes());
+        out.extend_from_slice(&self.public_key);
+        out.extend_from_slice(&((self.suites.len() * 4) as u16).to_be_bytes());
+        for suite in &self.suites {
+            out.extend_from_slice(&suite.kdf_id.to_be_bytes());
+            out.extend_from_slice(&suite.aead_id.to_be_bytes());
+        }
+        out
+    }
+
+    /// Decode a single KeyConfig
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let header = 1 + 2 + NPK + 2;
+        if data.len() < header {
+            return Err("Truncated OHTTP key config".to_string());
+        }
+        let kem_id = u16::from_be_bytes([data[1], data[2]]);
+        if kem_id != KEM_X25519_SHA256 {
+            return Err(format!("Unsupported OHTTP KEM: 0x{:04x}", kem_id));
+        }
+        let public_key: [u8; NPK] = data[3..3 + NPK].try_into().map_err(|_| "Invalid public key")?;
+        let suites_len = u16::from_be_bytes([data[header - 2], data[header - 1]]) as usize;
+        let suites_data = &data[header..];
+        if suites_len == 0 || !suites_len.is_multiple_of(4) || suites_data.len() != suites_len {
+            return Err("Invalid OHTTP cipher suite list".to_string());
+        }
+        let suites = suites_data
+            .chunks(4)
+            .map(|c| SymmetricSuite {
+                kdf_id: u16::from_be_bytes([c[0], c[1]]),
+                aead_id: u16::from_be_bytes([c[2], c[3]]),
+            })
+            .collect();
+        Ok(Self { key_id: data[0], kem_id, public_key, suites })
+    }
+
+    /// Decode an `application/ohttp-keys` body (length-prefixed KeyConfigs)
+    pub fn decode_list(data: &[u8]) -> Result<Vec<Self>, String> {
+        let mut configs = Vec::new();
+        let mut rest = data;
+        while !rest.is_empty() {
+            if rest.len() < 2 {
+                return Err("Truncated OHTTP key list".to_string());
+            }
+            let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let config = rest.get(2..2 + len).ok_or("Truncated OHTTP key list")?;
+            // Skip configs using algorithms we do not implement
+            if let Ok(config) = Self::decode(config) {
+                configs.push(config);
+            }
+            rest = &rest[2 + len..];
+        }
+        Ok(configs)
+    }
+
+    /// Encode as an `application/ohttp-keys` body
+    pub fn encode_list(configs: &[KeyConfig]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for config in configs {
+            let encoded = config.encode();
+            out.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+            out.extend_from_slice(&encoded);
+        }
+        out
+    }
+
+    /// Parse a base64 KeyConfig or `application/ohttp-keys` body, returning the first usable config
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| format!("Invalid base64 key config: {}", e))?;
+        Self::decode(&data)
+            .or_else(|_| Self::decode_list(&data)?.into_iter().next().ok_or("No usable OHTTP key config".to_string()))
+    }
+
+    fn select_suite(&self) -> Result<SymmetricSuite, String> {
+        self.suites
+            .iter()
+            .copied()
+            .find(SymmetricSuite::is_supported)
+            .ok_or_else(|| "No supported OHTTP cipher suite".to_string())
+    }
+}
+
+/// Request header: `key_id || kem_id || kdf_id || aead_id`
+fn request_header(key_id: u8, suite: SymmetricSuite) -> Vec<u8> {
+    let mut hdr = vec![key_id];
+    hdr.extend_from_slice(&KEM_X25519_SHA256.to_be_bytes());
+    hdr.extend_from_slice(&suite.kdf_id.to_be_bytes());
+    hdr.extend_from_slice(&suite.aead_id.to_be_bytes());
+    hdr
+}
+
+fn request_info(hdr: &[u8]) -> Vec<u8> {
+    let mut info = REQUEST_LABEL.to_vec();
+    info.push(0);
+    info.extend_from_slice(hdr);
+    info
+}
+
+// ---------------------------------------------------------------------------
+// HPKE (RFC 9180), base mode
+// ---------------------------------------------------------------------------
+
+enum AeadCipher {
+    Aes128(Box<Aes128Gcm>),
+    Aes256(Box<Aes256Gcm>),
+}
+
+impl AeadCipher {
+    fn new(aead_id: u16, key: &[u8]) -> Result<Self, String> {
+        match aead_id {
+            AEAD_AES_128_GCM => Aes128Gcm::new_from_slice(key).map(|c| AeadCipher::Aes128(Box::new(c))),
+            AEAD_AES_256_GCM => Aes256Gcm::new_from_slice(key).map(|c| AeadCipher::Aes256(Box::new(c))),
+            other => return Err(format!("Unsupported AEAD: 0x{:04x}", other)),
+        }
+        .map_err(|_| "Invalid AEAD key".to_string())
+    }
+
+    fn key_len(aead_id: u16) -> usize {
+        if aead_id == AEAD_AES_256_GCM { 32 } else { 16 }
+    }
+
+    fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let payload = Payload { msg: plaintext, aad };
+        match self {
+            AeadCipher::Aes128(c) => c.encrypt(nonce.into(), payload),
+            AeadCipher::Aes256(c) => c.encrypt(nonce.into(), payload),
+        }
+        .map_err(|_| "OHTTP encryption failed".to_string())
+    }
+
+    fn open(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let payload = Payload { msg: ciphertext, aad };
+        match self {
+            AeadCipher::Aes128(c) => c.decrypt(nonce.into(), payload),
+            AeadCipher::Aes256(c) => c.decrypt(nonce.into(), payload),
+        }
+        .map_err(|_| "OHTTP decryption failed".to_string())
+    }
+}
+
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let labeled_ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm].concat();
+    Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm).0.to_vec()
+}
+
+fn labeled_expand(suite_id: &[u8], prk: &[u8], label: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    let labeled_info = [&(len as u16).to_be_bytes(), b"HPKE-v1".as_slice(), suite_id, label, info].concat();
+    expand(prk, &labeled_info, len)
+}
+
+fn expand(prk: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    let mut okm = vec![0u8; len];
+    Hkdf::<Sha256>::from_prk(prk)
+        .expect("PRK has hash length")
+        .expand(info, &mut okm)
+        .expect("HKDF output length is valid");
+    okm
+}
+
+fn kem_suite_id() -> Vec<u8> {
+    [b"KEM".as_slice(), &KEM_X25519_SHA256.to_be_bytes()].concat()
+}
+
+/// DHKEM ExtractAndExpand
+fn kem_shared_secret(dh: &[u8], enc: &[u8], pk_r: &[u8]) -> Vec<u8> {
+    let suite_id = kem_suite_id();
+    let eae_prk = labeled_extract(&suite_id, b"", b"eae_prk", dh);
+    let kem_context = [enc, pk_r].concat();
+    labeled_expand(&suite_id, &eae_prk, b"shared_secret", &kem_context, 32)
+}
+
+/// HPKE encryption context
+struct HpkeContext {
+    cipher: AeadCipher,
+    base_nonce: Vec<u8>,
+    seq: u64,
+    exporter_secret: Vec<u8>,
+    suite_id: Vec<u8>,
+}
+
+impl HpkeContext {
+    fn key_schedule(suite: SymmetricSuite, shared_secret: &[u8], info: &[u8]) -> Result<Self, String> {
+        let suite_id = [
+            b"HPKE".as_slice(),
+            &KEM_X25519_SHA256.to_be_bytes(),
+            &suite.kdf_id.to_be_bytes(),
+            &suite.aead_id.to_be_bytes(),
+        ]
+        .concat();
+        let psk_id_hash = labeled_extract(&suite_id, b"", b"psk_id_hash", b"");
+        let info_hash = labeled_extract(&suite_id, b"", b"info_hash", info);
+        let context = [&[0u8], psk_id_hash.as_slice(), &info_hash].concat();
+        let secret = labeled_extract(&suite_id, shared_secret, b"secret", b"");
+        let key = labeled_expand(&suite_id, &secret, b"key", &context, AeadCipher::key_len(suite.aead_id));
+        let base_nonce = labeled_expand(&suite_id, &secret, b"base_nonce", &context, NN);
+        let exporter_secret = labeled_expand(&suite_id, &secret, b"exp", &context, 32);
+        Ok(Self { cipher: AeadCipher::new(suite.aead_id, &key)?, base_nonce, seq: 0, exporter_secret, suite_id })
+    }
+
+    fn nonce(&self) -> Vec<u8> {
+        let mut nonce = self.base_nonce.clone();
+        for (n, s) in nonce.iter_mut().rev().zip(self.seq.to_be_bytes().iter().rev()) {
+            *n ^= s;
+        }
+        nonce
+    }
+
+    fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let ciphertext = self.cipher.seal(&self.nonce(), aad, plaintext)?;
+        self.seq += 1;
+        Ok(ciphertext)
+    }
+
+    fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let plaintext = self.cipher.open(&self.nonce(), aad, ciphertext)?;
+        self.seq += 1;
+        Ok(plaintext)
+    }
+
+    fn export(&self, exporter_context: &[u8], len: usize) -> Vec<u8> {
+        labeled_expand(&self.suite_id, &self.exporter_secret, b"sec", exporter_context, len)
+    }
+}
+
+fn setup_base_s(pk_r: &[u8; NPK], info: &[u8], suite: SymmetricSuite, sk_e: StaticSecret) -> Result<(Vec<u8>, HpkeContext), String> {
+    let enc = PublicKey::from(&sk_e).to_bytes().to_vec();
+    let dh = sk_e.diffie_hellman(&PublicKey::from(*pk_r));
+    if !dh.was_contributory() {
+        return Err("Invalid OHTTP gateway key".to_string());
+    }
+    let shared_secret = kem_shared_secret(dh.as_bytes(), &enc, pk_r);
+    Ok((enc, HpkeContext::key_schedule(suite, &shared_secret, info)?))
+}
+
+fn setup_base_r(enc: &[u8; NPK], sk_r: &StaticSecret, info: &[u8], suite: SymmetricSuite) -> Result<HpkeContext, String> {
+    let dh = sk_r.diffie_hellman(&PublicKey::from(*enc));
+    if !dh.was_contributory() {
+        return Err("Invalid OHTTP encapsulated key".to_string());
+    }
+    let pk_r = PublicKey::from(sk_r);
+    let shared_secret = kem_shared_secret(dh.as_bytes(), enc, pk_r.as_bytes());
+    HpkeContext::key_schedule(suite, &shared_secret, info)
+}
+
+/// Response keys derived from the request context (RFC 9458, Section 4.4)
+fn response_cipher(secret: &[u8], enc: &[u8], response_nonce: &[u8], aead_id: u16) -> Result<(AeadCipher, Vec<u8>), String> {
+    let salt = [enc, response_nonce].concat();
+    let prk = Hkdf::<Sha256>::extract(Some(&salt), secret).0;
+    let key = expand(&prk, b"key", AeadCipher::key_len(aead_id));
+    let nonce = expand(&prk, b"nonce", NN);
+    Ok((AeadCipher::new(aead_id, &key)?, nonce))
+}
+
+fn response_secret_len(aead_id: u16) -> usize {
+    AeadCipher::key_len(aead_id).max(NN)
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// OHTTP client for one gateway key
+#[derive(Debug, Clone)]
+pub struct OhttpClient {
+    key_config: KeyConfig,
+    suite: SymmetricSuite,
+}
+
+/// Client state needed to decrypt the matching response
+pub struct ClientResponse {
+    enc: Vec<u8>,
+    secret: Vec<u8>,
+    aead_id: u16,
+}
+
+impl fmt::Debug for ClientResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ClientResponse(<redacted>)")
+    }
+}
+
+impl OhttpClient {
+    pub fn new(key_config: KeyConfig) -> Result<Self, String> {
+        let suite = key_config.select_suite()?;
+        Ok(Self { key_config, suite })
+    }
+
+    /// Fetch the gateway's `application/ohttp-keys` and use the first usable config
+    pub fn fetch(transport: &dyn Transport, keys_url: &str) -> Result<Self, String> {
+        let response = transport.send(HttpRequest::get(keys_url).with_header("Accept", KEYS_CONTENT_TYPE))?;
+        let response = response.error_for_status()?;
+        let config = KeyConfig::decode_list(&response.body)?
+            .into_iter()
+            .next()
+            .ok_or("No usable OHTTP key config")?;
+        Self::new(config)
+    }
+
+    pub fn key_config(&self) -> &KeyConfig {
+        &self.key_config
+    }
+
+    /// Encapsulate a Binary HTTP request
+    pub fn encapsulate(&self, request: &[u8]) -> Result<(Vec<u8>, ClientResponse), String> {
+        self.encapsulate_with(request, StaticSecret::random_from_rng(OsRng))
+    }
+
+    fn encapsulate_with(&self, request: &[u8], sk_e: StaticSecret) -> Result<(Vec<u8>, ClientResponse), String> {
+        let hdr = request_header(self.key_config.key_id, self.suite);
+        let (enc, mut context) = setup_base_s(&self.key_config.public_key, &request_info(&hdr), self.suite, sk_e)?;
+        let ct = context.seal(b"", request)?;
+        let secret = context.export(RESPONSE_LABEL, response_secret_len(self.suite.aead_id));
+        let encapsulated = [hdr.as_slice(), &enc, &ct].concat();
+        Ok((encapsulated, ClientResponse { enc, secret, aead_id: self.suite.aead_id }))
+    }
+
+    /// Wrap a request for the relay: returns the outer `message/ohttp-req` POST
+    pub fn encapsulate_request(&self, relay_url: &str, request: &HttpRequest) -> Result<(HttpRequest, ClientResponse), String> {
+        let (encapsulated, pending) = self.encapsulate(&bhttp::encode_request(request)?)?;
+        let outer = HttpRequest::post(relay_url, encapsulated).with_header("Content-Type", REQUEST_CONTENT_TYPE);
+        Ok((outer, pending))
+    }
+
+    /// Send a request through a relay and return the decapsulated response
+    pub fn send(&self, transport: &dyn Transport, relay_url: &str, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let (outer, pending) = self.encapsulate_request(relay_url, request)?;
+        pending.decapsulate_response(transport.send(outer)?)
+    }
+}
+
+impl ClientResponse {
+    /// Decrypt an encapsulated response
+    pub fn decapsulate(self, encapsulated: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce_len = response_secret_len(self.aead_id);
+        if encapsulated.len() < nonce_len {
+            return Err("Truncated OHTTP response".to_string());
+        }
+        let (response_nonce, ct) = encapsulated.split_at(nonce_len);
+        let (cipher, nonce) = response_cipher(&self.secret, &self.enc, response_nonce, self.aead_id)?;
+        cipher.open(&nonce, b"", ct)
+    }
+
+    /// Unwrap the relay's `message/ohttp-res` response
+    pub fn decapsulate_response(self, response: HttpResponse) -> Result<HttpResponse, String> {
+        let response = response.error_for_status()?;
+        let content_type = response.header("Content-Type").unwrap_or_default();
+        if !content_type.eq_ignore_ascii_case(RESPONSE_CONTENT_TYPE) {
+            return Err(format!("Unexpected OHTTP response content type: {}", content_type));
+        }
+        bhttp::decode_response(&self.decapsulate(&response.body)?)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Gateway
+// ---------------------------------------------------------------------------
+
+/// OHTTP gateway (holds the private key)
+pub struct OhttpGateway {
+    key_id: u8,
+    secret: StaticSecret,
+    suites: Vec<SymmetricSuite>,
+}
+
+impl fmt::Debug for OhttpGateway {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OhttpGateway")
+            .field("key_id", &self.key_id)
+            .field("suites", &self.suites)
+            .finish()
+    }
+}
+
+/// Gateway state needed to encrypt the matching response
+pub struct ServerResponse {
+    enc: Vec<u8>,
+    secret: Vec<u8>,
+    aead_id: u16,
+}
+
+impl fmt::Debug for ServerResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ServerResponse(<redacted>)")
+    }
+}
+
+impl OhttpGateway {
+    /// Gateway with a fresh random key
+    pub fn generate(key_id: u8) -> Self {
+        Self::from_secret(key_id, StaticSecret::random_from_rng(OsRng).to_bytes())
+    }
+
+    /// Gateway from a 32-byte X25519 secret key
+    pub fn from_secret(key_id: u8, secret: [u8; 32]) -> Self {
+        Self {
+            key_id,
+            secret: StaticSecret::from(secret),
+            suites: vec![
+                SymmetricSuite { kdf_id: KDF_HKDF_SHA256, aead_id: AEAD_AES_128_GCM },
+                SymmetricSuite { kdf_id: KDF_HKDF_SHA256, aead_id: AEAD_AES_256_GCM },
+            ],
+        }
+    }
+
+    /// Public key configuration to publish
+    pub fn key_config(&self) -> KeyConfig {
+        KeyConfig {
+            key_id: self.key_id,
+            kem_id: KEM_X25519_SHA256,
+            public_key: PublicKey::from(&self.secret).to_bytes(),
+            suites: self.suites.clone(),
+        }
+    }
+
+    /// `application/ohttp-keys` body to serve
+    pub fn encoded_keys(&self) -> Vec<u8> {
+        KeyConfig::encode_list(&[self.key_config()])
+    }
+
+    /// Decrypt an encapsulated request
+    pub fn decapsulate(&self, encapsulated: &[u8]) -> Result<(Vec<u8>, ServerResponse), String> {
+        if encapsulated.len() < 7 + NPK {
+            return Err("Truncated OHTTP request".to_string());
+        }
+        let (hdr, rest) = encapsulated.split_at(7);
+        if hdr[0] != self.key_id {
+            return Err(format!("Unknown OHTTP key id {}", hdr[0]));
+        }
+        if u16::from_be_bytes([hdr[1], hdr[2]]) != KEM_X25519_SHA256 {
+            return Err("Unsupported OHTTP KEM".to_string());
+        }
+        let suite = SymmetricSuite {
+            kdf_id: u16::from_be_bytes([hdr[3], hdr[4]]),
+            aead_id: u16::from_be_bytes([hdr[5], hdr[6]]),
+        };
+        if !self.suites.contains(&suite) {
+            return Err("Unsupported OHTTP cipher suite".to_string());
+        }
+        let (enc, ct) = rest.split_at(NPK);
+        let enc: [u8; NPK] = enc.try_into().map_err(|_| "Invalid encapsulated key")?;
+        let mut context = setup_base_r(&enc, &self.secret, &request_info(hdr), suite)?;
+        let request = context.open(b"", ct)?;
+        let secret = context.export(RESPONSE_LABEL, response_secret_len(suite.aead_id));
+        Ok((request, ServerResponse { enc: enc.to_vec(), secret, aead_id: suite.aead_id }))
+    }
+
+    /// Decapsulate a request, forward it with `forward` and encapsulate the response
+    pub fn handle<F>(&self, encapsulated: &[u8], forward: F) -> Result<Vec<u8>, String>
+    where
+        F: FnOnce(HttpRequest) -> Result<HttpResponse, String>,
+    {
+        let (request, pending) = self.decapsulate(encapsulated)?;
+        let response = forward(bhttp::decode_request(&request)?)?;
+        pending.encapsulate(&bhttp::encode_response(&response))
+    }
+}
+
+impl ServerResponse {
+    /// Encrypt a response
+    pub fn encapsulate(self, response: &[u8]) -> Result<Vec<u8>, String> {
+        let mut response_nonce = vec![0u8; response_secret_len(self.aead_id)];
+        OsRng.fill_bytes(&mut response_nonce);
+        let (cipher, nonce) = response_cipher(&self.secret, &self.enc, &response_nonce, self.aead_id)?;
+        let ct = cipher.seal(&nonce, b"", response)?;
+        Ok([response_nonce, ct].concat())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HttpClient routing
+// ---------------------------------------------------------------------------
+
+/// OHTTP settings for `HttpClient`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OhttpConfig {
+    /// Relay resource requests are posted to
+    pub relay_url: String,
+    /// Base64 KeyConfig or `application/ohttp-keys` body
+    pub key_config: Option<String>,
+    /// Gateway key URL, used when `key_config` is not set
+    pub keys_url: Option<String>,
+    /// Target hosts sent through OHTTP (empty: all non-loopback hosts)
+    pub targets: Vec<String>,
+}
+
+impl OhttpConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: OhttpConfig = serde_json::from_str(json).map_err(|e| format!("Invalid OHTTP config: {}", e))?;
+        if config.relay_url.is_empty() {
+            return Err("OHTTP config requires a relay_url".to_string());
+        }
+        if config.key_config.is_none() && config.keys_url.is_none() {
+            return Err("OHTTP config requires key_config or keys_url".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Whether a request URL should be sent through OHTTP
+    pub fn applies_to(&self, url: &str) -> bool {
+        if crate::transport::is_loopback_url(url) {
+            return false;
+        }
+        if self.targets.is_empty() {
+            return true;
+        }
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        self.targets.iter().any(|t| t.eq_ignore_ascii_case(&host))
+    }
+}
+
+/// Configured OHTTP route (settings plus gateway key)
+#[derive(Debug, Clone)]
+pub struct OhttpRoute {
+    pub config: OhttpConfig,
+    pub client: OhttpClient,
+}
+
+impl OhttpRoute {
+    /// Build a route, fetching the gateway key through `transport` if needed
+    pub fn new(config: OhttpConfig, transport: &dyn Transport) -> Result<Self, String> {
+        let client = match (&config.key_config, &config.keys_url) {
+            (Some(encoded), _) => OhttpClient::new(KeyConfig::from_base64(encoded)?)?,
+            (None, Some(url)) => OhttpClient::fetch(transport, url)?,
+            (None, None) => return Err("OHTTP config requires key_config or keys_url".to_string()),
+        };
+        Ok(Self { config, client })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_hpke_rfc9180_vector() {
+        // RFC 9180, Appendix A.1.1: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM
+        let info = unhex("4f6465206f6e2061204772656369616e2055726e");
+        let sk_e: [u8; 32] = unhex("52c4a758a802cd8b936eceea314432798d5baf2d7e9235dc084ab1b9cfa2f736").try_into().unwrap();
+        let sk_r: [u8; 32] = unhex("4612c550263fc8ad58375df3f557aac531d26850903e55a9f23f21d8534e8ac8").try_into().unwrap();
+        let pk_r = PublicKey::from(&StaticSecret::from(sk_r)).to_bytes();
+        let suite = SymmetricSuite { kdf_id: KDF_HKDF_SHA256, aead_id: AEAD_AES_128_GCM };
+
+        let (enc, mut sender) = setup_base_s(&pk_r, &info, suite, StaticSecret::from(sk_e)).unwrap();
+        assert_eq!(hex::encode(&enc), "37fda3567bdbd628e88668c3c8d7e97d1d1253b6d4ea6d44c150f741f1bf4431");
+        assert_eq!(hex::encode(&sender.base_nonce), "56d890e5accaaf011cff4b7d");
+        assert_eq!(
+            hex::encode(&sender.exporter_secret),
+            "45ff1c2e220db587171952c0592d5f5ebe103f1561a2614e38f2ffd47e99e3f8"
+        );
+        let ct = sender.seal(b"Count-0", b"Beauty is truth, truth beauty").unwrap();
+        assert_eq!(
+            hex::encode(&ct),
+            "f938558b5d72f1a23810b4be2ab4f84331acc02fc97babc53a52ae8218a355a96d8770ac83d07bea87e13c512a"
+        );
+
+        let enc: [u8; 32] = enc.try_into().unwrap();
+        let mut receiver = setup_base_r(&enc, &StaticSecret::from(sk_r), &info, suite).unwrap();
+        assert_eq!(receiver.open(b"Count-0", &ct).unwrap(), b"Beauty is truth, truth beauty");
+    }
+
+    #[test]
+    fn test_key_config_roundtrip() {
+        let gateway = OhttpGateway::generate(7);
+        let keys = gateway.encoded_keys();
+        let configs = KeyConfig::decode_list(&keys).unwrap();
+        assert_eq!(configs, vec![gateway.key_config()]);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&keys);
+        assert_eq!(KeyConfig::from_base64(&encoded).unwrap(), gateway.key_config());
+    }
+
+    #[test]
+    fn test_encapsulation_roundtrip() {
+        let gateway = OhttpGateway::generate(1);
+        let client = OhttpClient::new(gateway.key_config()).unwrap();
+        let (encapsulated, pending) = client.encapsulate(b"request").unwrap();
+        let (request, response_ctx) = gateway.decapsulate(&encapsulated).unwrap();
+        assert_eq!(request, b"request");
+        let response = response_ctx.encapsulate(b"response").unwrap();
+        assert_eq!(pending.decapsulate(&response).unwrap(), b"response");
+
+        let mut tampered = encapsulated.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(gateway.decapsulate(&tampered).is_err());
+        assert!(OhttpGateway::generate(2).decapsulate(&encapsulated).is_err());
+    }
+
+    /// Relay and gateway in one local HTTP/1.1 server: serves keys on `/keys`
+    /// and answers encapsulated requests on `/relay`
+    fn spawn_relay_gateway(gateway: OhttpGateway) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let (content_type, payload) = if request_line.contains("/keys") {
+                    (KEYS_CONTENT_TYPE, gateway.encoded_keys())
+                } else {
+                    let payload = gateway
+                        .handle(&body, |inner| {
+                            Ok(HttpResponse {
+                                status: 200,
+                                headers: vec![("content-type".to_string(), "text/plain".to_string())],
+                                body: format!("{} {} auth={}", inner.method, inner.url, inner.header("authorization").unwrap_or("-"))
+                                    .into_bytes(),
+                            })
+                        })
+                        .unwrap();
+                    (RESPONSE_CONTENT_TYPE, payload)
+                };
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    content_type,
+                    payload.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&payload).unwrap();
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_http_client_through_local_relay() {
+        let port = spawn_relay_gateway(OhttpGateway::generate(3));
+        let mut client = crate::HttpClient::new(String::new());
+        let config = format!(
+            r#"{{"relay_url": "http://127.0.0.1:{0}/relay", "keys_url": "http://127.0.0.1:{0}/keys"}}"#,
+            port
+        );
+        client.configure_ohttp(&config).unwrap();
+
+        let request = HttpRequest::get("https://api.example.com/v1/models").with_header("Authorization", "Bearer k");
+        let response = client.send(request).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text(), "GET https://api.example.com/v1/models auth=Bearer k");
+    }
+}
@@ -0,0 +1,71 @@
+//! Python PyO3 bindings for PII scrubbing
+
+use pyo3::prelude::*;
+use std::sync::Mutex;
+use crate::pii::*;
+
+/// Python wrapper for PiiScrubber (keeps its own placeholder vault)
+#[pyclass(name = "PiiScrubber", module = "privacy_http_sdk.policy")]
+pub struct PyPiiScrubber {
+    inner: PiiScrubber,
+    vault: Mutex<PiiVault>,
+}
+
+#[pymethods]
+impl PyPiiScrubber {
+    /// Create a scrubber from an optional JSON config
+    #[new]
+    #[pyo3(signature = (config_json=None))]
+    fn new(config_json: Option<String>) -> PyResult<Self> {
+        let config = match config_json {
+            Some(json) => PiiConfig::from_json(&json)
+                .map_err(pyo3::exceptions::PyValueError::new_err)?,
+            None => PiiConfig::default(),
+        };
+        let inner = PiiScrubber::new(&config)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyPiiScrubber { inner, vault: Mutex::new(PiiVault::with_capacity(config.vault_capacity)) })
+    }
+
+    /// Replace PII in text with placeholder tokens
+    fn scrub(&self, text: String) -> String {
+        let mut vault = self.vault.lock().unwrap();
+        self.inner.scrub(&text, &mut vault)
+    }
+
+    /// Scrub a JSON array of chat messages, returning the scrubbed JSON
+    fn scrub_messages(&self, messages_json: String) -> PyResult<String> {
+        let mut messages: serde_json::Value = serde_json::from_str(&messages_json)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid messages JSON: {}", e)))?;
+        let mut vault = self.vault.lock().unwrap();
+        self.inner.scrub_messages(&mut messages, &mut vault);
+        Ok(messages.to_string())
+    }
+
+    /// Restore original values in a response
+    fn rehydrate(&self, text: String) -> String {
+        self.vault.lock().unwrap().rehydrate(&text)
+    }
+
+    /// List findings as (kind, start, end) tuples without scrubbing
+    fn detect(&self, text: String) -> Vec<(String, usize, usize)> {
+        self.inner
+            .detect(&text)
+            .into_iter()
+            .map(|m| (m.kind.label(), m.start, m.end))
+            .collect()
+    }
+
+    /// Forget all stored placeholders
+    fn clear(&self) {
+        self.vault.lock().unwrap().clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.vault.lock().unwrap().len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PyPiiScrubber({} placeholders)", self.vault.lock().unwrap().len())
+    }
+}
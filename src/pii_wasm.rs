@@ -0,0 +1,53 @@
+//! WASM bindings for PII scrubbing via wasm-bindgen
+
+use wasm_bindgen::prelude::*;
+use crate::pii::*;
+
+/// JavaScript wrapper for PiiScrubber (keeps its own placeholder vault)
+#[wasm_bindgen]
+pub struct JsPiiScrubber {
+    inner: PiiScrubber,
+    vault: PiiVault,
+}
+
+#[wasm_bindgen]
+impl JsPiiScrubber {
+    /// Create a scrubber from an optional JSON config
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: Option<String>) -> Result<JsPiiScrubber, JsValue> {
+        let config = match config_json {
+            Some(json) => PiiConfig::from_json(&json).map_err(|e| JsValue::from_str(&e))?,
+            None => PiiConfig::default(),
+        };
+        let inner = PiiScrubber::new(&config).map_err(|e| JsValue::from_str(&e))?;
+        Ok(JsPiiScrubber { inner, vault: PiiVault::with_capacity(config.vault_capacity) })
+    }
+
+    /// Replace PII in text with placeholder tokens
+    pub fn scrub(&mut self, text: &str) -> String {
+        self.inner.scrub(text, &mut self.vault)
+    }
+
+    /// Scrub a JSON array of chat messages, returning the scrubbed JSON
+    pub fn scrub_messages(&mut self, messages_json: &str) -> Result<String, JsValue> {
+        let mut messages: serde_json::Value = serde_json::from_str(messages_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid messages JSON: {}", e)))?;
+        self.inner.scrub_messages(&mut messages, &mut self.vault);
+        Ok(messages.to_string())
+    }
+
+    /// Restore original values in a response
+    pub fn rehydrate(&self, text: &str) -> String {
+        self.vault.rehydrate(text)
+    }
+
+    /// Number of stored placeholders
+    pub fn placeholder_count(&self) -> usize {
+        self.vault.len()
+    }
+
+    /// Forget all stored placeholders
+    pub fn clear(&mut self) {
+        self.vault.clear();
+    }
+}
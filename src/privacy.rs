@@ -0,0 +1,376 @@
+//! Privacy Policy Module
+//!
+//! This module loads the JSON privacy configuration described in the spec and applies
+//! it to every request sent by `HttpClient`.
+//!
+//! # Features
+//! - Header filtering (`blacklist` or `whitelist`)
+//! - IP masking (removal of client-identifying forwarding headers)
+//! - TLS enforcement flag
+//! - User-Agent normalization and tracker blocking
+//! - Payload obfuscation settings (`base64`, `xor`, `aes-gcm`) exposed as a [`PayloadCodec`]
+//! - Proxy routing (see [`crate::proxy`])
+//! - DNS-over-HTTPS resolution (see [`crate::doh`])
+//! - Privacy budget / rate limits (see [`crate::ratelimit`])
+//! - Opt-in partitioned cookie jar (see [`crate::cookies`])
+//! - Logging mode
+
+use serde::{Deserialize, Serialize};
+use crate::codec::{CodecMethod, PayloadCodec};
+use crate::cookies::CookieConfig;
+use crate::doh::DohConfig;
+use crate::proxy::ProxyConfig;
+use crate::ratelimit::RateLimitConfig;
+use crate::transport::HttpRequest;
+
+/// Header filtering mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    /// Drop the listed headers
+    Blacklist,
+    /// Keep only the listed headers
+    Whitelist,
+}
+
+/// Header filtering configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeaderFilterConfig {
+    pub mode: FilterMode,
+    pub headers: Vec<String>,
+}
+
+impl Default for HeaderFilterConfig {
+    fn default() -> Self {
+        Self {
+            mode: FilterMode::Blacklist,
+            headers: vec!["Cookie".to_string(), "Referer".to_string()],
+        }
+    }
+}
+
+/// Payload obfuscation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObfuscationConfig {
+    pub enabled: bool,
+    pub method: CodecMethod,
+    /// Key material (`xor`: raw string, `aes-gcm`: base64 32-byte key)
+    #[serde(default, skip_serializing)]
+    pub key: Option<String>,
+}
+
+impl Default for ObfuscationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: CodecMethod::Base64,
+            key: None,
+        }
+    }
+}
+
+/// IP masking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpMaskingConfig {
+    pub enabled: bool,
+    pub remove_headers: Vec<String>,
+}
+
+impl Default for IpMaskingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            remove_headers: vec![
+                "X-Forwarded-For".to_string(),
+                "CF-Connecting-IP".to_string(),
+                "X-Real-IP".to_string(),
+                "True-Client-IP".to_string(),
+                "Forwarded".to_string(),
+            ],
+        }
+    }
+}
+
+/// Hosts of common analytics and advertising trackers
+pub const DEFAULT_TRACKERS: &[&str] = &[
+    "*.doubleclick.net",
+    "*.google-analytics.com",
+    "*.googletagmanager.com",
+    "*.googlesyndication.com",
+    "connect.facebook.net",
+    "*.scorecardresearch.com",
+    "*.hotjar.com",
+    "*.segment.io",
+    "*.mixpanel.com",
+    "*.amplitude.com",
+    "*.adnxs.com",
+    "*.criteo.com",
+];
+
+/// Tracker blocking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerConfig {
+    pub enabled: bool,
+    /// Host patterns: exact host, `*.suffix`, or `*`
+    pub hosts: Vec<String>,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hosts: DEFAULT_TRACKERS.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+}
+
+/// Logging mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingMode {
+    /// No logging
+    #[default]
+    None,
+    /// Method, host and status only
+    Metadata,
+    /// Full logging with sensitive values redacted
+    Redacted,
+}
+
+/// Privacy configuration (the spec's JSON config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    pub filter_headers: HeaderFilterConfig,
+    pub obfuscation: ObfuscationConfig,
+    pub ip_masking: IpMaskingConfig,
+    pub tls_enforce: bool,
+    pub logging: LoggingMode,
+    /// Replace the `User-Agent` of every request (while the header rules allow it)
+    pub user_agent: Option<String>,
+    pub block_trackers: TrackerConfig,
+    /// Proxy routing; `None` connects directly
+    pub proxy: Option<ProxyConfig>,
+    /// DNS-over-HTTPS resolution; `None` uses the system resolver
+    pub dns: Option<DohConfig>,
+    /// Privacy budget (rate limits); `None` is unlimited
+    pub budget: Option<RateLimitConfig>,
+    /// Cookie jar; `None` keeps no cookies. Cookies are only sent while the header
+    /// rules allow `Cookie`.
+    pub cookies: Option<CookieConfig>,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            filter_headers: HeaderFilterConfig::default(),
+            obfuscation: ObfuscationConfig::default(),
+            ip_masking: IpMaskingConfig::default(),
+            tls_enforce: true,
+            logging: LoggingMode::None,
+            user_agent: None,
+            block_trackers: TrackerConfig::default(),
+            proxy: None,
+            dns: None,
+            budget: None,
+            cookies: None,
+        }
+    }
+}
+
+impl PrivacyConfig {
+    /// Parse from JSON (missing sections fall back to defaults)
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: PrivacyConfig = serde_json::from_str(json).map_err(|e| format!("Invalid privacy config: {}", e))?;
+        if let Some(proxy) = &config.proxy {
+            proxy.validate()?;
+        }
+        if let Some(dns) = &config.dns {
+            dns.validate()?;
+        }
+        if let Some(budget) = &config.budget {
+            budget.validate()?;
+        }
+        if let Some(cookies) = &config.cookies {
+            cookies.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Load from a JSON file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::from_json(&json)
+    }
+}
+
+/// What the policy changed in a request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PolicyReport {
+    /// Removed headers and the rule that removed them
+    pub removed: Vec<(String, String)>,
+    /// Headers set or replaced by the policy, with their new value
+    pub rewritten: Vec<(String, String)>,
+}
+
+/// Privacy policy applied to outgoing requests
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyPolicy {
+    config: PrivacyConfig,
+}
+
+impl PrivacyPolicy {
+    pub fn new(config: PrivacyConfig) -> Self {
+        Self { config }
+    }
+
+    /// Underlying configuration
+    pub fn config(&self) -> &PrivacyConfig {
+        &self.config
+    }
+
+    /// Whether a header may be sent under this policy
+    pub fn allows_header(&self, name: &str) -> bool {
+        self.removal_rule(name).is_none()
+    }
+
+    /// Rule that removes a header, if any
+    pub fn removal_rule(&self, name: &str) -> Option<&'static str> {
+        let listed = |list: &[String]| list.iter().any(|h| h.eq_ignore_ascii_case(name));
+        if self.config.ip_masking.enabled && listed(&self.config.ip_masking.remove_headers) {
+            return Some("ip_masking");
+        }
+        match self.config.filter_headers.mode {
+            FilterMode::Blacklist if listed(&self.config.filter_headers.headers) => Some("filter_headers blacklist"),
+            FilterMode::Whitelist if !listed(&self.config.filter_headers.headers) => Some("filter_headers whitelist"),
+            _ => None,
+        }
+    }
+
+    /// Filter a header list, returning the names of removed headers
+    pub fn filter_headers(&self, headers: &mut Vec<(String, String)>) -> Vec<String> {
+        let mut removed = Vec::new();
+        headers.retain(|(name, _)| {
+            let keep = self.allows_header(name);
+            if !keep {
+                removed.push(name.clone());
+            }
+            keep
+        });
+        removed
+    }
+
+    /// Apply the policy to an outgoing request, returning the names of removed headers
+    pub fn apply(&self, request: &mut HttpRequest) -> Vec<String> {
+        self.explain(request).removed.into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Apply the policy to an outgoing request, reporting every change
+    pub fn explain(&self, request: &mut HttpRequest) -> PolicyReport {
+        let mut report = PolicyReport::default();
+        request.headers.retain(|(name, _)| match self.removal_rule(name) {
+            Some(rule) => {
+                report.removed.push((name.clone(), rule.to_string()));
+                false
+            }
+            None => true,
+        });
+        if let Some(user_agent) = self.config.user_agent.as_deref().filter(|_| self.allows_header("User-Agent")) {
+            request.headers.retain(|(k, _)| !k.eq_ignore_ascii_case("user-agent"));
+            request.headers.push(("User-Agent".to_string(), user_agent.to_string()));
+            report.rewritten.push(("User-Agent".to_string(), user_agent.to_string()));
+        }
+        report
+    }
+
+    /// Whether a host is a blocked tracker
+    pub fn blocks_host(&self, host: &str) -> bool {
+        let host = host.trim_matches(['[', ']']).trim_end_matches('.').to_ascii_lowercase();
+        let trackers = &self.config.block_trackers;
+        trackers.enabled && trackers.hosts.iter().any(|pattern| crate::proxy::host_matches(pattern, &host))
+    }
+
+    /// Whether a request URL targets a blocked tracker
+    pub fn blocks_url(&self, url: &str) -> bool {
+        reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(|h| self.blocks_host(h))).unwrap_or(false)
+    }
+
+    /// Payload codec, when obfuscation is enabled
+    pub fn codec(&self) -> Result<Option<PayloadCodec>, String> {
+        let obfuscation = &self.config.obfuscation;
+        if !obfuscation.enabled {
+            return Ok(None);
+        }
+        PayloadCodec::from_method(obfuscation.method, obfuscation.key.as_deref()).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC_CONFIG: &str = r#"{
+        "filter_headers": { "mode": "blacklist", "headers": ["User-Agent", "Referer", "Cookie"] },
+        "obfuscation": { "enabled": true, "method": "base64" },
+        "ip_masking": { "enabled": true, "remove_headers": ["X-Forwarded-For", "CF-Connecting-IP"] },
+        "tls_enforce": true,
+        "logging": "metadata"
+    }"#;
+
+    #[test]
+    fn test_parse_spec_config() {
+        let config = PrivacyConfig::from_json(SPEC_CONFIG).unwrap();
+        assert_eq!(config.filter_headers.headers.len(), 3);
+        assert_eq!(config.logging, LoggingMode::Metadata);
+        assert!(matches!(PrivacyPolicy::new(config).codec(), Ok(Some(PayloadCodec::Base64))));
+    }
+
+    #[test]
+    fn test_blacklist_and_ip_masking() {
+        let policy = PrivacyPolicy::new(PrivacyConfig::from_json(SPEC_CONFIG).unwrap());
+        let mut request = HttpRequest::get("https://example.com")
+            .with_header("user-agent", "curl")
+            .with_header("x-forwarded-for", "10.0.0.1")
+            .with_header("Accept", "application/json");
+        let removed = policy.apply(&mut request);
+        assert_eq!(removed, vec!["user-agent", "x-forwarded-for"]);
+        assert_eq!(request.headers, vec![("Accept".to_string(), "application/json".to_string())]);
+    }
+
+    #[test]
+    fn test_whitelist() {
+        let config = PrivacyConfig::from_json(
+            r#"{"filter_headers": {"mode": "whitelist", "headers": ["Accept"]}}"#,
+        )
+        .unwrap();
+        let policy = PrivacyPolicy::new(config);
+        assert!(policy.allows_header("accept"));
+        assert!(!policy.allows_header("Authorization"));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let policy = PrivacyPolicy::default();
+        assert!(policy.config().tls_enforce);
+        assert!(!policy.allows_header("Cookie"));
+        assert!(!policy.allows_header("CF-Connecting-IP"));
+        assert!(policy.allows_header("Authorization"));
+        assert!(policy.blocks_url("https://www.google-analytics.com/collect"));
+        assert!(!policy.blocks_url("https://api.openai.com/v1/models"));
+    }
+
+    #[test]
+    fn test_user_agent_normalization() {
+        let config = PrivacyConfig::from_json(r#"{"user_agent": "Mozilla/5.0", "block_trackers": {"enabled": false, "hosts": []}}"#);
+        let policy = PrivacyPolicy::new(config.unwrap());
+        let mut request = HttpRequest::get("https://example.com").with_header("user-agent", "python-requests/2.31");
+        policy.apply(&mut request);
+        assert_eq!(request.headers, vec![("User-Agent".to_string(), "Mozilla/5.0".to_string())]);
+        assert!(!policy.blocks_host("stats.g.doubleclick.net"));
+
+        let mut request = HttpRequest::get("https://example.com").with_header("Cookie", "id=1");
+        let report = policy.explain(&mut request);
+        assert_eq!(report.removed, vec![("Cookie".to_string(), "filter_headers blacklist".to_string())]);
+        assert_eq!(report.rewritten, vec![("User-Agent".to_string(), "Mozilla/5.0".to_string())]);
+    }
+}
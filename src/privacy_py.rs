@@ -0,0 +1,90 @@
+//! Python PyO3 bindings for the privacy policy (`privacy_http_sdk.policy`)
+
+use pyo3::prelude::*;
+use crate::privacy::*;
+use crate::transport::HttpRequest;
+
+/// Python wrapper for PolicyReport
+#[pyclass(name = "PolicyReport", module = "privacy_http_sdk.policy", frozen, get_all)]
+pub struct PyPolicyReport {
+    /// Removed headers and the rule that removed them
+    removed: Vec<(String, String)>,
+    /// Headers set or replaced by the policy
+    rewritten: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl PyPolicyReport {
+    fn __repr__(&self) -> String {
+        format!("PolicyReport(removed={:?}, rewritten={:?})", self.removed, self.rewritten)
+    }
+}
+
+/// Python wrapper for PrivacyPolicy
+#[pyclass(name = "PrivacyPolicy", module = "privacy_http_sdk.policy", frozen)]
+pub struct PyPrivacyPolicy {
+    inner: PrivacyPolicy,
+}
+
+#[pymethods]
+impl PyPrivacyPolicy {
+    /// Policy from a spec.md JSON config (defaults without one)
+    #[new]
+    #[pyo3(signature = (config_json=None))]
+    fn new(config_json: Option<String>) -> PyResult<Self> {
+        let config = match config_json {
+            Some(json) => PrivacyConfig::from_json(&json).map_err(pyo3::exceptions::PyValueError::new_err)?,
+            None => PrivacyConfig::default(),
+        };
+        Ok(PyPrivacyPolicy { inner: PrivacyPolicy::new(config) })
+    }
+
+    /// Policy from a JSON config file
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let config = PrivacyConfig::from_file(path).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(PyPrivacyPolicy { inner: PrivacyPolicy::new(config) })
+    }
+
+    /// Effective config as JSON
+    fn config_json(&self) -> PyResult<String> {
+        serde_json::to_string(self.inner.config()).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    fn allows_header(&self, name: &str) -> bool {
+        self.inner.allows_header(name)
+    }
+
+    /// Whether requests to `url` are blocked as trackers
+    fn blocks_url(&self, url: &str) -> bool {
+        self.inner.blocks_url(url)
+    }
+
+    /// Headers of a request after the policy
+    #[pyo3(signature = (method, url, headers=Vec::new()))]
+    fn apply(&self, method: &str, url: &str, headers: Vec<(String, String)>) -> Vec<(String, String)> {
+        let mut request = HttpRequest::new(method, url).with_headers(&headers);
+        self.inner.apply(&mut request);
+        request.headers
+    }
+
+    /// Which headers the policy removes or rewrites for a request
+    #[pyo3(signature = (method, url, headers=Vec::new()))]
+    fn explain(&self, method: &str, url: &str, headers: Vec<(String, String)>) -> PyPolicyReport {
+        let mut request = HttpRequest::new(method, url).with_headers(&headers);
+        let report = self.inner.explain(&mut request);
+        PyPolicyReport { removed: report.removed, rewritten: report.rewritten }
+    }
+
+    fn __repr__(&self) -> String {
+        "PrivacyPolicy(...)".to_string()
+    }
+}
+
+/// Populate the `policy` submodule
+pub fn init_privacy_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyPrivacyPolicy>()?;
+    module.add_class::<PyPolicyReport>()?;
+    module.add_class::<crate::pii_py::PyPiiScrubber>()?;
+    Ok(())
+}
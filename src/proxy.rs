@@ -0,0 +1,569 @@
+//! Proxy Routing Module
+//!
+//! This module routes `HttpClient` traffic through SOCKS5 or HTTP CONNECT proxies
+//! (Tor, corporate egress) as configured in the `proxy` section of the privacy config.
+//!
+//! # Features
+//! - `socks5h://` (DNS resolved by the proxy), `socks5://`, `http://` and `https://` proxies
+//! - Per-host rules (`*.onion`, exact hosts, `direct`)
+//! - Stream isolation: distinct SOCKS credentials per destination or per identity, so
+//!   Tor (`IsolateSOCKSAuth`) never shares a circuit between them
+//! - `.onion` guard: onion hosts are only ever sent to a proxy that resolves DNS remotely
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use crate::doh::DohResolver;
+use reqwest::{Client, ClientBuilder, Proxy, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Rule target meaning "connect without a proxy"
+pub const DIRECT: &str = "direct";
+
+/// Stream isolation mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IsolationMode {
+    /// Share proxy credentials (and circuits) across all requests
+    #[default]
+    None,
+    /// Distinct credentials per destination host
+    PerHost,
+    /// Distinct credentials per configured identity
+    PerIdentity,
+}
+
+/// Per-host proxy rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyRule {
+    /// Host patterns: exact host, `*.suffix`, or `*`
+    pub hosts: Vec<String>,
+    /// Proxy URL or `direct`
+    pub proxy: String,
+}
+
+/// Proxy configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Default proxy URL (`socks5h://127.0.0.1:9050` for Tor); `None` connects directly
+    pub url: Option<String>,
+    /// Rules checked in order before the default
+    pub rules: Vec<ProxyRule>,
+    pub isolation: IsolationMode,
+    /// Identity used with [`IsolationMode::PerIdentity`]
+    pub identity: Option<String>,
+    /// Allow `.onion` destinations (always through a remote-DNS proxy)
+    pub allow_onion: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            rules: Vec::new(),
+            isolation: IsolationMode::None,
+            identity: None,
+            allow_onion: true,
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: ProxyConfig = serde_json::from_str(json).map_err(|e| format!("Invalid proxy config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Tor defaults: everything through the local SOCKS port, isolated per host
+    pub fn tor(socks_port: u16) -> Self {
+        Self {
+            url: Some(format!("socks5h://127.0.0.1:{}", socks_port)),
+            isolation: IsolationMode::PerHost,
+            ..Self::default()
+        }
+    }
+
+    /// Check proxy URLs
+    pub fn validate(&self) -> Result<(), String> {
+        let targets = self.url.iter().chain(self.rules.iter().map(|rule| &rule.proxy));
+        for target in targets.filter(|t| t.as_str() != DIRECT) {
+            let url = Url::parse(target).map_err(|e| format!("Invalid proxy URL {}: {}", target, e))?;
+            if !matches!(url.scheme(), "socks5" | "socks5h" | "http" | "https") {
+                return Err(format!("Unsupported proxy scheme: {}", url.scheme()));
+            }
+        }
+        if self.isolation == IsolationMode::PerIdentity && self.identity.is_none() {
+            return Err("Per-identity isolation requires an identity".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Whether the URL targets a Tor onion service
+pub fn is_onion_url(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(is_onion_host))
+        .unwrap_or(false)
+}
+
+fn is_onion_host(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host == "onion" || host.ends_with(".onion")
+}
+
+pub(crate) fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_prefix("*.").or_else(|| pattern.strip_prefix('.')) {
+        Some(suffix) => host == suffix || host.ends_with(&format!(".{}", suffix)),
+        None => host == pattern,
+    }
+}
+
+/// Whether the proxy resolves destination names itself (no local DNS lookup)
+fn resolves_remotely(proxy: &Url) -> bool {
+    matches!(proxy.scheme(), "socks5h" | "http" | "https")
+}
+
+/// Resolves the proxy for each destination
+#[derive(Debug)]
+pub struct ProxyRouter {
+    config: ProxyConfig,
+    /// Random per-client tag, so a new client never reuses an earlier client's circuits
+    session: String,
+}
+
+impl ProxyRouter {
+    pub fn new(config: ProxyConfig) -> Result<Self, String> {
+        config.validate()?;
+        let mut session = [0u8; 8];
+        OsRng.fill_bytes(&mut session);
+        Ok(Self { config, session: hex::encode(session) })
+    }
+
+    pub fn config(&self) -> &ProxyConfig {
+        &self.config
+    }
+
+    /// Proxy for a destination URL (with isolation credentials), `None` for direct
+    pub fn route(&self, url: &Url) -> Option<Url> {
+        let host = url.host_str()?.trim_end_matches('.').to_ascii_lowercase();
+        let target = self
+            .config
+            .rules
+            .iter()
+            .find(|rule| rule.hosts.iter().any(|pattern| host_matches(pattern, &host)))
+            .map(|rule| rule.proxy.as_str())
+            .or(self.config.url.as_deref())?;
+        if target == DIRECT {
+            return None;
+        }
+        let mut proxy = Url::parse(target).ok()?;
+        if let Some(tag) = self.isolation_tag(&host) {
+            // Tor isolates streams by SOCKS username/password
+            let _ = proxy.set_username(&tag);
+            let _ = proxy.set_password(Some(&self.session));
+        }
+        Some(proxy)
+    }
+
+    fn isolation_tag(&self, host: &str) -> Option<String> {
+        let key = match self.config.isolation {
+            IsolationMode::None => return None,
+            IsolationMode::PerHost => format!("host:{}", host),
+            IsolationMode::PerIdentity => format!("identity:{}", self.config.identity.as_deref().unwrap_or_default()),
+        };
+        Some(hex::encode(&Sha256::digest(key.as_bytes())[..16]))
+    }
+
+    /// Refuse destinations that would leak: `.onion` hosts must go through a proxy
+    /// that resolves names remotely
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        if !parsed.host_str().is_some_and(is_onion_host) {
+            return Ok(());
+        }
+        if !self.config.allow_onion {
+            return Err("Onion destinations are disabled by the proxy config".to_string());
+        }
+        match self.route(&parsed) {
+            Some(proxy) if resolves_remotely(&proxy) => Ok(()),
+            Some(_) => Err("Onion destinations require a socks5h:// proxy (remote DNS)".to_string()),
+            None => Err("Onion destinations require a proxy".to_string()),
+        }
+    }
+}
+
+/// Route a client builder through the router
+pub fn apply(builder: ClientBuilder, router: &Arc<ProxyRouter>) -> ClientBuilder {
+    let router = Arc::clone(router);
+    builder.proxy(Proxy::custom(move |url| router.route(url)))
+}
+
+/// Client builder for the given policy settings
+pub fn client_builder(
+    router: Option<&Arc<ProxyRouter>>,
+    resolver: Option<&DohResolver>,
+    https_only: bool,
+) -> ClientBuilder {
+    let mut builder = Client::builder().https_only(https_only);
+    if let Some(router) = router {
+        builder = apply(builder, router);
+    }
+    if let Some(resolver) = resolver {
+        builder = builder.dns_resolver(resolver.clone());
+    }
+    builder
+}
+
+/// Build a client for the given policy settings
+pub fn build_client(
+    router: Option<&Arc<ProxyRouter>>,
+    resolver: Option<&DohResolver>,
+    https_only: bool,
+) -> Result<Client, String> {
+    client_builder(router, resolver, https_only)
+        .build()
+        .map_err(|e| format!("Failed to create reqwest client: {}", e))
+}
+
+/// Open a TCP connection to `host:port` routed like requests to that host: through
+/// the router's SOCKS5 or HTTP proxy, or directly (resolving with DoH when configured)
+pub async fn connect_tunnel(
+    router: Option<&ProxyRouter>,
+    resolver: Option<&DohResolver>,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, String> {
+    let host = host.trim_matches(['[', ']']);
+    let authority = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+    let url = Url::parse(&format!("https://{}/", authority)).map_err(|e| format!("Invalid host {}: {}", host, e))?;
+    if let Some(router) = router {
+        router.check(url.as_str())?;
+        if let Some(proxy) = router.route(&url) {
+            return connect_via_proxy(&proxy, resolver, host, port).await;
+        }
+    }
+    if is_onion_host(host) {
+        return Err("Onion destinations require a proxy".to_string());
+    }
+    connect_direct(resolver, host, port).await
+}
+
+async fn resolve(resolver: Option<&DohResolver>, host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    let addrs: Vec<SocketAddr> = match resolver {
+        Some(resolver) => resolver.lookup(host).await?.into_iter().map(|ip| SocketAddr::new(ip, port)).collect(),
+        None => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .collect(),
+    };
+    if addrs.is_empty() {
+        return Err(format!("No addresses for {}", host));
+    }
+    Ok(addrs)
+}
+
+async fn connect_direct(resolver: Option<&DohResolver>, host: &str, port: u16) -> Result<TcpStream, String> {
+    let addrs = resolve(resolver, host, port).await?;
+    TcpStream::connect(&addrs[..]).await.map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))
+}
+
+fn socks_len(field: &str, value: &str) -> Result<u8, String> {
+    u8::try_from(value.len()).map_err(|_| format!("SOCKS5 {} is longer than 255 bytes", field))
+}
+
+async fn connect_via_proxy(proxy: &Url, resolver: Option<&DohResolver>, host: &str, port: u16) -> Result<TcpStream, String> {
+    let proxy_host = proxy.host_str().ok_or("Proxy URL has no host")?.trim_matches(['[', ']']);
+    let proxy_port = proxy.port_or_known_default().unwrap_or(1080);
+    let socks = matches!(proxy.scheme(), "socks5" | "socks5h");
+    let (user, password) = (proxy.username(), proxy.password().unwrap_or_default());
+    // SOCKS5 length prefixes are single bytes; refuse rather than truncate
+    let (user_len, password_len, host_len) = if socks {
+        (socks_len("username", user)?, socks_len("password", password)?, socks_len("host name", host)?)
+    } else {
+        (0, 0, 0)
+    };
+    // The proxy itself is usually local; its address is never a destination leak
+    let mut stream = connect_direct(None, proxy_host, proxy_port).await?;
+    let io = |e: std::io::Error| format!("Proxy connection failed: {}", e);
+    match proxy.scheme() {
+        "socks5" | "socks5h" => {
+            let auth = !user.is_empty();
+            stream.write_all(if auth { &[5, 2, 0, 2] } else { &[5, 1, 0] }).await.map_err(io)?;
+            let mut choice = [0u8; 2];
+            stream.read_exact(&mut choice).await.map_err(io)?;
+            match choice {
+                [5, 0] => {}
+                [5, 2] if auth => {
+                    let mut login = vec![1, user_len];
+                    login.extend_from_slice(user.as_bytes());
+                    login.push(password_len);
+                    login.extend_from_slice(password.as_bytes());
+                    stream.write_all(&login).await.map_err(io)?;
+                    let mut status = [0u8; 2];
+                    stream.read_exact(&mut status).await.map_err(io)?;
+                    if status[1] != 0 {
+                        return Err("SOCKS5 authentication failed".to_string());
+                    }
+                }
+                _ => return Err("SOCKS5 proxy refused the authentication methods".to_string()),
+            }
+
+            let mut request = vec![5, 1, 0];
+            let remote_dns = proxy.scheme() == "socks5h" && host.parse::<IpAddr>().is_err();
+            let address = if remote_dns { None } else { Some(resolve(resolver, host, port).await?[0].ip()) };
+            match address {
+                None => {
+                    request.extend_from_slice(&[3, host_len]);
+                    request.extend_from_slice(host.as_bytes());
+                }
+                Some(IpAddr::V4(ip)) => {
+                    request.push(1);
+                    request.extend_from_slice(&ip.octets());
+                }
+                Some(IpAddr::V6(ip)) => {
+                    request.push(4);
+                    request.extend_from_slice(&ip.octets());
+                }
+            }
+            request.extend_from_slice(&port.to_be_bytes());
+            stream.write_all(&request).await.map_err(io)?;
+
+            let mut reply = [0u8; 4];
+            stream.read_exact(&mut reply).await.map_err(io)?;
+            if reply[1] != 0 {
+                return Err(format!("SOCKS5 connect to {}:{} failed (reply {})", host, port, reply[1]));
+            }
+            let bound_len = match reply[3] {
+                1 => 4,
+                4 => 16,
+                3 => {
+                    let mut len = [0u8; 1];
+                    stream.read_exact(&mut len).await.map_err(io)?;
+                    len[0] as usize
+                }
+                other => return Err(format!("Invalid SOCKS5 address type {}", other)),
+            };
+            let mut bound = vec![0u8; bound_len + 2];
+            stream.read_exact(&mut bound).await.map_err(io)?;
+            Ok(stream)
+        }
+        "http" => {
+            let mut connect = format!("CONNECT {0}:{1} HTTP/1.1\r\nHost: {0}:{1}\r\n", host, port);
+            if !proxy.username().is_empty() {
+                let credentials = format!("{}:{}", proxy.username(), proxy.password().unwrap_or_default());
+                connect.push_str(&format!("Proxy-Authorization: Basic {}\r\n", BASE64.encode(credentials)));
+            }
+            connect.push_str("\r\n");
+            stream.write_all(connect.as_bytes()).await.map_err(io)?;
+
+            // Read the response head byte by byte so no tunnelled data is consumed
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                if head.len() > 16 * 1024 {
+                    return Err("Proxy response too large".to_string());
+                }
+                let mut byte = [0u8; 1];
+                stream.read_exact(&mut byte).await.map_err(io)?;
+                head.push(byte[0]);
+            }
+            let status_line = String::from_utf8_lossy(&head).lines().next().unwrap_or_default().to_string();
+            if status_line.split_whitespace().nth(1) != Some("200") {
+                return Err(format!("Proxy refused CONNECT {}:{}: {}", host, port, status_line));
+            }
+            Ok(stream)
+        }
+        other => Err(format!("Unsupported proxy scheme for tunnels: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_rules_and_isolation() {
+        let config = ProxyConfig::from_json(
+            r#"{
+                "url": "socks5h://127.0.0.1:9050",
+                "rules": [
+                    { "hosts": ["*.corp.example"], "proxy": "http://egress:3128" },
+                    { "hosts": ["localhost"], "proxy": "direct" }
+                ],
+                "isolation": "per_host"
+            }"#,
+        )
+        .unwrap();
+        let router = ProxyRouter::new(config).unwrap();
+        let route = |url: &str| router.route(&Url::parse(url).unwrap());
+
+        assert_eq!(route("https://git.corp.example/").unwrap().host_str(), Some("egress"));
+        assert!(route("http://localhost:8080/").is_none());
+        let a = route("https://a.example/").unwrap();
+        let b = route("https://b.example/x").unwrap();
+        assert_eq!(a.scheme(), "socks5h");
+        assert_ne!(a.username(), b.username());
+        assert_eq!(a.username(), route("https://a.example/other").unwrap().username());
+        assert_eq!(a.password(), b.password());
+    }
+
+    #[test]
+    fn test_identity_isolation() {
+        let config = |identity: &str| ProxyConfig {
+            isolation: IsolationMode::PerIdentity,
+            identity: Some(identity.to_string()),
+            ..ProxyConfig::tor(9050)
+        };
+        let url = Url::parse("https://example.com/").unwrap();
+        let alice = ProxyRouter::new(config("alice")).unwrap();
+        let bob = ProxyRouter::new(config("bob")).unwrap();
+        assert_ne!(alice.route(&url).unwrap().username(), bob.route(&url).unwrap().username());
+        assert!(ProxyRouter::new(ProxyConfig { identity: None, ..config("x") }).is_err());
+    }
+
+    #[test]
+    fn test_onion_guard() {
+        let onion = "http://expyuzz4wqqyqhjn.onion/";
+        assert!(is_onion_url(onion));
+        assert!(ProxyRouter::new(ProxyConfig::tor(9050)).unwrap().check(onion).is_ok());
+        assert!(ProxyRouter::new(ProxyConfig::default()).unwrap().check(onion).is_err());
+        let local_dns = ProxyConfig { url: Some("socks5://127.0.0.1:9050".to_string()), ..ProxyConfig::default() };
+        assert!(ProxyRouter::new(local_dns).unwrap().check(onion).is_err());
+        assert!(ProxyRouter::new(ProxyConfig::default()).unwrap().check("https://example.com").is_ok());
+    }
+
+    /// Minimal SOCKS5 server that answers the tunneled HTTP request itself,
+    /// recording the credentials and destination it was asked for
+    async fn socks_server(seen: Arc<Mutex<Vec<(String, String)>>>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let seen = Arc::clone(&seen);
+                tokio::spawn(async move {
+                    let mut header = [0u8; 2];
+                    stream.read_exact(&mut header).await.unwrap();
+                    let mut methods = vec![0u8; header[1] as usize];
+                    stream.read_exact(&mut methods).await.unwrap();
+                    let auth = methods.contains(&2);
+                    stream.write_all(&[5, if auth { 2 } else { 0 }]).await.unwrap();
+
+                    let mut user = String::new();
+                    if auth {
+                        let mut version_len = [0u8; 2];
+                        stream.read_exact(&mut version_len).await.unwrap();
+                        let mut name = vec![0u8; version_len[1] as usize];
+                        stream.read_exact(&mut name).await.unwrap();
+                        let mut pass_len = [0u8; 1];
+                        stream.read_exact(&mut pass_len).await.unwrap();
+                        let mut pass = vec![0u8; pass_len[0] as usize];
+                        stream.read_exact(&mut pass).await.unwrap();
+                        user = String::from_utf8(name).unwrap();
+                        stream.write_all(&[1, 0]).await.unwrap();
+                    }
+
+                    let mut request = [0u8; 4];
+                    stream.read_exact(&mut request).await.unwrap();
+                    let destination = match request[3] {
+                        3 => {
+                            let mut len = [0u8; 1];
+                            stream.read_exact(&mut len).await.unwrap();
+                            let mut name = vec![0u8; len[0] as usize];
+                            stream.read_exact(&mut name).await.unwrap();
+                            String::from_utf8(name).unwrap()
+                        }
+                        _ => {
+                            let mut addr = [0u8; 4];
+                            stream.read_exact(&mut addr).await.unwrap();
+                            std::net::Ipv4Addr::from(addr).to_string()
+                        }
+                    };
+                    let mut port = [0u8; 2];
+                    stream.read_exact(&mut port).await.unwrap();
+                    stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0]).await.unwrap();
+                    seen.lock().unwrap().push((user, destination));
+
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf).await.unwrap();
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                        .await
+                        .unwrap();
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_socks5h_remote_dns_and_isolation() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let port = socks_server(Arc::clone(&seen)).await;
+        let config = ProxyConfig { isolation: IsolationMode::PerHost, ..ProxyConfig::tor(port) };
+        let router = Arc::new(ProxyRouter::new(config).unwrap());
+        let client = build_client(Some(&router), None, false).unwrap();
+
+        for url in ["http://expyuzz4wqqyqhjn.onion/", "http://example.com/"] {
+            router.check(url).unwrap();
+            let body = client.get(url).send().await.unwrap().text().await.unwrap();
+            assert_eq!(body, "ok");
+        }
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].1, "expyuzz4wqqyqhjn.onion");
+        assert_eq!(seen[1].1, "example.com");
+        assert!(!seen[0].0.is_empty());
+        assert_ne!(seen[0].0, seen[1].0);
+    }
+
+    #[tokio::test]
+    async fn test_socks5_rejects_long_fields() {
+        let long = "a".repeat(256);
+        let proxy = Url::parse(&format!("socks5h://{}:pw@127.0.0.1:1", long)).unwrap();
+        let err = connect_via_proxy(&proxy, None, "example.com", 443).await.unwrap_err();
+        assert!(err.contains("username"), "{}", err);
+        let proxy = Url::parse("socks5h://127.0.0.1:1").unwrap();
+        let host = format!("{}.example.com", long);
+        let err = connect_via_proxy(&proxy, None, &host, 443).await.unwrap_err();
+        assert!(err.contains("host name"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_through_socks5() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let port = socks_server(Arc::clone(&seen)).await;
+        let config = ProxyConfig { isolation: IsolationMode::PerHost, ..ProxyConfig::tor(port) };
+        let router = ProxyRouter::new(config).unwrap();
+
+        let mut stream = connect_tunnel(Some(&router), None, "example.com", 443).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.ends_with("ok"));
+        let (user, host) = seen.lock().unwrap()[0].clone();
+        assert_eq!(host, "example.com");
+        assert!(!user.is_empty());
+
+        let no_onion = connect_tunnel(None, None, "expyuzz4wqqyqhjn.onion", 443).await;
+        assert!(no_onion.unwrap_err().contains("require a proxy"));
+    }
+}
@@ -0,0 +1,506 @@
+//! Privacy Budget Module
+//!
+//! Token-bucket rate limiting for outgoing requests (per destination host, per
+//! provider, per identity DID and per session) and for incoming callers on the
+//! server side (see [`crate::did_nostr::VerificationMiddleware`]).
+//!
+//! # Features
+//! - Token buckets with a refill rate and a burst size
+//! - Blocking-wait (up to a maximum delay) or fail-fast behaviour
+//! - `Retry-After` support: 429/503 responses pause the host and provider buckets
+//! - Counters and remaining tokens exposed as [`RateLimitStats`]
+//! - Idle buckets (fully refilled) and expired pauses are evicted, so per-host and
+//!   per-caller state stays bounded by the recently active keys
+
+use crate::transport::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often `reserve` sweeps out idle buckets and expired pauses
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Refill rate and burst size of a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Tokens added per second
+    pub rate: f64,
+    /// Bucket capacity (requests allowed back to back)
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+impl RateLimit {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self { rate, burst }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(self.rate.is_finite() && self.rate > 0.0) || self.burst == 0 {
+            return Err(format!("Invalid rate limit (rate {}, burst {})", self.rate, self.burst));
+        }
+        Ok(())
+    }
+}
+
+/// Limit for destination hosts (each matching host gets its own bucket)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostLimit {
+    /// Host patterns: exact host, `*.suffix`, or `*`
+    pub hosts: Vec<String>,
+    #[serde(flatten)]
+    pub limit: RateLimit,
+}
+
+/// Limit shared by all hosts of a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderLimit {
+    pub name: String,
+    /// Host patterns: exact host, `*.suffix`, or `*`
+    pub hosts: Vec<String>,
+    #[serde(flatten)]
+    pub limit: RateLimit,
+}
+
+/// Behaviour when a bucket is empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitMode {
+    /// Wait for a token (up to `max_wait_secs`)
+    #[default]
+    Wait,
+    /// Fail immediately
+    FailFast,
+}
+
+/// Rate limit configuration (the `budget` section of the privacy config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Host limits, first match wins
+    pub hosts: Vec<HostLimit>,
+    /// Limit for hosts without a matching entry
+    pub default_host: Option<RateLimit>,
+    pub providers: Vec<ProviderLimit>,
+    /// Limits per DID (`did:nostr:...`)
+    pub identities: HashMap<String, RateLimit>,
+    /// Limit for DIDs without an entry
+    pub default_identity: Option<RateLimit>,
+    /// Limit over all requests of the session
+    pub session: Option<RateLimit>,
+    pub mode: LimitMode,
+    /// Longest wait in [`LimitMode::Wait`] before failing
+    pub max_wait_secs: f64,
+    /// Pause buckets on `Retry-After` from 429/503 responses
+    pub respect_retry_after: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            hosts: Vec::new(),
+            default_host: None,
+            providers: Vec::new(),
+            identities: HashMap::new(),
+            default_identity: None,
+            session: None,
+            mode: LimitMode::Wait,
+            max_wait_secs: 30.0,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: RateLimitConfig = serde_json::from_str(json).map_err(|e| format!("Invalid budget config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let limits = self.hosts.iter().map(|h| &h.limit)
+            .chain(self.providers.iter().map(|p| &p.limit))
+            .chain(self.identities.values())
+            .chain(self.default_host.iter())
+            .chain(self.default_identity.iter())
+            .chain(self.session.iter());
+        for limit in limits {
+            limit.validate()?;
+        }
+        if !(self.max_wait_secs.is_finite() && self.max_wait_secs >= 0.0) {
+            return Err(format!("Invalid max_wait_secs: {}", self.max_wait_secs));
+        }
+        Ok(())
+    }
+}
+
+/// Bucket identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LimitKey {
+    Session,
+    Host(String),
+    Provider(String),
+    Identity(String),
+}
+
+impl fmt::Display for LimitKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKey::Session => write!(f, "session"),
+            LimitKey::Host(host) => write!(f, "host:{}", host),
+            LimitKey::Provider(name) => write!(f, "provider:{}", name),
+            LimitKey::Identity(did) => write!(f, "identity:{}", did),
+        }
+    }
+}
+
+/// Rejected request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    /// Bucket that ran out
+    pub key: LimitKey,
+    /// Time until a token is available
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rate limit exceeded for {} (retry after {:.1}s)", self.key, self.retry_after.as_secs_f64())
+    }
+}
+
+/// Limiter counters
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RateLimitStats {
+    /// Requests let through (immediately or after waiting)
+    pub allowed: u64,
+    /// Requests that had to wait
+    pub delayed: u64,
+    pub rejected: u64,
+    /// `Retry-After` responses honoured
+    pub retry_after: u64,
+    /// Total time spent waiting, in milliseconds
+    pub waited_ms: u64,
+    /// Tokens left per bucket
+    pub buckets: BTreeMap<String, f64>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: HashMap<LimitKey, Bucket>,
+    /// `Retry-After` pauses (also for keys without a configured limit)
+    blocked: HashMap<LimitKey, Instant>,
+    stats: RateLimitStats,
+    last_eviction: Option<Instant>,
+}
+
+/// Token-bucket rate limiter
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    state: Mutex<State>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter").field("config", &self.config).finish()
+    }
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Result<Self, String> {
+        config.validate()?;
+        Ok(Self { config, state: Mutex::new(State::default()) })
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Configured limit for a key
+    pub fn limit_for(&self, key: &LimitKey) -> Option<RateLimit> {
+        match key {
+            LimitKey::Session => self.config.session,
+            LimitKey::Host(host) => self
+                .config
+                .hosts
+                .iter()
+                .find(|h| h.hosts.iter().any(|p| crate::proxy::host_matches(p, host)))
+                .map(|h| h.limit)
+                .or(self.config.default_host),
+            LimitKey::Provider(name) => self.config.providers.iter().find(|p| &p.name == name).map(|p| p.limit),
+            LimitKey::Identity(did) => self.config.identities.get(did).copied().or(self.config.default_identity),
+        }
+    }
+
+    /// Buckets charged for a request to `url` made as `identity`
+    pub fn keys_for(&self, url: &str, identity: Option<&str>) -> Vec<LimitKey> {
+        let mut keys = vec![LimitKey::Session];
+        if let Some(host) = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase)) {
+            if let Some(provider) = self
+                .config
+                .providers
+                .iter()
+                .find(|p| p.hosts.iter().any(|pattern| crate::proxy::host_matches(pattern, &host)))
+            {
+                keys.push(LimitKey::Provider(provider.name.clone()));
+            }
+            keys.push(LimitKey::Host(host));
+        }
+        if let Some(did) = identity {
+            keys.push(LimitKey::Identity(did.to_string()));
+        }
+        keys
+    }
+
+    /// Buckets charged for a request (identity from the `X-DID` header)
+    pub fn keys_for_request(&self, request: &HttpRequest) -> Vec<LimitKey> {
+        self.keys_for(&request.url, request.header(crate::did_nostr::DID_HEADER))
+    }
+
+    /// Take a token from every bucket, or fail if that would mean waiting longer than
+    /// `max_wait`. Returns how long the caller must wait before sending.
+    pub fn reserve(&self, keys: &[LimitKey], max_wait: Duration) -> Result<Duration, RateLimited> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.last_eviction.is_none_or(|last| now.saturating_duration_since(last) >= EVICTION_INTERVAL) {
+            self.evict_idle(&mut state, now);
+        }
+        let mut wait = Duration::ZERO;
+        let mut limiting = None;
+        for key in keys {
+            let mut key_wait = state.blocked.get(key).map(|until| until.saturating_duration_since(now)).unwrap_or_default();
+            if let Some(limit) = self.limit_for(key) {
+                let bucket = state.buckets.entry(key.clone()).or_insert(Bucket { tokens: f64::from(limit.burst), updated: now });
+                let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * limit.rate).min(f64::from(limit.burst));
+                bucket.updated = now;
+                if bucket.tokens < 1.0 {
+                    // Tiny rates can mean a wait longer than `Duration` holds
+                    let refill = Duration::try_from_secs_f64((1.0 - bucket.tokens) / limit.rate).unwrap_or(Duration::MAX);
+                    key_wait = key_wait.max(refill);
+                }
+            }
+            if key_wait > wait {
+                wait = key_wait;
+                limiting = Some(key.clone());
+            }
+        }
+
+        if let Some(key) = limiting.filter(|_| wait > max_wait) {
+            state.stats.rejected += 1;
+            return Err(RateLimited { key, retry_after: wait });
+        }
+        // Tokens may go negative: that reserves a slot for a waiting caller
+        for key in keys {
+            if let Some(bucket) = state.buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        state.stats.allowed += 1;
+        if !wait.is_zero() {
+            state.stats.delayed += 1;
+            state.stats.waited_ms = state.stats.waited_ms.saturating_add(wait.as_millis().try_into().unwrap_or(u64::MAX));
+        }
+        Ok(wait)
+    }
+
+    /// Drop buckets that have refilled completely (a fresh bucket starts full, so this
+    /// loses nothing) and pauses that have ended
+    fn evict_idle(&self, state: &mut State, now: Instant) {
+        state.buckets.retain(|key, bucket| {
+            self.limit_for(key).is_some_and(|limit| {
+                let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * limit.rate < f64::from(limit.burst)
+            })
+        });
+        state.blocked.retain(|_, until| *until > now);
+        state.last_eviction = Some(now);
+    }
+
+    /// Take a token without waiting (server-side throttling)
+    pub fn try_acquire(&self, keys: &[LimitKey]) -> Result<(), RateLimited> {
+        self.reserve(keys, Duration::ZERO).map(|_| ())
+    }
+
+    /// Take a token, waiting according to the configured mode
+    pub async fn acquire(&self, keys: &[LimitKey]) -> Result<(), RateLimited> {
+        let max_wait = match self.config.mode {
+            LimitMode::Wait => Duration::try_from_secs_f64(self.config.max_wait_secs).unwrap_or(Duration::MAX),
+            LimitMode::FailFast => Duration::ZERO,
+        };
+        let wait = self.reserve(keys, max_wait)?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Pause buckets until a point in time
+    pub fn block(&self, key: &LimitKey, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.state.lock().unwrap();
+        let entry = state.blocked.entry(key.clone()).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+
+    /// Honour `Retry-After` on 429/503 responses for the host and provider buckets
+    pub fn observe_response(&self, keys: &[LimitKey], response: &HttpResponse) {
+        if !self.config.respect_retry_after || !matches!(response.status, 429 | 503) {
+            return;
+        }
+        let Some(delay) = response.header("retry-after").and_then(parse_retry_after) else {
+            return;
+        };
+        let delay = delay.min(Duration::from_secs(3600));
+        for key in keys.iter().filter(|k| matches!(k, LimitKey::Host(_) | LimitKey::Provider(_))) {
+            self.block(key, delay);
+        }
+        self.state.lock().unwrap().stats.retry_after += 1;
+    }
+
+    /// Counters and remaining tokens
+    pub fn stats(&self) -> RateLimitStats {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        let mut stats = state.stats.clone();
+        for (key, bucket) in &state.buckets {
+            let Some(limit) = self.limit_for(key) else { continue };
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            let tokens = (bucket.tokens + elapsed * limit.rate).min(f64::from(limit.burst));
+            stats.buckets.insert(key.to_string(), tokens);
+        }
+        stats
+    }
+}
+
+/// Parse a `Retry-After` value (delay in seconds or an HTTP date)
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(json: &str) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig::from_json(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_keys_and_limits() {
+        let limiter = limiter(
+            r#"{
+                "hosts": [{ "hosts": ["*.openai.com"], "rate": 2, "burst": 4 }],
+                "providers": [{ "name": "openai", "hosts": ["api.openai.com"], "rate": 1 }],
+                "default_identity": { "rate": 0.5 },
+                "session": { "rate": 10, "burst": 10 }
+            }"#,
+        );
+        let keys = limiter.keys_for("https://api.openai.com/v1/chat", Some("did:nostr:abc"));
+        assert_eq!(
+            keys,
+            vec![
+                LimitKey::Session,
+                LimitKey::Provider("openai".to_string()),
+                LimitKey::Host("api.openai.com".to_string()),
+                LimitKey::Identity("did:nostr:abc".to_string()),
+            ]
+        );
+        assert_eq!(limiter.limit_for(&keys[2]), Some(RateLimit::new(2.0, 4)));
+        assert_eq!(limiter.limit_for(&keys[3]), Some(RateLimit::new(0.5, 1)));
+        assert_eq!(limiter.limit_for(&LimitKey::Host("example.com".to_string())), None);
+        assert!(RateLimitConfig::from_json(r#"{"session": {"rate": 0}}"#).is_err());
+    }
+
+    #[test]
+    fn test_fail_fast_and_waiting() {
+        let limiter = limiter(r#"{"default_host": {"rate": 10, "burst": 2}}"#);
+        let keys = limiter.keys_for("https://example.com/", None);
+        assert!(limiter.try_acquire(&keys).is_ok());
+        assert!(limiter.try_acquire(&keys).is_ok());
+        let rejected = limiter.try_acquire(&keys).unwrap_err();
+        assert_eq!(rejected.key, LimitKey::Host("example.com".to_string()));
+        assert!(rejected.retry_after <= Duration::from_millis(100));
+
+        // Queued callers wait one refill interval each
+        let first = limiter.reserve(&keys, Duration::from_secs(1)).unwrap();
+        let second = limiter.reserve(&keys, Duration::from_secs(1)).unwrap();
+        assert!(second > first && second <= Duration::from_millis(200));
+
+        let stats = limiter.stats();
+        assert_eq!((stats.allowed, stats.delayed, stats.rejected), (4, 2, 1));
+        assert!(stats.buckets["host:example.com"] < 0.0);
+        // Other hosts are independent
+        assert!(limiter.try_acquire(&limiter.keys_for("https://other.example/", None)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_extreme_rates_and_waits_do_not_overflow() {
+        let slow = limiter(r#"{"session": {"rate": 1e-300}}"#);
+        slow.try_acquire(&[LimitKey::Session]).unwrap();
+        let rejected = slow.try_acquire(&[LimitKey::Session]).unwrap_err();
+        assert_eq!(rejected.retry_after, Duration::MAX);
+
+        let patient = limiter(r#"{"session": {"rate": 1000}, "max_wait_secs": 1e300}"#);
+        for _ in 0..2 {
+            patient.acquire(&[LimitKey::Session]).await.unwrap();
+        }
+        assert_eq!(patient.stats().delayed, 1);
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evicted() {
+        let limiter = limiter(r#"{"default_host": {"rate": 10, "burst": 2}}"#);
+        for i in 0..100 {
+            limiter.try_acquire(&limiter.keys_for(&format!("https://host{}.example/", i), None)).unwrap();
+        }
+        limiter.block(&LimitKey::Host("host0.example".to_string()), Duration::from_secs(1));
+        let mut state = limiter.state.lock().unwrap();
+        assert_eq!(state.buckets.len(), 100);
+
+        // Still refilling: kept
+        limiter.evict_idle(&mut state, Instant::now());
+        assert_eq!((state.buckets.len(), state.blocked.len()), (100, 1));
+        // Refilled and unpaused: gone
+        limiter.evict_idle(&mut state, Instant::now() + Duration::from_secs(2));
+        assert_eq!((state.buckets.len(), state.blocked.len()), (0, 0));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon"), None);
+
+        let limiter = limiter("{}");
+        let keys = limiter.keys_for("https://api.example.com/", None);
+        let response = HttpResponse {
+            status: 429,
+            headers: vec![("Retry-After".to_string(), "30".to_string())],
+            body: Vec::new(),
+        };
+        limiter.observe_response(&keys, &response);
+        let rejected = limiter.try_acquire(&keys).unwrap_err();
+        assert_eq!(rejected.key, LimitKey::Host("api.example.com".to_string()));
+        assert!(rejected.retry_after > Duration::from_secs(29));
+        assert_eq!(limiter.stats().retry_after, 1);
+    }
+}
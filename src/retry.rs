@@ -0,0 +1,493 @@
+//! Retry Module
+//!
+//! Retries with exponential backoff and a per-host circuit breaker for requests sent
+//! by `HttpClient` (configured with `configure_retries`).
+//!
+//! # Features
+//! - Exponential backoff with full jitter, capped at `max_delay_ms`
+//! - `Retry-After` honoured on retried responses
+//! - Only idempotent methods, or requests carrying an idempotency key, are retried
+//! - Only transient errors (connection, timeout, I/O) are retried; policy and TLS
+//!   enforcement rejections or invalid requests fail at once
+//! - Per-host circuit breaker (closed / open / half-open) with inspectable state
+
+use crate::ratelimit::parse_retry_after;
+use crate::transport::{HttpRequest, HttpResponse};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Methods that are safe to repeat (RFC 9110, Section 9.2.2)
+pub const IDEMPOTENT_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS", "TRACE", "PUT", "DELETE"];
+
+/// Circuit breaker configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit rejects requests before a trial request
+    pub open_secs: f64,
+    /// How long a trial request may stay unresolved before another is admitted
+    pub trial_secs: f64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, open_secs: 30.0, trial_secs: 30.0 }
+    }
+}
+
+/// Retry configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry (doubled for each further retry)
+    pub base_delay_ms: u64,
+    /// Upper bound for a single delay, including `Retry-After`
+    pub max_delay_ms: u64,
+    /// Randomize delays ("full jitter")
+    pub jitter: bool,
+    /// Response statuses worth retrying
+    pub retry_statuses: Vec<u16>,
+    /// Header marking non-idempotent requests as safe to retry
+    pub idempotency_header: String,
+    /// `None` disables the circuit breaker
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 10_000,
+            jitter: true,
+            retry_statuses: vec![408, 425, 429, 500, 502, 503, 504],
+            idempotency_header: "Idempotency-Key".to_string(),
+            circuit_breaker: Some(CircuitBreakerConfig::default()),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: RetryConfig = serde_json::from_str(json).map_err(|e| format!("Invalid retry config: {}", e))?;
+        let valid_secs = |secs: f64| Duration::try_from_secs_f64(secs).is_ok();
+        if let Some(breaker) = &config.circuit_breaker
+            && (breaker.failure_threshold == 0 || !valid_secs(breaker.open_secs) || !valid_secs(breaker.trial_secs))
+        {
+            return Err("Invalid circuit breaker config".to_string());
+        }
+        Ok(config)
+    }
+}
+
+/// Error of a single attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptError {
+    /// Connection, timeout or I/O failure; sending again may succeed
+    Transient(String),
+    /// Rejected without a usable exchange (policy, TLS enforcement, invalid request)
+    Fatal(String),
+}
+
+impl AttemptError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, AttemptError::Transient(_))
+    }
+}
+
+impl From<String> for AttemptError {
+    fn from(message: String) -> Self {
+        AttemptError::Fatal(message)
+    }
+}
+
+impl From<AttemptError> for String {
+    fn from(error: AttemptError) -> Self {
+        match error {
+            AttemptError::Transient(message) | AttemptError::Fatal(message) => message,
+        }
+    }
+}
+
+/// Circuit breaker state of a host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are rejected without being sent
+    Open,
+    /// One trial request is allowed; its outcome closes or reopens the circuit
+    HalfOpen,
+}
+
+struct HostCircuit {
+    state: CircuitState,
+    failures: u32,
+    /// When the circuit opened, or when the current trial was admitted
+    opened_at: Instant,
+}
+
+/// Per-host circuit breaker
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    /// `open_secs` and `trial_secs`, saturated to the longest `Duration`
+    open_for: Duration,
+    trial_for: Duration,
+    hosts: Mutex<HashMap<String, HostCircuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        let duration = |secs: f64| {
+            Duration::try_from_secs_f64(secs).unwrap_or(if secs > 0.0 { Duration::MAX } else { Duration::ZERO })
+        };
+        let (open_for, trial_for) = (duration(config.open_secs), duration(config.trial_secs));
+        Self { config, open_for, trial_for, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    /// Admit a request to `host`, or fail while the circuit is open
+    pub fn check(&self, host: &str) -> Result<(), String> {
+        let mut hosts = self.hosts.lock().unwrap();
+        let Some(circuit) = hosts.get_mut(host) else {
+            return Ok(());
+        };
+        match circuit.state {
+            CircuitState::Closed => Ok(()),
+            // A trial that never reported back (e.g. rejected before reaching the host)
+            // must not block the host forever
+            CircuitState::HalfOpen if circuit.opened_at.elapsed() >= self.trial_for => {
+                circuit.opened_at = Instant::now();
+                Ok(())
+            }
+            CircuitState::HalfOpen => Err(format!("Circuit half-open for {} (trial request in flight)", host)),
+            CircuitState::Open => {
+                let open_for = self.open_for;
+                let elapsed = circuit.opened_at.elapsed();
+                if elapsed >= open_for {
+                    circuit.state = CircuitState::HalfOpen;
+                    circuit.opened_at = Instant::now();
+                    Ok(())
+                } else {
+                    Err(format!("Circuit open for {} (retry in {:.1}s)", host, (open_for - elapsed).as_secs_f64()))
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self, host: &str) {
+        self.hosts.lock().unwrap().remove(host);
+    }
+
+    pub fn record_failure(&self, host: &str) {
+        let mut hosts = self.hosts.lock().unwrap();
+        let circuit = hosts.entry(host.to_string()).or_insert(HostCircuit {
+            state: CircuitState::Closed,
+            failures: 0,
+            opened_at: Instant::now(),
+        });
+        circuit.failures += 1;
+        if circuit.state == CircuitState::HalfOpen || circuit.failures >= self.config.failure_threshold {
+            circuit.state = CircuitState::Open;
+            circuit.opened_at = Instant::now();
+        }
+    }
+
+    /// Current state of a host's circuit
+    pub fn state(&self, host: &str) -> CircuitState {
+        self.hosts.lock().unwrap().get(host).map_or(CircuitState::Closed, |c| c.state)
+    }
+
+    /// Hosts with recorded failures and their circuit state
+    pub fn states(&self) -> BTreeMap<String, CircuitState> {
+        self.hosts.lock().unwrap().iter().map(|(host, c)| (host.clone(), c.state)).collect()
+    }
+}
+
+/// Retry policy with an optional circuit breaker
+pub struct RetryPolicy {
+    config: RetryConfig,
+    breaker: Option<CircuitBreaker>,
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy").field("config", &self.config).finish()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(config: RetryConfig) -> Self {
+        let breaker = config.circuit_breaker.clone().map(CircuitBreaker::new);
+        Self { config, breaker }
+    }
+
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+
+    pub fn breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    /// Whether a request may be sent more than once
+    pub fn is_retryable(&self, request: &HttpRequest) -> bool {
+        IDEMPOTENT_METHODS.contains(&request.method.as_str()) || request.header(&self.config.idempotency_header).is_some()
+    }
+
+    /// Delay before retry number `attempt` (0-based); `Retry-After` replaces the backoff
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.config.max_delay_ms);
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(max);
+        }
+        let exponential = self.config.base_delay_ms.saturating_mul(1u64 << attempt.min(32)).min(self.config.max_delay_ms);
+        let delay = if self.config.jitter && exponential > 0 { OsRng.next_u64() % (exponential + 1) } else { exponential };
+        Duration::from_millis(delay)
+    }
+
+    /// Send `request` with `send`, retrying transient failures and statuses
+    pub async fn run<F, Fut>(&self, request: &HttpRequest, mut send: F) -> Result<HttpResponse, String>
+    where
+        F: FnMut(HttpRequest) -> Fut,
+        Fut: Future<Output = Result<HttpResponse, AttemptError>>,
+    {
+        let host = reqwest::Url::parse(&request.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        let retryable = self.is_retryable(request);
+        let mut attempt = 0;
+        loop {
+            if let Some(breaker) = &self.breaker {
+                breaker.check(&host)?;
+            }
+            let result = send(request.clone()).await;
+            if let Some(breaker) = &self.breaker {
+                match &result {
+                    Ok(response) if response.status < 500 => breaker.record_success(&host),
+                    Ok(_) | Err(AttemptError::Transient(_)) => breaker.record_failure(&host),
+                    // Says nothing about the host's health
+                    Err(AttemptError::Fatal(_)) => {}
+                }
+            }
+
+            let transient = match &result {
+                Ok(response) => self.config.retry_statuses.contains(&response.status),
+                Err(error) => error.is_transient(),
+            };
+            if !retryable || !transient || attempt >= self.config.max_retries {
+                return result.map_err(String::from);
+            }
+            let retry_after = result.as_ref().ok().and_then(|r| r.header("retry-after")).and_then(parse_retry_after);
+            if retry_after.is_some_and(|delay| delay > Duration::from_millis(self.config.max_delay_ms)) {
+                // The server asked for a longer pause than we are willing to wait
+                return result.map_err(String::from);
+            }
+            tokio::time::sleep(self.backoff(attempt, retry_after)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::Transport;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Local flaky server: the first `failures` requests get `status` (with
+    /// `Retry-After: 0`), later ones 200
+    fn flaky_server(failures: usize, status: u16) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://127.0.0.1:{}/", listener.local_addr().unwrap().port());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0u8; content_length]).unwrap();
+                let hit = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = if hit < failures { (status, "flaky") } else { (200, "ok") };
+                let head = format!(
+                    "HTTP/1.1 {} X\r\nRetry-After: 0\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(body.as_bytes()).unwrap();
+            }
+        });
+        (url, hits)
+    }
+
+    fn retrying_client(config: &str) -> crate::HttpClient {
+        let mut client = crate::HttpClient::new(String::new());
+        client.set_retry_config(RetryConfig::from_json(config).unwrap());
+        client
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(RetryConfig { jitter: false, max_delay_ms: 1000, ..RetryConfig::default() });
+        let delays: Vec<u64> = (0..4).map(|n| policy.backoff(n, None).as_millis() as u64).collect();
+        assert_eq!(delays, vec![200, 400, 800, 1000]);
+        assert_eq!(policy.backoff(0, Some(Duration::from_secs(5))), Duration::from_secs(1));
+
+        let jittered = RetryPolicy::new(RetryConfig::default());
+        assert!((0..20).all(|_| jittered.backoff(2, None) <= Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig { failure_threshold: 2, open_secs: 0.05, trial_secs: 0.05 });
+        breaker.record_failure("api.example.com");
+        assert_eq!(breaker.state("api.example.com"), CircuitState::Closed);
+        breaker.record_failure("api.example.com");
+        assert_eq!(breaker.state("api.example.com"), CircuitState::Open);
+        assert!(breaker.check("api.example.com").unwrap_err().contains("Circuit open"));
+        assert!(breaker.check("other.example.com").is_ok());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check("api.example.com").is_ok());
+        assert_eq!(breaker.state("api.example.com"), CircuitState::HalfOpen);
+        assert!(breaker.check("api.example.com").is_err());
+        breaker.record_failure("api.example.com");
+        assert_eq!(breaker.state("api.example.com"), CircuitState::Open);
+
+        // A trial that never reports back expires and admits another one
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.check("api.example.com").unwrap();
+        assert!(breaker.check("api.example.com").is_err());
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.check("api.example.com").unwrap();
+        breaker.record_success("api.example.com");
+        assert_eq!(breaker.state("api.example.com"), CircuitState::Closed);
+        assert!(breaker.states().is_empty());
+    }
+
+    #[test]
+    fn test_circuit_durations_beyond_duration_range() {
+        assert!(RetryConfig::from_json(r#"{"circuit_breaker": {"open_secs": 1e300}}"#).is_err());
+        assert!(RetryConfig::from_json(r#"{"circuit_breaker": {"trial_secs": 1e300}}"#).is_err());
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig { failure_threshold: 1, open_secs: 1e300, trial_secs: 1e300 });
+        breaker.record_failure("api.example.com");
+        assert!(breaker.check("api.example.com").unwrap_err().contains("Circuit open"));
+    }
+
+    #[test]
+    fn test_retries_against_flaky_server() {
+        let (url, hits) = flaky_server(2, 503);
+        let client = retrying_client(r#"{"base_delay_ms": 1}"#);
+        let response = client.send(HttpRequest::get(&url)).unwrap();
+        assert_eq!((response.status, response.text()), (200, "ok".to_string()));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Exhausted retries return the last response
+        let (url, hits) = flaky_server(10, 502);
+        let client = retrying_client(r#"{"base_delay_ms": 1, "max_retries": 2, "circuit_breaker": null}"#);
+        assert_eq!(client.send(HttpRequest::get(&url)).unwrap().status, 502);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    /// Records the nonce of every request it sees
+    #[derive(Default)]
+    struct NonceRecorder(Mutex<Vec<String>>);
+
+    impl crate::interceptor::Interceptor for NonceRecorder {
+        fn name(&self) -> &str {
+            "nonce-recorder"
+        }
+
+        fn on_request(&self, request: &mut HttpRequest) -> Result<Option<HttpResponse>, String> {
+            let nonce = request.header(crate::did_nostr::NONCE_HEADER).unwrap_or_default();
+            self.0.lock().unwrap().push(nonce.to_string());
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_retries_are_signed_afresh() {
+        use crate::interceptor::{InterceptorStage, SigningInterceptor};
+        let (url, hits) = flaky_server(2, 503);
+        let mut client = retrying_client(r#"{"base_delay_ms": 1}"#);
+        let signer = SigningInterceptor::new(crate::did_nostr::NostrSigner::generate());
+        let recorder = Arc::new(NonceRecorder::default());
+        client.add_interceptor(InterceptorStage::Inner, Arc::new(signer));
+        client.add_interceptor(InterceptorStage::Inner, recorder.clone());
+        assert_eq!(client.send(HttpRequest::get(&url)).unwrap().status, 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // A replay cache would reject a repeated nonce
+        let mut nonces = recorder.0.lock().unwrap().clone();
+        assert_eq!(nonces.len(), 3);
+        nonces.dedup();
+        assert!(nonces.len() == 3 && nonces.iter().all(|nonce| !nonce.is_empty()));
+    }
+
+    #[test]
+    fn test_only_idempotent_requests_are_retried() {
+        let (url, hits) = flaky_server(1, 503);
+        let client = retrying_client(r#"{"base_delay_ms": 1}"#);
+        assert_eq!(client.send(HttpRequest::post(&url, "{}")).unwrap().status, 503);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let request = HttpRequest::post(&url, "{}").with_header("Idempotency-Key", "k-1");
+        assert_eq!(client.send(request).unwrap().status, 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_circuit_opens_on_repeated_failures() {
+        let (url, hits) = flaky_server(100, 500);
+        let client = retrying_client(r#"{"max_retries": 0, "circuit_breaker": {"failure_threshold": 2, "open_secs": 60}}"#);
+        for _ in 0..2 {
+            assert_eq!(client.send(HttpRequest::get(&url)).unwrap().status, 500);
+        }
+        assert!(client.send(HttpRequest::get(&url)).unwrap_err().contains("Circuit open"));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(client.circuit_states_json().unwrap(), r#"{"127.0.0.1":"open"}"#);
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        let config = r#"{"base_delay_ms": 1, "max_retries": 2, "circuit_breaker": {"failure_threshold": 3}}"#;
+        // Connection refused: every attempt counts against the host
+        let client = retrying_client(config);
+        assert!(client.send(HttpRequest::get("http://127.0.0.1:1/")).unwrap_err().contains("Request failed"));
+        assert_eq!(client.circuit_states_json().unwrap(), r#"{"127.0.0.1":"open"}"#);
+
+        // Rejected before reaching the host: no retries, no breaker failures
+        let client = retrying_client(config);
+        let err = client.send(HttpRequest::get("http://expyuzz4wqqyqhjn.onion/")).unwrap_err();
+        assert!(err.contains("require a proxy"), "{}", err);
+        assert_eq!(client.circuit_states_json().unwrap(), "{}");
+    }
+}
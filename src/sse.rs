@@ -0,0 +1,107 @@
+//! Server-Sent Events Module
+//!
+//! Minimal `text/event-stream` parser used by the MCP and A2A clients. Input can be
+//! fed incrementally; complete events are returned as soon as their terminating
+//! blank line has been seen.
+
+/// A single server-sent event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type (`message` when not specified)
+    pub event: String,
+    /// Data lines joined with `\n`
+    pub data: String,
+    pub id: Option<String>,
+}
+
+/// Incremental SSE parser. Raw bytes are buffered until a line is complete, so
+/// multibyte characters split across chunks decode correctly.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    current: Option<SseEvent>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the stream, returning any events it completed
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let bytes: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&bytes);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if let Some(event) = self.current.take() {
+                    events.push(event);
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            let event = self.current.get_or_insert_with(|| SseEvent {
+                event: "message".to_string(),
+                ..SseEvent::default()
+            });
+            match field {
+                "event" => event.event = value.to_string(),
+                "data" => {
+                    if !event.data.is_empty() {
+                        event.data.push('\n');
+                    }
+                    event.data.push_str(value);
+                }
+                "id" => event.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Flush a trailing event not followed by a blank line
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let mut rest = std::mem::take(&mut self.buffer);
+            rest.push(b'\n');
+            let mut events = self.feed(&rest);
+            if let Some(event) = events.pop() {
+                return Some(event);
+            }
+        }
+        self.current.take()
+    }
+}
+
+/// Parse a complete event stream
+pub fn parse_events(body: &[u8]) -> Vec<SseEvent> {
+    let mut parser = SseParser::new();
+    let mut events = parser.feed(body);
+    events.extend(parser.finish());
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_multibyte_data() {
+        let stream = "event: update\ndata: caf\u{e9} \u{1f600}\n\ndata: tail".as_bytes();
+        let mut parser = SseParser::new();
+        let mut events = Vec::new();
+        for byte in stream {
+            events.extend(parser.feed(std::slice::from_ref(byte)));
+        }
+        events.extend(parser.finish());
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].event.as_str(), events[0].data.as_str()), ("update", "caf\u{e9} \u{1f600}"));
+        assert_eq!((events[1].event.as_str(), events[1].data.as_str()), ("message", "tail"));
+    }
+}
@@ -0,0 +1,423 @@
+//! Artifact Storage Module
+//!
+//! This module stores generated content (images, request/response transcripts) in
+//! S3-compatible object storage such as AWS S3 or MinIO.
+//!
+//! # Features
+//! - Mandatory client-side AES-256-GCM encryption via [`AesGcmCodec`]
+//! - Content-addressed object keys (HMAC-keyed, so key names cannot be used to confirm
+//!   known plaintexts). The cipher and naming keys are separate HKDF subkeys of the
+//!   configured key.
+//! - Presigned GET URLs for uploaded objects
+//! - Redacted request/response transcripts
+//! - `s3://bucket/prefix` URIs
+
+use aws_sdk_s3::config::{Credentials, Region};
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use base64::Engine;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::fmt;
+use std::time::Duration;
+use crate::codec::AesGcmCodec;
+use crate::image::GeneratedImage;
+use crate::transport::{HttpRequest, HttpResponse};
+
+/// Headers whose values are never written to transcripts
+const REDACTED_HEADERS: [&str; 6] = [
+    "Authorization",
+    "Proxy-Authorization",
+    "Cookie",
+    "Set-Cookie",
+    "X-Api-Key",
+    "Api-Key",
+];
+
+/// HKDF labels for the subkeys derived from `encryption_key`
+const CIPHER_KEY_INFO: &[u8] = b"privacy_http_sdk storage cipher key";
+const NAME_KEY_INFO: &[u8] = b"privacy_http_sdk storage object-name key";
+
+/// S3 storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Target bucket
+    pub bucket: String,
+    /// Key prefix inside the bucket
+    pub prefix: String,
+    pub region: String,
+    /// Custom endpoint (e.g. "http://127.0.0.1:9000" for MinIO)
+    pub endpoint_url: Option<String>,
+    /// Path-style addressing (required by MinIO)
+    pub force_path_style: bool,
+    /// Static credentials; the default AWS credential chain is used when unset
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    /// Base64-encoded 32-byte key for client-side encryption (required)
+    pub encryption_key: Option<String>,
+    /// Lifetime of presigned URLs in seconds
+    pub presign_expiry_secs: u64,
+    /// Store an encrypted, redacted transcript of every request/response
+    pub store_transcripts: bool,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            prefix: String::new(),
+            region: "us-east-1".to_string(),
+            endpoint_url: None,
+            force_path_style: false,
+            access_key_id: None,
+            secret_access_key: None,
+            encryption_key: None,
+            presign_expiry_secs: 3600,
+            store_transcripts: false,
+        }
+    }
+}
+
+impl StorageConfig {
+    /// Parse from JSON (missing fields fall back to defaults)
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid storage config: {}", e))
+    }
+}
+
+/// Bucket and key prefix parsed from an `s3://bucket/prefix` URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Location {
+    pub bucket: String,
+    pub prefix: String,
+}
+
+impl S3Location {
+    /// Parse `s3://bucket` or `s3://bucket/some/prefix`
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let rest = uri
+            .strip_prefix("s3://")
+            .ok_or_else(|| format!("Invalid S3 URI (expected s3://bucket/prefix): {}", uri))?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(format!("Missing bucket in S3 URI: {}", uri));
+        }
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        })
+    }
+}
+
+/// Result of an upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredArtifact {
+    pub bucket: String,
+    pub key: String,
+    /// Presigned GET URL (serves ciphertext)
+    pub presigned_url: String,
+    pub encrypted: bool,
+}
+
+impl StoredArtifact {
+    /// `s3://bucket/key` URI
+    pub fn uri(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.key)
+    }
+}
+
+/// S3-compatible artifact store
+pub struct ArtifactStore {
+    client: aws_sdk_s3::Client,
+    config: StorageConfig,
+    codec: AesGcmCodec,
+    /// Subkey for the content hash in object names
+    hash_key: [u8; 32],
+}
+
+impl fmt::Debug for ArtifactStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArtifactStore")
+            .field("bucket", &self.config.bucket)
+            .field("prefix", &self.config.prefix)
+            .field("endpoint_url", &self.config.endpoint_url)
+            .finish()
+    }
+}
+
+impl ArtifactStore {
+    /// Create a store, loading credentials from the config or the AWS default chain.
+    /// Generated images and transcripts are never uploaded in plaintext, so an
+    /// `encryption_key` is required.
+    pub async fn connect(config: StorageConfig) -> Result<Self, String> {
+        let key = config
+            .encryption_key
+            .as_deref()
+            .ok_or_else(|| "Artifact storage requires an encryption_key".to_string())?;
+        let master = base64::engine::general_purpose::STANDARD
+            .decode(key.trim())
+            .map_err(|e| format!("Invalid base64 key: {}", e))?;
+        if master.len() != 32 {
+            return Err(format!("Invalid encryption key length: expected 32, got {}", master.len()));
+        }
+        let codec = AesGcmCodec::new(&derive_subkey(&master, CIPHER_KEY_INFO))?;
+        let hash_key = derive_subkey(&master, NAME_KEY_INFO);
+
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(Region::new(config.region.clone()));
+        if let (Some(id), Some(secret)) = (&config.access_key_id, &config.secret_access_key) {
+            loader = loader.credentials_provider(Credentials::new(id, secret, None, None, "privacy_http_sdk"));
+        }
+        if let Some(endpoint) = &config.endpoint_url {
+            loader = loader.endpoint_url(endpoint);
+        }
+        let shared = loader.load().await;
+        let s3_config = aws_sdk_s3::config::Builder::from(&shared)
+            .force_path_style(config.force_path_style)
+            .build();
+
+        Ok(Self {
+            client: aws_sdk_s3::Client::from_conf(s3_config),
+            config,
+            codec,
+            hash_key,
+        })
+    }
+
+    /// Store configuration
+    pub fn config(&self) -> &StorageConfig {
+        &self.config
+    }
+
+    /// Derive the object key `<prefix>/<kind>/<hmac>.<ext>.enc` from the content
+    pub fn object_key(&self, prefix: &str, kind: &str, data: &[u8], extension: &str) -> String {
+        let digest = content_hash(&self.hash_key, data);
+        let name = format!("{}/{}.{}.enc", kind, digest, extension);
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) }
+    }
+
+    /// Upload bytes to the configured bucket and prefix
+    pub async fn put(&self, kind: &str, data: &[u8], extension: &str) -> Result<StoredArtifact, String> {
+        let location = S3Location {
+            bucket: self.config.bucket.clone(),
+            prefix: self.config.prefix.clone(),
+        };
+        self.put_at(&location, kind, data, extension).await
+    }
+
+    /// Upload bytes to an explicit bucket and prefix
+    pub async fn put_at(
+        &self,
+        location: &S3Location,
+        kind: &str,
+        data: &[u8],
+        extension: &str,
+    ) -> Result<StoredArtifact, String> {
+        if location.bucket.is_empty() {
+            return Err("No S3 bucket configured".to_string());
+        }
+        let key = self.object_key(&location.prefix, kind, data, extension);
+        let body = self.codec.encrypt(data)?;
+
+        self.client
+            .put_object()
+            .bucket(&location.bucket)
+            .key(&key)
+            .content_type("application/octet-stream")
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| format!("S3 upload failed: {}", aws_sdk_s3::error::DisplayErrorContext(e)))?;
+
+        let presigned_url = self.presign(&location.bucket, &key).await?;
+        Ok(StoredArtifact {
+            bucket: location.bucket.clone(),
+            key,
+            presigned_url,
+            encrypted: true,
+        })
+    }
+
+    /// Create a presigned GET URL for an object
+    pub async fn presign(&self, bucket: &str, key: &str) -> Result<String, String> {
+        let presigning = PresigningConfig::expires_in(Duration::from_secs(self.config.presign_expiry_secs))
+            .map_err(|e| format!("Invalid presign expiry: {}", e))?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| format!("S3 presign failed: {}", aws_sdk_s3::error::DisplayErrorContext(e)))?;
+        Ok(request.uri().to_string())
+    }
+
+    /// Download and decrypt an object
+    pub async fn get(&self, bucket: &str, key: &str) -> Result<Vec<u8>, String> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| format!("S3 download failed: {}", aws_sdk_s3::error::DisplayErrorContext(e)))?;
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| format!("S3 download failed: {}", e))?
+            .into_bytes();
+        self.codec.decrypt(&bytes)
+    }
+
+    /// Upload a generated image
+    pub async fn put_image(&self, location: Option<&S3Location>, image: &GeneratedImage) -> Result<StoredArtifact, String> {
+        let format = image.format;
+        match location {
+            Some(location) => self.put_at(location, "images", &image.data, format.as_str()).await,
+            None => self.put("images", &image.data, format.as_str()).await,
+        }
+    }
+
+    /// Upload a redacted request/response transcript
+    pub async fn put_transcript(&self, request: &HttpRequest, response: &HttpResponse) -> Result<StoredArtifact, String> {
+        let transcript = transcript_json(request, response);
+        self.put("transcripts", transcript.as_bytes(), "json").await
+    }
+}
+
+/// 32-byte HKDF-SHA256 subkey of the configured key
+fn derive_subkey(master: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut subkey = [0u8; 32];
+    Hkdf::<Sha256>::new(None, master)
+        .expand(info, &mut subkey)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    subkey
+}
+
+/// Hex HMAC-SHA256 of the content
+fn content_hash(key: &[u8], data: &[u8]) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Serialize a request/response pair with sensitive header values redacted
+pub fn transcript_json(request: &HttpRequest, response: &HttpResponse) -> String {
+    let redact = |headers: &[(String, String)]| -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(k, v)| {
+                if REDACTED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(k)) {
+                    (k.clone(), "[REDACTED]".to_string())
+                } else {
+                    (k.clone(), v.clone())
+                }
+            })
+            .collect()
+    };
+    json!({
+        "request": {
+            "method": request.method,
+            "url": request.url,
+            "headers": redact(&request.headers),
+            "body": String::from_utf8_lossy(&request.body),
+        },
+        "response": {
+            "status": response.status,
+            "headers": redact(&response.headers),
+            "body": String::from_utf8_lossy(&response.body),
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_s3_uri() {
+        let location = S3Location::parse("s3://artifacts/generated/images/").unwrap();
+        assert_eq!(location.bucket, "artifacts");
+        assert_eq!(location.prefix, "generated/images");
+        assert_eq!(S3Location::parse("s3://bucket").unwrap().prefix, "");
+        assert!(S3Location::parse("https://bucket").is_err());
+        assert!(S3Location::parse("s3:///key").is_err());
+    }
+
+    #[test]
+    fn test_content_hash_is_keyed() {
+        // Not the plain SHA-256 of "abc", so names cannot confirm a known image
+        let keyed = content_hash(b"key", b"abc");
+        assert_ne!(keyed, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(keyed, content_hash(b"key", b"abc"));
+        assert_ne!(keyed, content_hash(b"other", b"abc"));
+    }
+
+    #[test]
+    fn test_subkeys_are_separate() {
+        let master = [7u8; 32];
+        let (cipher, names) = (derive_subkey(&master, CIPHER_KEY_INFO), derive_subkey(&master, NAME_KEY_INFO));
+        assert_ne!(cipher, names);
+        assert_ne!(cipher, master);
+        assert_ne!(names, master);
+    }
+
+    #[tokio::test]
+    async fn test_storage_requires_encryption() {
+        for store_transcripts in [false, true] {
+            let config = StorageConfig {
+                bucket: "bucket".to_string(),
+                store_transcripts,
+                ..StorageConfig::default()
+            };
+            assert!(ArtifactStore::connect(config).await.unwrap_err().contains("encryption_key"));
+        }
+    }
+
+    #[test]
+    fn test_transcript_redacts_secrets() {
+        let request = HttpRequest::post("https://api.example.com/v1", "hello")
+            .with_header("Authorization", "Bearer sk-secret");
+        let response = HttpResponse {
+            status: 200,
+            headers: vec![("Set-Cookie".to_string(), "id=1".to_string())],
+            body: b"world".to_vec(),
+        };
+        let transcript = transcript_json(&request, &response);
+        assert!(!transcript.contains("sk-secret"));
+        assert!(!transcript.contains("id=1"));
+        assert!(transcript.contains("[REDACTED]"));
+        assert!(transcript.contains("world"));
+    }
+
+    /// Requires a local MinIO, e.g.
+    /// `docker run -p 9000:9000 -e MINIO_ROOT_USER=minio -e MINIO_ROOT_PASSWORD=minio123 minio/minio server /data`
+    /// with a bucket named `privacy-http-test`, then `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn test_minio_roundtrip() {
+        let config = StorageConfig {
+            bucket: "privacy-http-test".to_string(),
+            endpoint_url: Some(std::env::var("MINIO_ENDPOINT").unwrap_or("http://127.0.0.1:9000".to_string())),
+            force_path_style: true,
+            access_key_id: Some("minio".to_string()),
+            secret_access_key: Some("minio123".to_string()),
+            encryption_key: Some(AesGcmCodec::generate_key()),
+            ..StorageConfig::default()
+        };
+        let store = ArtifactStore::connect(config).await.unwrap();
+        let stored = store.put("images", b"png-bytes", "png").await.unwrap();
+        assert!(stored.key.starts_with("images/") && stored.key.ends_with(".png.enc"));
+        assert!(stored.presigned_url.contains("X-Amz-Signature"));
+        assert_eq!(store.get(&stored.bucket, &stored.key).await.unwrap(), b"png-bytes");
+    }
+}
@@ -0,0 +1,275 @@
+//! Transport Module
+//!
+//! Plain request/response types and the [`Transport`] trait used by provider
+//! integrations (image generation, protocol clients, ...). `HttpClient` implements
+//! [`Transport`], so every integration goes through the same privacy client while
+//! staying independent of the WASM/Python/C++ bindings.
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::Value;
+use std::net::{IpAddr, SocketAddr};
+use crate::doh::DohResolver;
+use crate::interceptor::Interceptor;
+
+/// Outgoing HTTP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Create a request with an empty body
+    pub fn new(method: &str, url: &str) -> Self {
+        Self {
+            method: method.to_uppercase(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Create a GET request
+    pub fn get(url: &str) -> Self {
+        Self::new("GET", url)
+    }
+
+    /// Create a POST request with a raw body
+    pub fn post(url: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self::new("POST", url).with_body(body)
+    }
+
+    /// Create a POST request with a JSON body and matching `Content-Type`
+    pub fn post_json(url: &str, body: &Value) -> Self {
+        Self::post(url, body.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    /// Append a header
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Append several headers
+    pub fn with_headers(mut self, headers: &[(String, String)]) -> Self {
+        self.headers.extend_from_slice(headers);
+        self
+    }
+
+    /// Replace the body
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Get the first header value with the given name (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Received HTTP response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Get the first header value with the given name (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Status and headers without the body
+    pub fn head(&self) -> Self {
+        Self { status: self.status, headers: self.headers.clone(), body: Vec::new() }
+    }
+
+    /// Body as (lossy) UTF-8 text
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Parse the body as JSON
+    pub fn json(&self) -> Result<Value, String> {
+        serde_json::from_slice(&self.body).map_err(|e| format!("Failed to parse JSON response: {}", e))
+    }
+
+    /// Turn non-2xx responses into an error carrying the status and body
+    pub fn error_for_status(self) -> Result<Self, String> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(format!("HTTP {}: {}", self.status, self.text()))
+        }
+    }
+}
+
+/// Blocking HTTP transport used by provider integrations
+pub trait Transport {
+    /// Send a request and return the full response
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, String>;
+
+    /// Send a request, passing the response head (status and headers) and each body
+    /// chunk to `on_chunk` as it arrives. Returns the complete response. Transports
+    /// that cannot stream deliver the whole body as one chunk.
+    fn send_streaming(&self, request: HttpRequest, on_chunk: &mut ChunkSink<'_>) -> Result<HttpResponse, String> {
+        let response = self.send(request)?;
+        on_chunk(&response.head(), &response.body)?;
+        Ok(response)
+    }
+
+    /// Send a request with `inner` as the innermost request step, so it sees the request
+    /// as sent (e.g. signing after PII scrubbing has rewritten the body). Transports
+    /// without an interceptor chain run it just before sending.
+    fn send_with(&self, request: HttpRequest, inner: &dyn Interceptor) -> Result<HttpResponse, String> {
+        intercept(inner, request, |request| self.send(request))
+    }
+
+    /// [`Transport::send_streaming`] with `inner` as the innermost request step
+    fn send_streaming_with(
+        &self,
+        request: HttpRequest,
+        inner: &dyn Interceptor,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<HttpResponse, String> {
+        intercept(inner, request, |request| self.send_streaming(request, on_chunk))
+    }
+}
+
+/// Run one interceptor's hooks around `send`
+fn intercept(
+    interceptor: &dyn Interceptor,
+    mut request: HttpRequest,
+    send: impl FnOnce(HttpRequest) -> Result<HttpResponse, String>,
+) -> Result<HttpResponse, String> {
+    if let Some(response) = interceptor.on_request(&mut request)? {
+        return Ok(response);
+    }
+    match send(request.clone()) {
+        Ok(response) => interceptor.on_response(&request, response),
+        Err(error) => interceptor.on_error(&request, error),
+    }
+}
+
+/// Receiver of streamed body chunks, see [`Transport::send_streaming`]
+pub type ChunkSink<'a> = dyn FnMut(&HttpResponse, &[u8]) -> Result<(), String> + 'a;
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Whether the URL targets the local machine (`localhost`, `127.0.0.0/8`, `::1`).
+/// Loopback traffic never leaves the host, so it is exempt from TLS enforcement.
+pub fn is_loopback_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    }
+}
+
+/// Whether the URL targets a publicly routable host. Loopback, private, link-local,
+/// shared (`100.64.0.0/10`), unique-local and unspecified addresses are refused, and a
+/// host name is refused unless every address it resolves to is public.
+pub fn is_public_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+        return false;
+    };
+    if is_loopback_url(url) {
+        return false;
+    }
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_public_ip(ip);
+    }
+    match std::net::ToSocketAddrs::to_socket_addrs(&(host, port)) {
+        Ok(addrs) => {
+            let addrs: Vec<_> = addrs.collect();
+            !addrs.is_empty() && addrs.iter().all(|addr| is_public_ip(addr.ip()))
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether the URL's host is an IP address that is not public. Such hosts are never
+/// resolved, so [`PublicOnlyResolver`] cannot refuse them.
+pub fn is_internal_ip_url(url: &str) -> bool {
+    let Some(host) = reqwest::Url::parse(url).ok().and_then(|parsed| parsed.host_str().map(str::to_string)) else {
+        return false;
+    };
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => !is_public_ip(ip),
+        Err(_) => false,
+    }
+}
+
+/// Resolver dropping every address that is not public, so a host checked with
+/// [`is_public_url`] cannot resolve to an internal address by the time the connection
+/// is made (DNS rebinding). Resolves with DoH when configured, the system resolver
+/// otherwise.
+#[derive(Clone, Default)]
+pub struct PublicOnlyResolver {
+    doh: Option<DohResolver>,
+}
+
+impl PublicOnlyResolver {
+    pub fn new(doh: Option<DohResolver>) -> Self {
+        Self { doh }
+    }
+}
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let doh = self.doh.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = match doh {
+                Some(doh) => doh.resolve(name).await?.collect(),
+                None => tokio::net::lookup_host((host.as_str(), 0)).await?.collect(),
+            };
+            let public: Vec<SocketAddr> = addrs.into_iter().filter(|addr| is_public_ip(addr.ip())).collect();
+            if public.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            let addrs: Addrs = Box::new(public.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let shared = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+            !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+                || ip.is_broadcast() || ip.is_multicast() || ip.is_documentation() || shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ip(IpAddr::V4(mapped)),
+            None => !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast()
+                || ip.is_unique_local() || ip.is_unicast_link_local()),
+        },
+    }
+}
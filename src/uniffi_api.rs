@@ -0,0 +1,306 @@
+//! UniFFI bindings for Swift, Kotlin and Python
+//!
+//! One proc-macro definition of the mobile API: `PrivacyHttpClient` configured with
+//! the spec.md JSON configs, the DID-NOSTR types, and request signing/verification.
+//! Bindings are generated from the built library:
+//!
+//! ```text
+//! cargo build --release
+//! cargo run --features uniffi-cli --bin uniffi-bindgen -- generate \
+//!     --library target/release/libprivacy_http_sdk.so --language kotlin --out-dir bindings/kotlin
+//! ```
+//!
+//! Client calls block until the response arrives; call them off the UI thread.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use crate::HttpClient;
+use crate::config::ClientConfig;
+use crate::did_nostr;
+use crate::interceptor::{Interceptor, InterceptorStage, SigningInterceptor};
+use crate::privacy::{PrivacyConfig, PrivacyPolicy};
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+/// Errors surfaced to the generated bindings (exceptions in Kotlin/Python, `throws` in Swift)
+#[derive(Debug, uniffi::Error)]
+pub enum PrivacyHttpError {
+    /// A JSON config was rejected
+    Config { message: String },
+    /// The request was blocked by a privacy rule or failed to send
+    Request { message: String },
+    /// A key, DID or signature was malformed
+    Identity { message: String },
+}
+
+impl fmt::Display for PrivacyHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config { message } => write!(f, "Invalid config: {}", message),
+            Self::Request { message } => write!(f, "Request failed: {}", message),
+            Self::Identity { message } => write!(f, "Invalid identity: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PrivacyHttpError {}
+
+fn config_error(message: String) -> PrivacyHttpError {
+    PrivacyHttpError::Config { message }
+}
+
+fn identity_error(message: String) -> PrivacyHttpError {
+    PrivacyHttpError::Identity { message }
+}
+
+/// Header name/value pair
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+fn to_pairs(headers: Vec<Header>) -> Vec<(String, String)> {
+    headers.into_iter().map(|h| (h.name, h.value)).collect()
+}
+
+fn from_pairs(headers: Vec<(String, String)>) -> Vec<Header> {
+    headers.into_iter().map(|(name, value)| Header { name, value }).collect()
+}
+
+/// Full HTTP response
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<Header>,
+    pub body: Vec<u8>,
+}
+
+impl From<HttpResponse> for Response {
+    fn from(response: HttpResponse) -> Self {
+        Self { status: response.status, headers: from_pairs(response.headers), body: response.body }
+    }
+}
+
+/// `HttpClient` shared with the bindings. Requests run concurrently; reconfiguring
+/// waits for in-flight requests.
+#[derive(uniffi::Object)]
+pub struct PrivacyHttpClient {
+    inner: RwLock<HttpClient>,
+}
+
+impl PrivacyHttpClient {
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HttpClient> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HttpClient> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[uniffi::export]
+impl PrivacyHttpClient {
+    /// Client from a `ClientConfig` JSON string (`None`: defaults)
+    #[uniffi::constructor(default(config_json = None))]
+    pub fn new(config_json: Option<String>) -> Result<Arc<Self>, PrivacyHttpError> {
+        let config = match config_json {
+            Some(json) => ClientConfig::from_json(&json).map_err(config_error)?,
+            None => ClientConfig::default(),
+        };
+        let client = HttpClient::from_config(config).map_err(config_error)?;
+        Ok(Arc::new(Self { inner: RwLock::new(client) }))
+    }
+
+    /// Replace the privacy policy with a spec.md JSON config
+    pub fn set_privacy_config(&self, config_json: String) -> Result<(), PrivacyHttpError> {
+        let config = PrivacyConfig::from_json(&config_json).map_err(config_error)?;
+        self.write().set_privacy_policy(PrivacyPolicy::new(config)).map_err(config_error)
+    }
+
+    /// Current privacy config as JSON
+    pub fn privacy_config(&self) -> String {
+        serde_json::to_string(self.read().privacy_policy.config()).unwrap_or_default()
+    }
+
+    /// Sign every request (`X-DID`, `X-Timestamp`, `X-Nonce`, `X-Signature`), covering the request as sent.
+    /// Replaces the signer set by an earlier call.
+    pub fn sign_requests(&self, signer: Arc<NostrSigner>) {
+        let interceptor = SigningInterceptor::new(signer.inner.clone());
+        let mut client = self.write();
+        client.remove_interceptor(interceptor.name());
+        client.add_interceptor(InterceptorStage::Inner, Arc::new(interceptor));
+    }
+
+    /// Send a request through the privacy pipeline
+    pub fn send(&self, method: String, url: String, headers: Vec<Header>, body: Vec<u8>) -> Result<Response, PrivacyHttpError> {
+        let request = HttpRequest::new(&method, &url).with_headers(&to_pairs(headers)).with_body(body);
+        let response = self.read().send(request).map_err(|message| PrivacyHttpError::Request { message })?;
+        Ok(response.into())
+    }
+
+    pub fn get(&self, url: String, headers: Vec<Header>) -> Result<Response, PrivacyHttpError> {
+        self.send("GET".to_string(), url, headers, Vec::new())
+    }
+
+    pub fn post(&self, url: String, body: Vec<u8>, headers: Vec<Header>) -> Result<Response, PrivacyHttpError> {
+        self.send("POST".to_string(), url, headers, body)
+    }
+}
+
+/// `did:nostr:<pubkey>` identifier
+#[derive(Debug, PartialEq, uniffi::Object)]
+#[uniffi::export(Display, Eq)]
+pub struct DidNostr {
+    inner: did_nostr::DidNostr,
+}
+
+impl fmt::Display for DidNostr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[uniffi::export]
+impl DidNostr {
+    /// Parse a `did:nostr:...` string
+    #[uniffi::constructor]
+    pub fn parse(did: String) -> Result<Arc<Self>, PrivacyHttpError> {
+        let inner = did_nostr::DidNostr::from_str(&did).map_err(identity_error)?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    /// DID of a hex-encoded x-only public key
+    #[uniffi::constructor]
+    pub fn from_pubkey(pubkey_hex: String) -> Result<Arc<Self>, PrivacyHttpError> {
+        let pubkey = did_nostr::NostrPublicKey::from_hex(&pubkey_hex).map_err(identity_error)?;
+        Ok(Arc::new(Self { inner: did_nostr::DidNostr::from_pubkey(pubkey) }))
+    }
+
+    /// Public key (hex)
+    pub fn pubkey_hex(&self) -> String {
+        self.inner.pubkey().as_hex().to_string()
+    }
+}
+
+/// Secret key signing messages and requests
+#[derive(uniffi::Object)]
+pub struct NostrSigner {
+    inner: did_nostr::NostrSigner,
+}
+
+#[uniffi::export]
+impl NostrSigner {
+    /// Signer from a hex-encoded secret key
+    #[uniffi::constructor]
+    pub fn from_secret_hex(secret_hex: String) -> Result<Arc<Self>, PrivacyHttpError> {
+        let inner = did_nostr::NostrSigner::from_secret_hex(&secret_hex).map_err(identity_error)?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    /// Fresh random key
+    #[uniffi::constructor]
+    pub fn generate() -> Arc<Self> {
+        Arc::new(Self { inner: did_nostr::NostrSigner::generate() })
+    }
+
+    pub fn did(&self) -> Arc<DidNostr> {
+        Arc::new(DidNostr { inner: self.inner.did() })
+    }
+
+    /// Secret key (hex), for storage in the platform keychain
+    pub fn secret_hex(&self) -> String {
+        self.inner.secret_hex()
+    }
+
+    /// BIP-340 signature (hex) over `sha256(message)`
+    pub fn sign(&self, message: String) -> String {
+        self.inner.sign(&message).as_hex().to_string()
+    }
+
+    /// Signed `X-DID`, `X-Timestamp`, `X-Nonce` and `X-Signature` headers for a request
+    pub fn sign_request(&self, method: String, path: String, body: String) -> Vec<Header> {
+        from_pairs(self.inner.sign_request(&method, &path, body.as_bytes()))
+    }
+}
+
+/// Outcome of a signature check
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct VerificationResult {
+    pub valid: bool,
+    /// Verified DID (if valid)
+    pub did: Option<String>,
+    /// Error message (if invalid)
+    pub error: Option<String>,
+}
+
+impl From<did_nostr::VerificationResult> for VerificationResult {
+    fn from(result: did_nostr::VerificationResult) -> Self {
+        Self { valid: result.valid, did: result.did.map(|did| did.to_string()), error: result.error }
+    }
+}
+
+/// Verify a signature (hex) over `message` by a public key (hex)
+#[uniffi::export]
+pub fn verify_signature(pubkey_hex: String, message: String, signature_hex: String) -> VerificationResult {
+    let (pubkey, signature) = match (
+        did_nostr::NostrPublicKey::from_hex(&pubkey_hex),
+        did_nostr::NostrSignature::from_hex(&signature_hex),
+    ) {
+        (Ok(pubkey), Ok(signature)) => (pubkey, signature),
+        (Err(e), _) | (_, Err(e)) => return did_nostr::VerificationResult::failure(e).into(),
+    };
+    did_nostr::NostrVerifier::verify(&pubkey, &message, &signature).into()
+}
+
+/// Verify a request signed with `NostrSigner.sign_request`
+#[uniffi::export]
+pub fn verify_request(method: String, path: String, headers: Vec<Header>, body: String) -> VerificationResult {
+    did_nostr::NostrVerifier::verify_request(&method, &path, &to_pairs(headers), body.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = NostrSigner::generate();
+        let restored = NostrSigner::from_secret_hex(signer.secret_hex()).unwrap();
+        assert_eq!(*restored.did(), *signer.did());
+
+        let did = signer.did();
+        assert_eq!(*DidNostr::parse(did.to_string()).unwrap(), *did);
+        assert_eq!(*DidNostr::from_pubkey(did.pubkey_hex()).unwrap(), *did);
+
+        let signature = signer.sign("hello".to_string());
+        assert!(verify_signature(did.pubkey_hex(), "hello".to_string(), signature.clone()).valid);
+        assert!(!verify_signature(did.pubkey_hex(), "hullo".to_string(), signature).valid);
+
+        let headers = signer.sign_request("post".to_string(), "/tasks".to_string(), "{}".to_string());
+        let result = verify_request("POST".to_string(), "/tasks".to_string(), headers.clone(), "{}".to_string());
+        assert_eq!(result.did, Some(did.to_string()));
+        assert!(!verify_request("POST".to_string(), "/other".to_string(), headers, "{}".to_string()).valid);
+
+        assert!(matches!(DidNostr::parse("did:web:example.com".to_string()), Err(PrivacyHttpError::Identity { .. })));
+    }
+
+    #[test]
+    fn test_client_config_errors() {
+        let client = PrivacyHttpClient::new(Some(r#"{"privacy": {"user_agent": "Mozilla/5.0"}}"#.to_string())).unwrap();
+        assert!(client.privacy_config().contains("\"user_agent\":\"Mozilla/5.0\""));
+        assert!(matches!(client.set_privacy_config(r#"{"tls_enforce": "yes"}"#.to_string()), Err(PrivacyHttpError::Config { .. })));
+        assert!(matches!(PrivacyHttpClient::new(Some("{".to_string())), Err(PrivacyHttpError::Config { .. })));
+        // TLS is enforced for non-loopback hosts by default
+        let result = client.get("http://example.com/".to_string(), Vec::new());
+        assert!(matches!(result, Err(PrivacyHttpError::Request { .. })));
+    }
+
+    #[test]
+    fn test_sign_requests_replaces_signer() {
+        let client = PrivacyHttpClient::new(None).unwrap();
+        client.sign_requests(NostrSigner::generate());
+        client.sign_requests(NostrSigner::generate());
+        let names = client.read().interceptor_names();
+        assert_eq!(names.iter().filter(|name| *name == "nostr-signing").count(), 1);
+    }
+}
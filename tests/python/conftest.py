@@ -0,0 +1,57 @@
+"""Fixtures shared by the extension tests: a local echo server and a client"""
+
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from privacy_http_sdk import HttpClientPy
+
+
+class EchoHandler(BaseHTTPRequestHandler):
+    """Answers with the request it received as JSON"""
+
+    def _echo(self):
+        self.server.requests += 1
+        body = self.rfile.read(int(self.headers.get("Content-Length", 0)))
+        payload = json.dumps({
+            "method": self.command,
+            "path": self.path,
+            "headers": {name.lower(): value for name, value in self.headers.items()},
+            "body": body.decode(),
+        }).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("X-Origin", "echo")
+        self.send_header("Content-Length", str(len(payload)))
+        self.end_headers()
+        self.wfile.write(payload)
+
+    do_GET = _echo
+    do_POST = _echo
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def server():
+    httpd = HTTPServer(("127.0.0.1", 0), EchoHandler)
+    httpd.requests = 0
+    thread = threading.Thread(target=httpd.serve_forever, daemon=True)
+    thread.start()
+    yield httpd
+    httpd.shutdown()
+    thread.join(timeout=5)
+    httpd.server_close()
+
+
+@pytest.fixture
+def url(server):
+    return "http://127.0.0.1:%d/echo" % server.server_port
+
+
+@pytest.fixture
+def client():
+    return HttpClientPy("", "")
@@ -0,0 +1,41 @@
+"""Tests for HttpClientPy requests against the built extension"""
+
+import json
+
+import pytest
+
+
+def test_get_with_dict_headers(client, url):
+    echoed = json.loads(client.get(url, {"X-Token": "abc", "Accept": "application/json"}))
+    assert echoed["method"] == "GET"
+    assert echoed["headers"]["x-token"] == "abc"
+    assert echoed["headers"]["accept"] == "application/json"
+
+
+def test_post_with_dict_headers(client, url):
+    echoed = json.loads(client.post(url, {"Content-Type": "text/plain"}, "hello"))
+    assert echoed["method"] == "POST"
+    assert echoed["headers"]["content-type"] == "text/plain"
+    assert echoed["body"] == "hello"
+
+
+def test_headers_as_pairs_and_keywords(client, url):
+    echoed = json.loads(client.post(url, headers=[("X-Token", "abc")], body="x"))
+    assert echoed["headers"]["x-token"] == "abc"
+    assert json.loads(client.get(url))["method"] == "GET"
+
+
+def test_invalid_headers_are_rejected(client, url, server):
+    with pytest.raises(TypeError):
+        client.get(url, {"X-Token": 1})
+    with pytest.raises(TypeError):
+        client.get(url, "X-Token: abc")
+    assert server.requests == 0
+
+
+def test_a2a_calls_release_the_gil(client, url, server):
+    # The server answers on a Python thread, so the call only returns if the GIL is released
+    client.set_a2a_server(url)
+    with pytest.raises(RuntimeError):
+        client.a2a_agent_card()
+    assert server.requests == 1
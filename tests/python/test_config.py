@@ -0,0 +1,117 @@
+"""Tests for HttpClientPy setters raising Python exceptions on invalid input"""
+
+import base64
+import json
+
+import pytest
+
+
+def test_pii_scrubbing_rejects_invalid_config(client):
+    with pytest.raises(ValueError):
+        client.enable_pii_scrubbing("{")
+    client.enable_pii_scrubbing()
+
+
+def test_scrub_chat_rejects_invalid_json(client):
+    client.enable_pii_scrubbing()
+    with pytest.raises(ValueError, match="Invalid messages JSON"):
+        client.scrub_chat("not json")
+    messages = json.loads(client.scrub_chat('[{"role": "user", "content": "mail alice@example.com"}]'))
+    assert "alice@example.com" not in messages[0]["content"]
+
+
+def test_unknown_image_provider_is_rejected(client):
+    with pytest.raises(ValueError, match="Invalid image provider config"):
+        client.set_image_provider('{"provider": "no-such-backend"}')
+    client.set_image_provider('{"provider": "automatic1111", "base_url": "http://127.0.0.1:7860"}')
+
+
+def test_proxy_identity_requires_a_proxy(client):
+    with pytest.raises(ValueError, match="No proxy configured"):
+        client.set_proxy_identity("alice")
+    client.set_privacy_config('{"proxy": {"url": "socks5h://127.0.0.1:9050", "isolation": "per_identity", "identity": "default"}}')
+    client.set_proxy_identity("alice")
+
+
+def test_rate_limit_stats_without_and_with_budget(client):
+    assert json.loads(client.rate_limit_stats()) is None
+    client.set_privacy_config('{"budget": {"default_host": {"rate": 1.0}}}')
+    assert json.loads(client.rate_limit_stats()) is not None
+
+
+def test_invalid_retry_config_is_rejected(client):
+    with pytest.raises(ValueError, match="Invalid retry config"):
+        client.configure_retries('{"max_retries": "many"}')
+    client.configure_retries('{"max_retries": 2}')
+    assert json.loads(client.circuit_states()) == {}
+
+
+def test_cookie_jar_reports_persistence_errors(client, tmp_path):
+    client.clear_cookies()
+    assert json.loads(client.cookie_partitions()) == []
+    key = base64.b64encode(bytes(32)).decode()
+    missing = tmp_path / "missing" / "cookies.bin"
+    client.set_privacy_config(json.dumps({"cookies": {"persist_path": str(missing), "persist_key": key}}))
+    assert json.loads(client.cookie_partitions()) == []
+    with pytest.raises(RuntimeError):
+        client.clear_cookies()
+
+
+def test_invalid_cache_config_is_rejected(client, tmp_path):
+    with pytest.raises(ValueError, match="disk_path requires disk_key"):
+        client.configure_cache(json.dumps({"disk_path": str(tmp_path)}))
+    with pytest.raises(ValueError, match="Invalid base64 key"):
+        client.configure_cache(json.dumps({"disk_path": str(tmp_path), "disk_key": "not base64!"}))
+    blocker = tmp_path / "file"
+    blocker.write_text("")
+    key = base64.b64encode(bytes(32)).decode()
+    with pytest.raises(ValueError, match="Failed to create"):
+        client.configure_cache(json.dumps({"disk_path": str(blocker / "cache"), "disk_key": key}))
+
+
+def test_clear_cache_reports_a_missing_directory(client, tmp_path):
+    client.clear_cache()
+    cache_dir = tmp_path / "cache"
+    key = base64.b64encode(bytes(32)).decode()
+    client.configure_cache(json.dumps({"disk_path": str(cache_dir), "disk_key": key}))
+    client.clear_cache()
+    cache_dir.rmdir()
+    with pytest.raises(RuntimeError, match="Failed to read cache directory"):
+        client.clear_cache()
+
+
+def test_invalid_privacy_config_is_rejected(client):
+    with pytest.raises(ValueError, match="Invalid privacy config"):
+        client.set_privacy_config("{")
+    with pytest.raises(ValueError):
+        client.set_privacy_config('{"proxy": {"isolation": "per_identity"}}')
+    client.set_privacy_config('{"enforce_https": true}')
+
+
+def test_invalid_ohttp_config_is_rejected(client):
+    with pytest.raises(ValueError, match="requires a relay_url"):
+        client.configure_ohttp("{}")
+    with pytest.raises(ValueError, match="requires key_config or keys_url"):
+        client.configure_ohttp('{"relay_url": "https://relay.example"}')
+    with pytest.raises(RuntimeError):
+        client.configure_ohttp('{"relay_url": "https://relay.example", "key_config": "not a key"}')
+
+
+def test_invalid_storage_config_is_rejected(client):
+    with pytest.raises(ValueError, match="Invalid storage config"):
+        client.configure_storage("{")
+    with pytest.raises(RuntimeError, match="requires an encryption_key"):
+        client.configure_storage('{"bucket": "artifacts"}')
+
+
+def test_unknown_interceptor_stage_is_rejected(client):
+    with pytest.raises(ValueError, match="Unknown interceptor stage"):
+        client.register_interceptor(on_request=lambda request: None, stage="middle")
+    assert "python" not in client.interceptor_names()
+
+
+def test_invalid_image_arguments_are_rejected(client, tmp_path):
+    with pytest.raises(ValueError, match="output_path or output_uri is required"):
+        client.generate_image("a cat", 64, 64, 1)
+    with pytest.raises(ValueError, match="Unsupported image format"):
+        client.generate_image("a cat", 64, 64, 1, output_path=str(tmp_path / "cat.gif"), format="gif")
@@ -0,0 +1,46 @@
+"""Tests for the identity value types: pickling, equality and hashing"""
+
+import pickle
+
+from privacy_http_sdk.identity import DidNostr, NostrEvent, NostrPublicKey, NostrSignature, NostrSigner
+
+
+def values():
+    signer = NostrSigner.generate()
+    return [
+        signer.pubkey(),
+        signer.did(),
+        signer.sign("hello"),
+        NostrEvent.sign(signer, 1, "hello", [["t", "test"]], 1700000000),
+    ]
+
+
+def test_pickle_round_trip():
+    for value in values():
+        restored = pickle.loads(pickle.dumps(value))
+        assert type(restored) is type(value)
+        assert restored == value
+        assert hash(restored) == hash(value)
+
+
+def test_equality_is_by_value():
+    signer = NostrSigner.generate()
+    did = signer.did()
+    assert did == DidNostr(did.to_string())
+    assert did == DidNostr.from_pubkey(NostrPublicKey(signer.pubkey().as_hex()))
+    assert did != NostrSigner.generate().did()
+    assert did != did.to_string()
+    signature = signer.sign("hello")
+    assert signature == NostrSignature(signature.as_hex())
+    assert signature != signer.sign("hullo")
+
+
+def test_usable_in_sets_and_dict_keys():
+    signer = NostrSigner.generate()
+    other = NostrSigner.generate()
+    trusted = {signer.did(), DidNostr(signer.did().to_string()), other.did()}
+    assert len(trusted) == 2
+    assert DidNostr(signer.did().to_string()) in trusted
+
+    names = {signer.pubkey(): "alice", other.pubkey(): "bob"}
+    assert names[NostrPublicKey(signer.pubkey().as_hex())] == "alice"
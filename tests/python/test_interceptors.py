@@ -0,0 +1,89 @@
+"""Tests for Python callback interceptors against the built extension.
+
+Build and install the extension into the active environment before running pytest
+(the fixtures live in ``conftest.py``):
+
+    maturin develop
+    pytest tests/python
+"""
+
+import json
+
+import pytest
+
+
+def test_request_view_edits_reach_server(client, url):
+    def on_request(view):
+        assert view.method == "GET"
+        view.method = "POST"
+        view.url = view.url + "?edited=1"
+        view.set_header("X-Edited", "yes")
+        view.body = b"from hook"
+
+    client.register_interceptor(on_request=on_request)
+    echoed = json.loads(client.get(url))
+    assert echoed["method"] == "POST"
+    assert echoed["path"] == "/echo?edited=1"
+    assert echoed["headers"]["x-edited"] == "yes"
+    assert echoed["body"] == "from hook"
+
+
+def test_response_view_edits_reach_caller(client, url):
+    seen = {}
+
+    def on_response(view):
+        seen["status"] = view.status
+        seen["origin"] = view.header("X-Origin")
+        seen["request_method"] = view.method
+        view.body = json.dumps({"rewritten": json.loads(view.text())["method"]}).encode()
+
+    client.register_interceptor(on_response=on_response)
+    assert json.loads(client.post(url, body="{}")) == {"rewritten": "POST"}
+    assert seen == {"status": 200, "origin": "echo", "request_method": "POST"}
+
+
+def test_block_stops_request(client, url, server):
+    client.register_interceptor(on_request=lambda view: view.block("no trackers"), name="guard")
+    with pytest.raises(RuntimeError, match="Blocked by guard: no trackers"):
+        client.get(url)
+    assert server.requests == 0
+
+
+def test_returning_false_blocks(client, url, server):
+    client.register_interceptor(on_request=lambda view: False, name="guard")
+    with pytest.raises(RuntimeError, match="Blocked by guard"):
+        client.get(url)
+    assert server.requests == 0
+
+
+def test_response_block(client, url):
+    client.register_interceptor(on_response=lambda view: view.block("bad answer"), name="check")
+    with pytest.raises(RuntimeError, match="Blocked by check: bad answer"):
+        client.get(url)
+
+
+def test_respond_short_circuits(client, url, server):
+    def on_request(view):
+        view.respond(200, b"cached", [("X-Local", "yes")])
+
+    client.register_interceptor(on_request=on_request)
+    assert client.get(url) == "cached"
+    assert server.requests == 0
+
+
+def test_hook_exception_fails_request(client, url, server):
+    def on_request(view):
+        raise ValueError("boom")
+
+    client.register_interceptor(on_request=on_request, name="broken")
+    with pytest.raises(RuntimeError, match="Interceptor broken failed: .*boom"):
+        client.get(url)
+    assert server.requests == 0
+
+
+def test_interceptor_names_and_clear(client, url):
+    client.register_interceptor(on_request=lambda view: False, name="guard")
+    assert "guard" in client.interceptor_names()
+    client.clear_interceptors()
+    assert "guard" not in client.interceptor_names()
+    assert json.loads(client.get(url))["method"] == "GET"